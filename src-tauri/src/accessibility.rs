@@ -0,0 +1,64 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+// 检查系统辅助功能设置变化的间隔
+const POLL_INTERVAL_SECS: u64 = 2;
+
+// 系统“辅助功能 > 显示”中影响面板表现的设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct AccessibilitySettings {
+    pub reduce_motion: bool,       // 减弱动态效果：面板关闭动画
+    pub reduce_transparency: bool, // 降低透明度：面板不使用模糊背景
+    pub increase_contrast: bool,   // 增强对比度：预览与报告使用高对比度样式
+}
+
+// 通过 NSWorkspace 读取当前的辅助功能显示设置
+#[cfg(target_os = "macos")]
+pub fn query_accessibility_settings() -> AccessibilitySettings {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+
+    unsafe {
+        let workspace: *mut AnyObject = msg_send![class!(NSWorkspace), sharedWorkspace];
+        if workspace.is_null() {
+            return AccessibilitySettings::default();
+        }
+        AccessibilitySettings {
+            reduce_motion: msg_send![workspace, accessibilityDisplayShouldReduceMotion],
+            reduce_transparency: msg_send![workspace, accessibilityDisplayShouldReduceTransparency],
+            increase_contrast: msg_send![workspace, accessibilityDisplayShouldIncreaseContrast],
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn query_accessibility_settings() -> AccessibilitySettings {
+    AccessibilitySettings::default()
+}
+
+// Tauri命令：获取系统辅助功能显示设置
+#[tauri::command]
+pub fn get_accessibility_settings() -> AccessibilitySettings {
+    query_accessibility_settings()
+}
+
+// 启动后台任务，设置变化时通知前端（accessibility-changed 事件）
+pub fn start_accessibility_watcher(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(POLL_INTERVAL_SECS));
+        let mut last = query_accessibility_settings();
+
+        loop {
+            interval.tick().await;
+
+            let current = query_accessibility_settings();
+            if current != last {
+                println!("辅助功能显示设置已变化: {:?}", current);
+                last = current;
+                if let Err(e) = app_handle.emit("accessibility-changed", current) {
+                    eprintln!("通知前端辅助功能设置变化失败: {}", e);
+                }
+            }
+        }
+    });
+}
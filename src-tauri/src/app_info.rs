@@ -1,8 +1,19 @@
+use crate::change_log::emit_change_event;
+use crate::db::{cache_app_icon, init_database, list_cached_app_icons};
+use crate::db_writer::submit_write;
+use crate::error::OicopyError;
+use crate::permissions::{require_feature, Feature, FeatureError};
 use base64::prelude::*;
+use once_cell::sync::Lazy;
+use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::Cursor;
 use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use tauri::AppHandle;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppInfo {
@@ -12,47 +23,105 @@ pub struct AppInfo {
     pub icon_base64: Option<String>,
 }
 
+// 前台应用的名称、Bundle ID 和应用包路径
 #[cfg(target_os = "macos")]
-pub fn get_frontmost_app() -> Result<AppInfo, String> {
-    use std::process::Command;
+struct FrontmostApp {
+    name: String,
+    bundle_id: String,
+    path: Option<String>,
+}
 
-    // 使用更简单的 AppleScript 获取前台应用信息
-    let script = r#"
-        tell application "System Events"
-            set frontApp to first application process whose frontmost is true
-            set appName to name of frontApp
-            try
-                set bundleId to bundle identifier of frontApp
-            on error
-                set bundleId to "unknown.bundle.id"
-            end try
-            return appName & "|" & bundleId
-        end tell
-    "#;
-
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .map_err(|e| format!("执行 AppleScript 失败: {}", e))?;
+// 将 NSString 转换为 Rust 字符串，空指针返回 None
+#[cfg(target_os = "macos")]
+unsafe fn ns_string_to_string(string: *mut objc2::runtime::AnyObject) -> Option<String> {
+    use objc2::msg_send;
+    use std::ffi::{c_char, CStr};
 
-    if !output.status.success() {
-        let error_msg = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("AppleScript 执行失败: {}", error_msg));
+    if string.is_null() {
+        return None;
+    }
+    let utf8: *const c_char = msg_send![string, UTF8String];
+    if utf8.is_null() {
+        return None;
     }
+    Some(CStr::from_ptr(utf8).to_string_lossy().into_owned())
+}
 
-    let result = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    let parts: Vec<&str> = result.split('|').collect();
+// 通过 NSWorkspace 查询前台应用（不启动子进程，也不需要自动化权限）
+#[cfg(target_os = "macos")]
+fn query_frontmost_app() -> Result<FrontmostApp, String> {
+    use objc2::rc::autoreleasepool;
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+
+    // 监听线程没有自动释放池，返回的对象在这里释放
+    autoreleasepool(|_| unsafe {
+        let workspace: *mut AnyObject = msg_send![class!(NSWorkspace), sharedWorkspace];
+        if workspace.is_null() {
+            return Err("无法访问 NSWorkspace".to_string());
+        }
+        let app: *mut AnyObject = msg_send![workspace, frontmostApplication];
+        if app.is_null() {
+            return Err("当前没有前台应用".to_string());
+        }
 
-    let (name, bundle_id) = if parts.len() >= 2 {
-        (parts[0].to_string(), parts[1].to_string())
-    } else {
-        (result, "unknown.bundle.id".to_string())
+        let bundle_id: *mut AnyObject = msg_send![app, bundleIdentifier];
+        let bundle_id = ns_string_to_string(bundle_id).unwrap_or_else(|| "unknown.bundle.id".to_string());
+        let name: *mut AnyObject = msg_send![app, localizedName];
+        let name = ns_string_to_string(name).unwrap_or_else(|| bundle_id.clone());
+        let url: *mut AnyObject = msg_send![app, bundleURL];
+        let path = if url.is_null() {
+            None
+        } else {
+            let path: *mut AnyObject = msg_send![url, path];
+            ns_string_to_string(path)
+        };
+
+        Ok(FrontmostApp { name, bundle_id, path })
+    })
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_frontmost_app() -> Result<AppInfo, String> {
+    let FrontmostApp { name, bundle_id, path } = query_frontmost_app()?;
+
+    // 获取应用图标，已知应用包路径时不再按 Bundle ID 查找
+    let (icon_path, icon_base64) = match path {
+        Some(path) => match extract_app_icon(&path) {
+            Some(icon) => (Some(path), Some(icon)),
+            None => (None, None),
+        },
+        None => get_app_icon(&bundle_id),
     };
 
-    // 获取应用图标
-    let (icon_path, icon_base64) = get_app_icon(&bundle_id);
+    Ok(AppInfo {
+        name,
+        bundle_id,
+        icon_path,
+        icon_base64,
+    })
+}
 
+// Windows：前台窗口所属进程，以可执行文件路径作为 Bundle ID
+#[cfg(target_os = "windows")]
+pub fn get_frontmost_app() -> Result<AppInfo, String> {
+    use crate::app_info_windows::{app_display_name, foreground_exe_path};
+
+    let path = foreground_exe_path()?;
+    let icon_base64 = extract_app_icon(&path);
+    Ok(AppInfo {
+        name: app_display_name(&path),
+        bundle_id: path.clone(),
+        icon_path: icon_base64.is_some().then(|| path.clone()),
+        icon_base64,
+    })
+}
+
+// Linux：X11 活动窗口或 Wayland 激活的窗口，以 .desktop 文件 ID 作为 Bundle ID
+#[cfg(target_os = "linux")]
+pub fn get_frontmost_app() -> Result<AppInfo, String> {
+    let (name, bundle_id) = crate::app_info_linux::active_app()?;
+    let (icon_path, icon_base64) = get_app_icon(&bundle_id);
     Ok(AppInfo {
         name,
         bundle_id,
@@ -61,9 +130,9 @@ pub fn get_frontmost_app() -> Result<AppInfo, String> {
     })
 }
 
-#[cfg(not(target_os = "macos"))]
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
 pub fn get_frontmost_app() -> Result<AppInfo, String> {
-    // 非 macOS 平台的占位实现
+    // 其他平台的占位实现
     Ok(AppInfo {
         name: "Unknown".to_string(),
         bundle_id: "unknown.bundle.id".to_string(),
@@ -72,6 +141,157 @@ pub fn get_frontmost_app() -> Result<AppInfo, String> {
     })
 }
 
+// 打开面板前处于前台的应用（名称, Bundle ID），用于粘贴时重新激活
+static PREVIOUS_FRONTMOST_APP: Mutex<Option<(String, String)>> = Mutex::new(None);
+
+// 记录当前前台应用（面板自身除外）
+#[cfg(target_os = "macos")]
+pub fn remember_frontmost_app(own_bundle_id: &str) {
+    match query_frontmost_app() {
+        Ok(FrontmostApp { name, bundle_id, .. }) => {
+            if bundle_id == own_bundle_id || bundle_id == "unknown.bundle.id" {
+                return;
+            }
+            if let Ok(mut previous) = PREVIOUS_FRONTMOST_APP.lock() {
+                *previous = Some((name, bundle_id));
+            }
+        }
+        Err(e) => eprintln!("记录前台应用失败: {}", e),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn remember_frontmost_app(_own_bundle_id: &str) {}
+
+// 获取打开面板前的前台应用（名称, Bundle ID）
+pub fn previous_frontmost_app() -> Option<(String, String)> {
+    PREVIOUS_FRONTMOST_APP
+        .lock()
+        .ok()
+        .and_then(|previous| previous.clone())
+}
+
+// 按 Bundle ID 激活应用
+#[cfg(target_os = "macos")]
+pub fn activate_app(bundle_id: &str) -> Result<(), String> {
+    use objc2::rc::autoreleasepool;
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use std::ffi::CString;
+
+    // NSApplicationActivateIgnoringOtherApps
+    const ACTIVATE_IGNORING_OTHER_APPS: usize = 1 << 1;
+
+    let ns_bundle_id = CString::new(bundle_id).map_err(|_| format!("Bundle ID 无效: {}", bundle_id))?;
+    autoreleasepool(|_| unsafe {
+        let ns_bundle_id: *mut AnyObject =
+            msg_send![class!(NSString), stringWithUTF8String: ns_bundle_id.as_ptr()];
+        if ns_bundle_id.is_null() {
+            return Err(format!("Bundle ID 无效: {}", bundle_id));
+        }
+        let apps: *mut AnyObject =
+            msg_send![class!(NSRunningApplication), runningApplicationsWithBundleIdentifier: ns_bundle_id];
+        let count: usize = if apps.is_null() { 0 } else { msg_send![apps, count] };
+        if count == 0 {
+            return Err(format!("应用未运行: {}", bundle_id));
+        }
+        let app: *mut AnyObject = msg_send![apps, objectAtIndex: 0usize];
+        let activated: bool = msg_send![app, activateWithOptions: ACTIVATE_IGNORING_OTHER_APPS];
+        if !activated {
+            return Err(format!("激活应用失败: {}", bundle_id));
+        }
+        Ok(())
+    })
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn activate_app(_bundle_id: &str) -> Result<(), String> {
+    Err("当前平台不支持激活应用".to_string())
+}
+
+// 图标提取任务：每个 Bundle ID 同时只排队一次
+struct IconJob {
+    bundle_id: String,
+    app_name: Option<String>,
+}
+
+struct IconQueue {
+    sender: Option<Sender<IconJob>>,
+    pending: HashSet<String>,
+}
+
+static ICON_QUEUE: Lazy<Mutex<IconQueue>> = Lazy::new(|| {
+    Mutex::new(IconQueue {
+        sender: None,
+        pending: HashSet::new(),
+    })
+});
+
+// 将图标提取加入后台队列（读取图标文件和解码 icns 较慢，不能放在剪贴板处理流程中）
+pub fn queue_icon_extraction(app_handle: &AppHandle, bundle_id: &str, app_name: Option<&str>) {
+    let Ok(mut queue) = ICON_QUEUE.lock() else {
+        return;
+    };
+    if !queue.pending.insert(bundle_id.to_string()) {
+        return; // 已在队列中
+    }
+
+    if queue.sender.is_none() {
+        match start_icon_worker(app_handle) {
+            Ok(sender) => queue.sender = Some(sender),
+            Err(e) => {
+                eprintln!("{}", e);
+                queue.pending.remove(bundle_id);
+                return;
+            }
+        }
+    }
+
+    let job = IconJob {
+        bundle_id: bundle_id.to_string(),
+        app_name: app_name.map(str::to_string),
+    };
+    if queue.sender.as_ref().is_some_and(|sender| sender.send(job).is_err()) {
+        queue.sender = None;
+        queue.pending.remove(bundle_id);
+    }
+}
+
+// 启动图标提取线程，提取结果交给数据库写入线程保存
+fn start_icon_worker(app_handle: &AppHandle) -> Result<Sender<IconJob>, String> {
+    let (sender, receiver) = channel::<IconJob>();
+    let app_handle = app_handle.clone();
+    std::thread::Builder::new()
+        .name("icon-extractor".to_string())
+        .spawn(move || {
+            for IconJob { bundle_id, app_name } in receiver {
+                let (app_path, icon_base64) = get_app_icon(&bundle_id);
+                if let Some(icon_data) = icon_base64 {
+                    let cached_id = bundle_id.clone();
+                    let version = app_path.as_deref().and_then(get_app_version);
+                    let result = submit_write(&app_handle, move |app, conn| {
+                        match cache_app_icon(conn, &cached_id, app_name.as_deref(), version.as_deref(), &icon_data) {
+                            Ok(()) => {
+                                println!("已缓存应用图标: {}", cached_id);
+                                emit_change_event(app, "clipboard-updated");
+                            }
+                            Err(e) => eprintln!("{}", e),
+                        }
+                    });
+                    if let Err(e) = result {
+                        eprintln!("保存应用图标失败: {}", e);
+                    }
+                }
+                // 写入任务已排在后续的缓存检查之前，此后再复制不会重复提取
+                if let Ok(mut queue) = ICON_QUEUE.lock() {
+                    queue.pending.remove(&bundle_id);
+                }
+            }
+        })
+        .map_err(|e| format!("启动图标提取线程失败: {}", e))?;
+    Ok(sender)
+}
+
 // 获取应用图标
 #[cfg(target_os = "macos")]
 pub fn get_app_icon(bundle_id: &str) -> (Option<String>, Option<String>) {
@@ -92,7 +312,19 @@ pub fn get_app_icon(bundle_id: &str) -> (Option<String>, Option<String>) {
     (None, None)
 }
 
-#[cfg(not(target_os = "macos"))]
+// Windows 的应用路径为可执行文件，Linux 为 .desktop 文件
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub fn get_app_icon(bundle_id: &str) -> (Option<String>, Option<String>) {
+    match get_app_path_by_bundle_id(bundle_id) {
+        Some(path) => match extract_app_icon(&path) {
+            Some(icon) => (Some(path), Some(icon)),
+            None => (None, None),
+        },
+        None => (None, None),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
 pub fn get_app_icon(_bundle_id: &str) -> (Option<String>, Option<String>) {
     (None, None)
 }
@@ -100,38 +332,122 @@ pub fn get_app_icon(_bundle_id: &str) -> (Option<String>, Option<String>) {
 // 通过 Bundle ID 获取应用路径
 #[cfg(target_os = "macos")]
 fn get_app_path_by_bundle_id(bundle_id: &str) -> Option<String> {
-    use std::process::Command;
+    use objc2::rc::autoreleasepool;
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use std::ffi::CString;
+
+    let bundle_id = CString::new(bundle_id).ok()?;
+    autoreleasepool(|_| unsafe {
+        let workspace: *mut AnyObject = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let bundle_id: *mut AnyObject = msg_send![class!(NSString), stringWithUTF8String: bundle_id.as_ptr()];
+        if workspace.is_null() || bundle_id.is_null() {
+            return None;
+        }
+        let url: *mut AnyObject = msg_send![workspace, URLForApplicationWithBundleIdentifier: bundle_id];
+        if url.is_null() {
+            return None;
+        }
+        let path: *mut AnyObject = msg_send![url, path];
+        ns_string_to_string(path).filter(|path| !path.is_empty())
+    })
+}
 
-    let script = format!(
-        r#"
-        tell application "System Events"
-            try
-                set appPath to POSIX path of (path to application id "{}")
-                return appPath
-            on error
-                return ""
-            end try
-        end tell
-        "#,
-        bundle_id
-    );
-
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .output()
-        .ok()?;
+#[cfg(target_os = "windows")]
+fn get_app_path_by_bundle_id(bundle_id: &str) -> Option<String> {
+    std::path::Path::new(bundle_id).is_file().then(|| bundle_id.to_string())
+}
 
-    if output.status.success() {
-        let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if !path.is_empty() && path != "missing value" {
-            return Some(path);
+#[cfg(target_os = "linux")]
+fn get_app_path_by_bundle_id(bundle_id: &str) -> Option<String> {
+    crate::app_info_linux::desktop_file_for(bundle_id).map(|path| path.to_string_lossy().into_owned())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn get_app_path_by_bundle_id(_bundle_id: &str) -> Option<String> {
+    None
+}
+
+// 读取应用包的版本号（版本 + 构建号），用于判断应用更新后是否需要刷新图标
+#[cfg(target_os = "macos")]
+fn get_app_version(app_path: &str) -> Option<String> {
+    use objc2::rc::autoreleasepool;
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use std::ffi::CString;
+
+    let path = CString::new(app_path).ok()?;
+    autoreleasepool(|_| unsafe {
+        let path: *mut AnyObject = msg_send![class!(NSString), stringWithUTF8String: path.as_ptr()];
+        if path.is_null() {
+            return None;
+        }
+        let bundle: *mut AnyObject = msg_send![class!(NSBundle), bundleWithPath: path];
+        if bundle.is_null() {
+            return None;
         }
-    }
 
+        let info_value = |key: &std::ffi::CStr| -> Option<String> {
+            let key: *mut AnyObject = msg_send![class!(NSString), stringWithUTF8String: key.as_ptr()];
+            let value: *mut AnyObject = msg_send![bundle, objectForInfoDictionaryKey: key];
+            if value.is_null() {
+                return None;
+            }
+            let description: *mut AnyObject = msg_send![value, description];
+            ns_string_to_string(description)
+        };
+        match (info_value(c"CFBundleShortVersionString"), info_value(c"CFBundleVersion")) {
+            (Some(version), Some(build)) if version != build => Some(format!("{} ({})", version, build)),
+            (version, build) => version.or(build),
+        }
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn get_app_version(app_path: &str) -> Option<String> {
+    crate::app_info_windows::app_version(app_path)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn get_app_version(_app_path: &str) -> Option<String> {
     None
 }
 
+// 重新提取过期（超过 APP_ICON_REFRESH_DAYS 天）或应用版本已变化的图标，返回刷新的数量
+pub fn refresh_stale_app_icons(conn: &Connection) -> Result<usize, OicopyError> {
+    let mut refreshed = 0;
+    for cached in list_cached_app_icons(conn)? {
+        let app_path = get_app_path_by_bundle_id(&cached.bundle_id);
+        let version = app_path.as_deref().and_then(get_app_version);
+        let updated = version.is_some() && version != cached.app_version;
+        if !cached.expired && !updated {
+            continue;
+        }
+
+        // 应用已卸载时保留旧图标，历史记录仍可显示
+        let Some(icon_data) = app_path.as_deref().and_then(extract_app_icon) else {
+            continue;
+        };
+        cache_app_icon(conn, &cached.bundle_id, cached.app_name.as_deref(), version.as_deref(), &icon_data)?;
+        refreshed += 1;
+    }
+    if refreshed > 0 {
+        println!("已刷新 {} 个应用图标", refreshed);
+    }
+    Ok(refreshed)
+}
+
+// Tauri 命令：刷新过期或应用已更新的图标
+#[tauri::command]
+pub async fn refresh_app_icons(app: tauri::AppHandle) -> Result<usize, OicopyError> {
+    let conn = init_database(&app)?;
+    let refreshed = refresh_stale_app_icons(&conn)?;
+    if refreshed > 0 {
+        emit_change_event(&app, "clipboard-updated");
+    }
+    Ok(refreshed)
+}
+
 // 从应用包中提取图标
 #[cfg(target_os = "macos")]
 fn extract_app_icon(app_path: &str) -> Option<String> {
@@ -186,6 +502,21 @@ fn extract_app_icon(app_path: &str) -> Option<String> {
     None
 }
 
+#[cfg(target_os = "windows")]
+fn extract_app_icon(app_path: &str) -> Option<String> {
+    crate::app_info_windows::extract_exe_icon_png(app_path).map(|png| BASE64_STANDARD.encode(png))
+}
+
+#[cfg(target_os = "linux")]
+fn extract_app_icon(app_path: &str) -> Option<String> {
+    crate::app_info_linux::desktop_icon_png(app_path).map(|png| BASE64_STANDARD.encode(png))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn extract_app_icon(_app_path: &str) -> Option<String> {
+    None
+}
+
 // 从 Info.plist 获取图标文件名
 #[cfg(target_os = "macos")]
 fn get_icon_name_from_plist(plist_path: &PathBuf) -> Option<String> {
@@ -282,8 +613,9 @@ fn convert_icns_to_png(_icns_data: &[u8]) -> Option<Vec<u8>> {
 
 // Tauri 命令：获取当前前台应用信息
 #[tauri::command]
-pub fn get_current_app_info() -> Result<AppInfo, String> {
-    get_frontmost_app()
+pub async fn get_current_app_info() -> Result<AppInfo, FeatureError> {
+    require_feature(Feature::SourceAppDetection).await?;
+    Ok(get_frontmost_app()?)
 }
 
 // Tauri 命令：获取指定应用的图标
@@ -292,7 +624,7 @@ pub fn get_app_icon_by_bundle_id(
     app: tauri::AppHandle,
     bundle_id: String,
 ) -> Result<Option<String>, String> {
-    use crate::db::{cache_app_icon, get_cached_app_icon, init_database};
+    use crate::db::get_cached_app_icon;
 
     // 1. 先从数据库缓存中查找
     if let Ok(conn) = init_database(&app) {
@@ -302,14 +634,56 @@ pub fn get_app_icon_by_bundle_id(
     }
 
     // 2. 如果缓存中没有，则获取图标
-    let (_, icon_base64) = get_app_icon(&bundle_id);
+    let (app_path, icon_base64) = get_app_icon(&bundle_id);
 
     // 3. 如果成功获取到图标，则缓存到数据库
     if let Some(ref icon_data) = icon_base64 {
         if let Ok(conn) = init_database(&app) {
-            let _ = cache_app_icon(&conn, &bundle_id, None, icon_data);
+            let version = app_path.as_deref().and_then(get_app_version);
+            let _ = cache_app_icon(&conn, &bundle_id, None, version.as_deref(), icon_data);
         }
     }
 
     Ok(icon_base64)
 }
+
+// Tauri 命令：批量获取应用图标，每个 Bundle ID 只返回一次
+#[tauri::command]
+pub fn get_app_icons(
+    app: tauri::AppHandle,
+    bundle_ids: Vec<String>,
+) -> Result<HashMap<String, Option<String>>, String> {
+    use crate::db::get_cached_app_icons;
+
+    // 去重并过滤无效的 Bundle ID
+    let unique_ids: Vec<String> = bundle_ids
+        .into_iter()
+        .filter(|id| !id.is_empty() && id != "unknown.bundle.id")
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let conn = init_database(&app)?;
+
+    // 1. 先从数据库缓存中批量查找
+    let cached = get_cached_app_icons(&conn, &unique_ids)?;
+
+    // 2. 缓存中没有的逐个提取并写入缓存
+    let mut icons = HashMap::with_capacity(unique_ids.len());
+    for bundle_id in unique_ids {
+        let icon = match cached.get(&bundle_id) {
+            Some(icon) => Some(icon.clone()),
+            None => {
+                let (app_path, icon_base64) = get_app_icon(&bundle_id);
+                if let Some(ref icon_data) = icon_base64 {
+                    let version = app_path.as_deref().and_then(get_app_version);
+                    let _ = cache_app_icon(&conn, &bundle_id, None, version.as_deref(), icon_data);
+                }
+                icon_base64
+            }
+        };
+        icons.insert(bundle_id, icon);
+    }
+
+    Ok(icons)
+}
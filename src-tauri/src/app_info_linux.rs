@@ -0,0 +1,368 @@
+// Linux 平台的前台应用识别和图标查找
+// 以 .desktop 文件 ID 作为应用标识，名称和图标来自 .desktop 文件
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// 按优先级查找的图标尺寸（只支持 PNG）
+const ICON_SIZES: &[&str] = &["256x256", "512x512", "128x128", "96x96", "64x64", "48x48", "32x32"];
+
+// 前台窗口信息：应用 ID（X11 的 WM_CLASS 或 Wayland 的 app_id）和可执行文件名
+struct ActiveWindow {
+    app_id: String,
+    exe_name: Option<String>,
+}
+
+// 从 .desktop 文件中读取的应用信息
+#[derive(Debug, Clone, Default, PartialEq)]
+struct DesktopEntry {
+    name: Option<String>,
+    icon: Option<String>,
+    startup_wm_class: Option<String>,
+}
+
+// 已解析的 .desktop 文件（按应用 ID 缓存，避免每次复制都扫描目录）
+static DESKTOP_FILES: Lazy<Mutex<HashMap<String, Option<PathBuf>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// 前台应用的名称和标识（.desktop 文件 ID，找不到时为应用 ID）
+pub fn active_app() -> Result<(String, String), String> {
+    let window = active_window()?;
+    let candidates: Vec<&str> = std::iter::once(window.app_id.as_str())
+        .chain(window.exe_name.as_deref())
+        .collect();
+
+    for candidate in candidates {
+        if let Some(path) = desktop_file_for(candidate) {
+            let id = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| candidate.to_string());
+            let name = read_desktop_entry(&path)
+                .and_then(|entry| entry.name)
+                .unwrap_or_else(|| window.app_id.clone());
+            return Ok((name, id));
+        }
+    }
+    Ok((window.app_id.clone(), window.app_id))
+}
+
+// 优先使用 Wayland 合成器的窗口列表，不支持时退回 X11（XWayland 窗口同样可以识别）
+fn active_window() -> Result<ActiveWindow, String> {
+    let wayland_error = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        match wayland::active_app_id() {
+            Ok(app_id) => return Ok(ActiveWindow { app_id, exe_name: None }),
+            Err(e) => Some(e),
+        }
+    } else {
+        None
+    };
+
+    x11::active_window().map_err(|e| match wayland_error {
+        Some(wayland_error) => format!("{}; {}", wayland_error, e),
+        None => e,
+    })
+}
+
+// XDG 数据目录（用户目录在前），另加 Flatpak 和 Snap 导出的应用目录
+fn data_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    match std::env::var_os("XDG_DATA_HOME") {
+        Some(home) => dirs.push(PathBuf::from(home)),
+        None => {
+            if let Some(home) = std::env::var_os("HOME") {
+                dirs.push(PathBuf::from(home).join(".local/share"));
+            }
+        }
+    }
+    let system = std::env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(system.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from));
+    dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+    dirs.push(PathBuf::from("/var/lib/snapd/desktop"));
+    dirs
+}
+
+// 查找应用 ID 对应的 .desktop 文件
+pub fn desktop_file_for(app_id: &str) -> Option<PathBuf> {
+    if app_id.is_empty() {
+        return None;
+    }
+    if let Some(cached) = DESKTOP_FILES.lock().ok().and_then(|cache| cache.get(app_id).cloned()) {
+        return cached;
+    }
+
+    let found = find_desktop_file(app_id);
+    if let Ok(mut cache) = DESKTOP_FILES.lock() {
+        cache.insert(app_id.to_string(), found.clone());
+    }
+    found
+}
+
+fn find_desktop_file(app_id: &str) -> Option<PathBuf> {
+    let app_dirs: Vec<PathBuf> = data_dirs().into_iter().map(|dir| dir.join("applications")).collect();
+    let lower = app_id.to_lowercase();
+
+    // 1. 文件名与应用 ID 相同
+    for dir in &app_dirs {
+        for name in [app_id, lower.as_str()] {
+            let path = dir.join(format!("{}.desktop", name));
+            if path.is_file() {
+                return Some(path);
+            }
+        }
+    }
+
+    // 2. StartupWMClass 匹配，或反向域名形式的文件名以应用 ID 结尾（如 org.gnome.Nautilus）
+    let suffix = format!(".{}", lower);
+    for dir in &app_dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if !path.extension().is_some_and(|ext| ext == "desktop") {
+                continue;
+            }
+            let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_lowercase()).unwrap_or_default();
+            let wm_class_matches = read_desktop_entry(&path)
+                .and_then(|entry| entry.startup_wm_class)
+                .is_some_and(|class| class.eq_ignore_ascii_case(app_id));
+            if wm_class_matches || stem.ends_with(&suffix) {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+fn read_desktop_entry(path: &Path) -> Option<DesktopEntry> {
+    fs::read_to_string(path).ok().map(|content| parse_desktop_entry(&content))
+}
+
+// 解析 .desktop 文件 [Desktop Entry] 组中的名称、图标和窗口类名（不处理本地化名称）
+fn parse_desktop_entry(content: &str) -> DesktopEntry {
+    let mut entry = DesktopEntry::default();
+    let mut in_main_group = false;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main_group = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_main_group {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "Name" => entry.name = Some(value),
+            "Icon" => entry.icon = Some(value),
+            "StartupWMClass" => entry.startup_wm_class = Some(value),
+            _ => {}
+        }
+    }
+    entry
+}
+
+// 读取 .desktop 文件指定的 PNG 图标
+pub fn desktop_icon_png(desktop_path: &str) -> Option<Vec<u8>> {
+    let icon = read_desktop_entry(Path::new(desktop_path))?.icon?;
+    let icon_path = Path::new(&icon);
+    if icon_path.is_absolute() {
+        return (icon_path.extension().is_some_and(|ext| ext == "png"))
+            .then(|| fs::read(icon_path).ok())
+            .flatten();
+    }
+
+    let mut candidates = Vec::new();
+    let icon_bases = std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".icons"))
+        .into_iter()
+        .chain(data_dirs().into_iter().map(|dir| dir.join("icons")));
+    for base in icon_bases {
+        for size in ICON_SIZES {
+            candidates.push(base.join("hicolor").join(size).join("apps").join(format!("{}.png", icon)));
+        }
+    }
+    candidates.push(PathBuf::from("/usr/share/pixmaps").join(format!("{}.png", icon)));
+
+    candidates.into_iter().find(|path| path.is_file()).and_then(|path| fs::read(path).ok())
+}
+
+// X11：根窗口的 _NET_ACTIVE_WINDOW 指向前台窗口，读取其 WM_CLASS 和进程 ID
+mod x11 {
+    use super::ActiveWindow;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, GetPropertyReply};
+
+    pub(super) fn active_window() -> Result<ActiveWindow, String> {
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| format!("连接 X11 失败: {}", e))?;
+        let root = conn.setup().roots[screen_num].root;
+
+        let atom = |name: &[u8]| -> Result<u32, String> {
+            conn.intern_atom(false, name)
+                .map_err(|e| e.to_string())?
+                .reply()
+                .map(|reply| reply.atom)
+                .map_err(|e| format!("查询 X11 原子失败: {}", e))
+        };
+        let property = |window: u32, property: u32, kind: AtomEnum, len: u32| -> Result<GetPropertyReply, String> {
+            conn.get_property(false, window, property, kind, 0, len)
+                .map_err(|e| e.to_string())?
+                .reply()
+                .map_err(|e| format!("读取窗口属性失败: {}", e))
+        };
+
+        let active = property(root, atom(b"_NET_ACTIVE_WINDOW")?, AtomEnum::WINDOW, 1)?;
+        let window = active
+            .value32()
+            .and_then(|mut values| values.next())
+            .filter(|window| *window != 0)
+            .ok_or_else(|| "当前没有前台窗口".to_string())?;
+
+        // WM_CLASS 为 "实例名\0类名\0"，类名通常与 .desktop 文件对应
+        let class = property(window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING, 256)?;
+        let mut parts = class.value.split(|byte| *byte == 0).filter(|part| !part.is_empty());
+        let instance = parts.next().map(|part| String::from_utf8_lossy(part).into_owned());
+        let class_name = parts.next().map(|part| String::from_utf8_lossy(part).into_owned());
+        let app_id = class_name.or(instance).ok_or_else(|| "前台窗口没有 WM_CLASS".to_string())?;
+
+        let exe_name = property(window, atom(b"_NET_WM_PID")?, AtomEnum::CARDINAL, 1)
+            .ok()
+            .and_then(|reply| reply.value32().and_then(|mut values| values.next()))
+            .and_then(|pid| std::fs::read_link(format!("/proc/{}/exe", pid)).ok())
+            .and_then(|exe| exe.file_name().map(|name| name.to_string_lossy().into_owned()));
+
+        Ok(ActiveWindow { app_id, exe_name })
+    }
+}
+
+// Wayland：通过 wlr-foreign-toplevel 协议列出窗口并找到处于激活状态的窗口
+// （wlroots 系合成器和 KDE 支持，GNOME 不提供该协议）
+mod wayland {
+    use std::collections::HashMap;
+    use wayland_client::backend::ObjectId;
+    use wayland_client::protocol::wl_registry;
+    use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle};
+    use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_handle_v1::{
+        self, ZwlrForeignToplevelHandleV1,
+    };
+    use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_manager_v1::{
+        self, ZwlrForeignToplevelManagerV1,
+    };
+
+    #[derive(Default)]
+    struct Toplevel {
+        app_id: Option<String>,
+        activated: bool,
+    }
+
+    #[derive(Default)]
+    struct ToplevelList {
+        manager: Option<ZwlrForeignToplevelManagerV1>,
+        toplevels: HashMap<ObjectId, Toplevel>,
+    }
+
+    impl Dispatch<wl_registry::WlRegistry, ()> for ToplevelList {
+        fn event(
+            state: &mut Self,
+            registry: &wl_registry::WlRegistry,
+            event: wl_registry::Event,
+            _: &(),
+            _: &Connection,
+            qh: &QueueHandle<Self>,
+        ) {
+            if let wl_registry::Event::Global { name, interface, version } = event {
+                if interface == ZwlrForeignToplevelManagerV1::interface().name {
+                    state.manager = Some(registry.bind(name, version.min(3), qh, ()));
+                }
+            }
+        }
+    }
+
+    impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for ToplevelList {
+        fn event(
+            _: &mut Self,
+            _: &ZwlrForeignToplevelManagerV1,
+            _: zwlr_foreign_toplevel_manager_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+        }
+
+        event_created_child!(ToplevelList, ZwlrForeignToplevelManagerV1, [
+            zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+        ]);
+    }
+
+    impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for ToplevelList {
+        fn event(
+            state: &mut Self,
+            handle: &ZwlrForeignToplevelHandleV1,
+            event: zwlr_foreign_toplevel_handle_v1::Event,
+            _: &(),
+            _: &Connection,
+            _: &QueueHandle<Self>,
+        ) {
+            let toplevel = state.toplevels.entry(handle.id()).or_default();
+            match event {
+                zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => toplevel.app_id = Some(app_id),
+                zwlr_foreign_toplevel_handle_v1::Event::State { state: states } => {
+                    let activated = zwlr_foreign_toplevel_handle_v1::State::Activated as u32;
+                    toplevel.activated = states
+                        .chunks_exact(4)
+                        .any(|value| u32::from_ne_bytes([value[0], value[1], value[2], value[3]]) == activated);
+                }
+                zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                    state.toplevels.remove(&handle.id());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(super) fn active_app_id() -> Result<String, String> {
+        let conn = Connection::connect_to_env().map_err(|e| format!("连接 Wayland 失败: {}", e))?;
+        let mut queue = conn.new_event_queue();
+        let qh = queue.handle();
+        conn.display().get_registry(&qh, ());
+
+        let mut list = ToplevelList::default();
+        queue.roundtrip(&mut list).map_err(|e| format!("读取 Wayland 全局对象失败: {}", e))?;
+        if list.manager.is_none() {
+            return Err("Wayland 合成器不支持 wlr-foreign-toplevel".to_string());
+        }
+        // 第一次往返收到窗口对象，第二次收到各窗口的属性
+        for _ in 0..2 {
+            queue.roundtrip(&mut list).map_err(|e| format!("读取 Wayland 窗口列表失败: {}", e))?;
+        }
+
+        list.toplevels
+            .into_values()
+            .find(|toplevel| toplevel.activated)
+            .and_then(|toplevel| toplevel.app_id)
+            .filter(|app_id| !app_id.is_empty())
+            .ok_or_else(|| "没有找到激活的 Wayland 窗口".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_desktop_entry() {
+        let content = "[Desktop Entry]\nName=Files\nName[zh_CN]=文件\nIcon=org.gnome.Nautilus\n\
+                       StartupWMClass=org.gnome.Nautilus\n\n[Desktop Action new-window]\nName=New Window\n";
+        assert_eq!(
+            parse_desktop_entry(content),
+            DesktopEntry {
+                name: Some("Files".to_string()),
+                icon: Some("org.gnome.Nautilus".to_string()),
+                startup_wm_class: Some("org.gnome.Nautilus".to_string()),
+            }
+        );
+    }
+}
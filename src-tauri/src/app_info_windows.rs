@@ -0,0 +1,226 @@
+// Windows 平台的前台应用识别和图标提取
+// 没有 Bundle ID 的概念，使用可执行文件的完整路径作为应用标识
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use std::ffi::c_void;
+use std::io::Cursor;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use windows_sys::Win32::Foundation::{CloseHandle, RECT};
+use windows_sys::Win32::Graphics::Gdi::{
+    CreateCompatibleDC, DeleteDC, DeleteObject, GetDIBits, GetObjectW, BITMAP, BITMAPINFO, BITMAPINFOHEADER,
+    BI_RGB, DIB_RGB_COLORS,
+};
+use windows_sys::Win32::Storage::FileSystem::{
+    GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW, VS_FIXEDFILEINFO,
+};
+use windows_sys::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows_sys::Win32::UI::Shell::SHDefExtractIconW;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    DestroyIcon, GetForegroundWindow, GetIconInfo, GetWindowRect, GetWindowThreadProcessId, HICON, ICONINFO,
+};
+
+// 提取图标的边长（像素），可执行文件没有该尺寸时系统会缩放
+const ICON_SIZE: u32 = 256;
+
+// 转换为以 0 结尾的 UTF-16 字符串
+fn wide(text: &str) -> Vec<u16> {
+    std::ffi::OsStr::new(text).encode_wide().chain(std::iter::once(0)).collect()
+}
+
+// 前台窗口所属进程的可执行文件路径
+pub fn foreground_exe_path() -> Result<String, String> {
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return Err("当前没有前台窗口".to_string());
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(window, &mut pid);
+        if pid == 0 {
+            return Err("获取前台窗口进程失败".to_string());
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return Err(format!("打开进程 {} 失败: {}", pid, std::io::Error::last_os_error()));
+        }
+        let mut buffer = vec![0u16; 1024];
+        let mut len = buffer.len() as u32;
+        let ok = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, buffer.as_mut_ptr(), &mut len);
+        CloseHandle(process);
+        if ok == 0 {
+            return Err(format!("获取进程路径失败: {}", std::io::Error::last_os_error()));
+        }
+        Ok(String::from_utf16_lossy(&buffer[..len as usize]))
+    }
+}
+
+// 前台窗口中心点的屏幕坐标（物理像素），用于确定面板显示的显示器
+pub fn foreground_window_center() -> Option<(f64, f64)> {
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return None;
+        }
+        let mut rect: RECT = std::mem::zeroed();
+        if GetWindowRect(window, &mut rect) == 0 {
+            return None;
+        }
+        Some(((rect.left + rect.right) as f64 / 2.0, (rect.top + rect.bottom) as f64 / 2.0))
+    }
+}
+
+// 读取可执行文件的版本信息块
+fn version_info(path: &str) -> Option<Vec<u8>> {
+    let path = wide(path);
+    unsafe {
+        let size = GetFileVersionInfoSizeW(path.as_ptr(), std::ptr::null_mut());
+        if size == 0 {
+            return None;
+        }
+        let mut data = vec![0u8; size as usize];
+        if GetFileVersionInfoW(path.as_ptr(), 0, size, data.as_mut_ptr() as *mut c_void) == 0 {
+            return None;
+        }
+        Some(data)
+    }
+}
+
+// 在版本信息块中查询指定项，返回指向块内数据的指针和长度
+fn query_version_value(data: &[u8], sub_block: &str) -> Option<(*const c_void, u32)> {
+    let sub_block = wide(sub_block);
+    let mut value: *mut c_void = std::ptr::null_mut();
+    let mut len = 0u32;
+    let ok = unsafe { VerQueryValueW(data.as_ptr() as *const c_void, sub_block.as_ptr(), &mut value, &mut len) };
+    (ok != 0 && !value.is_null() && len > 0).then_some((value as *const c_void, len))
+}
+
+// 应用的显示名称：版本信息中的 FileDescription，没有时使用文件名
+pub fn app_display_name(path: &str) -> String {
+    let description = version_info(path).and_then(|data| {
+        // Translation 为 (语言, 代码页) 对，取第一组
+        let (translation, _) = query_version_value(&data, "\\VarFileInfo\\Translation")?;
+        let (language, codepage) = unsafe {
+            let pair = translation as *const u16;
+            (*pair, *pair.add(1))
+        };
+        let key = format!("\\StringFileInfo\\{:04x}{:04x}\\FileDescription", language, codepage);
+        let (value, len) = query_version_value(&data, &key)?;
+        let chars = unsafe { std::slice::from_raw_parts(value as *const u16, len as usize) };
+        let text = String::from_utf16_lossy(chars).trim_end_matches('\0').trim().to_string();
+        (!text.is_empty()).then_some(text)
+    });
+
+    description.unwrap_or_else(|| {
+        Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string())
+    })
+}
+
+// 可执行文件的版本号（主.次.修订.构建）
+pub fn app_version(path: &str) -> Option<String> {
+    let data = version_info(path)?;
+    let (value, len) = query_version_value(&data, "\\")?;
+    if (len as usize) < std::mem::size_of::<VS_FIXEDFILEINFO>() {
+        return None;
+    }
+    let info = unsafe { &*(value as *const VS_FIXEDFILEINFO) };
+    Some(format!(
+        "{}.{}.{}.{}",
+        info.dwFileVersionMS >> 16,
+        info.dwFileVersionMS & 0xffff,
+        info.dwFileVersionLS >> 16,
+        info.dwFileVersionLS & 0xffff
+    ))
+}
+
+// 提取可执行文件的图标并编码为 PNG
+pub fn extract_exe_icon_png(path: &str) -> Option<Vec<u8>> {
+    let wide_path = wide(path);
+    let mut icon: HICON = std::ptr::null_mut();
+    let result = unsafe {
+        SHDefExtractIconW(wide_path.as_ptr(), 0, 0, &mut icon, std::ptr::null_mut(), ICON_SIZE)
+    };
+    if result != 0 || icon.is_null() {
+        return None;
+    }
+    let png = unsafe { icon_to_png(icon) };
+    unsafe { DestroyIcon(icon) };
+    png
+}
+
+// 读取图标的彩色位图（32 位 BGRA）并转换为 PNG
+unsafe fn icon_to_png(icon: HICON) -> Option<Vec<u8>> {
+    let mut info: ICONINFO = std::mem::zeroed();
+    if GetIconInfo(icon, &mut info) == 0 {
+        return None;
+    }
+    let png = bitmap_to_png(info.hbmColor);
+    if !info.hbmColor.is_null() {
+        DeleteObject(info.hbmColor);
+    }
+    if !info.hbmMask.is_null() {
+        DeleteObject(info.hbmMask);
+    }
+    png
+}
+
+unsafe fn bitmap_to_png(bitmap: windows_sys::Win32::Graphics::Gdi::HBITMAP) -> Option<Vec<u8>> {
+    if bitmap.is_null() {
+        return None;
+    }
+    let mut header: BITMAP = std::mem::zeroed();
+    if GetObjectW(bitmap, std::mem::size_of::<BITMAP>() as i32, &mut header as *mut BITMAP as *mut c_void) == 0 {
+        return None;
+    }
+    let (width, height) = (header.bmWidth, header.bmHeight.abs());
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+
+    let mut bitmap_info: BITMAPINFO = std::mem::zeroed();
+    bitmap_info.bmiHeader = BITMAPINFOHEADER {
+        biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+        biWidth: width,
+        biHeight: -height, // 负值表示自上而下的行顺序
+        biPlanes: 1,
+        biBitCount: 32,
+        biCompression: BI_RGB,
+        ..std::mem::zeroed()
+    };
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    let dc = CreateCompatibleDC(std::ptr::null_mut());
+    let lines = GetDIBits(
+        dc,
+        bitmap,
+        0,
+        height as u32,
+        pixels.as_mut_ptr() as *mut c_void,
+        &mut bitmap_info,
+        DIB_RGB_COLORS,
+    );
+    DeleteDC(dc);
+    if lines == 0 {
+        return None;
+    }
+
+    // BGRA 转为 RGBA；旧式图标没有透明通道时视为完全不透明
+    let has_alpha = pixels.chunks_exact(4).any(|pixel| pixel[3] != 0);
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+        if !has_alpha {
+            pixel[3] = 255;
+        }
+    }
+
+    let image = RgbaImage::from_raw(width as u32, height as u32, pixels)?;
+    let mut png = Vec::new();
+    DynamicImage::ImageRgba8(image)
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .ok()?;
+    Some(png)
+}
@@ -0,0 +1,47 @@
+use crate::clipboard_management::{get_item_full_content, is_monitoring_paused, paste_item_from_shortcut, set_monitoring_paused};
+use crate::db::{open_connection, ClipboardHistoryItem, ConnectionRole};
+use crate::error::OicopyError;
+use crate::lock_mode::ensure_unlocked;
+use rusqlite::{params, OptionalExtension};
+use tauri::AppHandle;
+
+// 自动化操作（快捷指令、AppleScript 和脚本）：通过控制 socket（oicopy 命令行工具）和 oicopy:// 链接调用
+// 例如在快捷指令中使用“运行 Shell 脚本”执行 `oicopy latest`，或在 AppleScript 中 `do shell script "oicopy paste 2"`
+// 记录序号按复制时间从新到旧排列，从 1 开始，不考虑置顶
+
+// 第 index 条记录的 ID
+pub fn nth_item_id(app: &AppHandle, index: u32) -> Result<i64, OicopyError> {
+    if index == 0 {
+        return Err(OicopyError::Validation("记录序号从 1 开始".to_string()));
+    }
+    let conn = open_connection(app, ConnectionRole::Analytics)?;
+    ensure_unlocked(&conn)?;
+    conn.query_row(
+        "SELECT id FROM clipboard_history ORDER BY id DESC LIMIT 1 OFFSET ?1",
+        params![index - 1],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| OicopyError::Db(format!("查询记录失败: {}", e)))?
+    .ok_or_else(|| OicopyError::NotFound(format!("第 {} 条记录不存在", index)))
+}
+
+// 最近一条记录的完整内容
+pub async fn latest_item(app: &AppHandle) -> Result<ClipboardHistoryItem, OicopyError> {
+    let id = nth_item_id(app, 1)?;
+    get_item_full_content(app.clone(), id).await
+}
+
+// 将第 index 条记录粘贴到当前前台应用
+pub async fn paste_nth_item(app: &AppHandle, index: u32) -> Result<i64, String> {
+    let id = nth_item_id(app, index)?;
+    paste_item_from_shortcut(app, id).await.map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+// 切换剪贴板监听，返回切换后是否暂停
+pub fn toggle_monitoring(app: &AppHandle) -> Result<bool, String> {
+    let paused = !is_monitoring_paused(app);
+    set_monitoring_paused(app, paused)?;
+    Ok(paused)
+}
@@ -0,0 +1,289 @@
+use crate::change_log::emit_change_event;
+use crate::db::{database_paths, get_settings, init_database, open_connection, save_settings, AppSettings, ConnectionRole};
+use crate::db_encryption::{apply_key, database_key};
+use crate::db_integrity::check_integrity;
+use crate::error::OicopyError;
+use crate::lock_mode::ensure_unlocked;
+use chrono::{DateTime, Local};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::AppHandle;
+
+// 定时检查是否需要备份的间隔
+const BACKUP_CHECK_INTERVAL_SECS: u64 = 10 * 60;
+const BACKUP_PREFIX: &str = "clipboard_history-";
+const BACKUP_EXTENSION: &str = ".db";
+// 备份 API 每步复制的页数，每步之间让出数据库锁
+const PAGES_PER_STEP: std::os::raw::c_int = 256;
+
+// 备份设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    pub interval_hours: i64,
+    pub keep_count: i64,
+    pub folder: Option<String>, // 为空时使用数据目录下的 backups
+}
+
+impl BackupConfig {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            enabled: settings.backup_enabled,
+            interval_hours: settings.backup_interval_hours.max(1),
+            keep_count: settings.backup_keep_count.max(1),
+            folder: settings.backup_folder.clone(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(1..=24 * 30).contains(&self.interval_hours) {
+            return Err("备份间隔必须在 1 小时到 30 天之间".to_string());
+        }
+        if !(1..=100).contains(&self.keep_count) {
+            return Err("保留的备份数量必须在 1 到 100 之间".to_string());
+        }
+        if let Some(folder) = &self.folder {
+            if !Path::new(folder).is_absolute() {
+                return Err(format!("备份目录必须是绝对路径: {}", folder));
+            }
+        }
+        Ok(())
+    }
+}
+
+// 备份文件信息
+#[derive(Debug, Serialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+}
+
+// 备份目录，不存在时创建
+fn backup_dir(app_handle: &AppHandle, settings: &AppSettings) -> Result<PathBuf, String> {
+    let dir = match settings.backup_folder.as_deref().filter(|folder| !folder.trim().is_empty()) {
+        Some(folder) => PathBuf::from(folder),
+        None => database_paths(app_handle)?.0.join("backups"),
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建备份目录失败: {}", e))?;
+    Ok(dir)
+}
+
+fn is_backup_file_name(name: &str) -> bool {
+    name.starts_with(BACKUP_PREFIX) && name.ends_with(BACKUP_EXTENSION) && !name.contains(['/', '\\'])
+}
+
+// 列出备份，最新的在前（文件名中的时间戳可按字典序排序）
+fn list_backup_files(dir: &Path) -> Result<Vec<BackupInfo>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("读取备份目录失败: {}", e))?;
+    let mut backups: Vec<BackupInfo> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !is_backup_file_name(&file_name) {
+                return None;
+            }
+            let metadata = entry.metadata().ok()?;
+            let created_at = metadata
+                .modified()
+                .map(|time| DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            Some(BackupInfo { file_name, size_bytes: metadata.len(), created_at })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(backups)
+}
+
+// 为连接设置与主数据库相同的密钥，SQLCipher 只能在密钥相同的数据库之间使用备份 API
+fn open_keyed(path: &Path, flags: OpenFlags) -> Result<Connection, String> {
+    let key = database_key().ok_or_else(|| "数据库尚未初始化".to_string())?;
+    let conn = Connection::open_with_flags(path, flags).map_err(|e| format!("打开备份文件失败: {}", e))?;
+    apply_key(&conn, key)?;
+    Ok(conn)
+}
+
+// 使用 SQLite 备份 API 创建快照（在 WAL 模式下也能得到一致的数据），完成后删除超出保留数量的旧备份
+// 图片文件保存在数据目录的图片存储中，不包含在快照内
+pub fn create_backup(app_handle: &AppHandle) -> Result<BackupInfo, String> {
+    let source = open_connection(app_handle, ConnectionRole::Analytics)?;
+    let settings = get_settings(&source)?;
+    let dir = backup_dir(app_handle, &settings)?;
+
+    let file_name = format!("{}{}{}", BACKUP_PREFIX, Local::now().format("%Y%m%d-%H%M%S"), BACKUP_EXTENSION);
+    let path = dir.join(&file_name);
+    // 先写入临时文件，避免列表和轮换看到未完成的备份
+    let partial_path = dir.join(format!("{}.partial", file_name));
+    let _ = std::fs::remove_file(&partial_path);
+
+    let copied = (|| {
+        let mut dest = open_keyed(&partial_path, OpenFlags::default())?;
+        let backup = Backup::new(&source, &mut dest).map_err(|e| format!("创建备份失败: {}", e))?;
+        backup
+            .run_to_completion(PAGES_PER_STEP, Duration::from_millis(10), None)
+            .map_err(|e| format!("备份数据库失败: {}", e))
+    })();
+    if let Err(e) = copied {
+        let _ = std::fs::remove_file(&partial_path);
+        return Err(e);
+    }
+    std::fs::rename(&partial_path, &path).map_err(|e| format!("保存备份文件失败: {}", e))?;
+
+    let backups = list_backup_files(&dir)?;
+    for old in backups.iter().skip(settings.backup_keep_count.max(1) as usize) {
+        match std::fs::remove_file(dir.join(&old.file_name)) {
+            Ok(_) => println!("已删除旧备份: {}", old.file_name),
+            Err(e) => eprintln!("删除旧备份失败: {}", e),
+        }
+    }
+
+    println!("数据库已备份到 {:?}", path);
+    backups
+        .into_iter()
+        .find(|backup| backup.file_name == file_name)
+        .ok_or_else(|| "备份文件不存在".to_string())
+}
+
+// 用备份替换当前数据，恢复前先备份当前数据，恢复错误时可以撤销
+fn restore_backup(app_handle: &AppHandle, file_name: &str) -> Result<(), String> {
+    if !is_backup_file_name(file_name) {
+        return Err(format!("无效的备份文件名: {}", file_name));
+    }
+    let mut conn = init_database(app_handle)?;
+    ensure_unlocked(&conn)?;
+    let dir = backup_dir(app_handle, &get_settings(&conn)?)?;
+    let path = dir.join(file_name);
+    if !path.exists() {
+        return Err(format!("备份文件不存在: {}", file_name));
+    }
+
+    let source = open_keyed(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let report = check_integrity(&source, false)?;
+    if !report.ok {
+        return Err(format!("备份文件已损坏: {}", report.problems.join("; ")));
+    }
+
+    create_backup(app_handle)?;
+
+    // 备份 API 直接写入正在使用的数据库，其他连接在下次读取时看到恢复后的数据
+    let backup = Backup::new(&source, &mut *conn).map_err(|e| format!("恢复备份失败: {}", e))?;
+    backup
+        .run_to_completion(PAGES_PER_STEP, Duration::from_millis(10), None)
+        .map_err(|e| format!("恢复备份失败: {}", e))?;
+
+    println!("已从备份恢复数据库: {}", file_name);
+    Ok(())
+}
+
+// 最新备份距今的时长，没有备份时为空
+fn last_backup_age(dir: &Path) -> Option<Duration> {
+    let entries = std::fs::read_dir(dir).ok()?;
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| is_backup_file_name(&entry.file_name().to_string_lossy()))
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+        .map(|modified| modified.elapsed().unwrap_or_default())
+}
+
+// 启动定时备份任务，每次检查时读取设置，修改后无需重启即可生效
+pub fn start_backup_scheduler(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(BACKUP_CHECK_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+
+            let Some(settings) = init_database(&app_handle).ok().and_then(|conn| get_settings(&conn).ok()) else {
+                continue;
+            };
+            if !settings.backup_enabled {
+                continue;
+            }
+            let Ok(dir) = backup_dir(&app_handle, &settings) else {
+                continue;
+            };
+            let interval_secs = settings.backup_interval_hours.max(1) as u64 * 3600;
+            if last_backup_age(&dir).is_some_and(|age| age.as_secs() < interval_secs) {
+                continue;
+            }
+
+            let app_handle = app_handle.clone();
+            match tauri::async_runtime::spawn_blocking(move || create_backup(&app_handle)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => eprintln!("定时备份失败: {}", e),
+                Err(e) => eprintln!("定时备份任务失败: {}", e),
+            }
+        }
+    });
+}
+
+// 获取备份设置命令
+#[tauri::command]
+pub async fn get_backup_config(app: AppHandle) -> Result<BackupConfig, OicopyError> {
+    let conn = init_database(&app)?;
+    Ok(BackupConfig::from_settings(&get_settings(&conn)?))
+}
+
+// 保存备份设置命令，定时任务每次检查时读取，无需重启
+#[tauri::command]
+pub async fn set_backup_config(app: AppHandle, config: BackupConfig) -> Result<BackupConfig, OicopyError> {
+    config.validate().map_err(OicopyError::Validation)?;
+
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let mut settings = get_settings(&conn)?;
+    settings.backup_enabled = config.enabled;
+    settings.backup_interval_hours = config.interval_hours;
+    settings.backup_keep_count = config.keep_count;
+    settings.backup_folder = config.folder.clone().filter(|folder| !folder.trim().is_empty());
+    backup_dir(&app, &settings).map_err(OicopyError::Io)?;
+    save_settings(&conn, &settings)?;
+
+    println!("备份设置已更新: {:?}", config);
+    Ok(config)
+}
+
+// 列出已有备份命令
+#[tauri::command]
+pub async fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, OicopyError> {
+    let conn = init_database(&app)?;
+    let dir = backup_dir(&app, &get_settings(&conn)?).map_err(OicopyError::Io)?;
+    list_backup_files(&dir).map_err(OicopyError::Io)
+}
+
+// 立即备份命令
+#[tauri::command]
+pub async fn create_backup_now(app: AppHandle) -> Result<BackupInfo, OicopyError> {
+    let backup = tauri::async_runtime::spawn_blocking(move || create_backup(&app))
+        .await
+        .map_err(|e| format!("备份任务失败: {}", e))??;
+    Ok(backup)
+}
+
+// 从备份恢复命令
+#[tauri::command]
+pub async fn restore_from_backup(app: AppHandle, file_name: String) -> Result<(), OicopyError> {
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || restore_backup(&app_handle, &file_name))
+        .await
+        .map_err(|e| format!("恢复任务失败: {}", e))??;
+    emit_change_event(&app, "clipboard-updated");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_file_name() {
+        assert!(is_backup_file_name("clipboard_history-20240101-120000.db"));
+        assert!(!is_backup_file_name("clipboard_history-20240101-120000.db.partial"));
+        assert!(!is_backup_file_name("clipboard_history.db"));
+        assert!(!is_backup_file_name("clipboard_history-../../etc.db/x.db"));
+    }
+}
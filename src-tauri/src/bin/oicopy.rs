@@ -0,0 +1,116 @@
+// oicopy 命令行工具：通过控制 socket 查询和操作正在运行的 oicopy
+use oicopy_tauri_nspanel_lib::control_socket::{default_socket_path, ControlRequest, ControlResponse};
+use std::process::exit;
+
+const USAGE: &str = "用法: oicopy [--json] <命令>
+
+命令:
+  history [-n 数量]          列出最近的记录
+  latest                     输出最近一条记录的内容
+  get <id>                   输出记录内容
+  copy <id>                  将记录写回剪贴板
+  paste <序号>               粘贴第几条记录到前台应用（从新到旧，从 1 开始）
+  search <关键词> [-n 数量]  搜索记录
+  pause                      暂停剪贴板监听
+  resume                     恢复剪贴板监听
+  toggle                     切换剪贴板监听
+
+在快捷指令中可通过“运行 Shell 脚本”调用，如 oicopy latest
+
+socket 路径可通过环境变量 OICOPY_SOCKET 指定";
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    exit(1)
+}
+
+// 解析命令行参数，返回请求和是否输出原始 JSON
+fn parse_args(args: Vec<String>) -> Result<(ControlRequest, bool), String> {
+    let json = args.iter().any(|arg| arg == "--json");
+    let mut limit = None;
+    let mut words = Vec::new();
+    let mut iter = args.into_iter().filter(|arg| arg != "--json");
+    while let Some(arg) = iter.next() {
+        if arg == "-n" {
+            let value = iter.next().ok_or("-n 需要一个数量")?;
+            limit = Some(value.parse::<u32>().map_err(|_| format!("无效的数量: {}", value))?);
+        } else {
+            words.push(arg);
+        }
+    }
+    let parse_id = |value: Option<&String>| -> Result<i64, String> {
+        let value = value.ok_or("缺少记录 ID")?;
+        value.parse().map_err(|_| format!("无效的记录 ID: {}", value))
+    };
+
+    let request = match words.first().map(String::as_str) {
+        Some("history") => ControlRequest::History { limit },
+        Some("get") => ControlRequest::Get { id: parse_id(words.get(1))? },
+        Some("copy") => ControlRequest::Copy { id: parse_id(words.get(1))? },
+        Some("search") if words.len() > 1 => ControlRequest::Search { query: words[1..].join(" "), limit },
+        Some("latest") => ControlRequest::Latest,
+        Some("paste") => {
+            let value = words.get(1).ok_or("缺少记录序号")?;
+            let index = value.parse::<u32>().ok().filter(|index| *index > 0);
+            ControlRequest::PasteIndex { index: index.ok_or_else(|| format!("无效的记录序号: {}", value))? }
+        }
+        Some("toggle") => ControlRequest::ToggleMonitoring,
+        Some("pause") => ControlRequest::Pause { paused: true },
+        Some("resume") => ControlRequest::Pause { paused: false },
+        _ => return Err(USAGE.to_string()),
+    };
+    Ok((request, json))
+}
+
+// 列表每行输出 ID、时间、类型和单行预览
+fn print_items(data: &serde_json::Value) {
+    for item in data.as_array().into_iter().flatten() {
+        let preview = item["preview"].as_str().unwrap_or_default().replace(['\n', '\r', '\t'], " ");
+        println!(
+            "{}\t{}\t{}\t{}",
+            item["id"],
+            item["timestamp"].as_str().unwrap_or_default(),
+            item["content_type"].as_str().unwrap_or_default(),
+            preview
+        );
+    }
+}
+
+fn print_response(request: &ControlRequest, response: &ControlResponse) {
+    match request {
+        ControlRequest::History { .. } | ControlRequest::Search { .. } => print_items(&response.data),
+        ControlRequest::Get { .. } | ControlRequest::Latest => match response.data["content"].as_str() {
+            Some(content) => println!("{}", content),
+            None => println!("{}", response.data),
+        },
+        ControlRequest::Copy { id } => println!("已将记录 {} 写回剪贴板", id),
+        ControlRequest::PasteIndex { index } => println!("已粘贴第 {} 条记录", index),
+        ControlRequest::Pause { .. } | ControlRequest::ToggleMonitoring => {
+            let paused = response.data.as_bool().unwrap_or_default();
+            println!("剪贴板监听已{}", if paused { "暂停" } else { "恢复" });
+        }
+    }
+}
+
+#[cfg(unix)]
+fn main() {
+    use oicopy_tauri_nspanel_lib::control_socket::send_request;
+
+    let (request, json) = parse_args(std::env::args().skip(1).collect()).unwrap_or_else(|e| fail(&e));
+    let path = default_socket_path().unwrap_or_else(|| fail("无法确定 socket 路径，请设置 OICOPY_SOCKET"));
+    let response = send_request(&path, &request).unwrap_or_else(|e| fail(&e));
+    if !response.ok {
+        fail(response.error.as_deref().unwrap_or("请求失败"));
+    }
+    if json {
+        println!("{}", response.data);
+    } else {
+        print_response(&request, &response);
+    }
+}
+
+#[cfg(not(unix))]
+fn main() {
+    let _ = (parse_args, print_response);
+    fail("当前平台不支持命令行控制");
+}
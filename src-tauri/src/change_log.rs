@@ -0,0 +1,167 @@
+use crate::db::init_database;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+// 变更日志保留的最大条数，更早的变更需要前端全量刷新
+const MAX_CHANGE_LOG_ROWS: i64 = 10000;
+// 单次返回的变更记录上限，超出时要求全量刷新
+const MAX_CHANGES_PER_QUERY: i64 = 1000;
+
+// 创建变更日志表，并通过触发器记录 clipboard_history 的所有修改
+// seq 使用 AUTOINCREMENT，删除旧记录后也不会重复使用
+pub fn create_change_log(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS change_log (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            item_id INTEGER NOT NULL,
+            change_kind TEXT NOT NULL,
+            changed_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TRIGGER IF NOT EXISTS change_log_insert AFTER INSERT ON clipboard_history BEGIN
+            INSERT INTO change_log(item_id, change_kind) VALUES (new.id, 'added');
+        END;
+        CREATE TRIGGER IF NOT EXISTS change_log_update AFTER UPDATE ON clipboard_history BEGIN
+            INSERT INTO change_log(item_id, change_kind) VALUES (new.id, 'updated');
+        END;
+        CREATE TRIGGER IF NOT EXISTS change_log_delete AFTER DELETE ON clipboard_history BEGIN
+            INSERT INTO change_log(item_id, change_kind) VALUES (old.id, 'deleted');
+        END;",
+    )
+    .map_err(|e| format!("创建变更日志失败: {}", e))
+}
+
+// 当前的变更序号（尚无变更时为 0）
+pub fn current_change_seq(conn: &Connection) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT seq FROM sqlite_sequence WHERE name = 'change_log'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .optional()
+    .map(|seq| seq.unwrap_or(0))
+    .map_err(|e| format!("查询变更序号失败: {}", e))
+}
+
+// 只保留最近的变更记录
+pub fn prune_change_log(conn: &Connection) -> Result<usize, String> {
+    conn.execute(
+        "DELETE FROM change_log WHERE seq <= (SELECT MAX(seq) FROM change_log) - ?1",
+        params![MAX_CHANGE_LOG_ROWS],
+    )
+    .map_err(|e| format!("清理变更日志失败: {}", e))
+}
+
+// 数据变更事件的负载
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeEvent {
+    pub seq: i64,
+}
+
+// 发送携带当前变更序号的数据变更事件
+pub fn emit_change_event(app: &AppHandle, event: &str) {
+    let seq = match init_database(app).map_err(String::from).and_then(|conn| current_change_seq(&conn)) {
+        Ok(seq) => seq,
+        Err(e) => {
+            eprintln!("{}", e);
+            0
+        }
+    };
+    if let Err(e) = app.emit(event, ChangeEvent { seq }) {
+        eprintln!("通知前端数据变更失败 ({}): {}", event, e);
+    }
+}
+
+// 单条记录的变更（同一记录多次变更时只保留最后一次）
+#[derive(Debug, Serialize)]
+pub struct ChangeRecord {
+    pub seq: i64,
+    pub item_id: i64,
+    pub kind: String, // added / updated / deleted
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangeSet {
+    pub current_seq: i64,
+    pub full_resync: bool, // 变更已被清理或数量过多，前端需要重新加载列表
+    pub changes: Vec<ChangeRecord>,
+}
+
+fn changes_since(conn: &Connection, since: i64) -> Result<ChangeSet, String> {
+    let current_seq = current_change_seq(conn)?;
+    let oldest_seq: Option<i64> = conn
+        .query_row("SELECT MIN(seq) FROM change_log", [], |row| row.get(0))
+        .map_err(|e| format!("查询变更日志失败: {}", e))?;
+
+    // 序号比当前还大说明数据库已被重建；早于保留范围说明中间的变更已被清理
+    let pruned = oldest_seq.is_some_and(|oldest| since + 1 < oldest);
+    if since > current_seq || pruned {
+        return Ok(ChangeSet { current_seq, full_resync: true, changes: Vec::new() });
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.seq, c.item_id, c.change_kind FROM change_log c
+             JOIN (SELECT item_id, MAX(seq) AS seq FROM change_log WHERE seq > ?1 GROUP BY item_id) latest
+               ON latest.seq = c.seq
+             ORDER BY c.seq
+             LIMIT ?2",
+        )
+        .map_err(|e| format!("准备变更查询失败: {}", e))?;
+    let changes = stmt
+        .query_map(params![since, MAX_CHANGES_PER_QUERY + 1], |row| {
+            Ok(ChangeRecord {
+                seq: row.get(0)?,
+                item_id: row.get(1)?,
+                kind: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("查询变更失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("处理变更数据失败: {}", e))?;
+
+    if changes.len() as i64 > MAX_CHANGES_PER_QUERY {
+        return Ok(ChangeSet { current_seq, full_resync: true, changes: Vec::new() });
+    }
+    Ok(ChangeSet { current_seq, full_resync: false, changes })
+}
+
+// Tauri命令：获取指定序号之后的变更，供面板在错过事件（休眠、刷新）后对账
+#[tauri::command]
+pub async fn get_changes_since(app: AppHandle, seq: i64) -> Result<ChangeSet, String> {
+    let conn = init_database(&app)?;
+    changes_since(&conn, seq)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE clipboard_history (id INTEGER PRIMARY KEY, content TEXT);")
+            .unwrap();
+        create_change_log(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_changes_are_compacted_per_item() {
+        let conn = setup();
+        conn.execute_batch(
+            "INSERT INTO clipboard_history (id, content) VALUES (1, 'a'), (2, 'b');
+             UPDATE clipboard_history SET content = 'c' WHERE id = 1;
+             DELETE FROM clipboard_history WHERE id = 2;",
+        )
+        .unwrap();
+
+        let set = changes_since(&conn, 0).unwrap();
+        assert_eq!(set.current_seq, 4);
+        assert!(!set.full_resync);
+        let changes: Vec<(i64, &str)> = set.changes.iter().map(|c| (c.item_id, c.kind.as_str())).collect();
+        assert_eq!(changes, vec![(1, "updated"), (2, "deleted")]);
+
+        assert!(changes_since(&conn, 4).unwrap().changes.is_empty());
+        assert!(changes_since(&conn, 99).unwrap().full_resync);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::clipboard_content::ClipboardContent;
+use crate::item_actions::detect_url;
+use regex::Regex;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+// 捕获时识别的内容类别（与数据库 category 列及前端筛选取值保持一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentCategory {
+    Url,
+    Email,
+    Phone,
+    Color,
+    Code,
+    Json,
+    FilePath,
+    PlainText,
+}
+
+impl ContentCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentCategory::Url => "url",
+            ContentCategory::Email => "email",
+            ContentCategory::Phone => "phone",
+            ContentCategory::Color => "color",
+            ContentCategory::Code => "code",
+            ContentCategory::Json => "json",
+            ContentCategory::FilePath => "file_path",
+            ContentCategory::PlainText => "plain_text",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "url" => Some(ContentCategory::Url),
+            "email" => Some(ContentCategory::Email),
+            "phone" => Some(ContentCategory::Phone),
+            "color" => Some(ContentCategory::Color),
+            "code" => Some(ContentCategory::Code),
+            "json" => Some(ContentCategory::Json),
+            "file_path" => Some(ContentCategory::FilePath),
+            "plain_text" => Some(ContentCategory::PlainText),
+            _ => None,
+        }
+    }
+}
+
+impl ToSql for ContentCategory {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for ContentCategory {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let raw = value.as_str()?;
+        ContentCategory::parse(raw)
+            .ok_or_else(|| FromSqlError::Other(format!("未知的内容类别: {}", raw).into()))
+    }
+}
+
+// 单行内容超过该长度时不再做邮箱、电话等识别
+const MAX_SINGLE_VALUE_CHARS: usize = 512;
+
+fn email_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}$").unwrap())
+}
+
+fn phone_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\+?[0-9][0-9 ()\-.]{5,22}[0-9]$").unwrap())
+}
+
+fn color_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)^(#(?:[0-9a-f]{3,4}|[0-9a-f]{6}|[0-9a-f]{8})|(?:rgba?|hsla?)\(\s*[0-9.%]+(?:\s*[,/ ]\s*[0-9.%]+){2,3}\s*\))$",
+        )
+        .unwrap()
+    })
+}
+
+fn code_line_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(^\s*(fn|pub|let|const|var|function|def|class|import|from|return|if|for|while|#include|package|use|struct|impl|async|export)\b)|([;{}]\s*$)|(=>|::|->)",
+        )
+        .unwrap()
+    })
+}
+
+// 代码语言特征：每处命中计一分
+const LANGUAGE_PATTERNS: &[(&str, &str)] = &[
+    ("rust", r"(?m)^\s*(pub(\(crate\))?\s+)?(fn|impl|struct|enum|trait|mod)\s+\w"),
+    ("rust", r"\blet\s+mut\b|&mut\s|\w+!\(|\b(Option|Result|Vec)<|\bunwrap\(\)"),
+    ("python", r"(?m)^\s*def\s+\w+\(.*\)\s*(->\s*[\w\[\], ]+)?:\s*$"),
+    ("python", r"(?m)^\s*(from\s+[\w.]+\s+)?import\s+[\w.]+(\s+as\s+\w+)?\s*$|\bself\.|\belif\b|__\w+__"),
+    ("javascript", r"\b(const|let|var)\s+\w+\s*=|=>|\bfunction\s*\w*\s*\("),
+    ("javascript", r"\bconsole\.log\(|\bdocument\.|\brequire\(|\bmodule\.exports\b|\bexport\s+default\b"),
+    ("typescript", r"(?m)^\s*(export\s+)?(interface|type)\s+\w+|:\s*(string|number|boolean|any|void)\b"),
+    ("go", r"(?m)^\s*package\s+\w+\s*$|^\s*func\s+(\(\w+\s+\*?\w+\)\s*)?\w+\(|:=|\bfmt\.\w+\("),
+    ("java", r"\b(public|private|protected)\s+(static\s+)?(final\s+)?(class|void|int|String)\b|System\.out\.print"),
+    ("c", r"(?m)^\s*#include\s*<\w+\.h>|\bprintf\s*\(|\bmalloc\s*\(|\bint\s+main\s*\("),
+    ("cpp", r"(?m)^\s*#include\s*<\w+>|\bstd::|\bcout\s*<<|\btemplate\s*<"),
+    ("swift", r"\bguard\s+let\b|\bimport\s+(SwiftUI|UIKit|Foundation)\b|(?m)^\s*func\s+\w+\(.*\)\s*(->\s*\w+\s*)?\{"),
+    ("shell", r"(?m)^#!/(usr/)?bin/(env\s+)?(ba|z)?sh|^\s*(echo|export|sudo|brew|apt|apt-get|npm|git|curl|cd)\s"),
+    ("sql", r"(?i)\b(select\s+.+\s+from|insert\s+into|update\s+\w+\s+set|create\s+table|delete\s+from)\b"),
+    ("html", r"(?i)<(!doctype|html|head|body|div|span|script|p|a|ul|li)\b[^>]*>"),
+    ("css", r"(?m)^\s*[.#][\w-]+[^{\n]*\{|^\s*[a-z-]+\s*:\s*[^;:]+;\s*$"),
+];
+
+fn language_regexes() -> &'static Vec<(&'static str, Regex)> {
+    static RE: OnceLock<Vec<(&'static str, Regex)>> = OnceLock::new();
+    RE.get_or_init(|| {
+        LANGUAGE_PATTERNS
+            .iter()
+            .map(|(language, pattern)| (*language, Regex::new(pattern).unwrap()))
+            .collect()
+    })
+}
+
+// 识别内容类别：图片不分类，文件列表为文件路径，HTML/RTF 按普通文本处理
+pub fn classify(content: &ClipboardContent) -> Option<ContentCategory> {
+    match content {
+        ClipboardContent::Text { text } => Some(classify_text(text)),
+        ClipboardContent::Html { .. } | ClipboardContent::Rtf { .. } => Some(ContentCategory::PlainText),
+        ClipboardContent::Files { .. } => Some(ContentCategory::FilePath),
+        ClipboardContent::Image { .. } => None,
+    }
+}
+
+// 按文本特征识别类别，单值类别（链接、邮箱等）要求整段内容只有一个值
+pub fn classify_text(text: &str) -> ContentCategory {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return ContentCategory::PlainText;
+    }
+
+    if !trimmed.contains('\n') && trimmed.chars().count() <= MAX_SINGLE_VALUE_CHARS {
+        if detect_url(trimmed).is_some() {
+            return ContentCategory::Url;
+        }
+        if email_regex().is_match(trimmed) {
+            return ContentCategory::Email;
+        }
+        if color_regex().is_match(trimmed) {
+            return ContentCategory::Color;
+        }
+        if is_phone_number(trimmed) {
+            return ContentCategory::Phone;
+        }
+        if is_file_path(trimmed) {
+            return ContentCategory::FilePath;
+        }
+    }
+
+    if is_json(trimmed) {
+        return ContentCategory::Json;
+    }
+    if looks_like_code(trimmed) {
+        return ContentCategory::Code;
+    }
+    ContentCategory::PlainText
+}
+
+// 推测代码的语言（供前端语法高亮），得分最高且唯一的语言作为结果
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let mut scores: Vec<(&'static str, usize)> = Vec::new();
+    for &(language, ref regex) in language_regexes() {
+        let hits = regex.find_iter(text).count();
+        match scores.iter_mut().find(|(name, _)| *name == language) {
+            Some((_, score)) => *score += hits,
+            None => scores.push((language, hits)),
+        }
+    }
+
+    // TypeScript 是 JavaScript 的超集，带类型特征时同时计入 JavaScript 的得分
+    let javascript = scores.iter().find(|(name, _)| *name == "javascript").map_or(0, |(_, score)| *score);
+    if let Some((_, score)) = scores.iter_mut().find(|(name, score)| *name == "typescript" && *score > 0) {
+        *score += javascript;
+    }
+
+    scores.sort_by(|a, b| b.1.cmp(&a.1));
+    match scores.as_slice() {
+        [(language, best), rest @ ..] if *best > 0 && rest.first().is_none_or(|(_, next)| next < best) => {
+            Some(*language)
+        }
+        _ => None,
+    }
+}
+
+// 文本内容被识别为代码时返回语言提示
+pub fn code_language(content: &ClipboardContent) -> Option<String> {
+    match content {
+        ClipboardContent::Text { text } if classify_text(text) == ContentCategory::Code => {
+            detect_language(text.trim()).map(String::from)
+        }
+        _ => None,
+    }
+}
+
+// 电话号码：7-15 位数字，只允许常见分隔符
+fn is_phone_number(text: &str) -> bool {
+    let digits = text.chars().filter(char::is_ascii_digit).count();
+    (7..=15).contains(&digits) && phone_regex().is_match(text)
+}
+
+// 绝对路径或主目录路径（至少包含两级）
+fn is_file_path(text: &str) -> bool {
+    let path = text.strip_prefix("file://").unwrap_or(text);
+    (path.starts_with('/') || path.starts_with("~/"))
+        && path.matches('/').count() >= 2
+        && !path.contains("//")
+}
+
+// JSON 对象或数组（纯数字、字符串等标量不算）
+fn is_json(text: &str) -> bool {
+    (text.starts_with('{') || text.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(text).is_ok()
+}
+
+// 代码：至少两行，且有三分之一以上的非空行带有关键字、语句结尾符或常见运算符
+fn looks_like_code(text: &str) -> bool {
+    let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+    if lines.len() < 2 {
+        return false;
+    }
+    let code_lines = lines
+        .iter()
+        .filter(|line| code_line_regex().is_match(line))
+        .count();
+    code_lines >= 2 && code_lines * 3 >= lines.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_text() {
+        assert_eq!(classify_text("https://github.com/tauri-apps/tauri"), ContentCategory::Url);
+        assert_eq!(classify_text(" someone@example.com "), ContentCategory::Email);
+        assert_eq!(classify_text("+86 138-0013-8000"), ContentCategory::Phone);
+        assert_eq!(classify_text("#1e90ff"), ContentCategory::Color);
+        assert_eq!(classify_text("rgb(30, 144, 255)"), ContentCategory::Color);
+        assert_eq!(classify_text("~/Documents/report.pdf"), ContentCategory::FilePath);
+        assert_eq!(classify_text(r#"{"name": "oicopy", "tags": [1, 2]}"#), ContentCategory::Json);
+        assert_eq!(
+            classify_text("fn main() {\n    println!(\"hi\");\n}"),
+            ContentCategory::Code
+        );
+        assert_eq!(classify_text("明天下午三点开会"), ContentCategory::PlainText);
+        assert_eq!(classify_text("2024"), ContentCategory::PlainText);
+    }
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(detect_language("fn main() {\n    println!(\"hi\");\n}"), Some("rust"));
+        assert_eq!(detect_language("def add(a, b):\n    return a + b"), Some("python"));
+        assert_eq!(detect_language("const x = 1;\nconsole.log(x);"), Some("javascript"));
+        assert_eq!(detect_language("interface User {\n  name: string;\n}"), Some("typescript"));
+        assert_eq!(detect_language("package main\n\nfunc main() {\n\tfmt.Println(\"hi\")\n}"), Some("go"));
+        assert_eq!(detect_language("SELECT id, name FROM users\nWHERE id = 1;"), Some("sql"));
+        assert_eq!(detect_language("明天下午三点开会"), None);
+    }
+}
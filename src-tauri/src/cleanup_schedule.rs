@@ -0,0 +1,136 @@
+use crate::db::AppSettings;
+use serde::{Deserialize, Serialize};
+
+// 系统无键盘鼠标输入超过该时长（秒）视为空闲
+pub const IDLE_THRESHOLD_SECS: f64 = 300.0;
+
+// 自动清理计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupSchedule {
+    pub enabled: bool,
+    pub interval_minutes: i64,
+    pub quiet_start_hour: Option<i64>, // 免打扰时段 [start, end)，可跨越午夜，两者都设置时生效
+    pub quiet_end_hour: Option<i64>,
+    pub only_when_idle: bool,
+}
+
+impl CleanupSchedule {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            enabled: settings.auto_cleanup_enabled,
+            interval_minutes: settings.cleanup_interval_minutes.max(1),
+            quiet_start_hour: settings.cleanup_quiet_start_hour,
+            quiet_end_hour: settings.cleanup_quiet_end_hour,
+            only_when_idle: settings.cleanup_only_when_idle,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(5..=7 * 24 * 60).contains(&self.interval_minutes) {
+            return Err("清理间隔必须在 5 分钟到 7 天之间".to_string());
+        }
+        for hour in [self.quiet_start_hour, self.quiet_end_hour].into_iter().flatten() {
+            if !(0..24).contains(&hour) {
+                return Err(format!("免打扰时段的小时必须在 0 到 23 之间: {}", hour));
+            }
+        }
+        Ok(())
+    }
+
+    // 指定小时是否处于免打扰时段
+    pub fn in_quiet_hours(&self, hour: i64) -> bool {
+        match (self.quiet_start_hour, self.quiet_end_hour) {
+            (Some(start), Some(end)) if start < end => hour >= start && hour < end,
+            (Some(start), Some(end)) if start > end => hour >= start || hour < end,
+            _ => false,
+        }
+    }
+
+    // 当前不能执行自动清理的原因；idle_secs 为空表示无法获取空闲时间，不做空闲限制
+    pub fn blocked_reason(&self, hour: i64, idle_secs: Option<f64>) -> Option<&'static str> {
+        if !self.enabled {
+            return Some("自动清理已关闭");
+        }
+        if self.in_quiet_hours(hour) {
+            return Some("处于免打扰时段");
+        }
+        if self.only_when_idle && idle_secs.is_some_and(|idle| idle < IDLE_THRESHOLD_SECS) {
+            return Some("用户正在使用电脑");
+        }
+        None
+    }
+}
+
+// 距离最后一次键盘鼠标输入的秒数
+#[cfg(target_os = "macos")]
+pub fn system_idle_seconds() -> Option<f64> {
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceSecondsSinceLastEventType(state_id: i32, event_type: u32) -> f64;
+    }
+    const COMBINED_SESSION_STATE: i32 = 0;
+    const ANY_INPUT_EVENT_TYPE: u32 = u32::MAX;
+
+    let idle = unsafe { CGEventSourceSecondsSinceLastEventType(COMBINED_SESSION_STATE, ANY_INPUT_EVENT_TYPE) };
+    idle.is_finite().then_some(idle)
+}
+
+#[cfg(target_os = "windows")]
+pub fn system_idle_seconds() -> Option<f64> {
+    use windows_sys::Win32::System::SystemInformation::GetTickCount;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO { cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    let elapsed_ms = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+    Some(elapsed_ms as f64 / 1000.0)
+}
+
+// Linux 等平台没有统一的空闲时间接口（Wayland 下无法获取），不做空闲限制
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn system_idle_seconds() -> Option<f64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(quiet: Option<(i64, i64)>, only_when_idle: bool) -> CleanupSchedule {
+        CleanupSchedule {
+            enabled: true,
+            interval_minutes: 60,
+            quiet_start_hour: quiet.map(|(start, _)| start),
+            quiet_end_hour: quiet.map(|(_, end)| end),
+            only_when_idle,
+        }
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let daytime = schedule(Some((9, 18)), false);
+        assert!(daytime.in_quiet_hours(9));
+        assert!(!daytime.in_quiet_hours(18));
+
+        // 跨越午夜的时段
+        let overnight = schedule(Some((22, 6)), false);
+        assert!(overnight.in_quiet_hours(23));
+        assert!(overnight.in_quiet_hours(2));
+        assert!(!overnight.in_quiet_hours(12));
+
+        assert!(!schedule(None, false).in_quiet_hours(3));
+    }
+
+    #[test]
+    fn test_blocked_reason() {
+        let idle_only = schedule(None, true);
+        assert!(idle_only.blocked_reason(12, Some(10.0)).is_some());
+        assert!(idle_only.blocked_reason(12, Some(IDLE_THRESHOLD_SECS + 1.0)).is_none());
+        assert!(idle_only.blocked_reason(12, None).is_none());
+
+        let disabled = CleanupSchedule { enabled: false, ..schedule(None, false) };
+        assert_eq!(disabled.blocked_reason(12, None), Some("自动清理已关闭"));
+    }
+}
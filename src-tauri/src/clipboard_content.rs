@@ -0,0 +1,469 @@
+use crate::image_store::{blob_format, is_blob_reference, load_image_blob};
+use crate::rtf::rtf_to_text;
+use base64::prelude::*;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+
+// 单行超过该长度（字节）的文本视为压缩/单行内容（如压缩后的 JSON、JS）
+const MINIFIED_LINE_BYTES: usize = 2048;
+// 压缩内容预览的折行宽度和最多行数
+const WRAPPED_PREVIEW_LINE_CHARS: usize = 60;
+const WRAPPED_PREVIEW_MAX_LINES: usize = 4;
+// 旧版本中 RTF 记录统一使用的预览文本
+pub const RTF_PREVIEW_FALLBACK: &str = "RTF格式文本";
+
+// 内容类型标识（与数据库 content_type 列及前端字段取值保持一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentKind {
+    Text,
+    Html,
+    Rtf,
+    Image,
+    Files,
+}
+
+impl ContentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentKind::Text => "text",
+            ContentKind::Html => "html",
+            ContentKind::Rtf => "rtf",
+            ContentKind::Image => "image",
+            ContentKind::Files => "files",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "text" => Some(ContentKind::Text),
+            "html" => Some(ContentKind::Html),
+            "rtf" => Some(ContentKind::Rtf),
+            "image" => Some(ContentKind::Image),
+            "files" => Some(ContentKind::Files),
+            _ => None,
+        }
+    }
+
+    // 用于日志输出的中文名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            ContentKind::Text => "文本",
+            ContentKind::Html => "HTML",
+            ContentKind::Rtf => "RTF",
+            ContentKind::Image => "图像",
+            ContentKind::Files => "文件列表",
+        }
+    }
+}
+
+impl std::fmt::Display for ContentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ToSql for ContentKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for ContentKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let raw = value.as_str()?;
+        ContentKind::parse(raw)
+            .ok_or_else(|| FromSqlError::Other(format!("未知的内容类型: {}", raw).into()))
+    }
+}
+
+// 结构化的剪贴板内容
+// 数据库与前端仍以 content_type + content 字符串交互，转换只发生在边界处
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ClipboardContent {
+    Text {
+        text: String,
+    },
+    Html {
+        raw: String,
+        sanitized: String,
+    },
+    Rtf {
+        rtf: String,
+    },
+    Image {
+        format: String,
+        #[serde(with = "base64_bytes")]
+        bytes: Vec<u8>,
+    },
+    Files {
+        entries: Vec<String>,
+    },
+}
+
+impl ClipboardContent {
+    pub fn kind(&self) -> ContentKind {
+        match self {
+            ClipboardContent::Text { .. } => ContentKind::Text,
+            ClipboardContent::Html { .. } => ContentKind::Html,
+            ClipboardContent::Rtf { .. } => ContentKind::Rtf,
+            ClipboardContent::Image { .. } => ContentKind::Image,
+            ClipboardContent::Files { .. } => ContentKind::Files,
+        }
+    }
+
+    // 从剪贴板插件返回的 base64 图像数据构建
+    pub fn image_from_base64(format: &str, data: &str) -> Result<Self, String> {
+        let bytes = BASE64_STANDARD
+            .decode(data.trim())
+            .map_err(|e| format!("解码图像数据失败: {}", e))?;
+        Ok(ClipboardContent::Image {
+            format: format.to_string(),
+            bytes,
+        })
+    }
+
+    // 转换为数据库 content 列中保存的字符串
+    pub fn to_stored(&self) -> String {
+        match self {
+            ClipboardContent::Text { text } => text.clone(),
+            ClipboardContent::Html { sanitized, .. } => sanitized.clone(),
+            ClipboardContent::Rtf { rtf } => rtf.clone(),
+            ClipboardContent::Image { bytes, .. } => BASE64_STANDARD.encode(bytes),
+            ClipboardContent::Files { entries } => {
+                serde_json::to_string(entries).unwrap_or_else(|_| "[]".to_string())
+            }
+        }
+    }
+
+    // 从数据库中的 content_type + content 还原
+    pub fn from_stored(kind: ContentKind, stored: String) -> Result<Self, String> {
+        match kind {
+            ContentKind::Text => Ok(ClipboardContent::Text { text: stored }),
+            ContentKind::Html => Ok(ClipboardContent::Html {
+                raw: stored.clone(),
+                sanitized: stored,
+            }),
+            ContentKind::Rtf => Ok(ClipboardContent::Rtf { rtf: stored }),
+            ContentKind::Image if is_blob_reference(&stored) => Ok(ClipboardContent::Image {
+                format: blob_format(&stored),
+                bytes: load_image_blob(&stored)?,
+            }),
+            ContentKind::Image => ClipboardContent::image_from_base64("png", &stored),
+            ContentKind::Files => {
+                let entries = serde_json::from_str::<Vec<String>>(&stored)
+                    .map_err(|e| format!("解析文件列表失败: {}", e))?;
+                Ok(ClipboardContent::Files { entries })
+            }
+        }
+    }
+
+    // 以新文本替换内容，仅文本类内容（文本/HTML/RTF）可编辑
+    pub fn with_text(&self, text: String) -> Result<Self, String> {
+        if text.is_empty() {
+            return Err("内容不能为空".to_string());
+        }
+        match self {
+            ClipboardContent::Text { .. } => Ok(ClipboardContent::Text { text }),
+            ClipboardContent::Html { .. } => Ok(ClipboardContent::Html {
+                raw: text.clone(),
+                sanitized: text,
+            }),
+            ClipboardContent::Rtf { .. } => Ok(ClipboardContent::Rtf { rtf: text }),
+            _ => Err(format!("{}内容不支持编辑", self.kind().label())),
+        }
+    }
+
+    // 将内容中出现的敏感片段替换为占位文本
+    pub fn redact(&self, target: &str, replacement: &str) -> Result<Self, String> {
+        if target.is_empty() {
+            return Err("要脱敏的内容不能为空".to_string());
+        }
+        let stored = self.to_stored();
+        if !stored.contains(target) {
+            return Err("未在内容中找到要脱敏的片段".to_string());
+        }
+        self.with_text(stored.replace(target, replacement))
+    }
+
+    // 是否没有任何有效内容
+    pub fn is_empty(&self) -> bool {
+        match self {
+            ClipboardContent::Text { text } => text.is_empty(),
+            ClipboardContent::Html { raw, .. } => raw.is_empty(),
+            ClipboardContent::Rtf { rtf } => rtf.is_empty(),
+            ClipboardContent::Image { bytes, .. } => bytes.is_empty(),
+            ClipboardContent::Files { entries } => entries.is_empty(),
+        }
+    }
+
+    // 内容大小（字节），文件列表按路径长度计算
+    pub fn byte_size(&self) -> usize {
+        match self {
+            ClipboardContent::Text { text } => text.len(),
+            ClipboardContent::Html { raw, .. } => raw.len(),
+            ClipboardContent::Rtf { rtf } => rtf.len(),
+            ClipboardContent::Image { bytes, .. } => bytes.len(),
+            ClipboardContent::Files { entries } => entries.iter().map(String::len).sum(),
+        }
+    }
+
+    // 是否为压缩/超长单行的文本内容
+    pub fn is_minified(&self) -> bool {
+        match self {
+            ClipboardContent::Text { text } => text.lines().any(|line| line.len() > MINIFIED_LINE_BYTES),
+            ClipboardContent::Html { raw, .. } => raw.lines().any(|line| line.len() > MINIFIED_LINE_BYTES),
+            _ => false,
+        }
+    }
+
+    // 生成列表中展示的预览文本
+    pub fn preview(&self) -> String {
+        match self {
+            ClipboardContent::Text { text } if self.is_minified() => {
+                generate_wrapped_preview(text, WRAPPED_PREVIEW_LINE_CHARS, WRAPPED_PREVIEW_MAX_LINES)
+            }
+            ClipboardContent::Text { text } => generate_preview(text, 100),
+            ClipboardContent::Html { .. } => "HTML内容".to_string(),
+            ClipboardContent::Rtf { rtf } => {
+                // 无法提取出文本时（如只含图片）显示类型说明
+                let text = rtf_to_text(rtf);
+                if text.is_empty() {
+                    RTF_PREVIEW_FALLBACK.to_string()
+                } else {
+                    generate_preview(&text, 100)
+                }
+            }
+            ClipboardContent::Image { .. } => "图像内容".to_string(),
+            ClipboardContent::Files { entries } => {
+                if entries.len() == 1 {
+                    format!("1个文件: {}", entries[0])
+                } else {
+                    format!("{}个文件", entries.len())
+                }
+            }
+        }
+    }
+
+    // 基于存储形式计算内容哈希，用于去重
+    pub fn content_hash(&self) -> String {
+        calculate_content_hash(&self.to_stored())
+    }
+}
+
+/// 计算内容的SHA256哈希值
+pub fn calculate_content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    let result = hasher.finalize();
+
+    // 转换为十六进制字符串
+    let mut hex_string = String::new();
+    for byte in result {
+        write!(&mut hex_string, "{:02x}", byte).unwrap();
+    }
+    hex_string
+}
+
+// 生成预览文本
+pub fn generate_preview(content: &str, max_chars: usize) -> String {
+    if content.chars().count() <= max_chars {
+        content.to_string()
+    } else {
+        let preview: String = content.chars().take(max_chars).collect();
+        format!("{}...", preview)
+    }
+}
+
+// 将超长单行内容按宽度折行生成预览，优先在空白或分隔符后断行，超出行数时截断
+pub fn generate_wrapped_preview(content: &str, line_chars: usize, max_lines: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+    // 当前行中最后一个可断行位置（字节下标, 字符数）
+    let mut break_at: Option<(usize, usize)> = None;
+    let mut truncated = false;
+
+    for c in content.chars().filter(|c| *c != '\r') {
+        if lines.len() == max_lines {
+            truncated = true;
+            break;
+        }
+        if c == '\n' {
+            lines.push(std::mem::take(&mut current));
+            current_chars = 0;
+            break_at = None;
+            continue;
+        }
+
+        current.push(c);
+        current_chars += 1;
+        if c.is_whitespace() || matches!(c, ',' | ';' | '{' | '}' | '[' | ']' | '(' | ')' | '>') {
+            break_at = Some((current.len(), current_chars));
+        }
+        if current_chars == line_chars {
+            // 断行位置过于靠前时直接按宽度断开
+            match break_at.filter(|(_, chars)| *chars >= line_chars / 2) {
+                Some((index, chars)) => {
+                    let rest = current.split_off(index);
+                    lines.push(std::mem::replace(&mut current, rest));
+                    current_chars -= chars;
+                }
+                None => {
+                    lines.push(std::mem::take(&mut current));
+                    current_chars = 0;
+                }
+            }
+            break_at = None;
+        }
+    }
+    if !current.is_empty() {
+        if lines.len() < max_lines {
+            lines.push(current);
+        } else {
+            truncated = true;
+        }
+    }
+
+    let preview = lines.join("\n");
+    if truncated {
+        format!("{}...", preview)
+    } else {
+        preview
+    }
+}
+
+// 图像字节以 base64 字符串形式序列化
+mod base64_bytes {
+    use base64::prelude::*;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        BASE64_STANDARD
+            .decode(encoded.as_bytes())
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// 默认的捕获格式优先级（HTML 与纯文本同时存在时由内容分析决定）
+pub const DEFAULT_CONTENT_PRIORITY: [ContentKind; 5] = [
+    ContentKind::Files,
+    ContentKind::Image,
+    ContentKind::Html,
+    ContentKind::Text,
+    ContentKind::Rtf,
+];
+
+// 校验用户设定的格式优先级：不允许重复，未列出的格式按默认顺序补在末尾
+pub fn normalize_content_priority(order: &[ContentKind]) -> Result<Vec<ContentKind>, String> {
+    let mut normalized: Vec<ContentKind> = Vec::with_capacity(DEFAULT_CONTENT_PRIORITY.len());
+    for kind in order {
+        if normalized.contains(kind) {
+            return Err(format!("格式优先级中{}重复", kind.label()));
+        }
+        normalized.push(*kind);
+    }
+    for kind in DEFAULT_CONTENT_PRIORITY {
+        if !normalized.contains(&kind) {
+            normalized.push(kind);
+        }
+    }
+    Ok(normalized)
+}
+
+// 按用户设定的顺序排列可用格式；HTML 和纯文本只保留靠前的一个，避免同一内容重复保存
+pub fn custom_content_priority(order: &[ContentKind], available: &[ContentKind]) -> Vec<ContentKind> {
+    let mut priority: Vec<ContentKind> = Vec::new();
+    for kind in order.iter().filter(|kind| available.contains(kind)) {
+        let is_text_like = matches!(kind, ContentKind::Html | ContentKind::Text);
+        if is_text_like && priority.iter().any(|existing| matches!(existing, ContentKind::Html | ContentKind::Text)) {
+            continue;
+        }
+        priority.push(*kind);
+    }
+    priority
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_content_priority() {
+        let order = normalize_content_priority(&[ContentKind::Rtf, ContentKind::Html]).unwrap();
+        assert_eq!(
+            order,
+            vec![ContentKind::Rtf, ContentKind::Html, ContentKind::Files, ContentKind::Image, ContentKind::Text]
+        );
+        assert!(normalize_content_priority(&[ContentKind::Text, ContentKind::Text]).is_err());
+
+        let available = [ContentKind::Text, ContentKind::Html, ContentKind::Rtf];
+        assert_eq!(custom_content_priority(&order, &available), vec![ContentKind::Rtf, ContentKind::Html]);
+    }
+
+    #[test]
+    fn test_stored_round_trip() {
+        let samples = vec![
+            ClipboardContent::Text { text: "hello".to_string() },
+            ClipboardContent::Rtf { rtf: "{\\rtf1 hi}".to_string() },
+            ClipboardContent::Image { format: "png".to_string(), bytes: vec![1, 2, 3, 4] },
+            ClipboardContent::Files { entries: vec!["/tmp/a.txt".to_string()] },
+        ];
+
+        for content in samples {
+            let restored = ClipboardContent::from_stored(content.kind(), content.to_stored()).unwrap();
+            assert_eq!(restored, content);
+        }
+    }
+
+    #[test]
+    fn test_redact_replaces_all_occurrences() {
+        let content = ClipboardContent::Text { text: "user=admin pass=hunter2 again hunter2".to_string() };
+        let redacted = content.redact("hunter2", "••••").unwrap();
+        assert_eq!(redacted.to_stored(), "user=admin pass=•••• again ••••");
+
+        let image = ClipboardContent::Image { format: "png".to_string(), bytes: vec![1] };
+        assert!(image.with_text("x".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_kind_parse_matches_as_str() {
+        for kind in [ContentKind::Text, ContentKind::Html, ContentKind::Rtf, ContentKind::Image, ContentKind::Files] {
+            assert_eq!(ContentKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(ContentKind::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_wrapped_preview() {
+        let json = format!("{{{}}}", (0..500).map(|i| format!("\"k{}\":{}", i, i)).collect::<Vec<_>>().join(","));
+        let content = ClipboardContent::Text { text: json };
+        assert!(content.is_minified());
+
+        let preview = content.preview();
+        assert!(preview.ends_with("..."));
+        let lines: Vec<&str> = preview.trim_end_matches("...").lines().collect();
+        assert_eq!(lines.len(), WRAPPED_PREVIEW_MAX_LINES);
+        assert!(lines.iter().all(|line| line.chars().count() <= WRAPPED_PREVIEW_LINE_CHARS));
+        assert!(lines[0].ends_with(','));
+
+        assert_eq!(generate_wrapped_preview("abcdef", 3, 4), "abc\ndef");
+        assert_eq!(generate_wrapped_preview("abcdefg", 3, 2), "abc\ndef...");
+    }
+
+    #[test]
+    fn test_serde_tagging() {
+        let content = ClipboardContent::Image { format: "png".to_string(), bytes: vec![0xff] };
+        let json = serde_json::to_value(&content).unwrap();
+        assert_eq!(json["kind"], "image");
+        assert_eq!(json["bytes"], "/w==");
+    }
+}
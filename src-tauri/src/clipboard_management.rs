@@ -1,16 +1,47 @@
-use crate::app_info::{get_app_icon, get_frontmost_app};
+use crate::app_info::{activate_app, get_frontmost_app, previous_frontmost_app, queue_icon_extraction};
+use crate::change_log::emit_change_event;
+use crate::classifier::ContentCategory;
+use crate::clipboard_content::{custom_content_priority, ClipboardContent, ContentKind};
+use crate::content_formats::{load_content_format, preferred_paste_kind, save_content_formats};
+use crate::color::{format_color, parse_color, ColorFormat};
+use crate::markdown::html_to_markdown;
+use crate::rtf::{rtf_to_html, rtf_to_text};
 use crate::db::{
-    cache_app_icon, get_cached_app_icon, init_database, save_to_database, ClipboardHistoryItem,
+    add_item_tags, build_fts_query, build_like_pattern, delete_items_by_filter,
+    app_icon_needs_refresh, find_item_id_by_hash, fts_index_exists, get_device_id, get_history_item,
+    get_item_before, get_settings, init_database, is_app_excluded, list_all_tags, open_connection,
+    parse_content_kinds, parse_tags, record_item_use, record_monitor_heartbeat, remove_item_tag, reorder_pinned_items,
+    save_settings, save_to_database, set_item_pinned, set_item_thumbnail, update_item_content,
+    AppSettings, ClipboardHistoryItem, ConnectionRole, TagInfo,
 };
+use crate::db_writer::submit_write;
+use crate::disk_guard::suppress_capture_for_disk_space;
+use crate::error::OicopyError;
+use crate::expiry::{expiry_timestamp, is_one_time_code};
+use crate::feedback::{trigger_feedback, FeedbackEvent};
+use crate::image_store::{blob_format, blob_size, generate_thumbnail, is_blob_reference, resolve_image_content};
+use crate::item_actions::detect_url;
+use crate::lock_mode::ensure_unlocked;
+use crate::notifications::{notify, NotificationEvent};
+use crate::panel_window::hide_panel_window;
+use crate::paste::synthesize_paste;
+use crate::pasteboard::{has_privacy_marker, has_promise_marker, write_image_promise, PROMISE_MIN_BYTES};
+use crate::permissions::{require_feature, Feature, FeatureError};
 use crate::performance_optimization::{
     AnalysisConfig, OptimizedContentAnalyzer, PerformanceError
 };
-use chrono::Local;
-use rusqlite::params;
-use sha2::{Digest, Sha256};
-
-use std::fmt::Write;
-use std::sync::OnceLock;
+use crate::private_mode::is_private_mode_active;
+use crate::screen_lock::is_paused_by_screen_lock;
+use crate::sensitive::{detect_sensitive_content, masked_preview, SensitiveDataMode};
+use crate::search_ranking::{candidate_limit, rank_results, RankCandidate, SAMPLE_CHARS};
+use crate::tracking_params::strip_tracking_from_content;
+use crate::trash::move_to_trash;
+use crate::url_metadata::start_url_metadata_fetch;
+use chrono::{DateTime, Local, NaiveDateTime};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use std::sync::{Mutex, OnceLock};
 use tauri::{AppHandle, Emitter, Listener, Manager, Runtime};
 
 // ===== 类型定义 =====
@@ -21,6 +52,9 @@ enum ApplicationType {
     ChatGPT,
     MicrosoftOffice,
     AppleEcosystem,
+    GoogleDocs,
+    Notion,
+    Slack,
     Unknown,
 }
 
@@ -83,7 +117,7 @@ struct OfficeFeature {
 static OPTIMIZED_ANALYZER: OnceLock<OptimizedContentAnalyzer> = OnceLock::new();
 
 // 获取优化分析器实例
-fn get_optimized_analyzer() -> &'static OptimizedContentAnalyzer {
+pub(crate) fn get_optimized_analyzer() -> &'static OptimizedContentAnalyzer {
     OPTIMIZED_ANALYZER.get_or_init(|| {
         let config = AnalysisConfig::default();
         OptimizedContentAnalyzer::new(config)
@@ -116,54 +150,54 @@ struct HtmlContentAnalysis {
 
 // 智能判断内容类型优先级
 fn determine_content_priority(
-    clipboard_state: &tauri_plugin_clipboard::Clipboard,
+    source: &dyn ClipboardSource,
     has_files: bool,
     has_image: bool,
     has_html: bool,
     has_text: bool,
     has_rtf: bool,
-) -> Result<Vec<&'static str>, String> {
+) -> Result<Vec<ContentKind>, String> {
     let mut priority = Vec::new();
 
     // 1. 文件类型始终优先级最高
     if has_files {
-        priority.push("files");
+        priority.push(ContentKind::Files);
     }
 
     // 2. 图像类型优先级较高
     if has_image {
-        priority.push("image");
+        priority.push(ContentKind::Image);
     }
 
     // 3. 智能判断 HTML vs Text
     if has_html && has_text {
         // 同时有 HTML 和 Text 时，需要智能判断
-        match (clipboard_state.read_html(), clipboard_state.read_text()) {
+        match (source.read_html(), source.read_text()) {
             (Ok(html_content), Ok(text_content)) => {
                 if should_prefer_html(&html_content, &text_content) {
-                    priority.push("html");
+                    priority.push(ContentKind::Html);
                     // 不添加 text 作为备选，避免重复保存
                 } else {
-                    priority.push("text");
+                    priority.push(ContentKind::Text);
                     // 不添加 html 作为备选，避免重复保存
                 }
             }
-            (Ok(_), Err(_)) => priority.push("html"),
-            (Err(_), Ok(_)) => priority.push("text"),
+            (Ok(_), Err(_)) => priority.push(ContentKind::Html),
+            (Err(_), Ok(_)) => priority.push(ContentKind::Text),
             (Err(_), Err(_)) => {
                 // 都读取失败，按默认顺序
-                priority.push("html");
+                priority.push(ContentKind::Html);
             }
         }
     } else if has_html {
-        priority.push("html");
+        priority.push(ContentKind::Html);
     } else if has_text {
-        priority.push("text");
+        priority.push(ContentKind::Text);
     }
 
     // 4. RTF 优先级较低
     if has_rtf {
-        priority.push("rtf");
+        priority.push(ContentKind::Rtf);
     }
 
     Ok(priority)
@@ -244,6 +278,13 @@ fn fallback_html_decision(html_content: &str, text_content: &str) -> bool {
         return false; // Office应用，优先文本
     }
     
+    // 快速检测 Google Docs / Slack（表格等结构化内容除外）
+    if (html_lower.contains("docs-internal-guid") || html_lower.contains("p-rich_text_section"))
+        && !html_lower.contains("<table")
+    {
+        return false;
+    }
+    
     // 快速长度比较
     let html_len = html_content.len();
     let text_len = text_content.len();
@@ -259,7 +300,7 @@ fn fallback_html_decision(html_content: &str, text_content: &str) -> bool {
 
 
 // 解码HTML实体的通用函数
-fn decode_html_entities(text: &str) -> String {
+pub(crate) fn decode_html_entities(text: &str) -> String {
     text.replace("&nbsp;", " ")
         .replace("&amp;", "&")
         .replace("&lt;", "<")
@@ -283,6 +324,7 @@ fn analyze_html_content_optimized(
     monitor: &crate::performance_optimization::PerformanceMonitor
 ) -> Result<HtmlContentAnalysis, PerformanceError> {
     let analyzer = get_optimized_analyzer();
+    let config = analyzer.config();
     
     // 检查超时
     monitor.check_timeout()?;
@@ -332,24 +374,27 @@ fn analyze_html_content_optimized(
     
     monitor.check_timeout()?;
 
-    // 5. 计算冗余评分（简化版本以提高性能）
-    let mut redundancy_score = calculate_redundancy_score_optimized(html_lower, &detected_app_name, monitor)?;
+    // 5. 计算冗余评分（简化版本以提高性能，可在设置中关闭）
+    let mut redundancy_score = if config.enable_redundancy_scoring {
+        calculate_redundancy_score_optimized(html_lower, &detected_app_name, monitor)?
+    } else {
+        0.0
+    };
 
     // 基于检测到的应用程序调整冗余评分
     if let Some(ref app_name) = detected_app_name {
-        match app_name.as_str() {
-            "ChatGPT" => redundancy_score += 3.0,
-            "MicrosoftOffice" => redundancy_score += 2.5,
-            "AppleEcosystem" => redundancy_score += 1.5,
-            _ => redundancy_score += 1.0,
-        }
+        redundancy_score += analyzer
+            .pattern_pack()
+            .application(app_name)
+            .map(|app| app.detection_bonus)
+            .unwrap_or(1.0);
     }
 
     // 高相似度增加冗余评分
     if content_similarity > 0.8 {
         redundancy_score += 2.0;
     }
-    if content_similarity > 0.95 {
+    if content_similarity > config.similarity_threshold {
         redundancy_score += 3.0;
     }
 
@@ -363,6 +408,9 @@ fn analyze_html_content_optimized(
             "ChatGPT" => ApplicationType::ChatGPT,
             "MicrosoftOffice" => ApplicationType::MicrosoftOffice,
             "AppleEcosystem" => ApplicationType::AppleEcosystem,
+            "GoogleDocs" => ApplicationType::GoogleDocs,
+            "Notion" => ApplicationType::Notion,
+            "Slack" => ApplicationType::Slack,
             _ => ApplicationType::Unknown,
         },
         confidence: 0.8, // 简化的置信度
@@ -427,23 +475,12 @@ fn calculate_redundancy_score_optimized(
 
     let mut redundancy_score: f64 = 0.0;
 
-    // 快速检测常见冗余指标
-    let redundancy_indicators = [
-        ("mso-", 3.0),
-        ("microsoft", 2.5),
-        ("office", 2.5),
-        ("xmlns:o=", 3.5),
-        ("<!--[if", 3.5),
-        ("apple-converted-space", 2.5),
-        ("webkit-", 2.0),
-        ("chatgpt", 3.0),
-        ("conversation-turn", 4.0),
-        ("data-testid=\"conversation", 4.0),
-    ];
-
-    for (indicator, score) in redundancy_indicators {
-        if html_lower.contains(indicator) {
-            redundancy_score += score;
+    let pack = get_optimized_analyzer().pattern_pack();
+
+    // 快速检测常见冗余指标（来自模式包）
+    for indicator in &pack.redundancy_indicators {
+        if html_lower.contains(indicator.pattern.as_str()) {
+            redundancy_score += indicator.score;
         }
         
         // 每几个检查一次超时
@@ -452,12 +489,10 @@ fn calculate_redundancy_score_optimized(
 
     // 基于检测到的应用程序调整评分
     if let Some(ref app_name) = detected_app_name {
-        match app_name.as_str() {
-            "ChatGPT" => redundancy_score += 2.0,
-            "MicrosoftOffice" => redundancy_score += 1.5,
-            "AppleEcosystem" => redundancy_score += 1.0,
-            _ => redundancy_score += 0.5,
-        }
+        redundancy_score += pack
+            .application(app_name)
+            .map(|app| app.redundancy_bonus)
+            .unwrap_or(0.5);
     }
 
     Ok(redundancy_score.min(10.0))
@@ -559,6 +594,34 @@ fn make_html_decision(analysis: &HtmlContentAnalysis) -> bool {
                     return false;
                 }
             }
+            ApplicationType::GoogleDocs => {
+                // Google Docs 的普通段落只是大量内联样式包裹的文本，表格/列表/图片才值得保留HTML
+                if analysis.content_similarity > 0.9
+                    && !analysis.features.has_complex_structure
+                    && !analysis.features.has_rich_content
+                {
+                    println!("  - Google Docs plain paragraphs detected (confidence: {:.2}) -> TEXT (high priority)", detected_app.confidence);
+                    return false;
+                }
+            }
+            ApplicationType::Notion => {
+                // Notion 导出的HTML结构干净，只有在几乎没有结构价值时才退回文本
+                if analysis.content_similarity > 0.95 && analysis.value_score < 2.0 {
+                    println!("  - Notion plain block detected (confidence: {:.2}) -> TEXT (moderate priority)", detected_app.confidence);
+                    return false;
+                }
+                if analysis.features.has_complex_structure {
+                    println!("  - Notion structured content detected -> HTML (forced)");
+                    return true;
+                }
+            }
+            ApplicationType::Slack => {
+                // Slack 消息与聊天应用类似，HTML 基本是冗余包装
+                if analysis.content_similarity > 0.8 && !analysis.features.has_rich_content {
+                    println!("  - Slack message detected (confidence: {:.2}) -> TEXT (highest priority)", detected_app.confidence);
+                    return false;
+                }
+            }
             _ => {}
         }
     }
@@ -628,21 +691,15 @@ fn make_html_decision(analysis: &HtmlContentAnalysis) -> bool {
 }
 
 
-fn cache_app_icon_if_needed(app_handle: &AppHandle, bundle_id: &str, app_name: Option<&str>) {
+// 尚未缓存图标的应用交给后台队列提取，不阻塞记录保存
+fn cache_app_icon_if_needed(app_handle: &AppHandle, conn: &Connection, bundle_id: &str, app_name: Option<&str>) {
     if bundle_id == "unknown.bundle.id" {
         return;
     }
 
-    if let Ok(conn) = init_database(app_handle) {
-        // 检查是否已经缓存
-        if get_cached_app_icon(&conn, bundle_id).is_none() {
-            // 获取图标
-            let (_, icon_base64) = get_app_icon(bundle_id);
-            if let Some(icon_data) = icon_base64 {
-                let _ = cache_app_icon(&conn, bundle_id, app_name, &icon_data);
-                println!("已缓存应用图标: {}", bundle_id);
-            }
-        }
+    // 未缓存或缓存超过 APP_ICON_REFRESH_DAYS 天时重新提取
+    if app_icon_needs_refresh(conn, bundle_id) {
+        queue_icon_extraction(app_handle, bundle_id, app_name);
     }
 }
 
@@ -661,9 +718,41 @@ fn start_clipboard_monitor<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), S
 }
 
 // 停止监听剪切板
-fn _stop_clipboard_monitor<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), String> {
+fn stop_clipboard_monitor<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), String> {
     let clipboard_state = app_handle.state::<tauri_plugin_clipboard::Clipboard>();
-    clipboard_state.stop_monitor(app_handle.clone())
+    if is_monitor_running(app_handle.clone()) {
+        clipboard_state.stop_monitor(app_handle.clone())
+    } else {
+        Ok(())
+    }
+}
+
+// 暂停或恢复剪贴板监听，并持久化暂停状态（重启后保持）
+pub fn set_monitoring_paused(app_handle: &AppHandle, paused: bool) -> Result<(), String> {
+    if paused {
+        stop_clipboard_monitor(app_handle.clone())?;
+    } else {
+        start_clipboard_monitor(app_handle.clone())?;
+    }
+
+    let conn = init_database(app_handle)?;
+    let mut settings = get_settings(&conn)?;
+    settings.monitoring_paused = paused;
+    save_settings(&conn, &settings)?;
+
+    println!("剪贴板监听已{}", if paused { "暂停" } else { "恢复" });
+    if let Err(e) = app_handle.emit("monitoring-paused-changed", paused) {
+        eprintln!("通知前端监听状态变化失败: {}", e);
+    }
+    Ok(())
+}
+
+// 是否已暂停剪贴板监听
+pub fn is_monitoring_paused(app_handle: &AppHandle) -> bool {
+    init_database(app_handle)
+        .and_then(|conn| get_settings(&conn))
+        .map(|settings| settings.monitoring_paused)
+        .unwrap_or(false)
 }
 
 // 是否已经监听剪切板
@@ -672,6 +761,31 @@ fn is_monitor_running<R: Runtime>(app_handle: AppHandle<R>) -> bool {
     clipboard_state.is_monitor_running()
 }
 
+// 定期检查剪贴板监听是否仍在运行，意外停止时自动重启
+fn start_monitor_watchdog(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
+
+        loop {
+            interval.tick().await;
+
+            // 心跳用于时间线中区分“机器活跃但没有捕获”和“应用未运行”
+            let running = is_monitor_running(app_handle.clone());
+            if let Err(e) = init_database(&app_handle).and_then(|conn| record_monitor_heartbeat(&conn, running)) {
+                eprintln!("{}", e);
+            }
+            // 用户主动暂停时不自动重启
+            if running || is_monitoring_paused(&app_handle) {
+                continue;
+            }
+            match start_clipboard_monitor(app_handle.clone()) {
+                Ok(()) => notify(&app_handle, NotificationEvent::MonitorRestarted),
+                Err(e) => eprintln!("重新启动剪贴板监听失败: {}", e),
+            }
+        }
+    });
+}
+
 /// 从完整 HTML 文件中提取 `<body>` 内部 HTML 内容
 fn fallback_strip_head_and_meta(html: &str) -> String {
     use regex::Regex;
@@ -682,27 +796,252 @@ fn fallback_strip_head_and_meta(html: &str) -> String {
         .to_string()
 }
 
-/// 计算内容的SHA256哈希值
-fn calculate_content_hash(content: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
-    let result = hasher.finalize();
+// 捕获流程读取的剪贴板来源：系统剪贴板，或开发模式下回放的样本
+pub(crate) trait ClipboardSource {
+    // 当前可用的格式，顺序与捕获时的智能优先级无关
+    fn available_kinds(&self) -> Result<Vec<ContentKind>, String>;
+    fn read_text(&self) -> Result<String, String>;
+    fn read_html(&self) -> Result<String, String>;
+    fn read_rtf(&self) -> Result<String, String>;
+    fn read_image_base64(&self) -> Result<String, String>;
+    fn read_files(&self) -> Result<Vec<String>, String>;
+}
+
+impl ClipboardSource for tauri_plugin_clipboard::Clipboard {
+    fn available_kinds(&self) -> Result<Vec<ContentKind>, String> {
+        let clipboard_type = self.available_types()?;
+        println!("clipboard_type: {:?}", clipboard_type);
+        Ok([
+            (ContentKind::Files, clipboard_type.files),
+            (ContentKind::Image, clipboard_type.image),
+            (ContentKind::Html, clipboard_type.html),
+            (ContentKind::Text, clipboard_type.text),
+            (ContentKind::Rtf, clipboard_type.rtf),
+        ]
+        .into_iter()
+        .filter(|(_, available)| *available)
+        .map(|(kind, _)| kind)
+        .collect())
+    }
+
+    fn read_text(&self) -> Result<String, String> {
+        tauri_plugin_clipboard::Clipboard::read_text(self)
+    }
+
+    fn read_html(&self) -> Result<String, String> {
+        tauri_plugin_clipboard::Clipboard::read_html(self)
+    }
+
+    fn read_rtf(&self) -> Result<String, String> {
+        tauri_plugin_clipboard::Clipboard::read_rtf(self)
+    }
+
+    fn read_image_base64(&self) -> Result<String, String> {
+        tauri_plugin_clipboard::Clipboard::read_image_base64(self)
+    }
+
+    fn read_files(&self) -> Result<Vec<String>, String> {
+        tauri_plugin_clipboard::Clipboard::read_files(self)
+    }
+}
+
+// 按指定类型读取剪贴板内容，读取出错返回 Err，内容为空返回 Ok(None)
+fn try_read_clipboard_content(
+    source: &dyn ClipboardSource,
+    kind: ContentKind,
+) -> Result<Option<ClipboardContent>, String> {
+    let content = match kind {
+        ContentKind::Image => {
+            let image_base64 = source.read_image_base64()?;
+            if image_base64.is_empty() {
+                return Ok(None);
+            }
+            ClipboardContent::image_from_base64("png", &image_base64)?
+        }
+        ContentKind::Rtf => ClipboardContent::Rtf {
+            rtf: source.read_rtf()?,
+        },
+        ContentKind::Files => ClipboardContent::Files {
+            entries: source.read_files()?,
+        },
+        ContentKind::Text => ClipboardContent::Text {
+            text: source.read_text()?,
+        },
+        ContentKind::Html => {
+            let html = source.read_html()?;
+            ClipboardContent::Html {
+                sanitized: fallback_strip_head_and_meta(&html),
+                raw: html,
+            }
+        }
+    };
+
+    if content.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(content))
+    }
+}
+
+// 按指定类型读取剪贴板内容，失败或为空时返回 None
+fn read_clipboard_content(
+    source: &dyn ClipboardSource,
+    kind: ContentKind,
+) -> Option<ClipboardContent> {
+    match try_read_clipboard_content(source, kind) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("读取{}失败: {}", kind.label(), e);
+            None
+        }
+    }
+}
+
+// 首选格式全部读取失败时的降级顺序（纯文本最可靠，放在最前）
+const CAPTURE_FALLBACK_ORDER: [ContentKind; 5] = [
+    ContentKind::Text,
+    ContentKind::Html,
+    ContentKind::Rtf,
+    ContentKind::Image,
+    ContentKind::Files,
+];
+
+// 先按智能优先级读取，失败后依次尝试其他可用格式（类型未知时尝试全部格式）
+// 返回读取到的内容和读取出错的格式
+fn read_with_fallback(
+    source: &dyn ClipboardSource,
+    priority: &[ContentKind],
+    available: Option<&[ContentKind]>,
+) -> (Option<ClipboardContent>, Vec<ContentKind>) {
+    let fallback = CAPTURE_FALLBACK_ORDER
+        .into_iter()
+        .filter(|kind| !priority.contains(kind))
+        .filter(|kind| available.map_or(true, |available| available.contains(kind)));
+
+    let mut failed_formats = Vec::new();
+    for kind in priority.iter().copied().chain(fallback) {
+        println!("Processing clipboard type: {}", kind);
+        match try_read_clipboard_content(source, kind) {
+            Ok(Some(content)) => return (Some(content), failed_formats),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("读取{}失败，尝试其他格式: {}", kind.label(), e);
+                failed_formats.push(kind);
+            }
+        }
+    }
+    (None, failed_formats)
+}
+
+// 读取主格式以外的其他可用格式，与主格式一起保存，粘贴时再按目标应用选择
+// 文件列表附带的文本和图标不保存
+fn read_alternate_formats(
+    source: &dyn ClipboardSource,
+    primary: ContentKind,
+    available: Option<&[ContentKind]>,
+    failed_formats: &[ContentKind],
+) -> Vec<ClipboardContent> {
+    let Some(available) = available else {
+        return Vec::new();
+    };
+    if primary == ContentKind::Files {
+        return Vec::new();
+    }
+    available
+        .iter()
+        .copied()
+        .filter(|kind| *kind != primary && *kind != ContentKind::Files && !failed_formats.contains(kind))
+        .filter_map(|kind| match try_read_clipboard_content(source, kind) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("读取{}失败，不保存该格式: {}", kind.label(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+// 将内容按原始格式写回系统剪贴板
+pub(crate) fn write_clipboard_content(
+    clipboard_state: &tauri_plugin_clipboard::Clipboard,
+    content: &ClipboardContent,
+) -> Result<(), String> {
+    match content {
+        ClipboardContent::Text { text } => clipboard_state.write_text(text.clone()),
+        ClipboardContent::Html { raw, .. } => {
+            // HTML需要同时写入纯文本，保证不支持富文本的应用也能粘贴
+            clipboard_state.write_html_and_text(raw.clone(), html_to_plain_text(raw))
+        }
+        ClipboardContent::Rtf { rtf } => clipboard_state.write_rtf(rtf.clone()),
+        ClipboardContent::Image { .. } => clipboard_state.write_image_base64(content.to_stored()),
+        ClipboardContent::Files { entries } => {
+            let uris = entries
+                .iter()
+                .map(|entry| {
+                    if entry.starts_with("file://") {
+                        entry.clone()
+                    } else {
+                        format!("file://{}", entry)
+                    }
+                })
+                .collect();
+            clipboard_state.write_files_uris(uris)
+        }
+    }
+    .map_err(|e| format!("写入剪贴板失败: {}", e))
+}
 
-    // 转换为十六进制字符串
-    let mut hex_string = String::new();
-    for byte in result {
-        write!(&mut hex_string, "{:02x}", byte).unwrap();
+// 转义HTML特殊字符
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
     }
-    hex_string
+    escaped
 }
 
-// 剪切板变化
+// 去除HTML标签得到纯文本
+pub(crate) fn html_to_plain_text(html: &str) -> String {
+    use regex::Regex;
+    let re_tag = Regex::new(r"<[^>]+>").unwrap();
+    decode_html_entities(&re_tag.replace_all(&fallback_strip_head_and_meta(html), ""))
+}
+
+// 剪切板变化：在监听线程上读取剪贴板，保存交给数据库写入线程，返回是否已提交保存
 fn handle_clipboard_change(app_handle: &AppHandle) -> Result<bool, String> {
-    let clipboard_state = app_handle.state::<tauri_plugin_clipboard::Clipboard>();
-    let clipboard_type = clipboard_state.available_types()?;
+    // 密码管理器标记为隐藏/临时的内容不记录
+    if has_privacy_marker() {
+        println!("剪贴板内容带有隐藏/临时标记，跳过保存");
+        return Ok(false);
+    }
+
+    // 延迟提供的图片来自历史记录，读取会触发图片数据生成
+    if has_promise_marker() {
+        println!("剪贴板内容为延迟提供的历史图片，跳过保存");
+        return Ok(false);
+    }
+
+    // 隐私模式下不保存任何内容
+    if is_private_mode_active() {
+        println!("隐私模式已开启，跳过保存");
+        return Ok(false);
+    }
+
+    // 按设置在锁屏期间不保存
+    if is_paused_by_screen_lock() {
+        println!("屏幕已锁定，跳过保存");
+        return Ok(false);
+    }
 
-    // 初始化数据库连接
-    let conn = init_database(&app_handle)?;
+    // 监听线程只做读取，使用只读连接，不与写入线程争用写锁
+    let conn = open_connection(app_handle, ConnectionRole::Analytics)?;
 
     // 获取当前时间作为时间戳
     let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
@@ -721,352 +1060,1341 @@ fn handle_clipboard_change(app_handle: &AppHandle) -> Result<bool, String> {
             (None, None)
         }
     };
+    record_clipboard_source(source_app.clone(), source_bundle_id.clone());
+
+    let clipboard_state = app_handle.state::<tauri_plugin_clipboard::Clipboard>();
+    let Some(captured) = capture_from_source(
+        &conn,
+        clipboard_state.inner(),
+        timestamp,
+        source_app,
+        source_bundle_id,
+    )? else {
+        return Ok(false);
+    };
+
+    submit_write(app_handle, move |app_handle, conn| {
+        if save_captured_content(app_handle, conn, captured) {
+            emit_change_event(app_handle, "clipboard-updated");
+        }
+    })?;
+    Ok(true)
+}
 
-    println!("clipboard_type: {:?}", clipboard_type);
+// 一次复制读取到的内容：主格式、待保存的记录和其他格式
+pub(crate) struct CapturedClipboard {
+    pub content: ClipboardContent,
+    pub history_item: ClipboardHistoryItem,
+    pub alternates: Vec<ClipboardContent>,
+}
+
+// 捕获流程的入口：来自排除应用的内容不记录，其余按设置读取并构建待保存的记录
+pub(crate) fn capture_from_source(
+    conn: &Connection,
+    source: &dyn ClipboardSource,
+    timestamp: String,
+    source_app: Option<String>,
+    source_bundle_id: Option<String>,
+) -> Result<Option<CapturedClipboard>, String> {
+    if let Some(ref bundle_id) = source_bundle_id {
+        if is_app_excluded(conn, bundle_id) {
+            println!("来源应用 {} 在排除列表中，跳过保存", bundle_id);
+            return Ok(None);
+        }
+    }
+    let settings = get_settings(conn)?;
+    read_capture(source, &settings, timestamp, source_app, source_bundle_id)
+}
 
-    // 智能判断内容类型优先级
-    let content_priority = determine_content_priority(
-        &clipboard_state,
-        clipboard_type.files,
-        clipboard_type.image,
-        clipboard_type.html,
-        clipboard_type.text,
-        clipboard_type.rtf,
-    )?;
+// 按智能优先级从剪贴板来源读取内容并构建待保存的记录，没有可保存的内容时返回 None
+pub(crate) fn read_capture(
+    source: &dyn ClipboardSource,
+    settings: &AppSettings,
+    timestamp: String,
+    source_app: Option<String>,
+    source_bundle_id: Option<String>,
+) -> Result<Option<CapturedClipboard>, String> {
+    // 获取类型失败时不直接放弃，后面逐个格式尝试读取
+    let available = match source.available_kinds() {
+        Ok(available) => Some(available),
+        Err(e) => {
+            eprintln!("获取剪贴板类型失败，将尝试逐个读取: {}", e);
+            None
+        }
+    };
+
+    // 用户设定了格式优先级时按设定顺序，否则智能判断内容类型优先级
+    let content_priority = match (&available, &settings.content_priority) {
+        (Some(available), Some(order)) => custom_content_priority(order, available),
+        (Some(available), None) => determine_content_priority(
+            source,
+            available.contains(&ContentKind::Files),
+            available.contains(&ContentKind::Image),
+            available.contains(&ContentKind::Html),
+            available.contains(&ContentKind::Text),
+            available.contains(&ContentKind::Rtf),
+        )?,
+        (None, _) => Vec::new(),
+    };
 
     println!("Determined content priority: {:?}", content_priority);
 
-    let mut saved = false;
-    let mut actually_saved = false; // 新增：跟踪是否真的保存了新数据
-
-    // 按照智能优先级处理内容
-    for ty in content_priority {
-        if !saved {
-            println!("Processing clipboard type: {}", ty);
-
-            match ty {
-                "image" => {
-                    if let Ok(image_base64) = clipboard_state.read_image_base64() {
-                        if !image_base64.is_empty() {
-                            let content_hash = calculate_content_hash(&image_base64);
-                            let preview = "图像内容".to_string();
-                            let history_item = ClipboardHistoryItem {
-                                id: None,
-                                content_type: "image".to_string(),
-                                content: image_base64,
-                                content_hash: Some(content_hash),
-                                preview: Some(preview),
-                                timestamp: timestamp.clone(),
-                                source_app: source_app.clone(),
-                                source_bundle_id: source_bundle_id.clone(),
-                                app_icon_base64: None,
-                            };
-                            match save_to_database(&conn, &history_item) {
-                                Ok(id) => {
-                                    println!("图像已保存到数据库，ID: {}", id);
-                                    actually_saved = true;
-
-                                    // 缓存应用图标
-                                    if let Some(ref bundle_id) = source_bundle_id {
-                                        cache_app_icon_if_needed(
-                                            &app_handle,
-                                            bundle_id,
-                                            source_app.as_deref(),
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    if e == "内容重复" {
-                                        println!("图像内容重复，跳过保存");
-                                    } else {
-                                        eprintln!("保存图像失败: {}", e);
-                                    }
-                                }
-                            }
-                            saved = true;
-                            break;
-                        }
-                    }
-                }
-                "rtf" => {
-                    if let Ok(rtf) = clipboard_state.read_rtf() {
-                        if !rtf.is_empty() {
-                            let content_hash = calculate_content_hash(&rtf);
-                            let preview = "RTF格式文本".to_string();
-                            let history_item = ClipboardHistoryItem {
-                                id: None,
-                                content_type: "rtf".to_string(),
-                                content: rtf,
-                                content_hash: Some(content_hash),
-                                preview: Some(preview),
-                                timestamp: timestamp.clone(),
-                                source_app: source_app.clone(),
-                                source_bundle_id: source_bundle_id.clone(),
-                                app_icon_base64: None,
-                            };
-                            match save_to_database(&conn, &history_item) {
-                                Ok(id) => {
-                                    println!("RTF已保存到数据库，ID: {}", id);
-                                    actually_saved = true;
-                                }
-                                Err(e) => {
-                                    if e == "内容重复" {
-                                        println!("RTF内容重复，跳过保存");
-                                    } else {
-                                        eprintln!("保存RTF失败: {}", e);
-                                    }
-                                }
-                            }
-                            saved = true;
-                            break;
-                        }
-                    }
-                }
-                "files" => {
-                    if let Ok(files) = clipboard_state.read_files() {
-                        if !files.is_empty() {
-                            let files_json =
-                                serde_json::to_string(&files).unwrap_or_else(|_| "[]".to_string());
-                            let content_hash = calculate_content_hash(&files_json);
-                            let preview = if files.len() == 1 {
-                                format!("1个文件: {}", files[0])
-                            } else {
-                                format!("{}个文件", files.len())
-                            };
-                            let history_item = ClipboardHistoryItem {
-                                id: None,
-                                content_type: "files".to_string(),
-                                content: files_json,
-                                content_hash: Some(content_hash),
-                                preview: Some(preview),
-                                timestamp: timestamp.clone(),
-                                source_app: source_app.clone(),
-                                source_bundle_id: source_bundle_id.clone(),
-                                app_icon_base64: None,
-                            };
-                            match save_to_database(&conn, &history_item) {
-                                Ok(id) => {
-                                    println!("文件列表已保存到数据库，ID: {}", id);
-                                    actually_saved = true;
-                                }
-                                Err(e) => {
-                                    if e == "内容重复" {
-                                        println!("文件列表内容重复，跳过保存");
-                                    } else {
-                                        eprintln!("保存文件列表失败: {}", e);
-                                    }
-                                }
-                            }
-                            saved = true;
-                            break;
-                        }
-                    }
-                }
-                "text" => {
-                    if let Ok(text) = clipboard_state.read_text() {
-                        if !text.is_empty() {
-                            let content_hash = calculate_content_hash(&text);
-                            let preview = generate_preview(&text, 100);
-                            let history_item = ClipboardHistoryItem {
-                                id: None,
-                                content_type: "text".to_string(),
-                                content: text,
-                                content_hash: Some(content_hash),
-                                preview: Some(preview),
-                                timestamp: timestamp.clone(),
-                                source_app: source_app.clone(),
-                                source_bundle_id: source_bundle_id.clone(),
-                                app_icon_base64: None,
-                            };
-                            match save_to_database(&conn, &history_item) {
-                                Ok(id) => {
-                                    println!("文本已保存到数据库，ID: {}", id);
-                                    actually_saved = true;
-                                }
-                                Err(e) => {
-                                    if e == "内容重复" {
-                                        println!("文本内容重复，跳过保存");
-                                    } else {
-                                        eprintln!("保存文本失败: {}", e);
-                                    }
-                                }
-                            }
-                            saved = true;
-                            break;
+    // 按优先级读取，首选格式失败时降级到其他可读格式，保证只要有格式可读就会保存
+    let (content, failed_formats) =
+        read_with_fallback(source, &content_priority, available.as_deref());
+
+    let Some(content) = content else {
+        if failed_formats.is_empty() {
+            println!("No clipboard data was saved");
+        } else {
+            eprintln!("所有格式均读取失败，未保存: {:?}", failed_formats);
+        }
+        return Ok(None);
+    };
+    let alternates = read_alternate_formats(source, content.kind(), available.as_deref(), &failed_formats);
+
+    // 链接去掉追踪参数后再计算哈希，同一链接从不同渠道复制时可以去重
+    let content = if settings.strip_tracking_params {
+        strip_tracking_from_content(&content).unwrap_or(content)
+    } else {
+        content
+    };
+
+    // 识别到敏感内容时按设置跳过保存，或标记为敏感记录并打码预览
+    let sensitive = match SensitiveDataMode::parse(&settings.sensitive_data_mode) {
+        Some(SensitiveDataMode::Off) => None,
+        mode => detect_sensitive_content(&content).map(|kind| (mode.unwrap_or(SensitiveDataMode::Mask), kind)),
+    };
+    if let Some((SensitiveDataMode::Skip, kind)) = sensitive {
+        println!("内容疑似{}，跳过保存", kind.label());
+        return Ok(None);
+    }
+
+    let mut history_item = ClipboardHistoryItem::from_content(
+        &content,
+        timestamp,
+        source_app,
+        source_bundle_id,
+    );
+    history_item.failed_formats = failed_formats;
+    if let Some((_, kind)) = sensitive {
+        println!("内容疑似{}，预览已打码", kind.label());
+        history_item.sensitive = true;
+        history_item.preview = Some(masked_preview(&content));
+    }
+    // 敏感记录和验证码按设置在一段时间后自动删除
+    if settings.ephemeral_expire_minutes > 0 && (history_item.sensitive || is_one_time_code(&content, history_item.source_bundle_id.as_deref())) {
+        history_item.expires_at = Some(expiry_timestamp(settings.ephemeral_expire_minutes));
+    }
+    Ok(Some(CapturedClipboard {
+        content,
+        history_item,
+        alternates,
+    }))
+}
+
+// 在写入线程上保存捕获的内容及其他格式，并生成缩略图、缓存应用图标，返回是否真的保存了新数据
+pub(crate) fn save_captured_content(app_handle: &AppHandle, conn: &Connection, captured: CapturedClipboard) -> bool {
+    let CapturedClipboard {
+        content,
+        mut history_item,
+        alternates,
+    } = captured;
+    let kind = content.kind();
+    if let Some(reason) = suppress_capture_for_disk_space(app_handle, conn, &content) {
+        println!("{}，跳过保存{}（{} 字节）", reason, kind.label(), content.byte_size());
+        return false;
+    }
+    history_item.device_id = get_device_id(conn).ok();
+
+    match save_to_database(conn, &history_item) {
+        Ok(id) => {
+            println!("{}已保存到数据库，ID: {}", kind.label(), id);
+
+            // 保存同一次复制的其他格式，粘贴时按目标应用选择
+            if let Err(e) = save_content_formats(conn, id, kind, &alternates) {
+                eprintln!("为记录 {} 保存其他格式失败: {}", id, e);
+            }
+
+            // 图片生成缩略图，列表中不再返回原图
+            if let ClipboardContent::Image { ref bytes, .. } = content {
+                match generate_thumbnail(bytes) {
+                    Ok(thumbnail) => {
+                        if let Err(e) = set_item_thumbnail(conn, id, &thumbnail) {
+                            eprintln!("{}", e);
                         }
                     }
+                    Err(e) => eprintln!("为记录 {} 生成缩略图失败: {}", id, e),
                 }
-                "html" => {
-                    if let Ok(html) = clipboard_state.read_html() {
-                        if !html.is_empty() {
-                            let cleaned_html = fallback_strip_head_and_meta(&html);
-                            let content_hash = calculate_content_hash(&cleaned_html);
-                            let preview = "HTML内容".to_string();
-                            let history_item = ClipboardHistoryItem {
-                                id: None,
-                                content_type: "html".to_string(),
-                                content: cleaned_html,
-                                content_hash: Some(content_hash),
-                                preview: Some(preview),
-                                timestamp: timestamp.clone(),
-                                source_app: source_app.clone(),
-                                source_bundle_id: source_bundle_id.clone(),
-                                app_icon_base64: None,
-                            };
-                            match save_to_database(&conn, &history_item) {
-                                Ok(id) => {
-                                    println!("HTML已保存到数据库，ID: {}", id);
-                                    actually_saved = true;
-                                }
-                                Err(e) => {
-                                    if e == "内容重复" {
-                                        println!("HTML内容重复，跳过保存");
-                                    } else {
-                                        eprintln!("保存HTML失败: {}", e);
-                                    }
-                                }
-                            }
-                            saved = true;
-                            break;
-                        }
+            }
+
+            // 缓存应用图标
+            if let Some(ref bundle_id) = history_item.source_bundle_id {
+                cache_app_icon_if_needed(app_handle, conn, bundle_id, history_item.source_app.as_deref());
+            }
+
+            // 链接在后台获取页面标题和图标；敏感链接（登录链接、带令牌的地址）不发请求，避免消耗一次性链接
+            if history_item.category == Some(ContentCategory::Url) && !history_item.sensitive {
+                let enabled = get_settings(conn).map(|settings| settings.fetch_url_metadata).unwrap_or(false);
+                if let (true, ClipboardContent::Text { text }) = (enabled, &content) {
+                    if let Some(url) = detect_url(text) {
+                        start_url_metadata_fetch(app_handle, id, url);
                     }
                 }
-                _ => {}
             }
+            trigger_feedback(app_handle, conn, FeedbackEvent::Capture);
+            true
         }
+        Err(e) => {
+            if matches!(e, OicopyError::Duplicate) {
+                println!("{}内容重复，跳过保存", kind.label());
+            } else {
+                eprintln!("保存{}失败: {}", kind.label(), e);
+            }
+            false
+        }
+    }
+}
+
+// 当前剪贴板内容摘要（仅读取，不保存）
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrentClipboardSummary {
+    pub available_types: Vec<ContentKind>,
+    pub content_type: Option<ContentKind>,
+    pub preview: Option<String>,
+    pub content_hash: Option<String>,
+    pub source_app: Option<String>,
+    pub source_bundle_id: Option<String>,
+    pub saved_item_id: Option<i64>, // 如果已保存到历史记录，对应的记录ID
+    pub observed_at: String,
+}
+
+// 最近一次剪贴板变化的来源应用（名称, Bundle ID）
+static LAST_CLIPBOARD_SOURCE: Mutex<(Option<String>, Option<String>)> = Mutex::new((None, None));
+
+fn record_clipboard_source(source_app: Option<String>, source_bundle_id: Option<String>) {
+    if let Ok(mut source) = LAST_CLIPBOARD_SOURCE.lock() {
+        *source = (source_app, source_bundle_id);
     }
-    // let types = [
-    //     ("text", clipboard_type.text),
-    //     ("html", clipboard_type.html),
-    //     ("rtf", clipboard_type.rtf),
-    //     ("image", clipboard_type.image),
-    //     ("files", clipboard_type.files),
-    // ];
-    // let mut content_types = Vec::new();
-    // for (ty, enabled) in types {
-    //     if enabled {
-    //         content_types.push(ty);
-    //         // 针对每种类型分别处理
-    //         println!("Clipboard contains type: {}", ty);
-
-    //     }
-    // }
-    // println!("content_types: {:?}", content_types);
-    if !saved {
-        // 如果循环结束后没保存任何内容，做个降级处理
-        println!("No clipboard data was saved");
-    }
-    Ok(actually_saved)
+}
+
+// 读取剪贴板当前内容并生成摘要（saved_item_id 由 fill_saved_item_id 查询数据库填充）
+fn build_clipboard_summary(app_handle: &AppHandle) -> Result<CurrentClipboardSummary, String> {
+    let clipboard_state = app_handle.state::<tauri_plugin_clipboard::Clipboard>();
+    let clipboard_type = clipboard_state.available_types()?;
+
+    let available_types: Vec<ContentKind> = [
+        (ContentKind::Files, clipboard_type.files),
+        (ContentKind::Image, clipboard_type.image),
+        (ContentKind::Text, clipboard_type.text),
+        (ContentKind::Html, clipboard_type.html),
+        (ContentKind::Rtf, clipboard_type.rtf),
+    ]
+    .into_iter()
+    .filter(|(_, available)| *available)
+    .map(|(kind, _)| kind)
+    .collect();
+
+    // 摘要只用于展示，优先选择可读性最好的格式，不做 HTML 冗余分析
+    // 带隐藏/临时标记的内容不读取，避免在面板中显示密码；延迟提供的图片不读取，避免生成图片数据
+    let content = if has_privacy_marker() || has_promise_marker() {
+        None
+    } else {
+        available_types
+            .iter()
+            .find_map(|kind| read_clipboard_content(clipboard_state.inner(), *kind))
+    };
+
+    let content_hash = content.as_ref().map(|c| c.content_hash());
+
+    let (source_app, source_bundle_id) = LAST_CLIPBOARD_SOURCE
+        .lock()
+        .map(|source| source.clone())
+        .unwrap_or((None, None));
+
+    Ok(CurrentClipboardSummary {
+        available_types,
+        content_type: content.as_ref().map(|c| c.kind()),
+        preview: content.as_ref().map(|c| c.preview()),
+        content_hash,
+        source_app,
+        source_bundle_id,
+        saved_item_id: None,
+        observed_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    })
+}
+
+// 查询摘要对应的内容是否已保存到历史记录
+fn fill_saved_item_id(conn: &Connection, summary: &mut CurrentClipboardSummary) -> Result<(), String> {
+    if let Some(ref hash) = summary.content_hash {
+        summary.saved_item_id = find_item_id_by_hash(conn, hash)?;
+    }
+    Ok(())
+}
+
+// 获取当前剪贴板内容摘要（用于面板中的“当前剪贴板”行）
+#[tauri::command]
+pub async fn get_current_clipboard_summary(app: AppHandle) -> Result<CurrentClipboardSummary, OicopyError> {
+    let mut summary = build_clipboard_summary(&app).map_err(OicopyError::Clipboard)?;
+    fill_saved_item_id(&open_connection(&app, ConnectionRole::Analytics)?, &mut summary)?;
+    Ok(summary)
 }
 
 pub fn setup_clipboard_monitor(app_handle: AppHandle) -> Result<(), String> {
-    // 启动监听
-    start_clipboard_monitor(app_handle.clone())?;
+    // 启动监听（上次退出前已暂停时保持暂停）
+    if is_monitoring_paused(&app_handle) {
+        println!("剪贴板监听处于暂停状态，跳过启动");
+    } else {
+        start_clipboard_monitor(app_handle.clone())?;
+    }
+    start_monitor_watchdog(app_handle.clone());
 
     // 监听剪贴板更新事件
     app_handle
         .clone()
         .listen("plugin:clipboard://clipboard-monitor/update", move |_| {
-            match handle_clipboard_change(&app_handle) {
-                Ok(saved) => {
-                    // 只有当内容真的被保存时才通知前端更新
-                    if saved {
-                        if let Err(e) = app_handle.emit("clipboard-updated", ()) {
-                            eprintln!("通知前端剪切板更新失败: {}", e);
-                        }
-                    }
-                }
+            // 保存由写入线程完成，保存成功后由写入线程通知前端更新
+            if let Err(e) = handle_clipboard_change(&app_handle) {
+                eprintln!("处理剪贴板变化出错: {}", e);
+            }
+
+            // 无论是否保存，都通知前端当前剪贴板已变化
+            // 摘要在写入队列中排在保存之后，保证 saved_item_id 能查到刚保存的记录
+            let mut summary = match build_clipboard_summary(&app_handle) {
+                Ok(summary) => summary,
                 Err(e) => {
-                    eprintln!("处理剪贴板变化出错: {}", e);
+                    eprintln!("读取当前剪贴板摘要失败: {}", e);
+                    return;
                 }
+            };
+            let queued = submit_write(&app_handle, move |app_handle, conn| {
+                if let Err(e) = fill_saved_item_id(conn, &mut summary) {
+                    eprintln!("{}", e);
+                }
+                if let Err(e) = app_handle.emit("current-clipboard-changed", summary) {
+                    eprintln!("通知前端当前剪贴板变化失败: {}", e);
+                }
+            });
+            if let Err(e) = queued {
+                eprintln!("{}", e);
             }
         });
 
     Ok(())
 }
 
-// 生成预览文本
-fn generate_preview(content: &str, max_chars: usize) -> String {
-    if content.chars().count() <= max_chars {
-        content.to_string()
-    } else {
-        let preview: String = content.chars().take(max_chars).collect();
-        format!("{}...", preview)
+// 历史记录列表查询的公共列（与 map_history_row 的下标对应）
+// 列表只返回元数据和预览，不读取 content 列；图片返回缩略图
+const HISTORY_COLUMNS: &str = "h.id, h.content_type, CASE WHEN h.content_type = 'image' THEN h.thumbnail END,
+                h.content_hash, h.preview, h.timestamp, h.source_app, h.source_bundle_id, h.edited,
+                h.device_id, h.pinned, h.pin_order, h.image_width, h.image_height, h.failed_formats,
+                h.minified, h.search_truncated,
+                (SELECT json_group_array(t.name) FROM item_tags it JOIN tags t ON t.id = it.tag_id WHERE it.item_id = h.id),
+                h.note, h.category, h.url_title, h.url_favicon, h.color, h.language,
+                (SELECT json_group_array(cf.kind) FROM content_formats cf WHERE cf.item_id = h.id),
+                h.use_count, h.last_used_at, h.sensitive, h.expires_at";
+
+// 将查询结果行映射为历史记录，内容通过 get_clipboard_item_content 按需获取
+fn map_history_row(row: &rusqlite::Row) -> rusqlite::Result<ClipboardHistoryItem> {
+    let thumbnail: Option<String> = row.get(2)?;
+
+    Ok(ClipboardHistoryItem {
+        id: Some(row.get(0)?),
+        content_type: row.get(1)?,
+        content: thumbnail.unwrap_or_default(),
+        content_hash: row.get::<_, Option<String>>(3)?,
+        preview: row.get(4)?,
+        timestamp: row.get(5)?,
+        source_app: row.get::<_, Option<String>>(6)?,
+        source_bundle_id: row.get::<_, Option<String>>(7)?,
+        app_icon_base64: None,
+        edited: row.get(8)?,
+        device_id: row.get::<_, Option<String>>(9)?,
+        pinned: row.get(10)?,
+        pin_order: row.get::<_, Option<i64>>(11)?,
+        truncated: false,
+        image_width: row.get::<_, Option<u32>>(12)?,
+        image_height: row.get::<_, Option<u32>>(13)?,
+        failed_formats: parse_content_kinds(row.get(14)?),
+        content_omitted: true,
+        minified: row.get(15)?,
+        search_truncated: row.get(16)?,
+        tags: parse_tags(row.get(17)?),
+        note: row.get(18)?,
+        category: row.get(19)?,
+        url_title: row.get(20)?,
+        url_favicon: row.get(21)?,
+        color: row.get(22)?,
+        language: row.get(23)?,
+        alternate_formats: parse_content_kinds(row.get(24)?),
+        use_count: row.get(25)?,
+        last_used_at: row.get(26)?,
+        sensitive: row.get(27)?,
+        expires_at: row.get(28)?,
+    })
+}
+
+// 对文本和HTML内容进行HTML实体解码，图片从文件读取为 base64
+fn decode_item_content(content_type: ContentKind, content: String) -> String {
+    match content_type {
+        ContentKind::Text | ContentKind::Html => decode_html_entities(&content),
+        ContentKind::Image => resolve_image_content(content),
+        ContentKind::Rtf | ContentKind::Files => content,
+    }
+}
+
+// 面板中单条记录的渲染限制，超出时列表只返回截断内容
+const MAX_RENDER_BYTES: usize = 64 * 1024;
+const MAX_RENDER_HTML_NODES: usize = 2000;
+
+// 按标签起始符近似统计HTML节点数
+fn count_html_tags(html: &str) -> usize {
+    html.as_bytes()
+        .windows(2)
+        .filter(|pair| pair[0] == b'<' && pair[1].is_ascii_alphabetic())
+        .count()
+}
+
+// 在不超过 max_bytes 的字符边界处截断
+fn truncate_at_char_boundary(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+// 超出渲染限制的文本类内容替换为截断内容，避免大体积HTML卡住面板
+fn apply_render_limits(item: &mut ClipboardHistoryItem) {
+    match item.content_type {
+        ContentKind::Text | ContentKind::Rtf => {
+            if item.content.len() > MAX_RENDER_BYTES {
+                item.content = truncate_at_char_boundary(&item.content, MAX_RENDER_BYTES).to_string();
+                item.truncated = true;
+            }
+        }
+        ContentKind::Html => {
+            if item.content.len() > MAX_RENDER_BYTES
+                || count_html_tags(&item.content) > MAX_RENDER_HTML_NODES
+            {
+                // 超限的HTML改为转义后的纯文本，不再交给webview解析
+                let text = html_to_plain_text(&item.content);
+                item.content = escape_html(truncate_at_char_boundary(&text, MAX_RENDER_BYTES));
+                item.truncated = true;
+            }
+        }
+        ContentKind::Image | ContentKind::Files => {}
+    }
+}
+
+// 历史列表的筛选条件，未提供的条件以 NULL 传入
+// ?1 内容类型、?2 标签、?3 内容类别、?4 来源应用 Bundle ID、?5 起始时间（包含）、?6 截止时间（不包含）
+const HISTORY_FILTER_SQL: &str = "(?1 IS NULL OR h.content_type = ?1)
+           AND (?2 IS NULL OR h.id IN (
+               SELECT it.item_id FROM item_tags it JOIN tags t ON t.id = it.tag_id WHERE t.name = ?2
+           ))
+           AND (?3 IS NULL OR h.category = ?3)
+           AND (?4 IS NULL OR h.source_bundle_id = ?4)
+           AND (?5 IS NULL OR h.timestamp >= ?5)
+           AND (?6 IS NULL OR h.timestamp < ?6)";
+
+// 历史列表的筛选参数
+struct HistoryFilter {
+    content_type: Option<ContentKind>,
+    tag: Option<String>,
+    category: Option<ContentCategory>,
+    source_bundle_id: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+}
+
+impl HistoryFilter {
+    fn new(
+        content_type: Option<ContentKind>,
+        tag: Option<String>,
+        category: Option<ContentCategory>,
+        source_bundle_id: Option<String>,
+        since: Option<String>,
+        until: Option<String>,
+    ) -> Result<Self, OicopyError> {
+        let non_empty = |value: Option<String>| value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+        let normalize = |value: Option<String>| non_empty(value).map(|value| normalize_timestamp(&value)).transpose();
+        Ok(Self {
+            content_type,
+            tag: non_empty(tag),
+            category,
+            source_bundle_id: non_empty(source_bundle_id),
+            since: normalize(since)?,
+            until: normalize(until)?,
+        })
+    }
+}
+
+// 历史列表的排序方式，置顶记录始终在前
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySort {
+    #[default]
+    Recent,       // 按复制时间从新到旧
+    MostUsed,     // 按使用次数从多到少
+    RecentlyUsed, // 按最近使用时间，从未使用过的记录排在最后
+}
+
+impl HistorySort {
+    fn order_sql(self) -> &'static str {
+        match self {
+            HistorySort::Recent => "h.id DESC",
+            HistorySort::MostUsed => "h.use_count DESC, h.last_used_at DESC, h.id DESC",
+            HistorySort::RecentlyUsed => "h.last_used_at IS NULL, h.last_used_at DESC, h.id DESC",
+        }
     }
 }
 
-// 获取剪贴板历史记录
+// 执行历史列表查询并收集结果
+fn query_history_items(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> Result<Vec<ClipboardHistoryItem>, OicopyError> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| OicopyError::Db(format!("准备查询失败: {}", e)))?;
+    let rows = stmt
+        .query_map(params, map_history_row)
+        .map_err(|e| OicopyError::Db(format!("查询失败: {}", e)))?;
+
+    let mut items = Vec::new();
+    for item in rows {
+        items.push(item.map_err(|e| OicopyError::Db(format!("处理行数据失败: {}", e)))?);
+    }
+    Ok(items)
+}
+
+// 获取剪贴板历史记录（可按类型、标签、内容类别、来源应用和时间范围筛选，可按使用频率排序）
 #[tauri::command]
 pub async fn get_clipboard_history(
     app: AppHandle,
     limit: Option<u32>,
     offset: Option<u32>,
-    content_type: Option<String>,
-) -> Result<Vec<ClipboardHistoryItem>, String> {
+    content_type: Option<ContentKind>,
+    tag: Option<String>,
+    category: Option<ContentCategory>,
+    source_bundle_id: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    sort: Option<HistorySort>,
+) -> Result<Vec<ClipboardHistoryItem>, OicopyError> {
     let limit = limit.unwrap_or(50);
     let offset = offset.unwrap_or(0);
+    let filter = HistoryFilter::new(content_type, tag, category, source_bundle_id, since, until)?;
+
+    // 列表查询使用只读连接，不等待写入线程
+    let conn = open_connection(&app, ConnectionRole::Analytics)?;
+
+    // 未提供的筛选条件以 NULL 传入，跳过对应的过滤
+    // 图标不随列表返回，前端按 source_bundle_id 通过 get_app_icons 单独获取并缓存
+    let sql = format!(
+        "SELECT {} FROM clipboard_history h
+         WHERE {}
+         ORDER BY h.pinned DESC, CASE WHEN h.pinned = 1 THEN h.pin_order END, {}
+         LIMIT ?7 OFFSET ?8",
+        HISTORY_COLUMNS,
+        HISTORY_FILTER_SQL,
+        sort.unwrap_or_default().order_sql()
+    );
+    query_history_items(
+        &conn,
+        &sql,
+        params![
+            filter.content_type,
+            filter.tag,
+            filter.category,
+            filter.source_bundle_id,
+            filter.since,
+            filter.until,
+            limit,
+            offset
+        ],
+    )
+}
 
-    // 获取数据库连接
-    let conn = init_database(&app)?;
+// 按游标分页的历史记录
+#[derive(Debug, Serialize)]
+pub struct HistoryPage {
+    pub items: Vec<ClipboardHistoryItem>,
+    pub next_cursor: Option<i64>, // 下一页的 before_id，没有更多记录时为空
+}
 
-    // 根据是否提供了 content_type 选择不同的 SQL，使用 LEFT JOIN 获取图标
-    let sql = if content_type.is_some() {
-        "SELECT h.id, h.content_type, h.content, h.content_hash, h.preview, h.timestamp, 
-                h.source_app, h.source_bundle_id, i.icon_base64
-         FROM clipboard_history h 
-         LEFT JOIN app_icons i ON h.source_bundle_id = i.bundle_id
-         WHERE h.content_type = ?1 ORDER BY h.id DESC LIMIT ?2 OFFSET ?3"
-    } else {
-        "SELECT h.id, h.content_type, h.content, h.content_hash, h.preview, h.timestamp, 
-                h.source_app, h.source_bundle_id, i.icon_base64
-         FROM clipboard_history h 
-         LEFT JOIN app_icons i ON h.source_bundle_id = i.bundle_id
-         ORDER BY h.id DESC LIMIT ?1 OFFSET ?2"
+// 按游标分页获取剪贴板历史记录，排序与 get_clipboard_history 默认的按时间排序相同
+// before_id 为上一页最后一条记录的 ID；置顶记录在前，之后的普通记录按 ID 倒序直接走主键范围扫描，
+// 查询成本只与 limit 有关，不随翻页深度增长
+#[tauri::command]
+pub async fn get_clipboard_history_page(
+    app: AppHandle,
+    limit: Option<u32>,
+    before_id: Option<i64>,
+    content_type: Option<ContentKind>,
+    tag: Option<String>,
+    category: Option<ContentCategory>,
+    source_bundle_id: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+) -> Result<HistoryPage, OicopyError> {
+    let limit = limit.unwrap_or(50).max(1) as usize;
+    let filter = HistoryFilter::new(content_type, tag, category, source_bundle_id, since, until)?;
+    let conn = open_connection(&app, ConnectionRole::Analytics)?;
+
+    // 游标记录的置顶顺序；游标记录已被删除时按普通记录处理
+    let cursor_pin_order: Option<i64> = match before_id {
+        Some(id) => conn
+            .query_row(
+                "SELECT COALESCE(pin_order, -1) FROM clipboard_history WHERE id = ?1 AND pinned = 1",
+                params![id],
+                |row| row.get(0),
+            )
+            .ok(),
+        None => None,
     };
+    let in_pinned_section = before_id.is_none() || cursor_pin_order.is_some();
 
-    // 定义统一的映射闭包
-    let map_row = |row: &rusqlite::Row| -> rusqlite::Result<ClipboardHistoryItem> {
-        let content: String = row.get(2)?;
-        let content_type: String = row.get(1)?;
+    let mut items = Vec::new();
+    if in_pinned_section {
+        let sql = format!(
+            "SELECT {} FROM clipboard_history h
+             WHERE {} AND h.pinned = 1
+               AND (?7 IS NULL OR COALESCE(h.pin_order, -1) > ?7
+                    OR (COALESCE(h.pin_order, -1) = ?7 AND h.id < ?8))
+             ORDER BY h.pin_order, h.id DESC
+             LIMIT ?9",
+            HISTORY_COLUMNS, HISTORY_FILTER_SQL
+        );
+        items = query_history_items(
+            &conn,
+            &sql,
+            params![
+                filter.content_type,
+                filter.tag,
+                filter.category,
+                filter.source_bundle_id,
+                filter.since,
+                filter.until,
+                cursor_pin_order,
+                before_id,
+                limit as i64
+            ],
+        )?;
+    }
 
-        // 对文本和HTML内容进行HTML实体解码
-        let decoded_content = if content_type == "text" || content_type == "html" {
-            decode_html_entities(&content)
-        } else {
-            content
-        };
+    if items.len() < limit {
+        let unpinned_before = if in_pinned_section { None } else { before_id };
+        let sql = format!(
+            "SELECT {} FROM clipboard_history h
+             WHERE {} AND h.pinned = 0 AND (?7 IS NULL OR h.id < ?7)
+             ORDER BY h.id DESC
+             LIMIT ?8",
+            HISTORY_COLUMNS, HISTORY_FILTER_SQL
+        );
+        items.extend(query_history_items(
+            &conn,
+            &sql,
+            params![
+                filter.content_type,
+                filter.tag,
+                filter.category,
+                filter.source_bundle_id,
+                filter.since,
+                filter.until,
+                unpinned_before,
+                (limit - items.len()) as i64
+            ],
+        )?);
+    }
 
-        Ok(ClipboardHistoryItem {
-            id: Some(row.get(0)?),
-            content_type,
-            content: decoded_content,
-            content_hash: row.get::<_, Option<String>>(3)?,
-            preview: row.get(4)?,
-            timestamp: row.get(5)?,
-            source_app: row.get::<_, Option<String>>(6)?,
-            source_bundle_id: row.get::<_, Option<String>>(7)?,
-            app_icon_base64: row.get::<_, Option<String>>(8)?,
+    let next_cursor = if items.len() == limit { items.last().and_then(|item| item.id) } else { None };
+    Ok(HistoryPage { items, next_cursor })
+}
+
+// 历史记录中出现过的来源应用
+#[derive(Debug, Serialize)]
+pub struct SourceAppSummary {
+    pub bundle_id: String,
+    pub app_name: Option<String>,
+    pub item_count: i64,
+    pub last_copied_at: String,
+}
+
+// 列出历史记录的来源应用及记录数，供筛选界面使用（按记录数从多到少）
+#[tauri::command]
+pub async fn list_source_apps(app: AppHandle) -> Result<Vec<SourceAppSummary>, OicopyError> {
+    let conn = open_connection(&app, ConnectionRole::Analytics)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT source_bundle_id, MAX(source_app), COUNT(*), MAX(timestamp)
+             FROM clipboard_history
+             WHERE source_bundle_id IS NOT NULL AND source_bundle_id != ''
+             GROUP BY source_bundle_id
+             ORDER BY COUNT(*) DESC, MAX(timestamp) DESC",
+        )
+        .map_err(|e| OicopyError::Db(format!("准备查询失败: {}", e)))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(SourceAppSummary {
+                bundle_id: row.get(0)?,
+                app_name: row.get(1)?,
+                item_count: row.get(2)?,
+                last_copied_at: row.get(3)?,
+            })
         })
+        .map_err(|e| OicopyError::Db(format!("查询来源应用失败: {}", e)))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("处理行数据失败: {}", e)))
+}
+
+// 全文搜索剪贴板历史记录（按相关度排序，支持分页）
+#[tauri::command]
+pub async fn search_clipboard_history(
+    app: AppHandle,
+    query: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    content_type: Option<ContentKind>,
+) -> Result<Vec<ClipboardHistoryItem>, OicopyError> {
+    let limit = limit.unwrap_or(50);
+    let offset = offset.unwrap_or(0);
+
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let conn = open_connection(&app, ConnectionRole::Analytics)?;
+    // 敏感记录默认不出现在搜索结果中
+    let include_sensitive = get_settings(&conn)?.search_sensitive_items;
+
+    // 优先使用FTS5索引，索引不可用或查询词过短时退回 LIKE 查询
+    let fts_query = if fts_index_exists(&conn) {
+        build_fts_query(&query)
+    } else {
+        None
+    };
+
+    // 取出较多候选记录，按相关度、语言和内容特征重排后再分页
+    let rank_columns = "length(h.content),
+                 CASE WHEN h.content_type IN ('text', 'html') THEN substr(h.content, 1, ?5) ELSE h.preview END";
+    let (sql, pattern) = match fts_query {
+        Some(fts_query) => (
+            format!(
+                "SELECT {}, {}, f.rank FROM clipboard_fts f
+                 JOIN clipboard_history h ON h.id = f.rowid
+                 WHERE clipboard_fts MATCH ?1 AND (?4 IS NULL OR h.content_type = ?4)
+                   AND (?6 = 1 OR h.sensitive = 0)
+                 ORDER BY f.rank, h.id DESC
+                 LIMIT ?2 OFFSET ?3",
+                HISTORY_COLUMNS, rank_columns
+            ),
+            fts_query,
+        ),
+        None => (
+            format!(
+                "SELECT {}, {}, NULL FROM clipboard_history h
+                 WHERE h.content_type != 'image'
+                   AND (h.content LIKE ?1 ESCAPE '\\' OR h.preview LIKE ?1 ESCAPE '\\' OR h.note LIKE ?1 ESCAPE '\\')
+                   AND (?4 IS NULL OR h.content_type = ?4)
+                   AND (?6 = 1 OR h.sensitive = 0)
+                 ORDER BY h.id DESC
+                 LIMIT ?2 OFFSET ?3",
+                HISTORY_COLUMNS, rank_columns
+            ),
+            build_like_pattern(&query),
+        ),
     };
 
-    // 准备查询语句
     let mut stmt = conn
-        .prepare(sql)
-        .map_err(|e| format!("准备查询失败: {}", e))?;
+        .prepare(&sql)
+        .map_err(|e| OicopyError::Db(format!("准备搜索查询失败: {}", e)))?;
+    let rows = stmt
+        .query_map(
+            params![pattern, candidate_limit(limit, offset), 0, content_type, SAMPLE_CHARS, include_sensitive],
+            |row| {
+                let candidate = RankCandidate {
+                    content_bytes: row.get::<_, Option<i64>>(29)?.unwrap_or(0),
+                    sample: row.get::<_, Option<String>>(30)?.unwrap_or_default(),
+                    fts_rank: row.get::<_, Option<f64>>(31)?,
+                };
+                Ok((map_history_row(row)?, candidate))
+            },
+        )
+        .map_err(|e| OicopyError::Db(format!("搜索失败: {}", e)))?;
+
+    let mut candidates = Vec::new();
+    for row in rows {
+        candidates.push(row.map_err(|e| OicopyError::Db(format!("处理行数据失败: {}", e)))?);
+    }
+
+    Ok(rank_results(&query, candidates, limit, offset))
+}
+
+// 替换历史记录中的内容并通知前端刷新
+fn rewrite_item_content<F>(app: &AppHandle, id: i64, rewrite: F) -> Result<(), OicopyError>
+where
+    F: FnOnce(&ClipboardContent) -> Result<ClipboardContent, String>,
+{
+    let conn = init_database(app)?;
+    ensure_unlocked(&conn)?;
+    let item = get_history_item(&conn, id)?.ok_or_else(|| OicopyError::NotFound(format!("记录不存在: {}", id)))?;
+    let content = rewrite(&item.to_content()?)?;
+    update_item_content(&conn, id, &content)?;
+
+    emit_change_event(&app, "clipboard-updated");
+    Ok(())
+}
+
+// 编辑历史记录内容
+#[tauri::command]
+pub async fn edit_item_content(app: AppHandle, id: i64, new_content: String) -> Result<(), OicopyError> {
+    rewrite_item_content(&app, id, |content| content.with_text(new_content))
+}
+
+// 脱敏历史记录：将其中的敏感片段替换为占位文本，保留其余内容
+#[tauri::command]
+pub async fn redact_item(
+    app: AppHandle,
+    id: i64,
+    target: String,
+    replacement: Option<String>,
+) -> Result<(), OicopyError> {
+    let replacement = replacement.unwrap_or_else(|| "••••••".to_string());
+    rewrite_item_content(&app, id, |content| content.redact(&target, &replacement))
+}
+
+// 删除单条历史记录（移入回收站，可通过 restore_clipboard_item 恢复）
+#[tauri::command]
+pub async fn delete_clipboard_item(app: AppHandle, id: i64) -> Result<(), OicopyError> {
+    let mut conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    if move_to_trash(&mut conn, &[id])? == 0 {
+        return Err(OicopyError::NotFound(format!("记录不存在: {}", id)));
+    }
+
+    println!("已将记录 {} 移入回收站", id);
+    emit_change_event(&app, "clipboard-updated");
+    Ok(())
+}
+
+// 批量删除历史记录（移入回收站），返回实际删除的条数（不存在的ID忽略）
+#[tauri::command]
+pub async fn delete_clipboard_items(app: AppHandle, ids: Vec<i64>) -> Result<usize, OicopyError> {
+    let mut conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let deleted = move_to_trash(&mut conn, &ids)?;
+
+    println!("已将 {} 条记录移入回收站", deleted);
+    if deleted > 0 {
+        emit_change_event(&app, "clipboard-updated");
+    }
+    Ok(deleted)
+}
+
+// 按条件批量删除历史记录（内容类型、来源应用、某个时间之前），置顶项除外，返回删除的条数
+// 至少需要一个条件，清空全部历史请使用 clear_all_history
+#[tauri::command]
+pub async fn delete_history_by_filter(
+    app: AppHandle,
+    content_type: Option<ContentKind>,
+    source_bundle_id: Option<String>,
+    before_date: Option<String>,
+) -> Result<usize, OicopyError> {
+    let source_bundle_id = source_bundle_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty());
+    let before = before_date
+        .filter(|date| !date.trim().is_empty())
+        .map(|date| normalize_timestamp(&date))
+        .transpose()?;
+    if content_type.is_none() && source_bundle_id.is_none() && before.is_none() {
+        return Err(OicopyError::Validation("至少需要指定一个删除条件".to_string()));
+    }
+
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let deleted = delete_items_by_filter(&conn, content_type, source_bundle_id.as_deref(), before.as_deref())?;
+
+    println!(
+        "按条件删除了 {} 条记录 (类型: {:?}, 来源: {:?}, 早于: {:?})",
+        deleted, content_type, source_bundle_id, before
+    );
+    if deleted > 0 {
+        emit_change_event(&app, "clipboard-updated");
+    }
+    Ok(deleted)
+}
+
+// 按用户指定顺序重排置顶项
+#[tauri::command]
+pub async fn reorder_pinned(app: AppHandle, ids_in_order: Vec<i64>) -> Result<usize, OicopyError> {
+    let mut conn = init_database(&app)?;
+    let updated = reorder_pinned_items(&mut conn, &ids_in_order)?;
+
+    emit_change_event(&app, "clipboard-updated");
+    Ok(updated)
+}
+
+// 置顶剪贴板记录（置顶项不会被自动清理）
+#[tauri::command]
+pub async fn pin_clipboard_item(app: AppHandle, id: i64) -> Result<(), OicopyError> {
+    let conn = init_database(&app)?;
+    set_item_pinned(&conn, id, true)?;
+
+    emit_change_event(&app, "clipboard-updated");
+    Ok(())
+}
+
+// 取消置顶剪贴板记录
+#[tauri::command]
+pub async fn unpin_clipboard_item(app: AppHandle, id: i64) -> Result<(), OicopyError> {
+    let conn = init_database(&app)?;
+    set_item_pinned(&conn, id, false)?;
+
+    emit_change_event(&app, "clipboard-updated");
+    Ok(())
+}
+
+// 设置记录备注，传入空值清除备注
+#[tauri::command]
+pub async fn set_item_note(app: AppHandle, id: i64, note: Option<String>) -> Result<(), OicopyError> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    crate::db::set_item_note(&conn, id, note.as_deref())?;
+
+    emit_change_event(&app, "clipboard-updated");
+    Ok(())
+}
+
+// 为记录添加标签
+#[tauri::command]
+pub async fn add_tag_to_item(app: AppHandle, id: i64, tag: String) -> Result<(), OicopyError> {
+    let tag = tag.trim().to_string();
+    if tag.is_empty() {
+        return Err(OicopyError::Validation("标签不能为空".to_string()));
+    }
+    let conn = init_database(&app)?;
+    if get_history_item(&conn, id)?.is_none() {
+        return Err(OicopyError::NotFound(format!("记录不存在: {}", id)));
+    }
+    add_item_tags(&conn, id, &[tag])?;
+
+    emit_change_event(&app, "clipboard-updated");
+    Ok(())
+}
+
+// 移除记录的标签
+#[tauri::command]
+pub async fn remove_tag_from_item(app: AppHandle, id: i64, tag: String) -> Result<(), OicopyError> {
+    let conn = init_database(&app)?;
+    if !remove_item_tag(&conn, id, &tag)? {
+        return Err(OicopyError::NotFound(format!("记录 {} 没有标签: {}", id, tag)));
+    }
+
+    emit_change_event(&app, "clipboard-updated");
+    Ok(())
+}
+
+// 获取全部标签及使用次数
+#[tauri::command]
+pub async fn list_tags(app: AppHandle) -> Result<Vec<TagInfo>, OicopyError> {
+    let conn = open_connection(&app, ConnectionRole::Analytics)?;
+    list_all_tags(&conn)
+}
+
+// 按需加载的单条记录内容
+#[derive(Debug, Serialize)]
+pub struct ItemContent {
+    pub id: i64,
+    pub content_type: ContentKind,
+    pub content: String,
+    pub truncated: bool,
+}
+
+// 按需获取单条记录的内容；render_limited 为 true 时按面板渲染限制截断（用于卡片展示）
+#[tauri::command]
+pub async fn get_clipboard_item_content(
+    app: AppHandle,
+    id: i64,
+    render_limited: Option<bool>,
+) -> Result<ItemContent, OicopyError> {
+    let conn = init_database(&app)?;
+    let mut item = get_history_item(&conn, id)?.ok_or_else(|| OicopyError::NotFound(format!("记录 {} 不存在", id)))?;
+    item.content = decode_item_content(item.content_type, item.content);
+    if render_limited.unwrap_or(false) {
+        apply_render_limits(&mut item);
+    }
+
+    Ok(ItemContent {
+        id,
+        content_type: item.content_type,
+        content: item.content,
+        truncated: item.truncated,
+    })
+}
+
+// 获取单条记录的完整内容（不受面板渲染限制）
+#[tauri::command]
+pub async fn get_item_full_content(app: AppHandle, id: i64) -> Result<ClipboardHistoryItem, OicopyError> {
+    let conn = init_database(&app)?;
+    let mut item = get_history_item(&conn, id)?.ok_or_else(|| OicopyError::NotFound(format!("记录 {} 不存在", id)))?;
+    item.content = decode_item_content(item.content_type, item.content);
+    Ok(item)
+}
+
+// 写回剪贴板的内容：大图片不读入内存，只保留图片文件路径
+enum PasteContent {
+    Loaded(ClipboardContent),
+    ImagePromise { reference: String, format: String },
+}
+
+impl PasteContent {
+    fn kind(&self) -> ContentKind {
+        match self {
+            PasteContent::Loaded(content) => content.kind(),
+            PasteContent::ImagePromise { .. } => ContentKind::Image,
+        }
+    }
+}
+
+// 读取历史记录的结构化内容，不小于 PROMISE_MIN_BYTES 的图片改为延迟提供
+fn load_item_content(conn: &rusqlite::Connection, id: i64) -> Result<PasteContent, OicopyError> {
+    let item = get_history_item(conn, id)?.ok_or_else(|| OicopyError::NotFound(format!("记录 {} 不存在", id)))?;
+    let promised = cfg!(target_os = "macos")
+        && item.content_type == ContentKind::Image
+        && is_blob_reference(&item.content)
+        && blob_size(&item.content).is_some_and(|size| size >= PROMISE_MIN_BYTES);
+    if promised {
+        return Ok(PasteContent::ImagePromise {
+            format: blob_format(&item.content),
+            reference: item.content,
+        });
+    }
+    Ok(PasteContent::Loaded(item.to_content()?))
+}
+
+// 按目标应用读取要粘贴的格式，记录没有更合适的格式时使用主格式
+fn load_item_content_for_target(
+    conn: &rusqlite::Connection,
+    id: i64,
+    bundle_id: &str,
+) -> Result<PasteContent, OicopyError> {
+    let item = get_history_item(conn, id)?.ok_or_else(|| OicopyError::NotFound(format!("记录 {} 不存在", id)))?;
+    let kind = preferred_paste_kind(bundle_id, item.content_type, &item.alternate_formats);
+    if kind != item.content_type {
+        if let Some(content) = load_content_format(conn, id, kind)? {
+            println!("粘贴到 {} 时使用记录 {} 的{}格式", bundle_id, id, kind.label());
+            return Ok(PasteContent::Loaded(content));
+        }
+    }
+    load_item_content(conn, id)
+}
+
+// 读取记录的 HTML：优先使用 HTML 主格式或附带格式，其次将 RTF 转换为 HTML
+fn load_item_html(conn: &rusqlite::Connection, id: i64) -> Result<String, OicopyError> {
+    let item = get_history_item(conn, id)?.ok_or_else(|| OicopyError::NotFound(format!("记录 {} 不存在", id)))?;
+    let content = match item.content_type {
+        ContentKind::Html | ContentKind::Rtf => Some(item.to_content()?),
+        _ if item.alternate_formats.contains(&ContentKind::Html) => load_content_format(conn, id, ContentKind::Html)?,
+        _ if item.alternate_formats.contains(&ContentKind::Rtf) => load_content_format(conn, id, ContentKind::Rtf)?,
+        _ => None,
+    };
+    match content {
+        Some(ClipboardContent::Html { raw, .. }) => Ok(raw),
+        Some(ClipboardContent::Rtf { rtf }) => Ok(rtf_to_html(&rtf)),
+        _ => Err(OicopyError::NotFound(format!("记录 {} 没有 HTML 或 RTF 内容", id))),
+    }
+}
+
+// 读取记录的 HTML/RTF 内容并转换为 Markdown 文本
+fn load_item_as_markdown(
+    conn: &rusqlite::Connection,
+    id: i64,
+    _bundle_id: &str,
+) -> Result<PasteContent, OicopyError> {
+    let html = load_item_html(conn, id)?;
+    Ok(PasteContent::Loaded(ClipboardContent::Text { text: html_to_markdown(&html) }))
+}
+
+// 获取记录的 HTML 形式，RTF 记录转换为 HTML 后供面板渲染
+#[tauri::command]
+pub async fn get_item_html(app: AppHandle, id: i64) -> Result<String, OicopyError> {
+    let conn = init_database(&app)?;
+    load_item_html(&conn, id)
+}
+
+// 将内容写回系统剪贴板并触发反馈
+fn write_item_to_clipboard(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+    id: i64,
+    content: &PasteContent,
+) -> Result<(), OicopyError> {
+    // 锁定模式下禁止复制/粘贴历史内容
+    ensure_unlocked(conn).map_err(OicopyError::Permission)?;
+
+    match content {
+        PasteContent::Loaded(content) => {
+            let clipboard_state = app.state::<tauri_plugin_clipboard::Clipboard>();
+            write_clipboard_content(&clipboard_state, content).map_err(OicopyError::Clipboard)?;
+        }
+        PasteContent::ImagePromise { reference, format } => {
+            write_image_promise(reference, format).map_err(OicopyError::Clipboard)?;
+        }
+    }
+    trigger_feedback(app, conn, FeedbackEvent::Paste);
+    if let Err(e) = record_item_use(conn, id) {
+        eprintln!("{}", e);
+    }
+
+    println!("已将记录 {} ({}) 写回剪贴板", id, content.kind().label());
+    Ok(())
+}
+
+// 将历史记录按原始格式写回系统剪贴板
+#[tauri::command]
+pub async fn copy_item_to_clipboard(app: AppHandle, id: i64) -> Result<(), OicopyError> {
+    copy_item_by_id(&app, id)
+}
+
+// 将指定记录写回剪贴板（供命令和托盘菜单使用）
+pub fn copy_item_by_id(app: &AppHandle, id: i64) -> Result<(), OicopyError> {
+    let conn = init_database(app)?;
+    let content = load_item_content(&conn, id)?;
+    write_item_to_clipboard(app, &conn, id, &content)
+}
+
+// 将颜色记录转换为指定格式（hex/rgb/hsl）后写入剪贴板，返回转换后的文本
+#[tauri::command]
+pub async fn copy_color_as(app: AppHandle, id: i64, format: ColorFormat) -> Result<String, OicopyError> {
+    let conn = init_database(&app)?;
+    let item = get_history_item(&conn, id)?.ok_or_else(|| OicopyError::NotFound(format!("记录不存在: {}", id)))?;
+    let color = match item.to_content()? {
+        ClipboardContent::Text { text } => parse_color(&text),
+        _ => None,
+    }
+    .ok_or_else(|| OicopyError::NotFound(format!("记录 {} 不是颜色", id)))?;
+
+    let text = format_color(color, format);
+    write_item_to_clipboard(&app, &conn, id, &PasteContent::Loaded(ClipboardContent::Text { text: text.clone() }))?;
+    Ok(text)
+}
 
-    // 执行查询，根据是否有 content_type 传递不同的参数
-    let rows = if let Some(typ) = content_type.as_deref() {
-        stmt.query_map(params![typ, limit, offset], map_row)
+// 常见终端应用，粘贴到这些应用前需要确认
+const TERMINAL_BUNDLE_IDS: &[&str] = &[
+    "com.apple.Terminal",
+    "com.googlecode.iterm2",
+    "dev.warp.Warp-Stable",
+    "net.kovidgoyal.kitty",
+    "org.alacritty",
+    "com.github.wez.wezterm",
+    "co.zeit.hyper",
+    "com.mitchellh.ghostty",
+];
+
+// 判断粘贴前是否需要用户确认，返回确认原因
+fn check_paste_confirmation(
+    settings: &AppSettings,
+    content: &ClipboardContent,
+    app_name: &str,
+    bundle_id: &str,
+) -> Option<FeatureError> {
+    if !settings.paste_confirm_enabled {
+        return None;
+    }
+
+    // 只检查会以文本形式粘贴的内容
+    let text = match content {
+        ClipboardContent::Text { text } => text.clone(),
+        ClipboardContent::Html { raw, .. } => html_to_plain_text(raw),
+        ClipboardContent::Rtf { rtf } => rtf_to_text(rtf),
+        ClipboardContent::Image { .. } | ClipboardContent::Files { .. } => return None,
+    };
+    let line_count = text.lines().count();
+    let byte_count = text.len();
+
+    let reason = if TERMINAL_BUNDLE_IDS.contains(&bundle_id) {
+        format!("即将粘贴到终端应用 {}", app_name)
+    } else if line_count as i64 > settings.paste_confirm_max_lines {
+        format!("内容共 {} 行，超过 {} 行", line_count, settings.paste_confirm_max_lines)
+    } else if byte_count as i64 > settings.paste_confirm_max_bytes {
+        format!("内容共 {} 字节，超过 {} 字节", byte_count, settings.paste_confirm_max_bytes)
     } else {
-        stmt.query_map(params![limit, offset], map_row)
+        return None;
+    };
+
+    Some(FeatureError::NeedsConfirmation {
+        message: format!("{}，确认后再粘贴", reason),
+        reason,
+        line_count,
+        byte_count,
+        target_app: app_name.to_string(),
+    })
+}
+
+// 将记录粘贴到打开面板前的前台应用，返回粘贴的内容
+// load 根据目标应用的 bundle id 决定粘贴的内容
+async fn paste_into_previous_app(
+    app: &AppHandle,
+    id: i64,
+    confirmed: bool,
+    load: fn(&rusqlite::Connection, i64, &str) -> Result<PasteContent, OicopyError>,
+) -> Result<PasteContent, FeatureError> {
+    require_feature(Feature::PasteBack).await?;
+
+    let (app_name, bundle_id) =
+        previous_frontmost_app().ok_or_else(|| "没有可粘贴的目标应用".to_string())?;
+
+    let conn = init_database(app)?;
+    let content = load(&conn, id, &bundle_id)?;
+    // 延迟提供的只有图片，图片不需要确认
+    if let (false, PasteContent::Loaded(loaded)) = (confirmed, &content) {
+        let settings = get_settings(&conn)?;
+        if let Some(confirmation) = check_paste_confirmation(&settings, loaded, &app_name, &bundle_id) {
+            println!("粘贴记录 {} 需要确认: {}", id, confirmation);
+            return Err(confirmation);
+        }
     }
-    .map_err(|e| format!("查询失败: {}", e))?;
 
-    // 收集查询结果
-    let mut items = Vec::new();
-    for item in rows {
-        items.push(item.map_err(|e| format!("处理行数据失败: {}", e))?);
+    write_item_to_clipboard(app, &conn, id, &content)?;
+    let _ = hide_panel_window(app.clone(), "copy-panel".to_string());
+
+    activate_app(&bundle_id)?;
+    // 等待目标应用重新成为前台窗口后再发送按键
+    tokio::time::sleep(tokio::time::Duration::from_millis(150)).await;
+    synthesize_paste()?;
+
+    println!("已将记录 {} 粘贴到 {}", id, app_name);
+    Ok(content)
+}
+
+// 通过记录快捷键直接粘贴记录到当前前台应用（不经过面板，用户主动绑定，不做粘贴确认）
+pub async fn paste_item_from_shortcut(app: &AppHandle, id: i64) -> Result<(), FeatureError> {
+    require_feature(Feature::PasteBack).await?;
+    copy_item_by_id(app, id)?;
+    synthesize_paste()?;
+    println!("已通过快捷键粘贴记录 {}", id);
+    Ok(())
+}
+
+// 将历史记录写回剪贴板，并直接粘贴到打开面板前的前台应用
+// 内容过大或目标为终端时返回 NeedsConfirmation，前端确认后以 confirmed = true 重新调用
+#[tauri::command]
+pub async fn paste_item(
+    app: AppHandle,
+    id: i64,
+    confirmed: Option<bool>,
+) -> Result<(), FeatureError> {
+    paste_into_previous_app(&app, id, confirmed.unwrap_or(false), load_item_content_for_target).await?;
+    Ok(())
+}
+
+// 将 HTML/RTF 记录转换为 Markdown（标题、列表、链接、代码块）后粘贴到前台应用，返回转换后的文本
+#[tauri::command]
+pub async fn paste_as_markdown(
+    app: AppHandle,
+    id: i64,
+    confirmed: Option<bool>,
+) -> Result<String, FeatureError> {
+    match paste_into_previous_app(&app, id, confirmed.unwrap_or(false), load_item_as_markdown).await? {
+        PasteContent::Loaded(ClipboardContent::Text { text }) => Ok(text),
+        _ => Ok(String::new()),
     }
+}
 
-    Ok(items)
+// 粘贴后清空剪贴板（可选删除该记录），用于只应使用一次的密码等内容
+#[tauri::command]
+pub async fn paste_and_clear(
+    app: AppHandle,
+    id: i64,
+    confirmed: Option<bool>,
+    remove_item: Option<bool>,
+    clear_after_ms: Option<u64>,
+) -> Result<(), FeatureError> {
+    let content = paste_into_previous_app(&app, id, confirmed.unwrap_or(false), load_item_content_for_target).await?;
+
+    // 留出时间让目标应用完成粘贴
+    let delay = clear_after_ms.unwrap_or(1500).clamp(200, 60_000);
+    tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+
+    // 只有剪贴板仍是刚粘贴的内容时才清空，避免清掉用户之后复制的内容
+    // 延迟提供的图片通过标记类型判断，读取内容会触发图片数据生成
+    let clipboard_state = app.state::<tauri_plugin_clipboard::Clipboard>();
+    let unchanged = match content {
+        PasteContent::Loaded(ref content) => read_clipboard_content(clipboard_state.inner(), content.kind())
+            .is_some_and(|current| current.content_hash() == content.content_hash()),
+        PasteContent::ImagePromise { .. } => has_promise_marker(),
+    };
+    if unchanged {
+        clipboard_state
+            .clear()
+            .map_err(|e| format!("清空剪贴板失败: {}", e))?;
+        println!("已清空剪贴板");
+    } else {
+        println!("剪贴板内容已变化，跳过清空");
+    }
+
+    // 和其他删除操作一样先移入回收站
+    if remove_item.unwrap_or(false) {
+        let mut conn = init_database(&app)?;
+        move_to_trash(&mut conn, &[id])?;
+        emit_change_event(&app, "clipboard-updated");
+    }
+    Ok(())
+}
+
+// 将前端传入的时间统一为数据库中的格式（%Y-%m-%d %H:%M:%S，本地时间）
+fn normalize_timestamp(timestamp: &str) -> Result<String, String> {
+    const DB_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+    let timestamp = timestamp.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(timestamp) {
+        return Ok(time.with_timezone(&Local).format(DB_FORMAT).to_string());
+    }
+    for format in [DB_FORMAT, "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(timestamp, format) {
+            return Ok(time.format(DB_FORMAT).to_string());
+        }
+    }
+    // 只有日期时视为当天零点
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(timestamp, "%Y-%m-%d") {
+        return Ok(format!("{} 00:00:00", date.format("%Y-%m-%d")));
+    }
+    Err(format!("无法解析时间: {}", timestamp))
+}
+
+// 获取指定时间点之前最近一次捕获的剪贴板内容
+#[tauri::command]
+pub async fn get_clipboard_at(
+    app: AppHandle,
+    timestamp: String,
+) -> Result<Option<ClipboardHistoryItem>, OicopyError> {
+    let timestamp = normalize_timestamp(&timestamp)?;
+    let conn = init_database(&app)?;
+    get_item_before(&conn, &timestamp)
+}
+
+// 将剪贴板恢复为指定时间点的内容
+#[tauri::command]
+pub async fn restore_clipboard_at(
+    app: AppHandle,
+    timestamp: String,
+) -> Result<ClipboardHistoryItem, OicopyError> {
+    let timestamp = normalize_timestamp(&timestamp)?;
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let item = get_item_before(&conn, &timestamp)?
+        .ok_or_else(|| OicopyError::NotFound(format!("{} 之前没有剪贴板记录", timestamp)))?;
+
+    let content = item.to_content()?;
+    let clipboard_state = app.state::<tauri_plugin_clipboard::Clipboard>();
+    write_clipboard_content(&clipboard_state, &content).map_err(OicopyError::Clipboard)?;
+    trigger_feedback(&app, &conn, FeedbackEvent::Paste);
+
+    println!("已恢复 {} 的剪贴板内容 (记录 {:?})", item.timestamp, item.id);
+    Ok(item)
 }
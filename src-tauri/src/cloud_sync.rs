@@ -0,0 +1,793 @@
+use crate::clipboard_content::{ClipboardContent, ContentKind};
+use crate::db::{get_device_id, init_database, open_connection, ConnectionRole};
+use crate::db_writer::execute_write;
+use crate::error::OicopyError;
+use crate::http::{http_request, HttpAuth, HttpResponse};
+use crate::lan_sync::{store_items, SyncItem};
+use crate::lock_mode::ensure_unlocked;
+use crate::sync_crypto::{from_hex, open, random_bytes, seal, stretch_key, to_hex, KEY_SIZE};
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::AppHandle;
+
+// 记录的云同步状态（clipboard_history.sync_state）
+pub const SYNC_STATE_PENDING: i64 = 0; // 待上传
+pub const SYNC_STATE_PUSHED: i64 = 1; // 已上传
+pub const SYNC_STATE_REMOTE: i64 = 2; // 从云端下载，不再上传
+
+// 定时同步的间隔
+const SYNC_INTERVAL_SECS: u64 = 10 * 60;
+const REQUEST_TIMEOUT_SECS: u32 = 120;
+// 云端目录结构：oicopy/key.json 保存口令校验信息，oicopy/batches/ 下每个文件是一批加密的记录
+const REMOTE_ROOT: &str = "oicopy/";
+const KEY_CHECK_OBJECT: &str = "oicopy/key.json";
+const BATCHES_PREFIX: &str = "oicopy/batches/";
+const BATCH_EXTENSION: &str = ".bin";
+// 由口令派生密钥（PBKDF2-HMAC-SHA256）的迭代次数
+const KEY_ROUNDS: u32 = 600_000;
+// 校验信息格式版本：2 为 XChaCha20-Poly1305 + PBKDF2，旧版本的云端数据无法解密
+const KEY_CHECK_VERSION: u32 = 2;
+const KEY_CHECK_PLAINTEXT: &[u8] = b"oicopy-cloud-sync";
+const MIN_PASSPHRASE_CHARS: usize = 8;
+// 每批上传的记录数和数据量上限
+const BATCH_ITEMS: usize = 200;
+const BATCH_BYTES: usize = 16 * 1024 * 1024;
+
+// 同一时间只运行一次同步
+static SYNCING: AtomicBool = AtomicBool::new(false);
+
+// 云存储类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloudProvider {
+    WebDav,
+    S3, // S3 兼容存储，endpoint 使用路径形式（https://host/bucket）
+}
+
+impl CloudProvider {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CloudProvider::WebDav => "webdav",
+            CloudProvider::S3 => "s3",
+        }
+    }
+
+    fn from_name(value: &str) -> Self {
+        match value {
+            "s3" => CloudProvider::S3,
+            _ => CloudProvider::WebDav,
+        }
+    }
+}
+
+// 保存的云同步配置，密码和加密口令保存在加密的数据库中，不返回给前端
+#[derive(Debug, Clone)]
+struct CloudSyncConfig {
+    enabled: bool,
+    provider: CloudProvider,
+    endpoint: String,
+    region: Option<String>,
+    username: String,
+    secret: String,
+    passphrase: String,
+}
+
+// 前端提交的配置，密码和口令为空时沿用已保存的值
+#[derive(Debug, Deserialize)]
+pub struct CloudSyncConfigInput {
+    pub enabled: bool,
+    pub provider: CloudProvider,
+    pub endpoint: String,
+    pub region: Option<String>,
+    pub username: String,
+    pub secret: Option<String>,
+    pub passphrase: Option<String>,
+}
+
+// 云同步状态
+#[derive(Debug, Serialize)]
+pub struct CloudSyncStatus {
+    pub enabled: bool,
+    pub provider: CloudProvider,
+    pub endpoint: String,
+    pub region: Option<String>,
+    pub username: String,
+    pub has_secret: bool,
+    pub has_passphrase: bool,
+    pub syncing: bool,
+    pub pending: i64,  // 待上传的记录数
+    pub pushed: i64,   // 已上传的记录数
+    pub received: i64, // 从云端下载的记录数
+    pub last_push_at: Option<String>,
+    pub last_pull_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+// 同步结果
+#[derive(Debug, Default, Serialize)]
+pub struct CloudSyncResult {
+    pub pushed: usize,
+    pub pulled: usize,
+}
+
+// 口令校验信息：盐值和用派生密钥加密的固定内容，其他设备据此确认口令一致
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyCheck {
+    version: u32,
+    salt: String,
+    check: String,
+}
+
+// 创建云同步配置表和已下载批次表，并为同步状态列建立索引
+pub fn create_cloud_sync_tables(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS cloud_sync_config (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            enabled INTEGER NOT NULL DEFAULT 0,
+            provider TEXT NOT NULL DEFAULT 'webdav',
+            endpoint TEXT NOT NULL DEFAULT '',
+            region TEXT,
+            username TEXT NOT NULL DEFAULT '',
+            secret TEXT NOT NULL DEFAULT '',
+            passphrase TEXT NOT NULL DEFAULT '',
+            last_push_at TEXT,
+            last_pull_at TEXT,
+            last_error TEXT
+        );
+        CREATE TABLE IF NOT EXISTS cloud_sync_pulled (
+            name TEXT PRIMARY KEY,
+            pulled_at TEXT NOT NULL,
+            failed_reason TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_clipboard_sync_state ON clipboard_history(sync_state);",
+    )
+    .map_err(|e| format!("创建云同步表失败: {}", e))?;
+
+    // 为旧表添加新列（忽略错误，因为列可能已存在）
+    let _ = conn.execute("ALTER TABLE cloud_sync_pulled ADD COLUMN failed_reason TEXT", []);
+    Ok(())
+}
+
+fn now_string() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn load_config(conn: &Connection) -> Result<Option<CloudSyncConfig>, String> {
+    conn.query_row(
+        "SELECT enabled, provider, endpoint, region, username, secret, passphrase FROM cloud_sync_config WHERE id = 1",
+        [],
+        |row| {
+            Ok(CloudSyncConfig {
+                enabled: row.get(0)?,
+                provider: CloudProvider::from_name(&row.get::<_, String>(1)?),
+                endpoint: row.get(2)?,
+                region: row.get(3)?,
+                username: row.get(4)?,
+                secret: row.get(5)?,
+                passphrase: row.get(6)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("读取云同步配置失败: {}", e))
+}
+
+// WebDAV 密码和 S3 密钥随请求发送，只允许 https；http 仅限本机（如本地测试的存储服务）
+fn is_secure_endpoint(endpoint: &str) -> bool {
+    let endpoint = endpoint.to_ascii_lowercase();
+    if endpoint.starts_with("https://") {
+        return true;
+    }
+    let Some(rest) = endpoint.strip_prefix("http://") else {
+        return false;
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = match authority.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+fn validate_config(config: &CloudSyncConfig) -> Result<(), String> {
+    let endpoint = config.endpoint.trim();
+    if !is_secure_endpoint(endpoint) {
+        return Err(format!("云存储地址必须以 https:// 开头: {}", endpoint));
+    }
+    if config.provider == CloudProvider::S3 && (config.username.is_empty() || config.secret.is_empty()) {
+        return Err("S3 存储需要填写 Access Key 和 Secret Key".to_string());
+    }
+    if config.passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(format!("加密口令至少需要 {} 个字符", MIN_PASSPHRASE_CHARS));
+    }
+    Ok(())
+}
+
+// 从 XML 响应中提取指定元素的文本，忽略命名空间前缀（WebDAV 的 d:href 和 S3 的 Key）
+fn xml_values(xml: &str, name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+        let tag_name = tag.split_whitespace().next().unwrap_or_default();
+        if tag_name.starts_with('/') || tag.ends_with('/') || tag_name.rsplit(':').next() != Some(name) {
+            continue;
+        }
+        if let Some(close) = rest.find(&format!("</{}>", tag_name)) {
+            values.push(
+                rest[..close]
+                    .replace("&lt;", "<")
+                    .replace("&gt;", ">")
+                    .replace("&quot;", "\"")
+                    .replace("&apos;", "'")
+                    .replace("&amp;", "&"),
+            );
+            rest = &rest[close..];
+        }
+    }
+    values
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// 批次文件名：毫秒时间戳-设备ID-随机数.bin，按名称排序即按上传时间排序
+fn batch_name(millis: i64, device_id: &str, nonce: &str) -> String {
+    format!("{:013}-{}-{}{}", millis, device_id, nonce, BATCH_EXTENSION)
+}
+
+// 从批次文件名中取出上传设备的 ID
+fn batch_device_id(name: &str) -> Option<&str> {
+    let stem = name.strip_suffix(BATCH_EXTENSION)?;
+    let (_, rest) = stem.split_once('-')?;
+    let (device_id, _) = rest.rsplit_once('-')?;
+    (!device_id.is_empty()).then_some(device_id)
+}
+
+// 云存储访问，WebDAV 和 S3 都通过 curl 发起请求
+struct Remote<'a> {
+    config: &'a CloudSyncConfig,
+}
+
+impl<'a> Remote<'a> {
+    fn new(config: &'a CloudSyncConfig) -> Self {
+        Self { config }
+    }
+
+    fn auth(&self) -> HttpAuth<'_> {
+        let config = self.config;
+        match config.provider {
+            CloudProvider::WebDav if config.username.is_empty() => HttpAuth::None,
+            CloudProvider::WebDav => HttpAuth::Basic { username: &config.username, password: &config.secret },
+            CloudProvider::S3 => HttpAuth::AwsSigV4 {
+                region: config.region.as_deref().filter(|r| !r.is_empty()).unwrap_or("us-east-1"),
+                access_key: &config.username,
+                secret_key: &config.secret,
+            },
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.config.endpoint.trim().trim_end_matches('/'), path)
+    }
+
+    fn request(&self, method: &str, url: &str, headers: &[&str], body: Option<&[u8]>) -> Result<HttpResponse, String> {
+        http_request(method, url, headers, &self.auth(), body, REQUEST_TIMEOUT_SECS)
+    }
+
+    // WebDAV 需要先创建目录，已存在时服务器返回 405
+    fn prepare(&self) -> Result<(), String> {
+        if self.config.provider != CloudProvider::WebDav {
+            return Ok(());
+        }
+        for dir in [REMOTE_ROOT, BATCHES_PREFIX] {
+            let response = self.request("MKCOL", &self.url(dir), &[], None)?;
+            if !response.is_success() && response.status != 405 {
+                return Err(format!("创建云端目录失败: HTTP {}", response.status));
+            }
+        }
+        Ok(())
+    }
+
+    fn put(&self, path: &str, data: &[u8]) -> Result<(), String> {
+        let response = self.request("PUT", &self.url(path), &["Content-Type: application/octet-stream"], Some(data))?;
+        if !response.is_success() {
+            return Err(format!("上传 {} 失败: HTTP {}", path, response.status));
+        }
+        Ok(())
+    }
+
+    // 文件不存在时返回 None
+    fn get(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+        let response = self.request("GET", &self.url(path), &[], None)?;
+        match response.status {
+            404 => Ok(None),
+            _ if response.is_success() => Ok(Some(response.body)),
+            status => Err(format!("下载 {} 失败: HTTP {}", path, status)),
+        }
+    }
+
+    // 列出云端所有批次的文件名
+    fn list_batches(&self) -> Result<Vec<String>, String> {
+        let mut names = match self.config.provider {
+            CloudProvider::WebDav => {
+                let response = self.request("PROPFIND", &self.url(BATCHES_PREFIX), &["Depth: 1"], None)?;
+                if response.status == 404 {
+                    return Ok(Vec::new());
+                }
+                if !response.is_success() {
+                    return Err(format!("列出云端文件失败: HTTP {}", response.status));
+                }
+                xml_values(&String::from_utf8_lossy(&response.body), "href")
+                    .iter()
+                    .filter_map(|href| href.trim_end_matches('/').rsplit('/').next().map(str::to_string))
+                    .collect::<Vec<_>>()
+            }
+            CloudProvider::S3 => {
+                let mut keys = Vec::new();
+                let mut token: Option<String> = None;
+                loop {
+                    let mut url = format!(
+                        "{}?list-type=2&prefix={}",
+                        self.config.endpoint.trim().trim_end_matches('/'),
+                        percent_encode(BATCHES_PREFIX)
+                    );
+                    if let Some(token) = &token {
+                        url.push_str(&format!("&continuation-token={}", percent_encode(token)));
+                    }
+                    let response = self.request("GET", &url, &[], None)?;
+                    if !response.is_success() {
+                        return Err(format!("列出云端文件失败: HTTP {}", response.status));
+                    }
+                    let xml = String::from_utf8_lossy(&response.body);
+                    keys.extend(
+                        xml_values(&xml, "Key")
+                            .iter()
+                            .filter_map(|key| key.strip_prefix(BATCHES_PREFIX).map(str::to_string)),
+                    );
+                    token = xml_values(&xml, "NextContinuationToken").into_iter().next();
+                    if token.is_none() {
+                        break;
+                    }
+                }
+                keys
+            }
+        };
+        names.retain(|name| name.ends_with(BATCH_EXTENSION));
+        names.sort();
+        Ok(names)
+    }
+}
+
+// 由口令派生加密密钥：云端已有校验信息时验证口令，否则生成新的盐值并上传校验信息
+fn encryption_key(remote: &Remote, passphrase: &str) -> Result<[u8; KEY_SIZE], String> {
+    if let Some(data) = remote.get(KEY_CHECK_OBJECT)? {
+        let check: KeyCheck = serde_json::from_slice(&data).map_err(|e| format!("云端校验信息格式无效: {}", e))?;
+        if check.version != KEY_CHECK_VERSION {
+            return Err("云端数据由旧版本加密，请清空云端 oicopy 目录后重新同步".to_string());
+        }
+        let key = stretch_key(passphrase.as_bytes(), &from_hex(&check.salt)?, KEY_ROUNDS);
+        let plaintext = open(&key, &from_hex(&check.check)?).map_err(|_| "加密口令与云端数据不一致".to_string())?;
+        if plaintext != KEY_CHECK_PLAINTEXT {
+            return Err("加密口令与云端数据不一致".to_string());
+        }
+        return Ok(key);
+    }
+
+    let salt = random_bytes(16)?;
+    let key = stretch_key(passphrase.as_bytes(), &salt, KEY_ROUNDS);
+    let check = KeyCheck {
+        version: KEY_CHECK_VERSION,
+        salt: to_hex(&salt),
+        check: to_hex(&seal(&key, KEY_CHECK_PLAINTEXT)?),
+    };
+    let data = serde_json::to_vec(&check).map_err(|e| format!("序列化校验信息失败: {}", e))?;
+    remote.put(KEY_CHECK_OBJECT, &data)?;
+    Ok(key)
+}
+
+// 读取一批待上传的记录，返回要标记为已上传的记录ID（包括无法读取而跳过的记录）
+fn load_pending(conn: &Connection) -> Result<(Vec<i64>, Vec<SyncItem>), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, content_type, content, timestamp, source_app, source_bundle_id, device_id, sensitive, expires_at
+             FROM clipboard_history
+             WHERE sync_state = ?1 AND content_type != ?2
+             ORDER BY id LIMIT ?3",
+        )
+        .map_err(|e| format!("查询待上传记录失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![SYNC_STATE_PENDING, ContentKind::Files, BATCH_ITEMS as i64], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, ContentKind>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, bool>(7)?,
+                row.get::<_, Option<String>>(8)?,
+            ))
+        })
+        .map_err(|e| format!("查询待上传记录失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取待上传记录失败: {}", e))?;
+
+    let mut ids = Vec::new();
+    let mut items = Vec::new();
+    let mut bytes = 0;
+    for (id, kind, stored, timestamp, source_app, source_bundle_id, device_id, sensitive, expires_at) in rows {
+        let content = match ClipboardContent::from_stored(kind, stored) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("读取待上传记录 {} 失败: {}", id, e);
+                ids.push(id);
+                continue;
+            }
+        };
+        bytes += content.byte_size();
+        if bytes > BATCH_BYTES && !items.is_empty() {
+            break;
+        }
+        items.push(SyncItem { content, timestamp, source_app, source_bundle_id, device_id, sensitive, expires_at });
+        ids.push(id);
+    }
+    Ok((ids, items))
+}
+
+// 上传所有待上传的记录，每批加密后作为一个文件上传
+fn push_pending(app: &AppHandle, remote: &Remote, key: &[u8], device_id: &str) -> Result<usize, String> {
+    let conn = open_connection(app, ConnectionRole::Analytics)?;
+    let mut pushed = 0;
+    loop {
+        let (ids, items) = load_pending(&conn)?;
+        if ids.is_empty() {
+            break;
+        }
+        if !items.is_empty() {
+            let json = serde_json::to_vec(&items).map_err(|e| format!("序列化同步记录失败: {}", e))?;
+            let name = batch_name(Local::now().timestamp_millis(), device_id, &to_hex(&random_bytes(4)?));
+            remote.put(&format!("{}{}", BATCHES_PREFIX, name), &seal(key, &json)?)?;
+            pushed += items.len();
+        }
+        execute_write(app, move |_, conn| {
+            let tx = conn.transaction().map_err(|e| format!("开启事务失败: {}", e))?;
+            for id in ids {
+                tx.execute(
+                    "UPDATE clipboard_history SET sync_state = ?1 WHERE id = ?2 AND sync_state = ?3",
+                    params![SYNC_STATE_PUSHED, id, SYNC_STATE_PENDING],
+                )
+                .map_err(|e| format!("更新同步状态失败: {}", e))?;
+            }
+            tx.commit().map_err(|e| format!("提交同步状态失败: {}", e))
+        })?;
+    }
+    Ok(pushed)
+}
+
+// 下载其他设备上传的新批次，已下载过的批次和本机上传的批次跳过
+fn pull_batches(app: &AppHandle, remote: &Remote, key: &[u8], device_id: &str) -> Result<usize, String> {
+    let names = remote.list_batches()?;
+    let pulled: HashSet<String> = {
+        let conn = open_connection(app, ConnectionRole::Analytics)?;
+        let mut stmt = conn
+            .prepare("SELECT name FROM cloud_sync_pulled")
+            .map_err(|e| format!("查询已下载批次失败: {}", e))?;
+        let names = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| format!("查询已下载批次失败: {}", e))?
+            .collect::<Result<HashSet<_>, _>>()
+            .map_err(|e| format!("读取已下载批次失败: {}", e))?;
+        names
+    };
+
+    let mut received = 0;
+    for name in names {
+        if pulled.contains(&name) {
+            continue;
+        }
+        let Some(source) = batch_device_id(&name) else {
+            continue;
+        };
+        if source == device_id {
+            continue;
+        }
+        let mut failed_reason = None;
+        if let Some(data) = remote.get(&format!("{}{}", BATCHES_PREFIX, name))? {
+            // 无法解密或解析的批次（损坏或由其他口令加密）记录后跳过，不阻塞之后的批次
+            let decoded = open(key, &data)
+                .map_err(|_| "解密失败".to_string())
+                .and_then(|json| serde_json::from_slice::<Vec<SyncItem>>(&json).map_err(|e| format!("格式无效: {}", e)));
+            match decoded {
+                Ok(items) => received += store_items(app, items, source, SYNC_STATE_REMOTE)?,
+                Err(reason) => {
+                    eprintln!("跳过云端数据 {}: {}", name, reason);
+                    failed_reason = Some(reason);
+                }
+            }
+        }
+        execute_write(app, move |_, conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO cloud_sync_pulled (name, pulled_at, failed_reason) VALUES (?1, ?2, ?3)",
+                params![name, now_string(), failed_reason],
+            )
+            .map_err(|e| format!("保存下载进度失败: {}", e))?;
+            Ok(())
+        })?;
+    }
+    Ok(received)
+}
+
+// 同步期间持有，结束时释放
+struct SyncGuard;
+
+impl SyncGuard {
+    fn acquire() -> Result<Self, String> {
+        SYNCING
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .map(|_| SyncGuard)
+            .map_err(|_| "云同步正在进行中".to_string())
+    }
+}
+
+impl Drop for SyncGuard {
+    fn drop(&mut self) {
+        SYNCING.store(false, Ordering::SeqCst);
+    }
+}
+
+// 执行一次同步，先下载再上传，结果记录到配置表供状态查询
+fn run_sync(app: &AppHandle, push: bool, pull: bool) -> Result<CloudSyncResult, String> {
+    let _guard = SyncGuard::acquire()?;
+    let outcome = (|| {
+        let conn = init_database(app)?;
+        ensure_unlocked(&conn)?;
+        let config = load_config(&conn)?.ok_or_else(|| "云同步未配置".to_string())?;
+        let device_id = get_device_id(&conn)?;
+        drop(conn);
+        validate_config(&config)?;
+
+        let remote = Remote::new(&config);
+        remote.prepare()?;
+        let key = encryption_key(&remote, &config.passphrase)?;
+        let mut result = CloudSyncResult::default();
+        if pull {
+            result.pulled = pull_batches(app, &remote, &key, &device_id)?;
+        }
+        if push {
+            result.pushed = push_pending(app, &remote, &key, &device_id)?;
+        }
+        Ok(result)
+    })();
+
+    let error = outcome.as_ref().err().cloned();
+    let succeeded = error.is_none();
+    let _ = execute_write(app, move |_, conn| {
+        let now = now_string();
+        conn.execute(
+            "UPDATE cloud_sync_config SET
+                 last_push_at = CASE WHEN ?1 THEN ?3 ELSE last_push_at END,
+                 last_pull_at = CASE WHEN ?2 THEN ?3 ELSE last_pull_at END,
+                 last_error = ?4
+             WHERE id = 1",
+            params![push && succeeded, pull && succeeded, now, error],
+        )
+        .map_err(|e| format!("保存同步状态失败: {}", e))?;
+        Ok(())
+    });
+    if let Ok(result) = &outcome {
+        if result.pushed > 0 || result.pulled > 0 {
+            println!("云同步完成: {:?}", result);
+        }
+    }
+    outcome
+}
+
+// 定时同步：开启云同步且未锁定时先下载再上传
+pub fn start_cloud_sync(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(SYNC_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+
+            let enabled = init_database(&app_handle)
+                .ok()
+                .filter(|conn| ensure_unlocked(conn).is_ok())
+                .and_then(|conn| load_config(&conn).ok().flatten())
+                .is_some_and(|config| config.enabled);
+            if !enabled {
+                continue;
+            }
+
+            let app = app_handle.clone();
+            match tauri::async_runtime::spawn_blocking(move || run_sync(&app, true, true)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => eprintln!("云同步失败: {}", e),
+                Err(e) => eprintln!("云同步任务失败: {}", e),
+            }
+        }
+    });
+}
+
+fn count_by_state(conn: &Connection, state: i64) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM clipboard_history WHERE sync_state = ?1 AND content_type != ?2",
+        params![state, ContentKind::Files],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("统计同步状态失败: {}", e))
+}
+
+// 获取云同步状态命令
+#[tauri::command]
+pub async fn get_cloud_sync_status(app: AppHandle) -> Result<CloudSyncStatus, OicopyError> {
+    let conn = init_database(&app)?;
+    let config = load_config(&conn)?;
+    let (last_push_at, last_pull_at, last_error) = conn
+        .query_row(
+            "SELECT last_push_at, last_pull_at, last_error FROM cloud_sync_config WHERE id = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| format!("读取云同步状态失败: {}", e))?
+        .unwrap_or((None, None, None));
+
+    Ok(CloudSyncStatus {
+        enabled: config.as_ref().is_some_and(|c| c.enabled),
+        provider: config.as_ref().map_or(CloudProvider::WebDav, |c| c.provider),
+        endpoint: config.as_ref().map(|c| c.endpoint.clone()).unwrap_or_default(),
+        region: config.as_ref().and_then(|c| c.region.clone()),
+        username: config.as_ref().map(|c| c.username.clone()).unwrap_or_default(),
+        has_secret: config.as_ref().is_some_and(|c| !c.secret.is_empty()),
+        has_passphrase: config.as_ref().is_some_and(|c| !c.passphrase.is_empty()),
+        syncing: SYNCING.load(Ordering::SeqCst),
+        pending: count_by_state(&conn, SYNC_STATE_PENDING)?,
+        pushed: count_by_state(&conn, SYNC_STATE_PUSHED)?,
+        received: count_by_state(&conn, SYNC_STATE_REMOTE)?,
+        last_push_at,
+        last_pull_at,
+        last_error,
+    })
+}
+
+// 保存云同步配置命令：开启时先验证能否访问云存储以及口令是否与云端一致
+// 更换存储位置或口令后，已上传的记录重新标记为待上传
+#[tauri::command]
+pub async fn set_cloud_sync_config(app: AppHandle, config: CloudSyncConfigInput) -> Result<CloudSyncStatus, OicopyError> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let previous = load_config(&conn)?;
+    drop(conn);
+
+    let keep = |value: Option<String>, saved: Option<String>| {
+        value.filter(|value| !value.is_empty()).or(saved).unwrap_or_default()
+    };
+    let new_config = CloudSyncConfig {
+        enabled: config.enabled,
+        provider: config.provider,
+        endpoint: config.endpoint.trim().trim_end_matches('/').to_string(),
+        region: config.region.map(|r| r.trim().to_string()).filter(|r| !r.is_empty()),
+        username: config.username.trim().to_string(),
+        secret: keep(config.secret, previous.as_ref().map(|p| p.secret.clone())),
+        passphrase: keep(config.passphrase, previous.as_ref().map(|p| p.passphrase.clone())),
+    };
+    if new_config.enabled {
+        validate_config(&new_config).map_err(OicopyError::Validation)?;
+        let verify = new_config.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let remote = Remote::new(&verify);
+            remote.prepare()?;
+            encryption_key(&remote, &verify.passphrase).map(|_| ())
+        })
+        .await
+        .map_err(|e| format!("验证云存储任务失败: {}", e))??;
+    }
+
+    let target_changed = previous.as_ref().map_or(true, |p| {
+        p.provider != new_config.provider || p.endpoint != new_config.endpoint || p.passphrase != new_config.passphrase
+    });
+    execute_write(&app, move |_, conn| {
+        let tx = conn.transaction().map_err(|e| format!("开启事务失败: {}", e))?;
+        tx.execute(
+            "INSERT INTO cloud_sync_config (id, enabled, provider, endpoint, region, username, secret, passphrase)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(id) DO UPDATE SET enabled = excluded.enabled, provider = excluded.provider,
+                 endpoint = excluded.endpoint, region = excluded.region, username = excluded.username,
+                 secret = excluded.secret, passphrase = excluded.passphrase, last_error = NULL",
+            params![
+                new_config.enabled,
+                new_config.provider.as_str(),
+                new_config.endpoint,
+                new_config.region,
+                new_config.username,
+                new_config.secret,
+                new_config.passphrase
+            ],
+        )
+        .map_err(|e| format!("保存云同步配置失败: {}", e))?;
+        if target_changed {
+            tx.execute(
+                "UPDATE clipboard_history SET sync_state = ?1 WHERE sync_state = ?2",
+                params![SYNC_STATE_PENDING, SYNC_STATE_PUSHED],
+            )
+            .map_err(|e| format!("重置同步状态失败: {}", e))?;
+            tx.execute("DELETE FROM cloud_sync_pulled", [])
+                .map_err(|e| format!("重置下载进度失败: {}", e))?;
+        }
+        tx.commit().map_err(|e| format!("保存云同步配置失败: {}", e))
+    })?;
+
+    get_cloud_sync_status(app).await
+}
+
+// 立即上传待同步记录命令
+#[tauri::command]
+pub async fn cloud_sync_push_now(app: AppHandle) -> Result<CloudSyncResult, OicopyError> {
+    let result = tauri::async_runtime::spawn_blocking(move || run_sync(&app, true, false))
+        .await
+        .map_err(|e| format!("云同步任务失败: {}", e))??;
+    Ok(result)
+}
+
+// 立即从云端下载新记录命令
+#[tauri::command]
+pub async fn cloud_sync_pull_now(app: AppHandle) -> Result<CloudSyncResult, OicopyError> {
+    let result = tauri::async_runtime::spawn_blocking(move || run_sync(&app, false, true))
+        .await
+        .map_err(|e| format!("云同步任务失败: {}", e))??;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_values() {
+        let webdav = r#"<d:multistatus xmlns:d="DAV:"><d:response><d:href>/dav/oicopy/batches/</d:href></d:response>
+            <d:response><d:href>/dav/oicopy/batches/a&amp;b.bin</d:href><d:propstat/></d:response></d:multistatus>"#;
+        assert_eq!(xml_values(webdav, "href"), vec!["/dav/oicopy/batches/", "/dav/oicopy/batches/a&b.bin"]);
+
+        let s3 = "<ListBucketResult><Contents><Key>oicopy/batches/1.bin</Key></Contents><IsTruncated>false</IsTruncated></ListBucketResult>";
+        assert_eq!(xml_values(s3, "Key"), vec!["oicopy/batches/1.bin"]);
+        assert!(xml_values(s3, "NextContinuationToken").is_empty());
+    }
+
+    #[test]
+    fn test_batch_name_round_trip() {
+        let name = batch_name(1_700_000_000_000, "ab12-cd34", "0f0f");
+        assert_eq!(name, "1700000000000-ab12-cd34-0f0f.bin");
+        assert_eq!(batch_device_id(&name), Some("ab12-cd34"));
+        assert_eq!(batch_device_id("key.json"), None);
+    }
+
+    #[test]
+    fn test_endpoint_requires_https() {
+        assert!(is_secure_endpoint("https://dav.example.com/remote.php/dav"));
+        assert!(is_secure_endpoint("HTTPS://s3.example.com"));
+        assert!(is_secure_endpoint("http://localhost:9000/bucket"));
+        assert!(is_secure_endpoint("http://127.0.0.1/dav"));
+        assert!(is_secure_endpoint("http://[::1]:8080"));
+        assert!(!is_secure_endpoint("http://dav.example.com"));
+        assert!(!is_secure_endpoint("http://localhost.example.com"));
+        assert!(!is_secure_endpoint("http://192.168.1.10:5005"));
+        assert!(!is_secure_endpoint("ftp://example.com"));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::clipboard_content::ClipboardContent;
+use serde::Deserialize;
+
+// 颜色值（各通道 0-255，透明度 0-1）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: f32,
+}
+
+// 颜色的文本格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorFormat {
+    Hex,
+    Rgb,
+    Hsl,
+}
+
+// 解析 #hex、rgb()/rgba()、hsl()/hsla() 格式的颜色，前后空白忽略
+pub fn parse_color(text: &str) -> Option<Rgba> {
+    let text = text.trim().to_ascii_lowercase();
+    if let Some(hex) = text.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+
+    let (name, rest) = text.split_once('(')?;
+    let args = parse_args(rest.strip_suffix(')')?);
+    if !(args.len() == 3 || args.len() == 4) {
+        return None;
+    }
+    let alpha = match args.get(3) {
+        Some(alpha) => parse_alpha(alpha)?,
+        None => 1.0,
+    };
+
+    match name.trim() {
+        "rgb" | "rgba" => Some(Rgba {
+            r: parse_channel(args[0])?,
+            g: parse_channel(args[1])?,
+            b: parse_channel(args[2])?,
+            a: alpha,
+        }),
+        "hsl" | "hsla" => {
+            let hue = args[0].trim_end_matches("deg").parse::<f32>().ok()?;
+            let saturation = parse_percent(args[1])?;
+            let lightness = parse_percent(args[2])?;
+            let (r, g, b) = hsl_to_rgb(hue, saturation, lightness);
+            Some(Rgba { r, g, b, a: alpha })
+        }
+        _ => None,
+    }
+}
+
+// 参数可以用逗号或空格分隔，透明度可以用 "/" 分隔
+fn parse_args(args: &str) -> Vec<&str> {
+    args.split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|arg| !arg.is_empty())
+        .collect()
+}
+
+fn parse_hex(hex: &str) -> Option<Rgba> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let expanded: String = match hex.len() {
+        3 | 4 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 | 8 => hex.to_string(),
+        _ => return None,
+    };
+    let channel = |index: usize| u8::from_str_radix(&expanded[index..index + 2], 16).ok();
+    let a = match expanded.len() {
+        8 => channel(6)? as f32 / 255.0,
+        _ => 1.0,
+    };
+    Some(Rgba {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+        a,
+    })
+}
+
+fn parse_channel(value: &str) -> Option<u8> {
+    let value = match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok()? * 2.55,
+        None => value.parse::<f32>().ok()?,
+    };
+    (0.0..=255.0).contains(&value).then(|| value.round() as u8)
+}
+
+fn parse_percent(value: &str) -> Option<f32> {
+    let value = value.strip_suffix('%')?.parse::<f32>().ok()?;
+    (0.0..=100.0).contains(&value).then_some(value / 100.0)
+}
+
+fn parse_alpha(value: &str) -> Option<f32> {
+    let alpha = match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+        None => value.parse::<f32>().ok()?,
+    };
+    (0.0..=1.0).contains(&alpha).then_some(alpha)
+}
+
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (u8, u8, u8) {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let to_u8 = |value: f32| ((value + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+fn rgb_to_hsl(color: Rgba) -> (f32, f32, f32) {
+    let r = color.r as f32 / 255.0;
+    let g = color.g as f32 / 255.0;
+    let b = color.b as f32 / 255.0;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, saturation, lightness)
+}
+
+// 透明度保留两位小数，去掉多余的零
+fn format_alpha(alpha: f32) -> String {
+    let formatted = format!("{:.2}", alpha);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// 按指定格式输出颜色，不透明时省略透明度
+pub fn format_color(color: Rgba, format: ColorFormat) -> String {
+    let opaque = color.a >= 1.0;
+    match format {
+        ColorFormat::Hex if opaque => format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b),
+        ColorFormat::Hex => format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.r,
+            color.g,
+            color.b,
+            (color.a * 255.0).round() as u8
+        ),
+        ColorFormat::Rgb if opaque => format!("rgb({}, {}, {})", color.r, color.g, color.b),
+        ColorFormat::Rgb => format!(
+            "rgba({}, {}, {}, {})",
+            color.r,
+            color.g,
+            color.b,
+            format_alpha(color.a)
+        ),
+        ColorFormat::Hsl => {
+            let (h, s, l) = rgb_to_hsl(color);
+            let (h, s, l) = (h.round(), (s * 100.0).round(), (l * 100.0).round());
+            if opaque {
+                format!("hsl({}, {}%, {}%)", h, s, l)
+            } else {
+                format!("hsla({}, {}%, {}%, {})", h, s, l, format_alpha(color.a))
+            }
+        }
+    }
+}
+
+// 归一化的颜色值（小写 #rrggbb 或 #rrggbbaa），用于存储和面板色块显示
+pub fn normalize_color(text: &str) -> Option<String> {
+    parse_color(text).map(|color| format_color(color, ColorFormat::Hex))
+}
+
+// 文本内容整体是一个颜色时返回归一化的颜色值
+pub fn content_color(content: &ClipboardContent) -> Option<String> {
+    match content {
+        ClipboardContent::Text { text } => normalize_color(text),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_color() {
+        assert_eq!(normalize_color(" #1E90FF "), Some("#1e90ff".to_string()));
+        assert_eq!(normalize_color("#abc"), Some("#aabbcc".to_string()));
+        assert_eq!(normalize_color("rgb(30, 144, 255)"), Some("#1e90ff".to_string()));
+        assert_eq!(normalize_color("rgba(0 0 0 / 50%)"), Some("#00000080".to_string()));
+        assert_eq!(normalize_color("hsl(0, 100%, 50%)"), Some("#ff0000".to_string()));
+        assert_eq!(normalize_color("#12345"), None);
+        assert_eq!(normalize_color("rgb(300, 0, 0)"), None);
+    }
+
+    #[test]
+    fn test_format_color() {
+        let color = parse_color("#1e90ff").unwrap();
+        assert_eq!(format_color(color, ColorFormat::Rgb), "rgb(30, 144, 255)");
+        assert_eq!(format_color(color, ColorFormat::Hsl), "hsl(210, 100%, 56%)");
+        let translucent = parse_color("rgba(255, 0, 0, 0.5)").unwrap();
+        assert_eq!(format_color(translucent, ColorFormat::Rgb), "rgba(255, 0, 0, 0.5)");
+        assert_eq!(format_color(translucent, ColorFormat::Hsl), "hsla(0, 100%, 50%, 0.5)");
+    }
+}
@@ -0,0 +1,135 @@
+use crate::clipboard_content::{ClipboardContent, ContentKind};
+use crate::image_store::store_image_blob;
+use rusqlite::{params, Connection, OptionalExtension};
+
+// 偏好纯文本的应用（代码编辑器、终端），富文本粘贴进去只会带来多余格式
+const PLAIN_TEXT_BUNDLE_IDS: &[&str] = &[
+    "com.microsoft.VSCode",
+    "com.sublimetext.4",
+    "com.jetbrains.intellij",
+    "com.apple.dt.Xcode",
+    "dev.zed.Zed",
+    "com.todesktop.230313mzl4w4u92", // Cursor
+    "com.apple.Terminal",
+    "com.googlecode.iterm2",
+    "dev.warp.Warp-Stable",
+    "net.kovidgoyal.kitty",
+    "org.alacritty",
+    "com.github.wez.wezterm",
+    "com.mitchellh.ghostty",
+];
+
+// 偏好富文本的文档类应用，优先粘贴 HTML，其次 RTF
+const RICH_TEXT_BUNDLE_IDS: &[&str] = &[
+    "com.apple.iWork.Pages",
+    "com.apple.iWork.Keynote",
+    "com.apple.mail",
+    "com.apple.Notes",
+    "com.apple.TextEdit",
+    "com.microsoft.Word",
+    "com.microsoft.Outlook",
+    "com.microsoft.Powerpoint",
+];
+
+// 创建内容格式表：同一次复制的其他格式（主格式仍保存在 clipboard_history）
+// 按记录ID关联，移入回收站再恢复后仍然保留
+pub fn create_content_formats_table(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS content_formats (
+            item_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            content TEXT NOT NULL,
+            PRIMARY KEY (item_id, kind)
+        );",
+    )
+    .map_err(|e| format!("创建内容格式表失败: {}", e))
+}
+
+// 保存记录的其他格式，图片写入 blobs 目录只保存引用；主格式和文件列表不重复保存
+pub fn save_content_formats(
+    conn: &Connection,
+    item_id: i64,
+    primary: ContentKind,
+    alternates: &[ClipboardContent],
+) -> Result<usize, String> {
+    let mut saved = 0;
+    for content in alternates {
+        let kind = content.kind();
+        if kind == primary || kind == ContentKind::Files {
+            continue;
+        }
+        let stored = match content {
+            ClipboardContent::Image { format, bytes } => store_image_blob(bytes, format)?.reference,
+            _ => content.to_stored(),
+        };
+        saved += conn
+            .execute(
+                "INSERT OR REPLACE INTO content_formats (item_id, kind, content) VALUES (?1, ?2, ?3)",
+                params![item_id, kind, stored],
+            )
+            .map_err(|e| format!("保存{}格式失败: {}", kind.label(), e))?;
+    }
+    Ok(saved)
+}
+
+// 读取记录保存的某个其他格式
+pub fn load_content_format(
+    conn: &Connection,
+    item_id: i64,
+    kind: ContentKind,
+) -> Result<Option<ClipboardContent>, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT content FROM content_formats WHERE item_id = ?1 AND kind = ?2",
+            params![item_id, kind],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("查询{}格式失败: {}", kind.label(), e))?;
+    stored.map(|stored| ClipboardContent::from_stored(kind, stored)).transpose()
+}
+
+// 删除历史和回收站中都已不存在的记录的其他格式（需在清理图片文件之前执行）
+pub fn prune_orphan_content_formats(conn: &Connection) -> Result<usize, String> {
+    conn.execute(
+        "DELETE FROM content_formats WHERE item_id NOT IN (
+            SELECT id FROM clipboard_history UNION SELECT id FROM clipboard_trash
+        )",
+        [],
+    )
+    .map_err(|e| format!("清理内容格式失败: {}", e))
+}
+
+// 根据目标应用选择粘贴的格式：代码编辑器和终端用纯文本，文档类应用用富文本，其余保持主格式
+pub fn preferred_paste_kind(bundle_id: &str, primary: ContentKind, alternates: &[ContentKind]) -> ContentKind {
+    let has = |kind: ContentKind| primary == kind || alternates.contains(&kind);
+    let preferences: &[ContentKind] = if PLAIN_TEXT_BUNDLE_IDS.contains(&bundle_id) {
+        &[ContentKind::Text]
+    } else if RICH_TEXT_BUNDLE_IDS.contains(&bundle_id) {
+        &[ContentKind::Html, ContentKind::Rtf]
+    } else {
+        &[]
+    };
+    preferences.iter().copied().find(|kind| has(*kind)).unwrap_or(primary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferred_paste_kind() {
+        let alternates = [ContentKind::Text, ContentKind::Rtf];
+        assert_eq!(
+            preferred_paste_kind("com.apple.Terminal", ContentKind::Html, &alternates),
+            ContentKind::Text
+        );
+        assert_eq!(preferred_paste_kind("com.apple.mail", ContentKind::Text, &alternates), ContentKind::Rtf);
+        assert_eq!(
+            preferred_paste_kind("com.apple.mail", ContentKind::Html, &alternates),
+            ContentKind::Html
+        );
+        assert_eq!(preferred_paste_kind("com.apple.Safari", ContentKind::Html, &alternates), ContentKind::Html);
+        assert_eq!(preferred_paste_kind("com.apple.Terminal", ContentKind::Image, &[]), ContentKind::Image);
+    }
+}
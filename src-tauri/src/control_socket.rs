@@ -0,0 +1,236 @@
+// 本机控制 socket：供 oicopy 命令行工具在终端中查询和操作历史记录
+// 协议：每行一个 JSON 请求，服务端对每个请求回复一行 JSON
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+const SOCKET_FILE_NAME: &str = "control.sock";
+// 与 tauri.conf.json 中的 identifier 一致，命令行工具据此找到数据目录
+const APP_IDENTIFIER: &str = "com.oicopy-tauri-nspanel.app";
+// 可通过环境变量指定 socket 路径
+pub const SOCKET_ENV: &str = "OICOPY_SOCKET";
+
+// 控制请求
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    History { limit: Option<u32> },
+    Get { id: i64 },
+    Copy { id: i64 },
+    Search { query: String, limit: Option<u32> },
+    Pause { paused: bool },
+    Latest,
+    PasteIndex { index: u32 }, // 粘贴第 index 条记录（按时间从新到旧，从 1 开始）
+    ToggleMonitoring,
+}
+
+// 控制响应，成功时 data 为命令的返回值
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(default)]
+    pub data: serde_json::Value,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl ControlResponse {
+    fn success<T: Serialize>(data: T) -> Self {
+        match serde_json::to_value(data) {
+            Ok(data) => Self { ok: true, data, error: None },
+            Err(e) => Self::failure(format!("序列化响应失败: {}", e)),
+        }
+    }
+
+    fn failure(error: String) -> Self {
+        Self { ok: false, data: serde_json::Value::Null, error: Some(error) }
+    }
+}
+
+// 应用运行时 socket 所在的路径（数据目录下），供命令行工具使用
+pub fn default_socket_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(SOCKET_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+    let data_dir = if cfg!(target_os = "macos") {
+        home.join("Library").join("Application Support")
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".local").join("share"))
+    };
+    Some(data_dir.join(APP_IDENTIFIER).join(SOCKET_FILE_NAME))
+}
+
+#[cfg(unix)]
+pub use unix::{send_request, start_control_socket};
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use crate::automation::{latest_item, paste_nth_item, toggle_monitoring};
+    use crate::clipboard_management::{
+        copy_item_by_id, get_clipboard_history, get_item_full_content, is_monitoring_paused, search_clipboard_history,
+        set_monitoring_paused,
+    };
+    use crate::db::{database_paths, init_database};
+    use crate::lock_mode::ensure_unlocked;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+    use tauri::AppHandle;
+
+    const DEFAULT_LIMIT: u32 = 20;
+    const MAX_LIMIT: u32 = 500;
+
+    // 发送一个请求并等待响应（命令行工具使用）
+    pub fn send_request(path: &Path, request: &ControlRequest) -> Result<ControlResponse, String> {
+        let mut stream =
+            UnixStream::connect(path).map_err(|e| format!("无法连接 oicopy（{}），请确认应用正在运行: {}", path.display(), e))?;
+        let mut line = serde_json::to_string(request).map_err(|e| format!("序列化请求失败: {}", e))?;
+        line.push('\n');
+        stream
+            .write_all(line.as_bytes())
+            .map_err(|e| format!("发送请求失败: {}", e))?;
+        let mut response = String::new();
+        BufReader::new(stream)
+            .read_line(&mut response)
+            .map_err(|e| format!("读取响应失败: {}", e))?;
+        serde_json::from_str(&response).map_err(|e| format!("响应格式无效: {}", e))
+    }
+
+    fn execute(app: &AppHandle, request: ControlRequest) -> Result<ControlResponse, String> {
+        // 暂停监听在锁定时也允许，其他命令会读取或写回历史内容
+        if !matches!(request, ControlRequest::Pause { .. } | ControlRequest::ToggleMonitoring) {
+            ensure_unlocked(&init_database(app)?)?;
+        }
+        let clamp = |limit: Option<u32>| Some(limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT));
+        let response = match request {
+            ControlRequest::History { limit } => tauri::async_runtime::block_on(get_clipboard_history(
+                app.clone(),
+                clamp(limit),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ))
+            .map(ControlResponse::success)?,
+            ControlRequest::Get { id } => {
+                tauri::async_runtime::block_on(get_item_full_content(app.clone(), id)).map(ControlResponse::success)?
+            }
+            ControlRequest::Copy { id } => copy_item_by_id(app, id).map(|_| ControlResponse::success(id))?,
+            ControlRequest::Search { query, limit } => {
+                tauri::async_runtime::block_on(search_clipboard_history(app.clone(), query, clamp(limit), None, None))
+                    .map(ControlResponse::success)?
+            }
+            ControlRequest::Pause { paused } => {
+                set_monitoring_paused(app, paused)?;
+                ControlResponse::success(is_monitoring_paused(app))
+            }
+            ControlRequest::Latest => tauri::async_runtime::block_on(latest_item(app)).map(ControlResponse::success)?,
+            ControlRequest::PasteIndex { index } => {
+                tauri::async_runtime::block_on(paste_nth_item(app, index)).map(ControlResponse::success)?
+            }
+            ControlRequest::ToggleMonitoring => toggle_monitoring(app).map(ControlResponse::success)?,
+        };
+        Ok(response)
+    }
+
+    fn handle_connection(app: &AppHandle, stream: UnixStream) -> Result<(), String> {
+        let mut writer = stream.try_clone().map_err(|e| format!("读取控制连接失败: {}", e))?;
+        for line in BufReader::new(stream).lines() {
+            let line = line.map_err(|e| format!("读取控制请求失败: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<ControlRequest>(&line) {
+                Ok(request) => execute(app, request).unwrap_or_else(ControlResponse::failure),
+                Err(e) => ControlResponse::failure(format!("无效的请求: {}", e)),
+            };
+            let mut output = serde_json::to_string(&response).map_err(|e| format!("序列化响应失败: {}", e))?;
+            output.push('\n');
+            writer
+                .write_all(output.as_bytes())
+                .map_err(|e| format!("发送控制响应失败: {}", e))?;
+        }
+        Ok(())
+    }
+
+    // 在数据目录下创建 socket，只允许当前用户访问
+    pub fn start_control_socket(app_handle: AppHandle) {
+        let path = match database_paths(&app_handle) {
+            Ok((app_data_dir, _)) => app_data_dir.join(SOCKET_FILE_NAME),
+            Err(e) => {
+                eprintln!("启动控制 socket 失败: {}", e);
+                return;
+            }
+        };
+        // 上次退出时遗留的 socket 文件需要先删除；能连接上说明已有实例在运行
+        if UnixStream::connect(&path).is_ok() {
+            eprintln!("控制 socket 已被其他实例占用: {:?}", path);
+            return;
+        }
+        let _ = std::fs::remove_file(&path);
+
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("启动控制 socket 失败: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)) {
+            eprintln!("设置控制 socket 权限失败: {}", e);
+        }
+
+        let spawned = std::thread::Builder::new()
+            .name("control-socket".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            let app = app_handle.clone();
+                            std::thread::spawn(move || {
+                                if let Err(e) = handle_connection(&app, stream) {
+                                    eprintln!("{}", e);
+                                }
+                            });
+                        }
+                        Err(e) => eprintln!("接受控制连接失败: {}", e),
+                    }
+                }
+            });
+        match spawned {
+            Ok(_) => println!("控制 socket 已启动: {:?}", path),
+            Err(e) => eprintln!("启动控制 socket 失败: {}", e),
+        }
+    }
+}
+
+// Windows 没有 Unix socket，不提供命令行控制
+#[cfg(not(unix))]
+pub fn start_control_socket(_app_handle: tauri::AppHandle) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_wire_format() {
+        let request: ControlRequest = serde_json::from_str(r#"{"command":"search","query":"foo","limit":5}"#).unwrap();
+        assert_eq!(request, ControlRequest::Search { query: "foo".to_string(), limit: Some(5) });
+        assert_eq!(
+            serde_json::to_string(&ControlRequest::Get { id: 7 }).unwrap(),
+            r#"{"command":"get","id":7}"#
+        );
+        let request: ControlRequest = serde_json::from_str(r#"{"command":"history"}"#).unwrap();
+        assert_eq!(request, ControlRequest::History { limit: None });
+        let request: ControlRequest = serde_json::from_str(r#"{"command":"paste_index","index":2}"#).unwrap();
+        assert_eq!(request, ControlRequest::PasteIndex { index: 2 });
+    }
+}
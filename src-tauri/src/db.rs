@@ -1,26 +1,421 @@
-use rusqlite::{params, Connection};
+use crate::app_info::refresh_stale_app_icons;
+use crate::change_log::create_change_log;
+use crate::classifier::{classify, code_language, ContentCategory};
+use crate::clipboard_content::{calculate_content_hash, ClipboardContent, ContentKind, RTF_PREVIEW_FALLBACK};
+use crate::color::content_color;
+use crate::content_formats::create_content_formats_table;
+use crate::db_encryption::{apply_key, prepare_database_key};
+use crate::db_integrity::{apply_staged_recovery, check_integrity, stage_recovery};
+use crate::db_location::configured_database_dir;
+use crate::profiles::{active_profile, profile_database_path};
+use crate::disk_guard::create_diagnostics_table;
+use crate::error::OicopyError;
+use crate::image_store::{blob_size, init_image_store, is_blob_reference, prune_orphan_blobs, store_image_blob};
+use crate::sensitive::masked_preview;
+use crate::retention::{simulate_retention, RetentionPolicy, RetentionSimulation};
+use crate::shortcut::create_item_shortcuts_table;
+use crate::trash::create_trash_table;
+use crate::lan_sync::create_sync_peers_table;
+use crate::cloud_sync::create_cloud_sync_tables;
+use base64::prelude::*;
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Instant;
 
 // 定义剪贴板历史记录结构体
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ClipboardHistoryItem {
     pub id: Option<i64>,
-    pub content_type: String,
+    pub content_type: ContentKind,
     pub content: String,
     pub preview: Option<String>,
     pub timestamp: String,
     pub content_hash: Option<String>, // 内容哈希字段
     pub source_app: Option<String>,   // 来源应用名称
     pub source_bundle_id: Option<String>, // 来源应用Bundle ID
-    pub app_icon_base64: Option<String>, // 应用图标base64数据
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_icon_base64: Option<String>, // 应用图标base64数据（列表接口不再返回，见 get_app_icons）
+    #[serde(default)]
+    pub edited: bool,                    // 内容是否被用户编辑/脱敏过
+    #[serde(default)]
+    pub device_id: Option<String>,       // 采集该记录的设备ID
+    #[serde(default)]
+    pub pinned: bool,                    // 是否已置顶收藏
+    #[serde(default)]
+    pub pin_order: Option<i64>,          // 置顶项的用户自定义顺序
+    #[serde(default)]
+    pub truncated: bool,                 // 内容超出面板渲染限制，已截断（完整内容见 get_item_full_content）
+    #[serde(default)]
+    pub image_width: Option<u32>,        // 图片宽度（仅图片记录）
+    #[serde(default)]
+    pub image_height: Option<u32>,       // 图片高度（仅图片记录）
+    #[serde(default)]
+    pub failed_formats: Vec<ContentKind>, // 捕获时读取失败、降级跳过的格式（用于诊断）
+    #[serde(default)]
+    pub content_omitted: bool,           // 列表中未返回内容（图片为缩略图），需通过 get_clipboard_item_content 获取
+    #[serde(default)]
+    pub minified: bool,                  // 压缩/超长单行内容（预览已折行截断）
+    #[serde(default)]
+    pub search_truncated: bool,          // 内容超出全文索引长度，只有前 FTS_INDEX_CHARS 个字符可被搜索到
+    #[serde(default)]
+    pub tags: Vec<String>,               // 记录的标签
+    #[serde(default)]
+    pub note: Option<String>,            // 用户为记录添加的备注（参与全文搜索）
+    #[serde(default)]
+    pub category: Option<ContentCategory>, // 捕获时识别的内容类别（图片为空）
+    #[serde(default)]
+    pub url_title: Option<String>,       // 链接的页面标题（保存后异步获取）
+    #[serde(default)]
+    pub url_favicon: Option<String>,     // 链接的站点图标（data URL）
+    #[serde(default)]
+    pub color: Option<String>,           // 颜色文本的归一化值（#rrggbb / #rrggbbaa），用于显示色块
+    #[serde(default)]
+    pub language: Option<String>,        // 代码内容的语言提示（用于语法高亮）
+    #[serde(default)]
+    pub alternate_formats: Vec<ContentKind>, // 同一次复制保存的其他格式，粘贴时按目标应用选择
+    #[serde(default)]
+    pub use_count: i64,                  // 从历史中复制回剪贴板或粘贴的次数
+    #[serde(default)]
+    pub last_used_at: Option<String>,    // 最近一次复制回剪贴板或粘贴的时间
+    #[serde(default)]
+    pub sensitive: bool,                 // 识别到敏感内容（银行卡号、密钥等），预览已打码
+    #[serde(default)]
+    pub expires_at: Option<String>,      // 自动删除的时间，为空表示不过期
+}
+
+impl ClipboardHistoryItem {
+    // 由结构化内容构建待保存的记录
+    pub fn from_content(
+        content: &ClipboardContent,
+        timestamp: String,
+        source_app: Option<String>,
+        source_bundle_id: Option<String>,
+    ) -> Self {
+        let stored = content.to_stored();
+        Self {
+            id: None,
+            content_type: content.kind(),
+            search_truncated: exceeds_search_index(content.kind(), &stored),
+            minified: content.is_minified(),
+            content: stored,
+            preview: Some(content.preview()),
+            timestamp,
+            content_hash: Some(content.content_hash()),
+            source_app,
+            source_bundle_id,
+            app_icon_base64: None,
+            edited: false,
+            device_id: None,
+            pinned: false,
+            pin_order: None,
+            truncated: false,
+            image_width: None,
+            image_height: None,
+            failed_formats: Vec::new(),
+            content_omitted: false,
+            tags: Vec::new(),
+            note: None,
+            category: classify(content),
+            url_title: None,
+            url_favicon: None,
+            color: content_color(content),
+            language: code_language(content),
+            alternate_formats: Vec::new(),
+            use_count: 0,
+            last_used_at: None,
+            sensitive: false,
+            expires_at: None,
+        }
+    }
+
+    // 还原为结构化内容
+    pub fn to_content(&self) -> Result<ClipboardContent, String> {
+        ClipboardContent::from_stored(self.content_type, self.content.clone())
+    }
+}
+
+// 全文索引只收录内容的前 N 个字符，避免超长内容拖慢分词；完整内容仍可按需读取
+pub const FTS_INDEX_CHARS: usize = 64 * 1024;
+
+// 内容是否超出全文索引收录的长度
+fn exceeds_search_index(kind: ContentKind, stored: &str) -> bool {
+    kind != ContentKind::Image && stored.len() > FTS_INDEX_CHARS && stored.chars().count() > FTS_INDEX_CHARS
 }
 
 // 定义设置结构体
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppSettings {
     pub retention_days: i32,
+    #[serde(default)]
+    pub feedback_sound: bool, // 捕获/粘贴时播放提示音
+    #[serde(default)]
+    pub feedback_haptic: bool, // 捕获/粘贴时触控板震动反馈
+    #[serde(default = "default_true")]
+    pub auto_check_updates: bool, // 后台定期检查更新
+    #[serde(default = "default_update_channel")]
+    pub update_channel: String, // 更新通道: stable / beta
+    #[serde(default = "default_true")]
+    pub notify_monitor_restart: bool, // 监听重启时发送通知
+    #[serde(default = "default_true")]
+    pub notify_cleanup: bool, // 自动清理删除记录时发送通知
+    #[serde(default = "default_true")]
+    pub paste_confirm_enabled: bool, // 大内容或粘贴到终端前需要确认
+    #[serde(default = "default_paste_confirm_max_lines")]
+    pub paste_confirm_max_lines: i64,
+    #[serde(default = "default_paste_confirm_max_bytes")]
+    pub paste_confirm_max_bytes: i64,
+    #[serde(default = "default_global_shortcut")]
+    pub global_shortcut: String, // 唤出面板的全局快捷键
+    #[serde(default)]
+    pub history_locked: bool, // 只读锁定模式：可查看历史，但禁止粘贴、删除、导出和修改设置
+    #[serde(default, skip_serializing)]
+    pub lock_passcode_hash: Option<String>, // 解锁密码（salt$sha256），不返回给前端
+    #[serde(default)]
+    pub monitoring_paused: bool, // 是否暂停剪贴板监听
+    #[serde(default = "default_min_free_disk_mb")]
+    pub min_free_disk_mb: i64, // 磁盘剩余空间低于该值（MB）时停止保存图片、文件和大段内容
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: i32, // 回收站中的记录保留天数，超过后由定时清理永久删除
+    #[serde(default = "default_true")]
+    pub fetch_url_metadata: bool, // 捕获链接后在后台获取页面标题和图标
+    #[serde(default)]
+    pub strip_tracking_params: bool, // 保存链接前去掉 utm_*、fbclid 等追踪参数
+    #[serde(default)]
+    pub content_priority: Option<Vec<ContentKind>>, // 用户自定义的捕获格式优先级（JSON 数组），为空时自动判断
+    #[serde(default = "default_analysis_similarity_threshold")]
+    pub analysis_similarity_threshold: f64, // HTML 与纯文本相似度超过该值时视为高度冗余
+    #[serde(default = "default_analysis_timeout_ms")]
+    pub analysis_timeout_ms: i64, // 单次 HTML 分析的超时（毫秒），超时后使用快速启发式判断
+    #[serde(default = "default_analysis_max_content_size")]
+    pub analysis_max_content_size: i64, // 参与分析的内容大小上限（字节），超过时使用快速启发式判断
+    #[serde(default = "default_true")]
+    pub analysis_app_detection: bool, // 分析时识别来源应用
+    #[serde(default = "default_true")]
+    pub analysis_redundancy_scoring: bool, // 分析时计算冗余评分
+    #[serde(default)]
+    pub analysis_log_details: bool, // 输出分析耗时等详细日志
+    #[serde(default = "default_panel_monitor")]
+    pub panel_monitor: String, // 面板显示的显示器: cursor / focused_window / primary
+    #[serde(default = "default_panel_edge")]
+    pub panel_edge: String, // 面板停靠的屏幕边缘: bottom / top / left / right
+    #[serde(default = "default_panel_height")]
+    pub panel_height: f64, // 停靠在顶部/底部时的面板高度（逻辑像素）
+    #[serde(default = "default_panel_width")]
+    pub panel_width: f64, // 停靠在左侧/右侧时的面板宽度（逻辑像素）
+    #[serde(default = "default_panel_mode")]
+    pub panel_mode: String, // 面板展示方式: dock（停靠在屏幕边缘）/ spotlight（居中的浮动搜索框）
+    #[serde(default = "default_cleanup_max_records")]
+    pub cleanup_max_records: i64, // 自动清理保留的非置顶记录数上限，0 表示不限制
+    #[serde(default = "default_cleanup_max_size_mb")]
+    pub cleanup_max_size_mb: f64, // 自动清理的数据库大小上限（MB），0 表示不限制
+    #[serde(default = "default_true")]
+    pub auto_cleanup_enabled: bool, // 是否定时执行自动清理
+    #[serde(default = "default_cleanup_interval_minutes")]
+    pub cleanup_interval_minutes: i64, // 自动清理的执行间隔（分钟）
+    #[serde(default)]
+    pub cleanup_quiet_start_hour: Option<i64>, // 免打扰时段开始的小时（0-23），该时段内不执行自动清理
+    #[serde(default)]
+    pub cleanup_quiet_end_hour: Option<i64>, // 免打扰时段结束的小时（0-23，不含），可跨越午夜
+    #[serde(default)]
+    pub cleanup_only_when_idle: bool, // 只在系统空闲（无键盘鼠标输入）时执行自动清理
+    #[serde(default)]
+    pub backup_enabled: bool, // 是否定时备份数据库
+    #[serde(default = "default_backup_interval_hours")]
+    pub backup_interval_hours: i64, // 定时备份的间隔（小时）
+    #[serde(default = "default_backup_keep_count")]
+    pub backup_keep_count: i64, // 保留最近的备份数量，更早的备份自动删除
+    #[serde(default)]
+    pub backup_folder: Option<String>, // 备份目录，为空时使用数据目录下的 backups
+    #[serde(default)]
+    pub lan_sync_enabled: bool, // 是否开启局域网同步（广播本机并定时与已配对设备同步）
+    #[serde(default)]
+    pub local_api_enabled: bool, // 是否开启本机 HTTP/WebSocket 接口（只监听 127.0.0.1）
+    #[serde(default = "default_local_api_port")]
+    pub local_api_port: i64, // 本机接口监听的端口
+    #[serde(default)]
+    pub local_api_token: Option<String>, // 本机接口的访问令牌，开启接口时自动生成
+    #[serde(default)]
+    pub deep_link_allow_destructive: bool, // 是否允许通过 oicopy:// 链接执行删除等破坏性操作
+    #[serde(default = "default_sensitive_data_mode")]
+    pub sensitive_data_mode: String, // 敏感内容处理: off / mask / skip
+    #[serde(default)]
+    pub search_sensitive_items: bool, // 搜索结果是否包含敏感记录
+    #[serde(default)]
+    pub ephemeral_expire_minutes: i64, // 敏感记录和验证码在 N 分钟后自动删除，0 表示不自动过期
+    #[serde(default = "default_lock_clear_action")]
+    pub lock_clear_action: String, // 锁屏/睡眠时的处理: off / pause / purge
+    #[serde(default = "default_lock_purge_minutes")]
+    pub lock_purge_minutes: i64, // 锁屏/睡眠时删除最近多少分钟内的记录
+    #[serde(default)]
+    pub deep_link_allow_actions: bool, // 是否允许通过 oicopy:// 链接粘贴、复制、暂停监听等（搜索始终允许）
+}
+
+fn default_paste_confirm_max_lines() -> i64 {
+    50
+}
+
+fn default_paste_confirm_max_bytes() -> i64 {
+    10 * 1024
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_update_channel() -> String {
+    "stable".to_string()
+}
+
+fn default_sensitive_data_mode() -> String {
+    "mask".to_string()
+}
+
+fn default_lock_clear_action() -> String {
+    "off".to_string()
+}
+
+fn default_lock_purge_minutes() -> i64 {
+    15
+}
+
+fn default_panel_monitor() -> String {
+    "cursor".to_string()
+}
+
+fn default_cleanup_max_records() -> i64 {
+    50_000
+}
+
+fn default_cleanup_max_size_mb() -> f64 {
+    500.0
+}
+
+fn default_cleanup_interval_minutes() -> i64 {
+    60
+}
+
+fn default_backup_interval_hours() -> i64 {
+    24
+}
+
+fn default_backup_keep_count() -> i64 {
+    7
+}
+
+fn default_local_api_port() -> i64 {
+    52700
+}
+
+fn default_panel_mode() -> String {
+    "dock".to_string()
+}
+
+fn default_panel_edge() -> String {
+    "bottom".to_string()
+}
+
+fn default_panel_height() -> f64 {
+    332.0
+}
+
+fn default_panel_width() -> f64 {
+    360.0
+}
+
+fn default_global_shortcut() -> String {
+    "command+shift+v".to_string()
+}
+
+fn default_min_free_disk_mb() -> i64 {
+    1024
+}
+
+fn default_trash_retention_days() -> i32 {
+    30
+}
+
+fn default_analysis_similarity_threshold() -> f64 {
+    0.95
+}
+
+fn default_analysis_timeout_ms() -> i64 {
+    200
+}
+
+fn default_analysis_max_content_size() -> i64 {
+    1024 * 1024
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            retention_days: 30,
+            feedback_sound: false,
+            feedback_haptic: false,
+            auto_check_updates: true,
+            update_channel: default_update_channel(),
+            notify_monitor_restart: true,
+            notify_cleanup: true,
+            paste_confirm_enabled: true,
+            paste_confirm_max_lines: default_paste_confirm_max_lines(),
+            paste_confirm_max_bytes: default_paste_confirm_max_bytes(),
+            global_shortcut: default_global_shortcut(),
+            history_locked: false,
+            lock_passcode_hash: None,
+            monitoring_paused: false,
+            min_free_disk_mb: default_min_free_disk_mb(),
+            trash_retention_days: default_trash_retention_days(),
+            fetch_url_metadata: true,
+            strip_tracking_params: false,
+            content_priority: None,
+            analysis_similarity_threshold: default_analysis_similarity_threshold(),
+            analysis_timeout_ms: default_analysis_timeout_ms(),
+            analysis_max_content_size: default_analysis_max_content_size(),
+            analysis_app_detection: true,
+            analysis_redundancy_scoring: true,
+            analysis_log_details: false,
+            panel_monitor: default_panel_monitor(),
+            panel_edge: default_panel_edge(),
+            panel_height: default_panel_height(),
+            panel_width: default_panel_width(),
+            panel_mode: default_panel_mode(),
+            cleanup_max_records: default_cleanup_max_records(),
+            cleanup_max_size_mb: default_cleanup_max_size_mb(),
+            auto_cleanup_enabled: true,
+            cleanup_interval_minutes: default_cleanup_interval_minutes(),
+            cleanup_quiet_start_hour: None,
+            cleanup_quiet_end_hour: None,
+            cleanup_only_when_idle: false,
+            backup_enabled: false,
+            backup_interval_hours: 24,
+            backup_keep_count: 7,
+            backup_folder: None,
+            lan_sync_enabled: false,
+            local_api_enabled: false,
+            local_api_port: default_local_api_port(),
+            local_api_token: None,
+            deep_link_allow_destructive: false,
+            sensitive_data_mode: default_sensitive_data_mode(),
+            search_sensitive_items: false,
+            ephemeral_expire_minutes: 0,
+            lock_clear_action: default_lock_clear_action(),
+            lock_purge_minutes: default_lock_purge_minutes(),
+            deep_link_allow_actions: false,
+        }
+    }
+}
+
+// 排除记录的应用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludedApp {
+    pub bundle_id: String,
+    pub app_name: Option<String>,
+    pub created_at: String,
 }
 
 // 数据库性能统计
@@ -50,32 +445,251 @@ pub struct MaintenanceResult {
     pub reindex_completed: bool,
     pub analyze_completed: bool,
     pub records_cleaned: usize,
+    pub icons_refreshed: usize,
     pub size_before_mb: f64,
     pub size_after_mb: f64,
     pub duration_ms: u64,
+    pub integrity_ok: bool,     // quick_check 结果
+    pub recovery_staged: bool,  // 检查发现损坏并已准备恢复，重启后生效
 }
 
-// 创建数据库连接和表结构
-pub fn init_database(app_handle: &AppHandle) -> Result<Connection, String> {
+// 数据库连接的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionRole {
+    Writer,    // 捕获写入、建表和迁移
+    Analytics, // 只读的统计/聚合查询，WAL 模式下不会阻塞写入连接
+}
+
+pub const DATABASE_FILE_NAME: &str = "clipboard_history.db";
+
+// 获取应用数据目录（不存在时创建）和当前配置的数据库文件路径，数据库可通过 set_database_path 移到其他目录
+pub fn database_paths(app_handle: &AppHandle) -> Result<(PathBuf, PathBuf), OicopyError> {
     // 拿到 PathResolver
     let resolver = app_handle.path();
     // 获取应用数据目录
     let app_data_dir = resolver
         .app_data_dir()
-        .or_else(|_| Err("无法获取应用数据目录".to_string()))?;
+        .map_err(|e| OicopyError::Io(format!("无法获取应用数据目录: {}", e)))?;
 
     // 确保目录存在
-    std::fs::create_dir_all(&app_data_dir).map_err(|e| format!("创建数据目录失败: {}", e))?;
+    std::fs::create_dir_all(&app_data_dir).map_err(|e| OicopyError::Io(format!("创建数据目录失败: {}", e)))?;
+
+    let db_dir = configured_database_dir(&app_data_dir).unwrap_or_else(|| app_data_dir.clone());
+    let db_path = profile_database_path(&db_dir, &active_profile(&app_data_dir));
+    Ok((app_data_dir, db_path))
+}
+
+// 数据库连接池：启动时打开数据库并初始化表结构一次，之后各命令从池中借用连接
+// 连接用完后放回池中复用，避免每次调用都重新打开数据库、设置密钥和执行建表迁移
+pub struct DatabasePool {
+    db_path: RwLock<PathBuf>,
+    generation: AtomicU64, // 数据库移动后递增，旧位置的连接不再放回池中
+    key: &'static str,
+    writers: Mutex<Vec<Connection>>,
+    readers: Mutex<Vec<Connection>>,
+}
+
+// 每种用途最多保留的空闲连接数
+const MAX_IDLE_CONNECTIONS: usize = 4;
+// 连接等待写锁的超时时间，避免与监听线程并发写入时直接返回 SQLITE_BUSY
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+// 防止并发初始化连接池
+static POOL_INIT_LOCK: Mutex<()> = Mutex::new(());
+
+impl DatabasePool {
+    pub(crate) fn new(db_path: PathBuf, key: &'static str) -> Self {
+        DatabasePool {
+            db_path: RwLock::new(db_path),
+            generation: AtomicU64::new(0),
+            key,
+            writers: Mutex::new(Vec::new()),
+            readers: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn idle(&self, role: ConnectionRole) -> &Mutex<Vec<Connection>> {
+        match role {
+            ConnectionRole::Writer => &self.writers,
+            ConnectionRole::Analytics => &self.readers,
+        }
+    }
+
+    pub fn current_path(&self) -> PathBuf {
+        self.db_path.read().map(|path| path.clone()).unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    // 打开新的连接并设置密钥
+    fn open(&self, role: ConnectionRole) -> Result<Connection, OicopyError> {
+        self.open_at(&self.current_path(), role)
+    }
+
+    fn open_at(&self, db_path: &Path, role: ConnectionRole) -> Result<Connection, OicopyError> {
+        let conn = match role {
+            ConnectionRole::Writer => {
+                Connection::open(db_path).map_err(|e| OicopyError::Db(format!("打开数据库失败: {}", e)))?
+            }
+            // 只读的分析连接，耗时的聚合查询不占用写锁
+            ConnectionRole::Analytics => Connection::open_with_flags(
+                db_path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )
+            .map_err(|e| OicopyError::Db(format!("打开只读数据库连接失败: {}", e)))?,
+        };
+        apply_key(&conn, self.key)?;
+        conn.busy_timeout(BUSY_TIMEOUT)
+            .map_err(|e| OicopyError::Db(format!("设置数据库超时失败: {}", e)))?;
+
+        if role == ConnectionRole::Writer {
+            // 启用WAL模式和性能优化设置（如果失败不影响应用运行）
+            if let Err(e) = optimize_database_performance(&conn) {
+                println!("数据库性能优化失败，但不影响应用运行: {}", e);
+            }
+        }
+        Ok(conn)
+    }
+
+    // 打开指定的数据库文件并创建表结构，文件不存在时创建新数据库
+    pub fn open_database_file(&self, db_path: &Path) -> Result<Connection, OicopyError> {
+        let conn = self.open_at(db_path, ConnectionRole::Writer)?;
+        create_schema(&conn)?;
+        Ok(conn)
+    }
+
+    // 切换连接池使用的数据库文件（移动位置或切换配置）：之后打开的连接使用新文件，空闲连接全部关闭
+    // 返回新文件的写入连接，用于替换调用方（写入线程）正在使用的连接
+    pub fn switch_path(&self, new_path: PathBuf) -> Result<Connection, OicopyError> {
+        let conn = self.open_database_file(&new_path)?;
+        let mut db_path = self
+            .db_path
+            .write()
+            .map_err(|e| OicopyError::Db(format!("切换数据库位置失败: {}", e)))?;
+        *db_path = new_path;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        for role in [ConnectionRole::Writer, ConnectionRole::Analytics] {
+            if let Ok(mut idle) = self.idle(role).lock() {
+                idle.clear();
+            }
+        }
+        Ok(conn)
+    }
+
+    // 优先复用空闲连接，没有时打开新连接
+    fn acquire(self: &Arc<Self>, role: ConnectionRole) -> Result<PooledConnection, OicopyError> {
+        let idle = self.idle(role).lock().ok().and_then(|mut idle| idle.pop());
+        let conn = match idle {
+            Some(conn) => conn,
+            None => self.open(role)?,
+        };
+        Ok(PooledConnection {
+            conn: Some(conn),
+            role,
+            generation: self.generation.load(Ordering::SeqCst),
+            pool: Arc::clone(self),
+        })
+    }
+
+    fn release(&self, role: ConnectionRole, conn: Connection) {
+        self.release_from(role, conn, self.generation.load(Ordering::SeqCst));
+    }
+
+    fn release_from(&self, role: ConnectionRole, conn: Connection, generation: u64) {
+        if generation != self.generation.load(Ordering::SeqCst) {
+            return;
+        }
+        if let Ok(mut idle) = self.idle(role).lock() {
+            if idle.len() < MAX_IDLE_CONNECTIONS {
+                idle.push(conn);
+            }
+        }
+    }
+}
+
+// 从连接池借出的连接，离开作用域时自动归还
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    role: ConnectionRole,
+    generation: u64,
+    pool: Arc<DatabasePool>,
+}
+
+impl std::ops::Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("连接已归还")
+    }
+}
+
+impl std::ops::DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("连接已归还")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            // 未结束的事务说明调用方中途出错，不放回池中
+            if conn.is_autocommit() {
+                self.pool.release_from(self.role, conn, self.generation);
+            }
+        }
+    }
+}
+
+// 初始化连接池（在 setup 中调用一次）：打开数据库、创建表结构并放入 Tauri 托管状态
+pub fn setup_database_pool(app_handle: &AppHandle) -> Result<(), OicopyError> {
+    let _guard = POOL_INIT_LOCK
+        .lock()
+        .map_err(|e| OicopyError::Db(format!("初始化数据库连接池失败: {}", e)))?;
+    if app_handle.try_state::<Arc<DatabasePool>>().is_some() {
+        return Ok(());
+    }
+
+    let (app_data_dir, db_path) = database_paths(app_handle)?;
+
+    // 上次运行时执行了数据库修复，先用恢复的数据库替换损坏的数据库
+    apply_staged_recovery(&db_path)?;
 
-    // 创建或打开数据库连接
-    let db_path = app_data_dir.join("clipboard_history.db");
-    let conn = Connection::open(db_path).map_err(|e| format!("打开数据库失败: {}", e))?;
+    // 数据库使用 SQLCipher 加密，密钥保存在钥匙串中
+    let key = prepare_database_key(&app_data_dir, &db_path)?;
 
-    // 启用WAL模式和性能优化设置（如果失败不影响应用启动）
-    if let Err(e) = optimize_database_performance(&conn) {
-        println!("数据库性能优化失败，但不影响应用运行: {}", e);
+    // 图片以加密文件形式保存在数据目录下，密钥由数据库密钥派生
+    init_image_store(&app_data_dir, key)?;
+    let pool = Arc::new(DatabasePool::new(db_path, key));
+
+    let conn = pool.open(ConnectionRole::Writer)?;
+    create_schema(&conn)?;
+    pool.release(ConnectionRole::Writer, conn);
+
+    app_handle.manage(pool);
+    println!("数据库连接池已初始化");
+    Ok(())
+}
+
+// 按用途从连接池获取连接，连接池尚未初始化时先初始化
+pub fn open_connection(app_handle: &AppHandle, role: ConnectionRole) -> Result<PooledConnection, OicopyError> {
+    if app_handle.try_state::<Arc<DatabasePool>>().is_none() {
+        setup_database_pool(app_handle)?;
     }
+    let pool = app_handle
+        .try_state::<Arc<DatabasePool>>()
+        .ok_or_else(|| OicopyError::Db("数据库连接池未初始化".to_string()))?;
+    pool.acquire(role)
+}
+
+// 获取写入连接（表结构已在初始化连接池时创建）
+pub fn init_database(app_handle: &AppHandle) -> Result<PooledConnection, OicopyError> {
+    open_connection(app_handle, ConnectionRole::Writer)
+}
 
+// 创建表结构并执行迁移，只在初始化连接池时执行一次
+fn create_schema(conn: &Connection) -> Result<(), OicopyError> {
     // 创建表
     conn.execute(
         "CREATE TABLE IF NOT EXISTS clipboard_history (
@@ -86,11 +700,15 @@ pub fn init_database(app_handle: &AppHandle) -> Result<Connection, String> {
             preview TEXT,
             timestamp TEXT NOT NULL,
             source_app TEXT,
-            source_bundle_id TEXT
+            source_bundle_id TEXT,
+            edited INTEGER NOT NULL DEFAULT 0,
+            device_id TEXT,
+            pinned INTEGER NOT NULL DEFAULT 0,
+            pin_order INTEGER
         )",
         [],
     )
-    .map_err(|e| format!("创建剪贴板历史表失败: {}", e))?;
+    .map_err(|e| OicopyError::Db(format!("创建剪贴板历史表失败: {}", e)))?;
     
     // 为旧表添加新列（如果不存在）
     let _ = conn.execute(
@@ -108,11 +726,165 @@ pub fn init_database(app_handle: &AppHandle) -> Result<Connection, String> {
         [],
     ); // 忽略错误，因为列可能已存在
     
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN edited INTEGER NOT NULL DEFAULT 0",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN device_id TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN pin_order INTEGER",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN image_width INTEGER",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN image_height INTEGER",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN failed_formats TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN thumbnail TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN minified INTEGER NOT NULL DEFAULT 0",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN search_truncated INTEGER NOT NULL DEFAULT 0",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN note TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN category TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    let _ = conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_history_category ON clipboard_history(category)",
+        [],
+    );
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN url_title TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN url_favicon TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN color TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN language TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN use_count INTEGER NOT NULL DEFAULT 0",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN last_used_at TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    // 云同步状态：0 待上传，1 已上传，2 从云端下载
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN sync_state INTEGER NOT NULL DEFAULT 0",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    // 敏感记录：捕获时识别到银行卡号、密钥等，预览已打码
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN sensitive INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    // 自动过期时间，到期后由定时清理删除
+    let _ = conn.execute(
+        "ALTER TABLE clipboard_history ADD COLUMN expires_at TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_history_expires_at ON clipboard_history(expires_at) WHERE expires_at IS NOT NULL",
+        [],
+    );
+    
     // 创建性能优化索引（如果失败不影响应用启动）
-    if let Err(e) = create_performance_indexes(&conn) {
+    if let Err(e) = create_performance_indexes(conn) {
         println!("创建性能优化索引失败，但不影响应用运行: {}", e);
     }
     
+    // 创建全文搜索索引（如果失败，搜索会退回到 LIKE 查询）
+    if let Err(e) = create_fts_index(conn) {
+        println!("创建全文搜索索引失败，但不影响应用运行: {}", e);
+    }
+    
+    // 创建变更日志（供面板按序号增量同步）
+    create_change_log(conn)?;
+    
+    // 创建捕获诊断表
+    create_diagnostics_table(conn)?;
+    
+    // 创建回收站表
+    create_trash_table(conn)?;
+    
+    // 创建记录快捷键表
+    create_item_shortcuts_table(conn)?;
+    
+    // 创建局域网同步的已配对设备表
+    create_sync_peers_table(conn)?;
+    
+    // 创建云同步配置表
+    create_cloud_sync_tables(conn)?;
+    
+    // 创建内容格式表（同一次复制的其他格式）
+    create_content_formats_table(conn)?;
+    
+    // 创建标签表（记录与标签的关联按记录ID保存，移入回收站再恢复后标签仍然保留）
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE
+        );
+        CREATE TABLE IF NOT EXISTS item_tags (
+            item_id INTEGER NOT NULL,
+            tag_id INTEGER NOT NULL,
+            PRIMARY KEY (item_id, tag_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_item_tags_tag ON item_tags(tag_id);",
+    )
+    .map_err(|e| OicopyError::Db(format!("创建标签表失败: {}", e)))?;
+    
     // 创建应用图标缓存表
     conn.execute(
         "CREATE TABLE IF NOT EXISTS app_icons (
@@ -124,7 +896,38 @@ pub fn init_database(app_handle: &AppHandle) -> Result<Connection, String> {
         )",
         [],
     )
-    .map_err(|e| format!("创建应用图标缓存表失败: {}", e))?;
+    .map_err(|e| OicopyError::Db(format!("创建应用图标缓存表失败: {}", e)))?;
+
+    // 记录提取图标时的应用版本，应用更新后重新提取
+    let _ = conn.execute(
+        "ALTER TABLE app_icons ADD COLUMN app_version TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    
+    // 创建排除应用表（来自这些应用的复制不会被记录）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS excluded_apps (
+            bundle_id TEXT PRIMARY KEY,
+            app_name TEXT,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| OicopyError::Db(format!("创建排除应用表失败: {}", e)))?;
+    
+    // 创建监听心跳表（记录应用运行期间剪贴板监听的健康状态）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS monitor_heartbeats (
+            timestamp TEXT NOT NULL,
+            monitor_running INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| OicopyError::Db(format!("创建监听心跳表失败: {}", e)))?;
+    let _ = conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_heartbeat_timestamp ON monitor_heartbeats(timestamp)",
+        [],
+    );
     
     // 创建设置表
     conn.execute(
@@ -134,87 +937,846 @@ pub fn init_database(app_handle: &AppHandle) -> Result<Connection, String> {
         )",
         [],
     )
-    .map_err(|e| format!("创建设置表失败: {}", e))?;
-    
-    // 初始化默认设置
+    .map_err(|e| OicopyError::Db(format!("创建设置表失败: {}", e)))?;
+    
+    // 初始化默认设置
+    conn.execute(
+        "INSERT OR IGNORE INTO app_settings (id, retention_days) VALUES (1, 30)",
+        [],
+    )
+    .map_err(|e| OicopyError::Db(format!("初始化设置失败: {}", e)))?;
+    
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN device_id TEXT",
+        [],
+    ); // 忽略错误，因为列可能已存在
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN feedback_sound INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN feedback_haptic INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN auto_check_updates INTEGER NOT NULL DEFAULT 1",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN update_channel TEXT NOT NULL DEFAULT 'stable'",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN notify_monitor_restart INTEGER NOT NULL DEFAULT 1",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN notify_cleanup INTEGER NOT NULL DEFAULT 1",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN paste_confirm_enabled INTEGER NOT NULL DEFAULT 1",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN paste_confirm_max_lines INTEGER NOT NULL DEFAULT 50",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN paste_confirm_max_bytes INTEGER NOT NULL DEFAULT 10240",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN global_shortcut TEXT NOT NULL DEFAULT 'command+shift+v'",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN history_locked INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN lock_passcode_hash TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN monitoring_paused INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN min_free_disk_mb INTEGER NOT NULL DEFAULT 1024",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN trash_retention_days INTEGER NOT NULL DEFAULT 30",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN fetch_url_metadata INTEGER NOT NULL DEFAULT 1",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN strip_tracking_params INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN content_priority TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN analysis_similarity_threshold REAL NOT NULL DEFAULT 0.95",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN analysis_timeout_ms INTEGER NOT NULL DEFAULT 200",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN analysis_max_content_size INTEGER NOT NULL DEFAULT 1048576",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN analysis_app_detection INTEGER NOT NULL DEFAULT 1",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN analysis_redundancy_scoring INTEGER NOT NULL DEFAULT 1",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN analysis_log_details INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN panel_monitor TEXT NOT NULL DEFAULT 'cursor'",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN panel_edge TEXT NOT NULL DEFAULT 'bottom'",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN panel_height REAL NOT NULL DEFAULT 332",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN panel_width REAL NOT NULL DEFAULT 360",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN panel_mode TEXT NOT NULL DEFAULT 'dock'",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN cleanup_max_records INTEGER NOT NULL DEFAULT 50000",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN cleanup_max_size_mb REAL NOT NULL DEFAULT 500",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN auto_cleanup_enabled INTEGER NOT NULL DEFAULT 1",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN cleanup_interval_minutes INTEGER NOT NULL DEFAULT 60",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN cleanup_quiet_start_hour INTEGER",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN cleanup_quiet_end_hour INTEGER",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN cleanup_only_when_idle INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN backup_enabled INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN backup_interval_hours INTEGER NOT NULL DEFAULT 24",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN backup_keep_count INTEGER NOT NULL DEFAULT 7",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN backup_folder TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN lan_sync_enabled INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN local_api_enabled INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN local_api_port INTEGER NOT NULL DEFAULT 52700",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN local_api_token TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN deep_link_allow_destructive INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN sensitive_data_mode TEXT NOT NULL DEFAULT 'mask'",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN search_sensitive_items INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN ephemeral_expire_minutes INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN lock_clear_action TEXT NOT NULL DEFAULT 'off'",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN lock_purge_minutes INTEGER NOT NULL DEFAULT 15",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN deep_link_allow_actions INTEGER NOT NULL DEFAULT 0",
+        [],
+    );
+    
+    // 首次运行时生成设备ID
+    get_device_id(conn)?;
+
+    Ok(())
+}
+
+// 获取本机设备ID，不存在时生成并保存
+pub fn get_device_id(conn: &Connection) -> Result<String, OicopyError> {
+    let existing: Option<String> = conn
+        .query_row("SELECT device_id FROM app_settings WHERE id = 1", [], |row| row.get(0))
+        .map_err(|e| OicopyError::Db(format!("查询设备ID失败: {}", e)))?;
+
+    if let Some(device_id) = existing.filter(|id| !id.is_empty()) {
+        return Ok(device_id);
+    }
+
+    let device_id = generate_device_id();
+    conn.execute(
+        "UPDATE app_settings SET device_id = ?1 WHERE id = 1",
+        params![device_id],
+    )
+    .map_err(|e| OicopyError::Db(format!("保存设备ID失败: {}", e)))?;
+
+    println!("已生成设备ID: {}", device_id);
+    Ok(device_id)
+}
+
+// 生成随机设备ID（基于时间、进程和主机信息的哈希）
+fn generate_device_id() -> String {
+    let seed = format!(
+        "{}-{}-{:?}-{}",
+        chrono::Local::now().timestamp_nanos_opt().unwrap_or_default(),
+        std::process::id(),
+        std::thread::current().id(),
+        std::env::var("HOSTNAME").or_else(|_| std::env::var("USER")).unwrap_or_default(),
+    );
+    let hash = calculate_content_hash(&seed);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hash[0..8],
+        &hash[8..12],
+        &hash[12..16],
+        &hash[16..20],
+        &hash[20..32]
+    )
+}
+
+// 保存剪贴板内容到数据库
+pub fn save_to_database(conn: &Connection, item: &ClipboardHistoryItem) -> Result<i64, OicopyError> {
+    // 图片写入 blobs 目录，数据库只保存相对路径和尺寸
+    let (content, image_width, image_height) =
+        if item.content_type == ContentKind::Image && !is_blob_reference(&item.content) {
+            let bytes = BASE64_STANDARD
+                .decode(item.content.trim())
+                .map_err(|e| OicopyError::Db(format!("解码图像数据失败: {}", e)))?;
+            let stored = store_image_blob(&bytes, "png")?;
+            (stored.reference, stored.width, stored.height)
+        } else {
+            (item.content.clone(), item.image_width, item.image_height)
+        };
+
+    let failed_formats = if item.failed_formats.is_empty() {
+        None
+    } else {
+        serde_json::to_string(&item.failed_formats).ok()
+    };
+
+    let result = conn.execute(
+        "INSERT INTO clipboard_history (content_type, content, content_hash, preview, timestamp, source_app, source_bundle_id, device_id, image_width, image_height, failed_formats, minified, search_truncated, note, category, color, language, sensitive, expires_at) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+        params![
+            item.content_type,
+            content,
+            item.content_hash,
+            item.preview,
+            item.timestamp,
+            item.source_app,
+            item.source_bundle_id,
+            item.device_id,
+            image_width,
+            image_height,
+            failed_formats,
+            item.minified,
+            item.search_truncated,
+            item.note,
+            item.category,
+            item.color,
+            item.language,
+            item.sensitive,
+            item.expires_at
+        ],
+    );
+
+    match result {
+        Ok(_) => {
+            // 返回最后插入的行ID
+            Ok(conn.last_insert_rowid())
+        }
+        Err(e) => {
+            // 检查是否是唯一约束失败（即内容重复）
+            if e.to_string().contains("UNIQUE constraint failed") {
+                println!("内容已存在，跳过保存");
+                return Err(OicopyError::Duplicate);
+            }
+            Err(OicopyError::Db(format!("保存到数据库失败: {}", e)))
+        }
+    }
+}
+
+// 记录一次使用（复制回剪贴板或粘贴），用于按使用频率排序
+pub fn record_item_use(conn: &Connection, id: i64) -> Result<(), OicopyError> {
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    conn.execute(
+        "UPDATE clipboard_history SET use_count = use_count + 1, last_used_at = ?1 WHERE id = ?2",
+        params![now, id],
+    )
+    .map_err(|e| OicopyError::Db(format!("记录使用次数失败: {}", e)))?;
+    Ok(())
+}
+
+// 单条历史记录查询的公共列（与 item_from_row 的下标对应）
+const ITEM_COLUMNS: &str = "id, content_type, content, content_hash, preview, timestamp, source_app, source_bundle_id, edited, device_id, pinned, pin_order, image_width, image_height, failed_formats, minified, search_truncated,
+    (SELECT json_group_array(t.name) FROM item_tags it JOIN tags t ON t.id = it.tag_id WHERE it.item_id = clipboard_history.id), note, category, url_title, url_favicon, color, language,
+    (SELECT json_group_array(kind) FROM content_formats WHERE item_id = clipboard_history.id), use_count, last_used_at, sensitive, expires_at";
+
+fn item_from_row(row: &rusqlite::Row) -> rusqlite::Result<ClipboardHistoryItem> {
+    Ok(ClipboardHistoryItem {
+        id: Some(row.get(0)?),
+        content_type: row.get(1)?,
+        content: row.get(2)?,
+        content_hash: row.get(3)?,
+        preview: row.get(4)?,
+        timestamp: row.get(5)?,
+        source_app: row.get(6)?,
+        source_bundle_id: row.get(7)?,
+        app_icon_base64: None,
+        edited: row.get(8)?,
+        device_id: row.get(9)?,
+        pinned: row.get(10)?,
+        pin_order: row.get(11)?,
+        truncated: false,
+        image_width: row.get(12)?,
+        image_height: row.get(13)?,
+        failed_formats: parse_content_kinds(row.get(14)?),
+        content_omitted: false,
+        minified: row.get(15)?,
+        search_truncated: row.get(16)?,
+        tags: parse_tags(row.get(17)?),
+        note: row.get(18)?,
+        category: row.get(19)?,
+        url_title: row.get(20)?,
+        url_favicon: row.get(21)?,
+        color: row.get(22)?,
+        language: row.get(23)?,
+        alternate_formats: parse_content_kinds(row.get(24)?),
+        use_count: row.get(25)?,
+        last_used_at: row.get(26)?,
+        sensitive: row.get(27)?,
+        expires_at: row.get(28)?,
+    })
+}
+
+// 解析格式列表（failed_formats 列或其他格式子查询结果，JSON 数组），无法解析时视为空
+pub fn parse_content_kinds(raw: Option<String>) -> Vec<ContentKind> {
+    raw.and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+// 解析标签子查询结果（json_group_array），无法解析时视为空
+pub fn parse_tags(raw: Option<String>) -> Vec<String> {
+    let mut tags: Vec<String> = raw
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default();
+    tags.sort_by_key(|tag| tag.to_lowercase());
+    tags
+}
+
+// 按ID获取单条历史记录
+pub fn get_history_item(conn: &Connection, id: i64) -> Result<Option<ClipboardHistoryItem>, OicopyError> {
+    conn.query_row(
+        &format!("SELECT {} FROM clipboard_history WHERE id = ?1", ITEM_COLUMNS),
+        params![id],
+        item_from_row,
+    )
+    .optional()
+    .map_err(|e| OicopyError::Db(format!("查询历史记录失败: {}", e)))
+}
+
+// 获取最近的若干条记录的 (ID, 类型, 预览)，用于托盘菜单
+pub fn get_recent_previews(conn: &Connection, limit: u32) -> Result<Vec<(i64, ContentKind, String)>, OicopyError> {
+    let mut stmt = conn
+        .prepare("SELECT id, content_type, preview FROM clipboard_history ORDER BY id DESC LIMIT ?1")
+        .map_err(|e| OicopyError::Db(format!("准备查询最近记录失败: {}", e)))?;
+    let rows = stmt
+        .query_map(params![limit], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get::<_, Option<String>>(2)?.unwrap_or_default()))
+        })
+        .map_err(|e| OicopyError::Db(format!("查询最近记录失败: {}", e)))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("处理最近记录失败: {}", e)))
+}
+
+// 获取指定时间点（含）之前最近一次捕获的记录
+pub fn get_item_before(conn: &Connection, timestamp: &str) -> Result<Option<ClipboardHistoryItem>, OicopyError> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM clipboard_history WHERE timestamp <= ?1 ORDER BY timestamp DESC, id DESC LIMIT 1",
+            ITEM_COLUMNS
+        ),
+        params![timestamp],
+        item_from_row,
+    )
+    .optional()
+    .map_err(|e| OicopyError::Db(format!("查询历史记录失败: {}", e)))
+}
+
+// 保存图片记录的缩略图
+pub fn set_item_thumbnail(conn: &Connection, id: i64, thumbnail_base64: &str) -> Result<(), OicopyError> {
     conn.execute(
-        "INSERT OR IGNORE INTO app_settings (id, retention_days) VALUES (1, 30)",
-        [],
+        "UPDATE clipboard_history SET thumbnail = ?1 WHERE id = ?2",
+        params![thumbnail_base64, id],
     )
-    .map_err(|e| format!("初始化设置失败: {}", e))?;
+    .map_err(|e| OicopyError::Db(format!("保存缩略图失败: {}", e)))?;
+    Ok(())
+}
 
-    Ok(conn)
+// 按内容哈希查找已保存的记录ID
+pub fn find_item_id_by_hash(conn: &Connection, content_hash: &str) -> Result<Option<i64>, OicopyError> {
+    conn.query_row(
+        "SELECT id FROM clipboard_history WHERE content_hash = ?1",
+        params![content_hash],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| OicopyError::Db(format!("按哈希查询记录失败: {}", e)))
 }
 
-// 保存剪贴板内容到数据库
-pub fn save_to_database(conn: &Connection, item: &ClipboardHistoryItem) -> Result<i64, String> {
+// 改写记录内容（编辑/脱敏），同步刷新哈希和预览，并标记为已编辑；链接元数据随旧内容失效
+// 敏感记录的预览仍然打码
+pub fn update_item_content(conn: &Connection, id: i64, content: &ClipboardContent) -> Result<(), OicopyError> {
+    let stored = content.to_stored();
     let result = conn.execute(
-        "INSERT INTO clipboard_history (content_type, content, content_hash, preview, timestamp, source_app, source_bundle_id) 
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "UPDATE clipboard_history SET content = ?1, content_hash = ?2,
+            preview = CASE WHEN sensitive = 1 THEN ?10 ELSE ?3 END, edited = 1,
+            minified = ?4, search_truncated = ?5, category = ?6, color = ?7, language = ?8,
+            url_title = NULL, url_favicon = NULL WHERE id = ?9",
         params![
-            item.content_type,
-            item.content,
-            item.content_hash,
-            item.preview,
-            item.timestamp,
-            item.source_app,
-            item.source_bundle_id
+            stored,
+            content.content_hash(),
+            content.preview(),
+            content.is_minified(),
+            exceeds_search_index(content.kind(), &stored),
+            classify(content),
+            content_color(content),
+            code_language(content),
+            id,
+            masked_preview(content)
         ],
     );
 
     match result {
-        Ok(_) => {
-            // 返回最后插入的行ID
-            Ok(conn.last_insert_rowid())
-        }
+        Ok(0) => Err(OicopyError::NotFound(format!("记录不存在: {}", id))),
+        Ok(_) => Ok(()),
         Err(e) => {
-            // 检查是否是唯一约束失败（即内容重复）
+            // 改写后的内容与已有记录相同
             if e.to_string().contains("UNIQUE constraint failed") {
-                println!("内容已存在，跳过保存");
-                return Err("内容重复".to_string());
+                return Err(OicopyError::Duplicate);
             }
-            Err(format!("保存到数据库失败: {}", e))
+            Err(OicopyError::Db(format!("更新记录内容失败: {}", e)))
+        }
+    }
+}
+
+// 为旧版本保存、还没有类别（或颜色值）的记录补充类别、颜色值和语言提示（图片不分类），返回补充的条数
+pub fn backfill_categories(conn: &Connection) -> Result<usize, OicopyError> {
+    let mut stmt = conn
+        .prepare("SELECT id, content_type, content FROM clipboard_history
+                  WHERE (category IS NULL AND content_type != 'image') OR (category = 'color' AND color IS NULL)")
+        .map_err(|e| OicopyError::Db(format!("准备类别查询失败: {}", e)))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, ContentKind>(1)?, row.get::<_, String>(2)?)))
+        .map_err(|e| OicopyError::Db(format!("查询待分类记录失败: {}", e)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("处理待分类记录失败: {}", e)))?;
+
+    let mut updated = 0;
+    for (id, kind, stored) in rows {
+        let Ok(content) = ClipboardContent::from_stored(kind, stored) else {
+            continue;
+        };
+        updated += conn
+            .execute(
+                "UPDATE clipboard_history SET category = ?1, color = ?2, language = ?3 WHERE id = ?4",
+                params![classify(&content), content_color(&content), code_language(&content), id],
+            )
+            .map_err(|e| OicopyError::Db(format!("更新记录类别失败: {}", e)))?;
+    }
+    Ok(updated)
+}
+
+// 旧版本的 RTF 记录预览只有类型说明，改为从 RTF 中提取的文本（预览参与全文搜索），返回更新的条数
+pub fn backfill_rtf_previews(conn: &Connection) -> Result<usize, OicopyError> {
+    let mut stmt = conn
+        .prepare("SELECT id, content FROM clipboard_history WHERE content_type = 'rtf' AND preview = ?1")
+        .map_err(|e| OicopyError::Db(format!("准备RTF预览查询失败: {}", e)))?;
+    let rows = stmt
+        .query_map(params![RTF_PREVIEW_FALLBACK], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| OicopyError::Db(format!("查询RTF记录失败: {}", e)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("处理RTF记录失败: {}", e)))?;
+
+    let mut updated = 0;
+    for (id, rtf) in rows {
+        let preview = ClipboardContent::Rtf { rtf }.preview();
+        if preview == RTF_PREVIEW_FALLBACK {
+            continue;
+        }
+        updated += conn
+            .execute("UPDATE clipboard_history SET preview = ?1 WHERE id = ?2", params![preview, id])
+            .map_err(|e| OicopyError::Db(format!("更新RTF预览失败: {}", e)))?;
+    }
+    Ok(updated)
+}
+
+// 设置记录备注，空白备注视为清除
+pub fn set_item_note(conn: &Connection, id: i64, note: Option<&str>) -> Result<(), OicopyError> {
+    let note = note.map(str::trim).filter(|note| !note.is_empty());
+    let changed = conn
+        .execute("UPDATE clipboard_history SET note = ?1 WHERE id = ?2", params![note, id])
+        .map_err(|e| OicopyError::Db(format!("更新备注失败: {}", e)))?;
+    if changed == 0 {
+        return Err(OicopyError::NotFound(format!("记录 {} 不存在", id)));
+    }
+    Ok(())
+}
+
+// 置顶或取消置顶记录，新置顶的记录排在已有置顶项之后
+pub fn set_item_pinned(conn: &Connection, id: i64, pinned: bool) -> Result<(), OicopyError> {
+    let changed = if pinned {
+        conn.execute(
+            "UPDATE clipboard_history
+             SET pinned = 1,
+                 pin_order = (SELECT COALESCE(MAX(pin_order), -1) + 1 FROM clipboard_history WHERE pinned = 1)
+             WHERE id = ?1 AND pinned = 0",
+            params![id],
+        )
+    } else {
+        conn.execute(
+            "UPDATE clipboard_history SET pinned = 0, pin_order = NULL WHERE id = ?1",
+            params![id],
+        )
+    }
+    .map_err(|e| OicopyError::Db(format!("更新置顶状态失败: {}", e)))?;
+
+    // 已经置顶的记录再次置顶时不算错误
+    if changed == 0 && get_history_item(conn, id)?.is_none() {
+        return Err(OicopyError::NotFound(format!("记录 {} 不存在", id)));
+    }
+    Ok(())
+}
+
+// 按给定顺序重排置顶项，返回更新的记录数
+pub fn reorder_pinned_items(conn: &mut Connection, ids_in_order: &[i64]) -> Result<usize, OicopyError> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| OicopyError::Db(format!("开启事务失败: {}", e)))?;
+
+    let mut updated = 0;
+    for (order, id) in ids_in_order.iter().enumerate() {
+        let changed = tx
+            .execute(
+                "UPDATE clipboard_history SET pin_order = ?1 WHERE id = ?2 AND pinned = 1",
+                params![order as i64, id],
+            )
+            .map_err(|e| OicopyError::Db(format!("更新置顶顺序失败: {}", e)))?;
+        if changed == 0 {
+            // 事务未提交，之前的修改会自动回滚
+            return Err(OicopyError::NotFound(format!("记录 {} 不存在或未置顶", id)));
         }
+        updated += changed;
     }
+
+    tx.commit().map_err(|e| OicopyError::Db(format!("提交置顶顺序失败: {}", e)))?;
+    Ok(updated)
 }
 
 // 获取应用设置
-pub fn get_settings(conn: &Connection) -> Result<AppSettings, String> {
+pub fn get_settings(conn: &Connection) -> Result<AppSettings, OicopyError> {
     let mut stmt = conn
-        .prepare("SELECT retention_days FROM app_settings WHERE id = 1")
-        .map_err(|e| format!("准备查询设置失败: {}", e))?;
-    
-    let retention_days = stmt
+        .prepare("SELECT retention_days, feedback_sound, feedback_haptic, auto_check_updates, update_channel,
+                    notify_monitor_restart, notify_cleanup,
+                    paste_confirm_enabled, paste_confirm_max_lines, paste_confirm_max_bytes,
+                    global_shortcut,
+                    history_locked,
+                    lock_passcode_hash,
+                    monitoring_paused,
+                    min_free_disk_mb,
+                    trash_retention_days,
+                    fetch_url_metadata,
+                    strip_tracking_params,
+                    content_priority,
+                    analysis_similarity_threshold,
+                    analysis_timeout_ms,
+                    analysis_max_content_size,
+                    analysis_app_detection,
+                    analysis_redundancy_scoring,
+                    analysis_log_details,
+                    panel_monitor,
+                    panel_edge,
+                    panel_height,
+                    panel_width,
+                    panel_mode,
+                    cleanup_max_records,
+                    cleanup_max_size_mb,
+                    auto_cleanup_enabled,
+                    cleanup_interval_minutes,
+                    cleanup_quiet_start_hour,
+                    cleanup_quiet_end_hour,
+                    cleanup_only_when_idle,
+                    backup_enabled,
+                    backup_interval_hours,
+                    backup_keep_count,
+                    backup_folder,
+                    lan_sync_enabled,
+                    local_api_enabled,
+                    local_api_port,
+                    local_api_token,
+                    deep_link_allow_destructive,
+                    sensitive_data_mode,
+                    search_sensitive_items,
+                    ephemeral_expire_minutes,
+                    lock_clear_action,
+                    lock_purge_minutes,
+                    deep_link_allow_actions
+             FROM app_settings WHERE id = 1")
+        .map_err(|e| OicopyError::Db(format!("准备查询设置失败: {}", e)))?;
+    
+    let settings = stmt
         .query_row([], |row| {
-            Ok(row.get::<_, i32>(0)?)
+            Ok(AppSettings {
+                retention_days: row.get(0)?,
+                feedback_sound: row.get(1)?,
+                feedback_haptic: row.get(2)?,
+                auto_check_updates: row.get(3)?,
+                update_channel: row.get(4)?,
+                notify_monitor_restart: row.get(5)?,
+                notify_cleanup: row.get(6)?,
+                paste_confirm_enabled: row.get(7)?,
+                paste_confirm_max_lines: row.get(8)?,
+                paste_confirm_max_bytes: row.get(9)?,
+                global_shortcut: row.get(10)?,
+                history_locked: row.get(11)?,
+                lock_passcode_hash: row.get(12)?,
+                monitoring_paused: row.get(13)?,
+                min_free_disk_mb: row.get(14)?,
+                trash_retention_days: row.get(15)?,
+                fetch_url_metadata: row.get(16)?,
+                strip_tracking_params: row.get(17)?,
+                content_priority: row.get::<_, Option<String>>(18)?.and_then(|raw| serde_json::from_str(&raw).ok()),
+                analysis_similarity_threshold: row.get(19)?,
+                analysis_timeout_ms: row.get(20)?,
+                analysis_max_content_size: row.get(21)?,
+                analysis_app_detection: row.get(22)?,
+                analysis_redundancy_scoring: row.get(23)?,
+                analysis_log_details: row.get(24)?,
+                panel_monitor: row.get(25)?,
+                panel_edge: row.get(26)?,
+                panel_height: row.get(27)?,
+                panel_width: row.get(28)?,
+                panel_mode: row.get(29)?,
+                cleanup_max_records: row.get(30)?,
+                cleanup_max_size_mb: row.get(31)?,
+                auto_cleanup_enabled: row.get(32)?,
+                cleanup_interval_minutes: row.get(33)?,
+                cleanup_quiet_start_hour: row.get(34)?,
+                cleanup_quiet_end_hour: row.get(35)?,
+                cleanup_only_when_idle: row.get(36)?,
+                backup_enabled: row.get(37)?,
+                backup_interval_hours: row.get(38)?,
+                backup_keep_count: row.get(39)?,
+                backup_folder: row.get(40)?,
+                lan_sync_enabled: row.get(41)?,
+                local_api_enabled: row.get(42)?,
+                local_api_port: row.get(43)?,
+                local_api_token: row.get(44)?,
+                deep_link_allow_destructive: row.get(45)?,
+                sensitive_data_mode: row.get(46)?,
+                search_sensitive_items: row.get(47)?,
+                ephemeral_expire_minutes: row.get(48)?,
+                lock_clear_action: row.get(49)?,
+                lock_purge_minutes: row.get(50)?,
+                deep_link_allow_actions: row.get(51)?,
+            })
         })
-        .unwrap_or(30); // 默认值
+        .optional()
+        .map_err(|e| OicopyError::Db(format!("查询设置失败: {}", e)))?
+        .unwrap_or_default(); // 设置行不存在时使用默认值；查询出错时返回错误，不能当作未锁定
     
-    Ok(AppSettings { retention_days })
+    Ok(settings)
 }
 
 // 保存应用设置
-pub fn save_settings(conn: &Connection, settings: &AppSettings) -> Result<(), String> {
+pub fn save_settings(conn: &Connection, settings: &AppSettings) -> Result<(), OicopyError> {
     conn.execute(
-        "UPDATE app_settings SET retention_days = ?1 WHERE id = 1",
-        params![settings.retention_days],
+        "UPDATE app_settings
+         SET retention_days = ?1, feedback_sound = ?2, feedback_haptic = ?3, auto_check_updates = ?4, update_channel = ?5,
+             notify_monitor_restart = ?6, notify_cleanup = ?7,
+             paste_confirm_enabled = ?8, paste_confirm_max_lines = ?9, paste_confirm_max_bytes = ?10,
+             global_shortcut = ?11,
+             history_locked = ?12,
+             lock_passcode_hash = ?13,
+             monitoring_paused = ?14,
+             min_free_disk_mb = ?15,
+             trash_retention_days = ?16,
+             fetch_url_metadata = ?17,
+             strip_tracking_params = ?18,
+             content_priority = ?19,
+             analysis_similarity_threshold = ?20,
+             analysis_timeout_ms = ?21,
+             analysis_max_content_size = ?22,
+             analysis_app_detection = ?23,
+             analysis_redundancy_scoring = ?24,
+             analysis_log_details = ?25,
+             panel_monitor = ?26,
+             panel_edge = ?27,
+             panel_height = ?28,
+             panel_width = ?29,
+             panel_mode = ?30,
+             cleanup_max_records = ?31,
+             cleanup_max_size_mb = ?32,
+             auto_cleanup_enabled = ?33,
+             cleanup_interval_minutes = ?34,
+             cleanup_quiet_start_hour = ?35,
+             cleanup_quiet_end_hour = ?36,
+             cleanup_only_when_idle = ?37,
+             backup_enabled = ?38,
+             backup_interval_hours = ?39,
+             backup_keep_count = ?40,
+             backup_folder = ?41,
+             lan_sync_enabled = ?42,
+             local_api_enabled = ?43,
+             local_api_port = ?44,
+             local_api_token = ?45,
+             deep_link_allow_destructive = ?46,
+             sensitive_data_mode = ?47,
+             search_sensitive_items = ?48,
+             ephemeral_expire_minutes = ?49,
+             lock_clear_action = ?50,
+             lock_purge_minutes = ?51,
+             deep_link_allow_actions = ?52
+         WHERE id = 1",
+        params![
+            settings.retention_days,
+            settings.feedback_sound,
+            settings.feedback_haptic,
+            settings.auto_check_updates,
+            settings.update_channel,
+            settings.notify_monitor_restart,
+            settings.notify_cleanup,
+            settings.paste_confirm_enabled,
+            settings.paste_confirm_max_lines,
+            settings.paste_confirm_max_bytes,
+            settings.global_shortcut,
+            settings.history_locked,
+            settings.lock_passcode_hash,
+            settings.monitoring_paused,
+            settings.min_free_disk_mb,
+            settings.trash_retention_days,
+            settings.fetch_url_metadata,
+            settings.strip_tracking_params,
+            settings.content_priority.as_ref().and_then(|order| serde_json::to_string(order).ok()),
+            settings.analysis_similarity_threshold,
+            settings.analysis_timeout_ms,
+            settings.analysis_max_content_size,
+            settings.analysis_app_detection,
+            settings.analysis_redundancy_scoring,
+            settings.analysis_log_details,
+            settings.panel_monitor,
+            settings.panel_edge,
+            settings.panel_height,
+            settings.panel_width,
+            settings.panel_mode,
+            settings.cleanup_max_records,
+            settings.cleanup_max_size_mb,
+            settings.auto_cleanup_enabled,
+            settings.cleanup_interval_minutes,
+            settings.cleanup_quiet_start_hour,
+            settings.cleanup_quiet_end_hour,
+            settings.cleanup_only_when_idle,
+            settings.backup_enabled,
+            settings.backup_interval_hours,
+            settings.backup_keep_count,
+            settings.backup_folder,
+            settings.lan_sync_enabled,
+            settings.local_api_enabled,
+            settings.local_api_port,
+            settings.local_api_token,
+            settings.deep_link_allow_destructive,
+            settings.sensitive_data_mode,
+            settings.search_sensitive_items,
+            settings.ephemeral_expire_minutes,
+            settings.lock_clear_action,
+            settings.lock_purge_minutes,
+            settings.deep_link_allow_actions
+        ],
     )
-    .map_err(|e| format!("保存设置失败: {}", e))?;
+    .map_err(|e| OicopyError::Db(format!("保存设置失败: {}", e)))?;
     
     Ok(())
 }
 
 // 清理过期的历史记录
-pub fn cleanup_old_history(conn: &Connection, retention_days: i32) -> Result<usize, String> {
+pub fn cleanup_old_history(conn: &Connection, retention_days: i32) -> Result<usize, OicopyError> {
     let cutoff_date = chrono::Local::now() - chrono::Duration::days(retention_days as i64);
     let cutoff_str = cutoff_date.format("%Y-%m-%d %H:%M:%S").to_string();
     
     let deleted_count = conn
         .execute(
-            "DELETE FROM clipboard_history WHERE timestamp < ?1",
+            "DELETE FROM clipboard_history WHERE timestamp < ?1 AND pinned = 0",
             params![cutoff_str],
         )
-        .map_err(|e| format!("清理历史记录失败: {}", e))?;
+        .map_err(|e| OicopyError::Db(format!("清理历史记录失败: {}", e)))?;
     
     Ok(deleted_count)
 }
@@ -230,22 +1792,261 @@ pub fn get_cached_app_icon(conn: &Connection, bundle_id: &str) -> Option<String>
     }).ok()
 }
 
-// 缓存应用图标
-pub fn cache_app_icon(conn: &Connection, bundle_id: &str, app_name: Option<&str>, icon_base64: &str) -> Result<(), String> {
+// 批量从缓存中获取应用图标
+pub fn get_cached_app_icons(conn: &Connection, bundle_ids: &[String]) -> Result<HashMap<String, String>, OicopyError> {
+    let mut icons = HashMap::new();
+    if bundle_ids.is_empty() {
+        return Ok(icons);
+    }
+
+    let placeholders = vec!["?"; bundle_ids.len()].join(", ");
+    let sql = format!(
+        "SELECT bundle_id, icon_base64 FROM app_icons WHERE bundle_id IN ({}) AND icon_base64 IS NOT NULL",
+        placeholders
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| OicopyError::Db(format!("准备查询应用图标失败: {}", e)))?;
+    let rows = stmt
+        .query_map(params_from_iter(bundle_ids.iter()), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| OicopyError::Db(format!("查询应用图标失败: {}", e)))?;
+
+    for row in rows {
+        let (bundle_id, icon) = row.map_err(|e| OicopyError::Db(format!("处理应用图标数据失败: {}", e)))?;
+        icons.insert(bundle_id, icon);
+    }
+    Ok(icons)
+}
+
+// 缓存应用图标，已有缓存时保留首次缓存时间和应用名称
+pub fn cache_app_icon(
+    conn: &Connection,
+    bundle_id: &str,
+    app_name: Option<&str>,
+    app_version: Option<&str>,
+    icon_base64: &str,
+) -> Result<(), OicopyError> {
     let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
     
     conn.execute(
-        "INSERT OR REPLACE INTO app_icons (bundle_id, app_name, icon_base64, created_at, updated_at) 
-         VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![bundle_id, app_name, icon_base64, now, now],
+        "INSERT INTO app_icons (bundle_id, app_name, icon_base64, app_version, created_at, updated_at) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+         ON CONFLICT(bundle_id) DO UPDATE SET
+            app_name = COALESCE(excluded.app_name, app_name),
+            icon_base64 = excluded.icon_base64,
+            app_version = excluded.app_version,
+            updated_at = excluded.updated_at",
+        params![bundle_id, app_name, icon_base64, app_version, now],
     )
-    .map_err(|e| format!("缓存应用图标失败: {}", e))?;
+    .map_err(|e| OicopyError::Db(format!("缓存应用图标失败: {}", e)))?;
     
     Ok(())
 }
 
+// 图标缓存超过该天数后重新提取
+pub const APP_ICON_REFRESH_DAYS: i64 = 30;
+
+// 缓存的应用图标信息（用于刷新检查）
+#[derive(Debug, Clone)]
+pub struct CachedAppIcon {
+    pub bundle_id: String,
+    pub app_name: Option<String>,
+    pub app_version: Option<String>,
+    pub expired: bool, // 超过 APP_ICON_REFRESH_DAYS 未更新
+}
+
+// 应用图标是否需要（重新）提取：未缓存或缓存已过期
+pub fn app_icon_needs_refresh(conn: &Connection, bundle_id: &str) -> bool {
+    conn.query_row(
+        "SELECT icon_base64 IS NULL OR updated_at < datetime('now', 'localtime', ?2)
+         FROM app_icons WHERE bundle_id = ?1",
+        params![bundle_id, format!("-{} days", APP_ICON_REFRESH_DAYS)],
+        |row| row.get::<_, bool>(0),
+    )
+    .unwrap_or(true)
+}
+
+// 列出所有缓存的应用图标及其是否过期
+pub fn list_cached_app_icons(conn: &Connection) -> Result<Vec<CachedAppIcon>, OicopyError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT bundle_id, app_name, app_version,
+                    icon_base64 IS NULL OR updated_at < datetime('now', 'localtime', ?1)
+             FROM app_icons ORDER BY updated_at",
+        )
+        .map_err(|e| OicopyError::Db(format!("准备查询应用图标失败: {}", e)))?;
+    let rows = stmt
+        .query_map(params![format!("-{} days", APP_ICON_REFRESH_DAYS)], |row| {
+            Ok(CachedAppIcon {
+                bundle_id: row.get(0)?,
+                app_name: row.get(1)?,
+                app_version: row.get(2)?,
+                expired: row.get(3)?,
+            })
+        })
+        .map_err(|e| OicopyError::Db(format!("查询应用图标失败: {}", e)))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("处理应用图标数据失败: {}", e)))
+}
+
+// 获取排除应用列表
+pub fn get_excluded_apps(conn: &Connection) -> Result<Vec<ExcludedApp>, OicopyError> {
+    let mut stmt = conn
+        .prepare("SELECT bundle_id, app_name, created_at FROM excluded_apps ORDER BY created_at")
+        .map_err(|e| OicopyError::Db(format!("准备查询排除应用失败: {}", e)))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ExcludedApp {
+                bundle_id: row.get(0)?,
+                app_name: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| OicopyError::Db(format!("查询排除应用失败: {}", e)))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("处理排除应用数据失败: {}", e)))
+}
+
+// 为记录添加标签（标签不存在时创建，名称不区分大小写，空白名称忽略）
+pub fn add_item_tags(conn: &Connection, item_id: i64, tags: &[String]) -> Result<(), OicopyError> {
+    for name in tags.iter().map(|tag| tag.trim()).filter(|tag| !tag.is_empty()) {
+        conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![name])
+            .map_err(|e| OicopyError::Db(format!("创建标签失败: {}", e)))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO item_tags (item_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+            params![item_id, name],
+        )
+        .map_err(|e| OicopyError::Db(format!("添加标签失败: {}", e)))?;
+    }
+    Ok(())
+}
+
+// 移除记录的标签，返回是否确实移除；不再被任何记录使用的标签一并删除
+pub fn remove_item_tag(conn: &Connection, item_id: i64, name: &str) -> Result<bool, OicopyError> {
+    let removed = conn
+        .execute(
+            "DELETE FROM item_tags WHERE item_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+            params![item_id, name.trim()],
+        )
+        .map_err(|e| OicopyError::Db(format!("移除标签失败: {}", e)))?;
+    conn.execute("DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM item_tags)", [])
+        .map_err(|e| OicopyError::Db(format!("清理未使用的标签失败: {}", e)))?;
+    Ok(removed > 0)
+}
+
+// 标签及其关联的历史记录数
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagInfo {
+    pub id: i64,
+    pub name: String,
+    pub item_count: i64,
+}
+
+// 获取全部标签（按名称排序，只统计仍在历史记录中的记录）
+pub fn list_all_tags(conn: &Connection) -> Result<Vec<TagInfo>, OicopyError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.id, t.name, COUNT(h.id) FROM tags t
+             LEFT JOIN item_tags it ON it.tag_id = t.id
+             LEFT JOIN clipboard_history h ON h.id = it.item_id
+             GROUP BY t.id
+             ORDER BY t.name",
+        )
+        .map_err(|e| OicopyError::Db(format!("准备标签查询失败: {}", e)))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(TagInfo {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                item_count: row.get(2)?,
+            })
+        })
+        .map_err(|e| OicopyError::Db(format!("查询标签失败: {}", e)))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("处理标签数据失败: {}", e)))
+}
+
+// 删除历史和回收站中都已不存在的记录的标签关联，以及不再被使用的标签
+pub fn prune_orphan_item_tags(conn: &Connection) -> Result<usize, OicopyError> {
+    let pruned = conn
+        .execute(
+            "DELETE FROM item_tags WHERE item_id NOT IN (
+                SELECT id FROM clipboard_history UNION SELECT id FROM clipboard_trash
+            )",
+            [],
+        )
+        .map_err(|e| OicopyError::Db(format!("清理标签关联失败: {}", e)))?;
+    conn.execute("DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM item_tags)", [])
+        .map_err(|e| OicopyError::Db(format!("清理未使用的标签失败: {}", e)))?;
+    Ok(pruned)
+}
+
+// 添加排除应用
+pub fn add_excluded_app(conn: &Connection, bundle_id: &str, app_name: Option<&str>) -> Result<(), OicopyError> {
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    conn.execute(
+        "INSERT OR REPLACE INTO excluded_apps (bundle_id, app_name, created_at) VALUES (?1, ?2, ?3)",
+        params![bundle_id, app_name, now],
+    )
+    .map_err(|e| OicopyError::Db(format!("添加排除应用失败: {}", e)))?;
+    Ok(())
+}
+
+// 移除排除应用
+pub fn remove_excluded_app(conn: &Connection, bundle_id: &str) -> Result<bool, OicopyError> {
+    let deleted = conn
+        .execute("DELETE FROM excluded_apps WHERE bundle_id = ?1", params![bundle_id])
+        .map_err(|e| OicopyError::Db(format!("移除排除应用失败: {}", e)))?;
+    Ok(deleted > 0)
+}
+
+// 应用是否在排除列表中
+pub fn is_app_excluded(conn: &Connection, bundle_id: &str) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM excluded_apps WHERE bundle_id = ?1",
+        params![bundle_id],
+        |_| Ok(()),
+    )
+    .is_ok()
+}
+
+// 记录一次监听心跳，并清理30天前的心跳记录
+pub fn record_monitor_heartbeat(conn: &Connection, monitor_running: bool) -> Result<(), OicopyError> {
+    let now = chrono::Local::now();
+    let cutoff = (now - chrono::Duration::days(30)).format("%Y-%m-%d %H:%M:%S").to_string();
+
+    conn.execute(
+        "INSERT INTO monitor_heartbeats (timestamp, monitor_running) VALUES (?1, ?2)",
+        params![now.format("%Y-%m-%d %H:%M:%S").to_string(), monitor_running],
+    )
+    .map_err(|e| OicopyError::Db(format!("记录监听心跳失败: {}", e)))?;
+    conn.execute("DELETE FROM monitor_heartbeats WHERE timestamp < ?1", params![cutoff])
+        .map_err(|e| OicopyError::Db(format!("清理监听心跳失败: {}", e)))?;
+    Ok(())
+}
+
+// 获取时间范围内的心跳记录（时间戳, 监听是否运行）
+pub fn get_monitor_heartbeats(conn: &Connection, start: &str, end: &str) -> Result<Vec<(String, bool)>, OicopyError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT timestamp, monitor_running FROM monitor_heartbeats
+             WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp",
+        )
+        .map_err(|e| OicopyError::Db(format!("准备查询监听心跳失败: {}", e)))?;
+    let rows = stmt
+        .query_map(params![start, end], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| OicopyError::Db(format!("查询监听心跳失败: {}", e)))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("处理监听心跳数据失败: {}", e)))
+}
+
 // 启用WAL模式和性能优化设置
-pub fn optimize_database_performance(conn: &Connection) -> Result<(), String> {
+pub fn optimize_database_performance(conn: &Connection) -> Result<(), OicopyError> {
     println!("启用数据库性能优化...");
     
     // 启用WAL模式（Write-Ahead Logging）- 返回结果，使用query_row
@@ -377,7 +2178,7 @@ pub fn optimize_database_performance(conn: &Connection) -> Result<(), String> {
 }
 
 // 创建性能优化索引
-pub fn create_performance_indexes(conn: &Connection) -> Result<(), String> {
+pub fn create_performance_indexes(conn: &Connection) -> Result<(), OicopyError> {
     println!("正在创建性能优化索引...");
     
     let indexes = vec![
@@ -386,6 +2187,9 @@ pub fn create_performance_indexes(conn: &Connection) -> Result<(), String> {
         ("idx_source_timestamp", "CREATE INDEX IF NOT EXISTS idx_source_timestamp ON clipboard_history(source_app, timestamp DESC)", "来源应用索引"),
         ("idx_content_hash", "CREATE UNIQUE INDEX IF NOT EXISTS idx_content_hash ON clipboard_history(content_hash)", "内容哈希索引"),
         ("idx_bundle_id", "CREATE INDEX IF NOT EXISTS idx_bundle_id ON clipboard_history(source_bundle_id)", "Bundle ID索引"),
+        ("idx_pinned_order", "CREATE INDEX IF NOT EXISTS idx_pinned_order ON clipboard_history(pinned DESC, pin_order)", "置顶顺序索引"),
+        ("idx_use_count", "CREATE INDEX IF NOT EXISTS idx_use_count ON clipboard_history(use_count DESC, last_used_at DESC)", "使用次数索引"),
+        ("idx_last_used", "CREATE INDEX IF NOT EXISTS idx_last_used ON clipboard_history(last_used_at DESC)", "最近使用时间索引"),
         ("idx_app_icons_updated", "CREATE INDEX IF NOT EXISTS idx_app_icons_updated ON app_icons(updated_at DESC)", "应用图标更新时间索引"),
     ];
     
@@ -409,23 +2213,217 @@ pub fn create_performance_indexes(conn: &Connection) -> Result<(), String> {
     println!("索引创建完成: 成功 {}, 失败 {}", success_count, error_count);
     
     if error_count > 0 {
-        Err(format!("部分索引创建失败: 成功 {}, 失败 {}", success_count, error_count))
+        Err(OicopyError::Db(format!("部分索引创建失败: 成功 {}, 失败 {}", success_count, error_count)))
     } else {
         Ok(())
     }
 }
 
+// 创建FTS5全文搜索索引，并通过触发器与 clipboard_history 保持同步
+// 图片内容为base64，不参与索引；备注与内容一起收录
+pub fn create_fts_index(conn: &Connection) -> Result<(), OicopyError> {
+    // 旧版本的索引表没有 note 列，FTS5 表不能添加列，只能删除后重建
+    let mut table_exists = fts_index_exists(conn);
+    let table_current = table_exists && fts_table_has_note(conn);
+    if table_current && fts_triggers_current(conn) {
+        return Ok(());
+    }
+
+    // 旧版本的视图和触发器收录完整内容、不含备注，重建为只收录前 FTS_INDEX_CHARS 个字符并收录备注
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS clipboard_fts_insert;
+        DROP TRIGGER IF EXISTS clipboard_fts_delete;
+        DROP TRIGGER IF EXISTS clipboard_fts_update;
+        DROP VIEW IF EXISTS clipboard_fts_source;",
+    )
+    .map_err(|e| OicopyError::Db(format!("移除旧的全文搜索触发器失败: {}", e)))?;
+    if table_exists && !table_current {
+        conn.execute("DROP TABLE clipboard_fts", [])
+            .map_err(|e| OicopyError::Db(format!("移除旧的全文搜索表失败: {}", e)))?;
+        table_exists = false;
+    }
+
+    conn.execute_batch(&format!(
+        "CREATE VIEW IF NOT EXISTS clipboard_fts_source AS
+            SELECT id, {} AS content, preview, note
+            FROM clipboard_history;",
+        fts_content_expr("clipboard_history")
+    ))
+    .map_err(|e| OicopyError::Db(format!("创建全文搜索视图失败: {}", e)))?;
+
+    if !table_exists {
+        // 优先使用 trigram 分词（支持中文子串匹配），不支持时退回 unicode61
+        let create_table = |tokenizer: &str| {
+            conn.execute_batch(&format!(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS clipboard_fts USING fts5(
+                    content, preview, note,
+                    content='clipboard_fts_source', content_rowid='id',
+                    tokenize='{}'
+                );",
+                tokenizer
+            ))
+        };
+        create_table("trigram")
+            .or_else(|_| create_table("unicode61"))
+            .map_err(|e| OicopyError::Db(format!("创建全文搜索表失败: {}", e)))?;
+    }
+
+    conn.execute_batch(&format!(
+        "CREATE TRIGGER IF NOT EXISTS clipboard_fts_insert AFTER INSERT ON clipboard_history BEGIN
+            INSERT INTO clipboard_fts(rowid, content, preview, note)
+            VALUES (new.id, {new}, new.preview, new.note);
+        END;
+        CREATE TRIGGER IF NOT EXISTS clipboard_fts_delete AFTER DELETE ON clipboard_history BEGIN
+            INSERT INTO clipboard_fts(clipboard_fts, rowid, content, preview, note)
+            VALUES ('delete', old.id, {old}, old.preview, old.note);
+        END;
+        CREATE TRIGGER IF NOT EXISTS clipboard_fts_update AFTER UPDATE OF content, preview, note ON clipboard_history BEGIN
+            INSERT INTO clipboard_fts(clipboard_fts, rowid, content, preview, note)
+            VALUES ('delete', old.id, {old}, old.preview, old.note);
+            INSERT INTO clipboard_fts(rowid, content, preview, note)
+            VALUES (new.id, {new}, new.preview, new.note);
+        END;",
+        new = fts_content_expr("new"),
+        old = fts_content_expr("old"),
+    ))
+    .map_err(|e| OicopyError::Db(format!("创建全文搜索触发器失败: {}", e)))?;
+
+    // 标记已有的超长记录，并按新的收录规则重建索引
+    conn.execute(
+        &format!(
+            "UPDATE clipboard_history SET search_truncated = 1
+             WHERE content_type != 'image' AND length(content) > {}",
+            FTS_INDEX_CHARS
+        ),
+        [],
+    )
+    .map_err(|e| OicopyError::Db(format!("标记超长记录失败: {}", e)))?;
+    conn.execute("INSERT INTO clipboard_fts(clipboard_fts) VALUES ('rebuild')", [])
+        .map_err(|e| OicopyError::Db(format!("重建全文搜索索引失败: {}", e)))?;
+
+    println!("✅ 全文搜索索引创建完成");
+    Ok(())
+}
+
+// 全文索引收录的内容：图片不收录，其他内容只取前 FTS_INDEX_CHARS 个字符
+fn fts_content_expr(row: &str) -> String {
+    format!(
+        "CASE WHEN {0}.content_type = 'image' THEN '' ELSE substr({0}.content, 1, {1}) END",
+        row, FTS_INDEX_CHARS
+    )
+}
+
+// 触发器是否已按当前的收录规则创建
+fn fts_triggers_current(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'trigger' AND name = 'clipboard_fts_insert'",
+        [],
+        |row| row.get::<_, String>(0),
+    )
+    .map(|sql| sql.contains(&fts_content_expr("new")))
+    .unwrap_or(false)
+}
+
+// 全文搜索表是否已包含备注列
+fn fts_table_has_note(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'clipboard_fts'",
+        [],
+        |row| row.get::<_, String>(0),
+    )
+    .map(|sql| sql.contains("note"))
+    .unwrap_or(false)
+}
+
+// 全文搜索表是否存在
+pub fn fts_index_exists(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'clipboard_fts'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .unwrap_or(false)
+}
+
+// 将用户输入转换为FTS5查询：每个词作为短语，词之间为 AND
+// trigram 分词无法匹配少于3个字符的词，此时返回 None 由调用方退回 LIKE 查询
+pub fn build_fts_query(input: &str) -> Option<String> {
+    let terms: Vec<&str> = input.split_whitespace().collect();
+    if terms.is_empty() || terms.iter().any(|term| term.chars().count() < 3) {
+        return None;
+    }
+
+    Some(
+        terms
+            .iter()
+            .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+// 转义 LIKE 模式中的通配符
+pub fn build_like_pattern(input: &str) -> String {
+    let escaped = input
+        .trim()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
 // 数据库维护任务
-pub fn perform_maintenance(conn: &Connection) -> Result<MaintenanceResult, String> {
+pub fn perform_maintenance(conn: &Connection) -> Result<MaintenanceResult, OicopyError> {
     let start_time = Instant::now();
     println!("开始数据库维护任务...");
     
     // 获取维护前的数据库大小
     let size_before = get_database_size_mb(conn)?;
     
+    // 先检查完整性，数据库损坏时 VACUUM 和 REINDEX 可能失败或扩大损坏，改为准备恢复
+    // 检查本身出错（如数据库忙）不代表数据库损坏，不能准备恢复，跳过本次维护
+    let integrity_ok = match check_integrity(conn, false) {
+        Ok(report) => report.ok,
+        Err(e) => {
+            println!("✗ 完整性检查失败，跳过本次维护: {}", e);
+            return Err(e);
+        }
+    };
+    if !integrity_ok {
+        let recovery_staged = match stage_recovery(conn) {
+            Ok(_) => true,
+            Err(e) => {
+                println!("✗ 准备数据库恢复失败: {}", e);
+                false
+            }
+        };
+        return Ok(MaintenanceResult {
+            vacuum_completed: false,
+            reindex_completed: false,
+            analyze_completed: false,
+            records_cleaned: 0,
+            icons_refreshed: 0,
+            size_before_mb: size_before,
+            size_after_mb: size_before,
+            duration_ms: start_time.elapsed().as_millis() as u64,
+            integrity_ok,
+            recovery_staged,
+        });
+    }
+    
     // 清理过期数据
     let settings = get_settings(conn)?;
     let records_cleaned = cleanup_old_history(conn, settings.retention_days)?;
+
+    // 重新提取过期或应用已更新的图标
+    let icons_refreshed = match refresh_stale_app_icons(conn) {
+        Ok(refreshed) => refreshed,
+        Err(e) => {
+            println!("✗ 刷新应用图标失败: {}", e);
+            0
+        }
+    };
     
     // 执行VACUUM（清理碎片，压缩数据库）- 不返回结果，使用execute
     let vacuum_completed = match conn.execute("VACUUM", []) {
@@ -493,14 +2491,17 @@ pub fn perform_maintenance(conn: &Connection) -> Result<MaintenanceResult, Strin
         reindex_completed,
         analyze_completed,
         records_cleaned,
+        icons_refreshed,
         size_before_mb: size_before,
         size_after_mb: size_after,
         duration_ms: duration.as_millis() as u64,
+        integrity_ok,
+        recovery_staged: false,
     })
 }
 
 // 获取数据库统计信息
-pub fn get_database_stats(conn: &Connection) -> Result<DatabaseStats, String> {
+pub fn get_database_stats(conn: &Connection) -> Result<DatabaseStats, OicopyError> {
     // 获取总记录数
     let total_records: i64 = conn
         .query_row("SELECT COUNT(*) FROM clipboard_history", [], |row| {
@@ -547,7 +2548,7 @@ pub fn get_database_stats(conn: &Connection) -> Result<DatabaseStats, String> {
 }
 
 // 获取数据库文件大小（MB）
-fn get_database_size_mb(conn: &Connection) -> Result<f64, String> {
+fn get_database_size_mb(conn: &Connection) -> Result<f64, OicopyError> {
     let page_count: i64 = conn
         .query_row("PRAGMA page_count", [], |row| Ok(row.get(0)?))
         .unwrap_or(0);
@@ -562,8 +2563,21 @@ fn get_database_size_mb(conn: &Connection) -> Result<f64, String> {
     Ok(size_mb)
 }
 
+// 数据实际占用的大小（MB），不计入空闲页
+// 删除记录后在 VACUUM 之前文件不会缩小，按大小清理需使用该值，否则会反复删除
+fn get_used_size_mb(conn: &Connection) -> Result<f64, OicopyError> {
+    let freelist_count: i64 = conn
+        .query_row("PRAGMA freelist_count", [], |row| Ok(row.get(0)?))
+        .unwrap_or(0);
+    let page_size: i64 = conn
+        .query_row("PRAGMA page_size", [], |row| Ok(row.get(0)?))
+        .unwrap_or(4096);
+    let free_mb = (freelist_count * page_size) as f64 / 1024.0 / 1024.0;
+    Ok((get_database_size_mb(conn)? - free_mb).max(0.0))
+}
+
 // 测试查询性能
-fn test_query_performance(conn: &Connection) -> Result<Vec<QueryPerformance>, String> {
+fn test_query_performance(conn: &Connection) -> Result<Vec<QueryPerformance>, OicopyError> {
     let mut results = Vec::new();
     
     // 首先检查表是否存在
@@ -619,12 +2633,13 @@ fn test_query_performance(conn: &Connection) -> Result<Vec<QueryPerformance>, St
 }
 
 // 智能清理功能：按数量限制清理
-pub fn cleanup_by_limit(conn: &Connection, max_records: i64) -> Result<usize, String> {
+// 置顶项不计入数量限制，也不会被删除
+pub fn cleanup_by_limit(conn: &Connection, max_records: i64) -> Result<usize, OicopyError> {
     let current_count: i64 = conn
-        .query_row("SELECT COUNT(*) FROM clipboard_history", [], |row| {
+        .query_row("SELECT COUNT(*) FROM clipboard_history WHERE pinned = 0", [], |row| {
             Ok(row.get(0)?)
         })
-        .map_err(|e| format!("获取记录数失败: {}", e))?;
+        .map_err(|e| OicopyError::Db(format!("获取记录数失败: {}", e)))?;
     
     if current_count <= max_records {
         return Ok(0);
@@ -633,31 +2648,31 @@ pub fn cleanup_by_limit(conn: &Connection, max_records: i64) -> Result<usize, St
     let _to_delete = current_count - max_records;
     let deleted_count = conn
         .execute(
-            "DELETE FROM clipboard_history WHERE id NOT IN (
-                SELECT id FROM clipboard_history ORDER BY timestamp DESC LIMIT ?1
+            "DELETE FROM clipboard_history WHERE pinned = 0 AND id NOT IN (
+                SELECT id FROM clipboard_history WHERE pinned = 0 ORDER BY timestamp DESC LIMIT ?1
             )",
             params![max_records],
         )
-        .map_err(|e| format!("按数量清理失败: {}", e))?;
+        .map_err(|e| OicopyError::Db(format!("按数量清理失败: {}", e)))?;
     
     println!("按数量清理完成，删除了 {} 条记录", deleted_count);
     Ok(deleted_count)
 }
 
 // 智能清理功能：按大小限制清理
-pub fn cleanup_by_size(conn: &Connection, max_size_mb: f64) -> Result<usize, String> {
-    let current_size = get_database_size_mb(conn)?;
+pub fn cleanup_by_size(conn: &Connection, max_size_mb: f64) -> Result<usize, OicopyError> {
+    let current_size = get_used_size_mb(conn)?;
     
     if current_size <= max_size_mb {
         return Ok(0);
     }
     
-    // 估算需要删除的记录数（简单估算）
+    // 估算需要删除的记录数（简单估算，置顶项不参与）
     let total_records: i64 = conn
-        .query_row("SELECT COUNT(*) FROM clipboard_history", [], |row| {
+        .query_row("SELECT COUNT(*) FROM clipboard_history WHERE pinned = 0", [], |row| {
             Ok(row.get(0)?)
         })
-        .map_err(|e| format!("获取记录数失败: {}", e))?;
+        .map_err(|e| OicopyError::Db(format!("获取记录数失败: {}", e)))?;
     
     let size_ratio = max_size_mb / current_size;
     let target_records = (total_records as f64 * size_ratio) as i64;
@@ -665,8 +2680,119 @@ pub fn cleanup_by_size(conn: &Connection, max_size_mb: f64) -> Result<usize, Str
     cleanup_by_limit(conn, target_records)
 }
 
+// 占用空间较大的记录
+#[derive(Debug, Serialize)]
+pub struct LargeItem {
+    pub id: i64,
+    pub content_type: ContentKind,
+    pub preview: Option<String>,
+    pub timestamp: String,
+    pub source_app: Option<String>,
+    pub source_bundle_id: Option<String>,
+    pub pinned: bool,
+    pub byte_size: i64, // 内容、预览和缩略图的长度，图片另加文件实际大小
+}
+
+// 计算所有记录的占用空间，按从大到小排序
+fn load_item_sizes(conn: &Connection) -> Result<Vec<LargeItem>, OicopyError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, content_type, preview, timestamp, source_app, source_bundle_id, pinned,
+                    length(content) + COALESCE(length(preview), 0) + COALESCE(length(thumbnail), 0),
+                    CASE WHEN content_type = 'image' AND content LIKE 'blobs/%' THEN content END
+             FROM clipboard_history",
+        )
+        .map_err(|e| OicopyError::Db(format!("准备记录大小查询失败: {}", e)))?;
+    let rows = stmt
+        .query_map([], |row| {
+            let blob: Option<String> = row.get(8)?;
+            let blob_bytes = blob
+                .filter(|reference| is_blob_reference(reference))
+                .and_then(|reference| blob_size(&reference))
+                .unwrap_or(0) as i64;
+            Ok(LargeItem {
+                id: row.get(0)?,
+                content_type: row.get(1)?,
+                preview: row.get(2)?,
+                timestamp: row.get(3)?,
+                source_app: row.get(4)?,
+                source_bundle_id: row.get(5)?,
+                pinned: row.get(6)?,
+                byte_size: row.get::<_, Option<i64>>(7)?.unwrap_or(0) + blob_bytes,
+            })
+        })
+        .map_err(|e| OicopyError::Db(format!("查询记录大小失败: {}", e)))?;
+
+    let mut items = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("处理记录大小失败: {}", e)))?;
+    items.sort_by(|a, b| b.byte_size.cmp(&a.byte_size).then(b.id.cmp(&a.id)));
+    Ok(items)
+}
+
+// 占用空间最大的若干条记录
+pub fn list_largest_items(conn: &Connection, limit: usize) -> Result<Vec<LargeItem>, OicopyError> {
+    let mut items = load_item_sizes(conn)?;
+    items.truncate(limit);
+    Ok(items)
+}
+
+// 删除占用空间超过 max_bytes 的记录（置顶项除外），并清理不再被引用的图片文件，返回删除的条数
+pub fn delete_items_over_size(conn: &Connection, max_bytes: i64) -> Result<usize, OicopyError> {
+    let ids: Vec<i64> = load_item_sizes(conn)?
+        .into_iter()
+        .take_while(|item| item.byte_size > max_bytes)
+        .filter(|item| !item.pinned)
+        .map(|item| item.id)
+        .collect();
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let ids_json = serde_json::to_string(&ids).map_err(|e| OicopyError::Db(format!("序列化记录ID失败: {}", e)))?;
+    let deleted = conn
+        .execute(
+            "DELETE FROM clipboard_history WHERE pinned = 0 AND id IN (SELECT value FROM json_each(?1))",
+            params![ids_json],
+        )
+        .map_err(|e| OicopyError::Db(format!("按大小删除记录失败: {}", e)))?;
+
+    match prune_orphan_blobs(conn) {
+        Ok(removed) => println!("按大小删除了 {} 条记录，清理 {} 个图片文件", deleted, removed),
+        Err(e) => println!("按大小删除了 {} 条记录，清理图片文件失败: {}", deleted, e),
+    }
+    Ok(deleted)
+}
+
+// 按条件永久删除记录（置顶项除外），未提供的条件不参与过滤，返回删除的条数
+// before 为数据库时间格式，删除该时间之前（不含）捕获的记录
+pub fn delete_items_by_filter(
+    conn: &Connection,
+    content_type: Option<ContentKind>,
+    source_bundle_id: Option<&str>,
+    before: Option<&str>,
+) -> Result<usize, OicopyError> {
+    let deleted = conn
+        .execute(
+            "DELETE FROM clipboard_history
+             WHERE pinned = 0
+               AND (?1 IS NULL OR content_type = ?1)
+               AND (?2 IS NULL OR source_bundle_id = ?2)
+               AND (?3 IS NULL OR timestamp < ?3)",
+            params![content_type, source_bundle_id, before],
+        )
+        .map_err(|e| OicopyError::Db(format!("按条件删除记录失败: {}", e)))?;
+
+    if deleted > 0 {
+        if let Err(e) = prune_orphan_blobs(conn) {
+            println!("清理图片文件失败: {}", e);
+        }
+    }
+    Ok(deleted)
+}
+
 // 智能清理：综合策略
-pub fn perform_smart_cleanup(conn: &Connection) -> Result<SmartCleanupResult, String> {
+pub fn perform_smart_cleanup(conn: &Connection) -> Result<SmartCleanupResult, OicopyError> {
     let start_time = Instant::now();
     println!("开始智能清理...");
     
@@ -686,30 +2812,32 @@ pub fn perform_smart_cleanup(conn: &Connection) -> Result<SmartCleanupResult, St
         }
     }
     
-    // 2. 如果记录数仍然过多，按数量限制清理
+    // 2. 如果记录数仍然过多，按数量限制清理（置顶项除外）
     let current_records: i64 = conn
-        .query_row("SELECT COUNT(*) FROM clipboard_history", [], |row| {
+        .query_row("SELECT COUNT(*) FROM clipboard_history WHERE pinned = 0", [], |row| {
             Ok(row.get(0)?)
         })
         .unwrap_or(0);
     
-    if current_records > 50000 {
-        match cleanup_by_limit(conn, 50000) {
+    let max_records = settings.cleanup_max_records;
+    if max_records > 0 && current_records > max_records {
+        match cleanup_by_limit(conn, max_records) {
             Ok(deleted) => {
                 total_deleted += deleted;
-                operations.push(format!("按数量清理: 删除 {} 条记录，保留最新50000条", deleted));
+                operations.push(format!("按数量清理: 删除 {} 条记录，保留最新{}条", deleted, max_records));
             }
             Err(e) => operations.push(format!("按数量清理失败: {}", e)),
         }
     }
     
     // 3. 如果数据库文件过大，按大小清理
-    let current_size = get_database_size_mb(conn).unwrap_or(0.0);
-    if current_size > 500.0 {
-        match cleanup_by_size(conn, 500.0) {
+    let current_size = get_used_size_mb(conn).unwrap_or(0.0);
+    let max_size_mb = settings.cleanup_max_size_mb;
+    if max_size_mb > 0.0 && current_size > max_size_mb {
+        match cleanup_by_size(conn, max_size_mb) {
             Ok(deleted) => {
                 total_deleted += deleted;
-                operations.push(format!("按大小清理: 删除 {} 条记录，限制大小500MB", deleted));
+                operations.push(format!("按大小清理: 删除 {} 条记录，限制大小{}MB", deleted, max_size_mb));
             }
             Err(e) => operations.push(format!("按大小清理失败: {}", e)),
         }
@@ -745,7 +2873,10 @@ pub fn perform_smart_cleanup(conn: &Connection) -> Result<SmartCleanupResult, St
 }
 
 // 分析数据库性能
-pub fn analyze_database_performance(conn: &Connection) -> Result<PerformanceAnalysis, String> {
+pub fn analyze_database_performance(
+    conn: &Connection,
+    retention_policies: &[RetentionPolicy],
+) -> Result<PerformanceAnalysis, OicopyError> {
     println!("开始数据库性能分析...");
     
     let stats = get_database_stats(conn)?;
@@ -807,6 +2938,12 @@ pub fn analyze_database_performance(conn: &Connection) -> Result<PerformanceAnal
     
     let slow_queries_cloned: Vec<QueryPerformance> = slow_queries.into_iter().cloned().collect();
     
+    // 用实际的时间和大小分布模拟候选保留策略，供设置界面对比
+    let retention_simulations = simulate_retention(conn, retention_policies).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        Vec::new()
+    });
+    
     Ok(PerformanceAnalysis {
         score,
         grade: performance_grade.to_string(),
@@ -814,6 +2951,7 @@ pub fn analyze_database_performance(conn: &Connection) -> Result<PerformanceAnal
         recommendations,
         stats,
         slow_queries: slow_queries_cloned,
+        retention_simulations,
     })
 }
 
@@ -838,4 +2976,126 @@ pub struct PerformanceAnalysis {
     pub recommendations: Vec<String>,
     pub stats: DatabaseStats,
     pub slow_queries: Vec<QueryPerformance>,
+    #[serde(default)]
+    pub retention_simulations: Vec<RetentionSimulation>, // 候选保留策略对记录数和大小的影响
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // 带完整表结构的内存数据库
+    pub(crate) fn test_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        create_schema(&conn).unwrap();
+        conn
+    }
+
+    // 带完整表结构的数据库文件（未加密）
+    pub(crate) fn create_test_database(path: &Path) -> Connection {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        let _ = std::fs::remove_file(path);
+        let conn = Connection::open(path).unwrap();
+        create_schema(&conn).unwrap();
+        conn
+    }
+
+    // 测试用的数据库密钥
+    pub(crate) const TEST_DATABASE_KEY: &str = "5c0f3e9a1b7d24c86e0a4f1d9b3c7e25a8d64f0b1e9c3a7d5f2b8e4c6a1d0f93";
+
+    // 使用测试密钥的连接池，数据库文件不存在时创建
+    pub(crate) fn test_pool(db_path: &Path) -> Arc<DatabasePool> {
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        let pool = Arc::new(DatabasePool::new(db_path.to_path_buf(), TEST_DATABASE_KEY));
+        pool.open_database_file(db_path).unwrap();
+        pool
+    }
+
+    fn database_file(conn: &Connection) -> String {
+        conn.query_row("PRAGMA database_list", [], |row| row.get(2)).unwrap()
+    }
+
+    #[test]
+    fn test_switch_path_drops_stale_connections() {
+        let dir = std::env::temp_dir().join(format!("oicopy-pool-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let pool = test_pool(&dir.join("old.db"));
+
+        let stale = pool.acquire(ConnectionRole::Writer).unwrap();
+        let reader = pool.acquire(ConnectionRole::Analytics).unwrap();
+        drop(reader);
+        assert_eq!(pool.readers.lock().unwrap().len(), 1);
+
+        // 切换后空闲连接被关闭，切换前借出的连接归还时也不放回池中
+        let switched = pool.switch_path(dir.join("new.db")).unwrap();
+        assert!(database_file(&switched).ends_with("new.db"));
+        assert!(pool.readers.lock().unwrap().is_empty());
+        drop(stale);
+        assert!(pool.writers.lock().unwrap().is_empty());
+
+        let fresh = pool.acquire(ConnectionRole::Writer).unwrap();
+        assert!(database_file(&fresh).ends_with("new.db"));
+        drop(fresh);
+        assert_eq!(pool.writers.lock().unwrap().len(), 1);
+
+        // 事务未结束的连接不放回池中
+        let mut busy = pool.acquire(ConnectionRole::Writer).unwrap();
+        busy.execute_batch("BEGIN").unwrap();
+        drop(busy);
+        assert!(pool.writers.lock().unwrap().is_empty());
+
+        drop(switched);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn history_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM clipboard_history", [], |row| row.get(0)).unwrap()
+    }
+
+    // 通过捕获流程读取样本，有待保存的记录时写入数据库
+    fn capture_fixture(conn: &Connection, bundle_id: &str) -> bool {
+        use crate::clipboard_management::capture_from_source;
+        use crate::fixture_replay::ClipboardFixture;
+
+        let fixture: ClipboardFixture = serde_json::from_value(serde_json::json!({
+            "text": format!("copied from {}", bundle_id),
+            "source_app": "Test",
+            "source_bundle_id": bundle_id,
+        }))
+        .unwrap();
+        let captured = capture_from_source(
+            conn,
+            &fixture,
+            "2026-01-01 10:00:00".to_string(),
+            fixture.source_app.clone(),
+            fixture.source_bundle_id.clone(),
+        )
+        .unwrap();
+        match captured {
+            Some(captured) => save_to_database(conn, &captured.history_item).is_ok(),
+            None => false,
+        }
+    }
+
+    #[test]
+    fn test_excluded_app_is_skipped() {
+        let conn = test_connection();
+        add_excluded_app(&conn, "com.agilebits.onepassword7", Some("1Password")).unwrap();
+
+        // 来自排除应用的复制不会产生记录，其他应用正常保存
+        assert!(!capture_fixture(&conn, "com.agilebits.onepassword7"));
+        assert_eq!(history_count(&conn), 0);
+        assert!(capture_fixture(&conn, "com.apple.Safari"));
+        assert_eq!(history_count(&conn), 1);
+
+        assert!(is_app_excluded(&conn, "com.agilebits.onepassword7"));
+        assert!(!is_app_excluded(&conn, "com.apple.Safari"));
+
+        assert!(remove_excluded_app(&conn, "com.agilebits.onepassword7").unwrap());
+        assert!(!is_app_excluded(&conn, "com.agilebits.onepassword7"));
+    }
 }
@@ -0,0 +1,209 @@
+use crate::sync_crypto::{random_bytes, to_hex};
+use rusqlite::Connection;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+const KEYCHAIN_SERVICE: &str = "com.oicopy-tauri-nspanel.app.database";
+const KEYCHAIN_ACCOUNT: &str = "clipboard_history";
+const PLAINTEXT_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+// 进程内缓存密钥，避免每次打开连接都访问钥匙串
+static DATABASE_KEY: OnceLock<String> = OnceLock::new();
+// 保证密钥生成和加密迁移只执行一次
+static PREPARE_LOCK: Mutex<()> = Mutex::new(());
+
+// 生成 32 字节随机密钥（十六进制）
+fn generate_key() -> Result<String, String> {
+    let bytes = random_bytes(32).map_err(|e| format!("生成数据库密钥失败: {}", e))?;
+    Ok(to_hex(&bytes))
+}
+
+fn is_valid_key(key: &str) -> bool {
+    key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// 从钥匙串读取密钥，不存在时生成并保存
+#[cfg(target_os = "macos")]
+fn load_or_create_key(_app_data_dir: &Path) -> Result<String, String> {
+    use security_framework::passwords::{get_generic_password, set_generic_password};
+
+    // errSecItemNotFound：只有密钥确实不存在时才生成新密钥
+    // 钥匙串锁定、拒绝访问等错误直接返回，否则会覆盖原密钥导致已加密的数据库无法打开
+    const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+    match get_generic_password(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT) {
+        Ok(existing) => {
+            let key = String::from_utf8(existing).map_err(|_| "钥匙串中的数据库密钥无效".to_string())?;
+            if is_valid_key(&key) {
+                return Ok(key);
+            }
+            return Err("钥匙串中的数据库密钥格式错误".to_string());
+        }
+        Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => {}
+        Err(e) => return Err(format!("读取钥匙串中的数据库密钥失败: {}", e)),
+    }
+
+    let key = generate_key()?;
+    set_generic_password(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT, key.as_bytes())
+        .map_err(|e| format!("保存数据库密钥到钥匙串失败: {}", e))?;
+    println!("已生成数据库密钥并保存到钥匙串");
+    Ok(key)
+}
+
+// 非 macOS 平台将密钥保存在仅当前用户可读的文件中
+#[cfg(not(target_os = "macos"))]
+fn load_or_create_key(app_data_dir: &Path) -> Result<String, String> {
+    use std::io::Write;
+
+    let key_path = app_data_dir.join("database.key");
+    match std::fs::read_to_string(&key_path) {
+        Ok(existing) => {
+            let key = existing.trim().to_string();
+            if is_valid_key(&key) {
+                return Ok(key);
+            }
+            return Err("数据库密钥文件格式错误".to_string());
+        }
+        // 只有文件不存在时才生成新密钥，其他读取错误不能覆盖原密钥
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("读取数据库密钥失败: {}", e)),
+    }
+
+    let key = generate_key()?;
+    // 创建时即设置为仅当前用户可读写；create_new 避免覆盖并发创建的密钥文件
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&key_path)
+        .and_then(|mut file| file.write_all(key.as_bytes()).and_then(|_| file.sync_all()))
+        .map_err(|e| format!("保存数据库密钥失败: {}", e))?;
+    Ok(key)
+}
+
+// 获取数据库密钥，首次调用时从钥匙串加载并加密已有的明文数据库
+pub fn prepare_database_key(app_data_dir: &Path, db_path: &Path) -> Result<&'static str, String> {
+    if let Some(key) = DATABASE_KEY.get() {
+        return Ok(key);
+    }
+
+    let _guard = PREPARE_LOCK
+        .lock()
+        .map_err(|_| "数据库密钥初始化锁已失效".to_string())?;
+    if let Some(key) = DATABASE_KEY.get() {
+        return Ok(key);
+    }
+
+    let key = load_or_create_key(app_data_dir)?;
+    migrate_plaintext_database(db_path, &key)?;
+    Ok(DATABASE_KEY.get_or_init(|| key))
+}
+
+// 已加载的数据库密钥，连接池初始化之前为空
+pub fn database_key() -> Option<&'static str> {
+    DATABASE_KEY.get().map(String::as_str)
+}
+
+// 为连接设置 SQLCipher 密钥，必须在任何其他操作之前调用
+pub fn apply_key(conn: &Connection, key: &str) -> Result<(), String> {
+    conn.execute_batch(&format!("PRAGMA key = \"x'{}'\";", key))
+        .map_err(|e| format!("设置数据库密钥失败: {}", e))?;
+
+    // 验证密钥是否正确
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .map_err(|e| format!("数据库密钥无效或数据库已损坏: {}", e))?;
+    Ok(())
+}
+
+// 数据库文件是否为未加密的 SQLite 格式
+fn is_plaintext_database(db_path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    std::fs::File::open(db_path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map(|_| &header == PLAINTEXT_HEADER)
+        .unwrap_or(false)
+}
+
+pub fn sidecar_path(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = db_path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// 将已有的明文数据库加密（首次启用加密时执行一次）
+fn migrate_plaintext_database(db_path: &Path, key: &str) -> Result<(), String> {
+    if !is_plaintext_database(db_path) {
+        return Ok(());
+    }
+    println!("检测到未加密的数据库，开始加密迁移...");
+
+    let encrypted_path = sidecar_path(db_path, ".encrypting");
+    let _ = std::fs::remove_file(&encrypted_path);
+
+    {
+        let conn = Connection::open(db_path).map_err(|e| format!("打开明文数据库失败: {}", e))?;
+        // 先把 WAL 中的数据写回主文件
+        let _ = conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);");
+        conn.execute(
+            &format!("ATTACH DATABASE ?1 AS encrypted KEY \"x'{}'\"", key),
+            [encrypted_path.to_string_lossy()],
+        )
+        .map_err(|e| format!("创建加密数据库失败: {}", e))?;
+        conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+            .map_err(|e| format!("导出到加密数据库失败: {}", e))?;
+        conn.execute_batch("DETACH DATABASE encrypted;")
+            .map_err(|e| format!("分离加密数据库失败: {}", e))?;
+    }
+
+    // 用加密后的文件替换原数据库，并移除旧的 WAL/SHM 文件
+    let _ = std::fs::remove_file(sidecar_path(db_path, "-wal"));
+    let _ = std::fs::remove_file(sidecar_path(db_path, "-shm"));
+    std::fs::rename(&encrypted_path, db_path).map_err(|e| format!("替换数据库文件失败: {}", e))?;
+
+    println!("数据库加密迁移完成");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_plaintext_database() {
+        let dir = std::env::temp_dir().join(format!("oicopy-encryption-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("clipboard_history.db");
+        let _ = std::fs::remove_file(&db_path);
+
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch("CREATE TABLE items (text TEXT); INSERT INTO items VALUES ('明文内容');")
+                .unwrap();
+        }
+        assert!(is_plaintext_database(&db_path));
+
+        let key = generate_key().unwrap();
+        assert!(is_valid_key(&key));
+        migrate_plaintext_database(&db_path, &key).unwrap();
+        assert!(!is_plaintext_database(&db_path));
+
+        // 加密后用密钥可以读取原有数据，不带密钥或用错误的密钥无法打开
+        let conn = Connection::open(&db_path).unwrap();
+        apply_key(&conn, &key).unwrap();
+        let text: String = conn.query_row("SELECT text FROM items", [], |row| row.get(0)).unwrap();
+        assert_eq!(text, "明文内容");
+        drop(conn);
+        let conn = Connection::open(&db_path).unwrap();
+        assert!(apply_key(&conn, &generate_key().unwrap()).is_err());
+        drop(conn);
+
+        // 已加密的数据库不会重复迁移
+        migrate_plaintext_database(&db_path, &key).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
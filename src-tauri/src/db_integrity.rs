@@ -0,0 +1,294 @@
+use crate::db_encryption::sidecar_path;
+use crate::error::OicopyError;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+// 恢复中的数据库写入 .recovering，完成后改名为 .recovered，下次启动时替换原数据库
+const RECOVERING_SUFFIX: &str = ".recovering";
+const RECOVERED_SUFFIX: &str = ".recovered";
+
+// 外键约束违规
+#[derive(Debug, Serialize)]
+pub struct ForeignKeyViolation {
+    pub table: String,
+    pub rowid: Option<i64>,
+    pub parent: String,
+}
+
+// 完整性检查结果
+#[derive(Debug, Serialize)]
+pub struct IntegrityReport {
+    pub ok: bool,
+    pub full_check: bool,     // true 为 integrity_check，false 为较快的 quick_check
+    pub problems: Vec<String>, // 检查返回的错误信息，正常时为空
+    pub foreign_key_violations: Vec<ForeignKeyViolation>,
+}
+
+// 恢复结果
+#[derive(Debug, Serialize)]
+pub struct RecoveryResult {
+    pub method: String,             // export：整库导出；table_copy：逐表（必要时逐行）复制
+    pub tables_copied: usize,
+    pub rows_copied: i64,
+    pub rows_lost: i64,             // 逐行复制时读取失败的行数
+    pub failed_tables: Vec<String>, // 无法读取的表
+    pub restart_required: bool,     // 恢复后的数据库在下次启动时替换原数据库
+}
+
+// 检查数据库完整性和外键约束
+pub fn check_integrity(conn: &Connection, full_check: bool) -> Result<IntegrityReport, OicopyError> {
+    let pragma = if full_check { "PRAGMA integrity_check" } else { "PRAGMA quick_check" };
+    let mut stmt = conn
+        .prepare(pragma)
+        .map_err(|e| OicopyError::Db(format!("准备完整性检查失败: {}", e)))?;
+    let problems: Vec<String> = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| OicopyError::Db(format!("完整性检查失败: {}", e)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("读取完整性检查结果失败: {}", e)))?
+        .into_iter()
+        .filter(|line| line != "ok")
+        .collect();
+
+    let mut stmt = conn
+        .prepare("PRAGMA foreign_key_check")
+        .map_err(|e| OicopyError::Db(format!("准备外键检查失败: {}", e)))?;
+    let foreign_key_violations = stmt
+        .query_map([], |row| {
+            Ok(ForeignKeyViolation {
+                table: row.get(0)?,
+                rowid: row.get(1)?,
+                parent: row.get(2)?,
+            })
+        })
+        .map_err(|e| OicopyError::Db(format!("外键检查失败: {}", e)))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OicopyError::Db(format!("读取外键检查结果失败: {}", e)))?;
+
+    Ok(IntegrityReport {
+        ok: problems.is_empty() && foreign_key_violations.is_empty(),
+        full_check,
+        problems,
+        foreign_key_violations,
+    })
+}
+
+fn main_database_path(conn: &Connection) -> Result<PathBuf, OicopyError> {
+    conn.query_row("SELECT file FROM pragma_database_list WHERE name = 'main'", [], |row| {
+        row.get::<_, String>(0)
+    })
+    .map(PathBuf::from)
+    .map_err(|e| OicopyError::Db(format!("获取数据库路径失败: {}", e)))
+}
+
+// 将当前数据库中能读取的数据导出到新的数据库文件，下次启动时替换原数据库
+// 写入线程一直持有连接，运行中无法安全替换数据库文件
+// 附加数据库时不指定 KEY，SQLCipher 会使用与主数据库相同的密钥
+pub fn stage_recovery(conn: &Connection) -> Result<RecoveryResult, OicopyError> {
+    let db_path = main_database_path(conn)?;
+    let recovering_path = sidecar_path(&db_path, RECOVERING_SUFFIX);
+
+    // 优先整库导出（包含索引、触发器和全文索引），遇到损坏页失败时改为逐表复制
+    let result = match export_database(conn, &recovering_path) {
+        Ok(result) => result,
+        Err(e) => {
+            println!("整库导出失败，改为逐表复制: {}", e);
+            copy_tables(conn, &recovering_path)?
+        }
+    };
+
+    std::fs::rename(&recovering_path, sidecar_path(&db_path, RECOVERED_SUFFIX))
+        .map_err(|e| OicopyError::Io(format!("保存恢复的数据库失败: {}", e)))?;
+    println!("数据库恢复已准备好，将在下次启动时生效: {:?}", result);
+    Ok(result)
+}
+
+fn attach_recovering(conn: &Connection, path: &Path) -> Result<(), OicopyError> {
+    let _ = std::fs::remove_file(path);
+    conn.execute("ATTACH DATABASE ?1 AS recovered", [path.to_string_lossy()])
+        .map_err(|e| OicopyError::Db(format!("创建恢复数据库失败: {}", e)))?;
+    Ok(())
+}
+
+fn detach_recovering(conn: &Connection) {
+    let _ = conn.execute_batch("DETACH DATABASE recovered;");
+}
+
+fn export_database(conn: &Connection, path: &Path) -> Result<RecoveryResult, OicopyError> {
+    attach_recovering(conn, path)?;
+    let exported = conn.query_row("SELECT sqlcipher_export('recovered')", [], |_| Ok(()));
+    let rows_copied = exported
+        .as_ref()
+        .ok()
+        .and_then(|_| {
+            conn.query_row("SELECT COUNT(*) FROM recovered.clipboard_history", [], |row| row.get(0))
+                .ok()
+        })
+        .unwrap_or(0);
+    detach_recovering(conn);
+    exported.map_err(|e| OicopyError::Db(format!("导出数据库失败: {}", e)))?;
+
+    Ok(RecoveryResult {
+        method: "export".to_string(),
+        tables_copied: 0,
+        rows_copied,
+        rows_lost: 0,
+        failed_tables: Vec::new(),
+        restart_required: true,
+    })
+}
+
+// 逐表复制数据，整表读取失败时按 rowid 逐行复制，跳过无法读取的行
+// 只复制普通表，全文索引、视图、触发器和索引在下次启动建表时重新创建
+fn copy_tables(conn: &Connection, path: &Path) -> Result<RecoveryResult, OicopyError> {
+    attach_recovering(conn, path)?;
+    let mut result = RecoveryResult {
+        method: "table_copy".to_string(),
+        tables_copied: 0,
+        rows_copied: 0,
+        rows_lost: 0,
+        failed_tables: Vec::new(),
+        restart_required: true,
+    };
+
+    let tables: Vec<(String, String)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT name, sql FROM main.sqlite_master
+                 WHERE type = 'table' AND rootpage > 0 AND sql IS NOT NULL
+                   AND name NOT LIKE 'sqlite_%' AND name NOT LIKE 'clipboard_fts_%'",
+            )
+            .map_err(|e| OicopyError::Db(format!("读取表结构失败: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| OicopyError::Db(format!("读取表结构失败: {}", e)))?;
+        rows.filter_map(Result::ok).collect()
+    };
+
+    for (name, sql) in tables {
+        // 与 sqlcipher_export 相同，在建表语句中加上附加数据库的前缀
+        let Some(definition) = sql.strip_prefix("CREATE TABLE ") else {
+            result.failed_tables.push(name);
+            continue;
+        };
+        if let Err(e) = conn.execute_batch(&format!("CREATE TABLE recovered.{};", definition)) {
+            println!("创建表 {} 失败: {}", name, e);
+            result.failed_tables.push(name);
+            continue;
+        }
+
+        match conn.execute(&format!("INSERT INTO recovered.\"{0}\" SELECT * FROM main.\"{0}\"", name), []) {
+            Ok(copied) => result.rows_copied += copied as i64,
+            Err(e) => {
+                println!("整表复制 {} 失败，改为逐行复制: {}", name, e);
+                match copy_rows(conn, &name) {
+                    Some((copied, lost)) => {
+                        result.rows_copied += copied;
+                        result.rows_lost += lost;
+                    }
+                    None => {
+                        result.failed_tables.push(name);
+                        continue;
+                    }
+                }
+            }
+        }
+        result.tables_copied += 1;
+    }
+
+    // 保留自增序号，避免恢复后复用已删除记录的 ID
+    let _ = conn.execute_batch(
+        "DELETE FROM recovered.sqlite_sequence;
+         INSERT INTO recovered.sqlite_sequence SELECT * FROM main.sqlite_sequence;",
+    );
+    detach_recovering(conn);
+    Ok(result)
+}
+
+// 按 rowid 逐行复制，返回 (复制的行数, 丢失的行数)；无法读取 rowid 时返回 None
+fn copy_rows(conn: &Connection, table: &str) -> Option<(i64, i64)> {
+    let mut rowids = Vec::new();
+    {
+        let mut stmt = conn.prepare(&format!("SELECT rowid FROM main.\"{}\"", table)).ok()?;
+        let mut rows = stmt.query([]).ok()?;
+        // 遇到损坏页时停止，保留已读取到的 rowid
+        while let Ok(Some(row)) = rows.next() {
+            if let Ok(rowid) = row.get::<_, i64>(0) {
+                rowids.push(rowid);
+            }
+        }
+    }
+
+    let insert = format!(
+        "INSERT OR IGNORE INTO recovered.\"{0}\" SELECT * FROM main.\"{0}\" WHERE rowid = ?1",
+        table
+    );
+    let (mut copied, mut lost) = (0, 0);
+    for rowid in rowids {
+        match conn.execute(&insert, params![rowid]) {
+            Ok(_) => copied += 1,
+            Err(_) => lost += 1,
+        }
+    }
+    Some((copied, lost))
+}
+
+// 启动时（打开数据库之前）用已准备好的恢复数据库替换原数据库，原数据库保留为 .corrupt-时间 备份
+pub fn apply_staged_recovery(db_path: &Path) -> Result<bool, String> {
+    let recovered_path = sidecar_path(db_path, RECOVERED_SUFFIX);
+    if !recovered_path.exists() {
+        return Ok(false);
+    }
+
+    let backup_suffix = format!(".corrupt-{}", chrono::Local::now().format("%Y%m%d%H%M%S"));
+    let backup_path = sidecar_path(db_path, &backup_suffix);
+    if db_path.exists() {
+        std::fs::rename(db_path, &backup_path).map_err(|e| format!("备份损坏的数据库失败: {}", e))?;
+    }
+    for suffix in ["-wal", "-shm"] {
+        let _ = std::fs::rename(sidecar_path(db_path, suffix), sidecar_path(&backup_path, suffix));
+    }
+    std::fs::rename(&recovered_path, db_path).map_err(|e| format!("替换数据库文件失败: {}", e))?;
+
+    println!("已使用恢复的数据库替换原数据库，原数据库备份为 {:?}", backup_path);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_staged_recovery() {
+        let dir = std::env::temp_dir().join(format!("oicopy-recovery-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("clipboard_history.db");
+
+        // 没有准备好的恢复数据库时不做任何操作
+        std::fs::write(&db_path, "损坏").unwrap();
+        assert!(!apply_staged_recovery(&db_path).unwrap());
+        assert_eq!(std::fs::read_to_string(&db_path).unwrap(), "损坏");
+
+        std::fs::write(sidecar_path(&db_path, "-wal"), "日志").unwrap();
+        std::fs::write(sidecar_path(&db_path, RECOVERED_SUFFIX), "恢复").unwrap();
+        assert!(apply_staged_recovery(&db_path).unwrap());
+        assert_eq!(std::fs::read_to_string(&db_path).unwrap(), "恢复");
+        assert!(!sidecar_path(&db_path, RECOVERED_SUFFIX).exists());
+        assert!(!sidecar_path(&db_path, "-wal").exists());
+
+        // 原数据库和日志文件一起保留为 .corrupt-时间 备份
+        let mut backups: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.starts_with("clipboard_history.db.corrupt-"))
+            .collect();
+        backups.sort();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(std::fs::read_to_string(dir.join(&backups[0])).unwrap(), "损坏");
+        assert!(backups[1].ends_with("-wal"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::change_log::emit_change_event;
+use crate::db::{database_paths, init_database, DatabasePool};
+use crate::db_encryption::{apply_key, sidecar_path};
+use crate::db_writer::execute_write;
+use crate::error::OicopyError;
+use crate::lock_mode::ensure_unlocked;
+use crate::profiles::{active_profile, move_inactive_profiles, profile_database_path};
+use rusqlite::backup::Backup;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+// 自定义数据库目录保存在数据目录下的文件中：打开数据库之前就需要读取，不能保存在数据库的设置表里
+const LOCATION_FILE: &str = "database_location";
+
+// 数据库位置
+#[derive(Debug, Serialize)]
+pub struct DatabaseLocation {
+    pub path: String,        // 数据库文件的完整路径
+    pub is_default: bool,    // 是否位于应用数据目录
+    pub available: bool,     // 自定义目录是否可用，不可用时（如外接磁盘未连接）暂时使用数据目录
+}
+
+fn read_location_file(app_data_dir: &Path) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(app_data_dir.join(LOCATION_FILE)).ok()?;
+    let dir = content.trim();
+    (!dir.is_empty()).then(|| PathBuf::from(dir))
+}
+
+// 用户设置的数据库目录，目录不可用时返回空并使用数据目录，目录恢复后重启即可使用原数据库
+pub fn configured_database_dir(app_data_dir: &Path) -> Option<PathBuf> {
+    let dir = read_location_file(app_data_dir)?;
+    if dir.is_dir() {
+        Some(dir)
+    } else {
+        eprintln!("数据库目录不可用，暂时使用数据目录: {:?}", dir);
+        None
+    }
+}
+
+// 先写入临时文件再改名，避免写入中断后留下不完整的路径
+fn write_location_file(app_data_dir: &Path, dir: Option<&Path>) -> Result<(), String> {
+    let path = app_data_dir.join(LOCATION_FILE);
+    let Some(dir) = dir else {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("删除数据库位置设置失败: {}", e)),
+            _ => Ok(()),
+        };
+    };
+    let tmp_path = sidecar_path(&path, ".tmp");
+    std::fs::write(&tmp_path, dir.to_string_lossy().as_bytes())
+        .and_then(|_| std::fs::rename(&tmp_path, &path))
+        .map_err(|e| format!("保存数据库位置设置失败: {}", e))
+}
+
+// 使用备份 API 将当前数据库复制到新位置，先写入临时文件，完成后改名
+fn copy_database(conn: &Connection, key: &str, new_path: &Path) -> Result<(), String> {
+    let partial_path = sidecar_path(new_path, ".partial");
+    let _ = std::fs::remove_file(&partial_path);
+
+    let copied = (|| {
+        let mut dest = Connection::open(&partial_path).map_err(|e| format!("创建数据库文件失败: {}", e))?;
+        apply_key(&dest, key)?;
+        let backup = Backup::new(conn, &mut dest).map_err(|e| format!("复制数据库失败: {}", e))?;
+        backup
+            .run_to_completion(256, Duration::from_millis(10), None)
+            .map_err(|e| format!("复制数据库失败: {}", e))
+    })();
+    if let Err(e) = copied {
+        let _ = std::fs::remove_file(&partial_path);
+        return Err(e);
+    }
+    std::fs::rename(&partial_path, new_path).map_err(|e| format!("保存数据库文件失败: {}", e))
+}
+
+// 在写入线程上移动数据库：写入任务在移动期间排队等待，之后使用新位置的连接执行
+fn relocate_database(app: &AppHandle, conn: &mut Connection, new_dir: Option<PathBuf>) -> Result<PathBuf, String> {
+    let pool = app
+        .try_state::<Arc<DatabasePool>>()
+        .ok_or_else(|| "数据库连接池未初始化".to_string())?;
+    let (app_data_dir, _) = database_paths(app)?;
+    move_database(&pool, conn, &app_data_dir, new_dir)
+}
+
+// 复制数据库到新目录并切换连接池，任何一步失败都恢复到原来的位置
+fn move_database(
+    pool: &DatabasePool,
+    conn: &mut Connection,
+    app_data_dir: &Path,
+    new_dir: Option<PathBuf>,
+) -> Result<PathBuf, String> {
+    let active = active_profile(app_data_dir);
+    let old_dir = configured_database_dir(app_data_dir).unwrap_or_else(|| app_data_dir.to_path_buf());
+    let new_base = new_dir.clone().unwrap_or_else(|| app_data_dir.to_path_buf());
+    let old_path = pool.current_path();
+    let new_path = profile_database_path(&new_base, &active);
+    if new_path == old_path {
+        return Ok(new_path);
+    }
+    if new_path.exists() {
+        return Err(format!("目标目录中已存在数据库文件: {:?}", new_path));
+    }
+
+    // 未使用的配置直接移动文件，当前配置通过备份 API 复制
+    let rollback = |error: String| {
+        let _ = std::fs::remove_file(&new_path);
+        let _ = move_inactive_profiles(&new_base, &old_dir, &active);
+        error
+    };
+    move_inactive_profiles(&old_dir, &new_base, &active).map_err(rollback)?;
+    if let Some(parent) = new_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| rollback(format!("创建数据库目录失败: {}", e)))?;
+    }
+    copy_database(conn, pool.key(), &new_path).map_err(rollback)?;
+    write_location_file(app_data_dir, new_dir.as_deref()).map_err(rollback)?;
+    let new_conn = match pool.switch_path(new_path.clone()) {
+        Ok(new_conn) => new_conn,
+        Err(e) => {
+            // 恢复原来的位置设置，原数据库仍在使用
+            let _ = write_location_file(app_data_dir, (old_dir != app_data_dir).then_some(old_dir.as_path()));
+            return Err(rollback(e.into()));
+        }
+    };
+
+    // 关闭写入线程的旧连接后删除原数据库文件；其他命令借出的旧连接关闭后不会放回池中
+    drop(std::mem::replace(conn, new_conn));
+    for path in [old_path.clone(), sidecar_path(&old_path, "-wal"), sidecar_path(&old_path, "-shm")] {
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("删除原数据库文件失败: {:?}: {}", path, e);
+            }
+        }
+    }
+
+    println!("数据库已移动到 {:?}", new_path);
+    Ok(new_path)
+}
+
+// 获取数据库位置命令
+#[tauri::command]
+pub async fn get_database_path(app: AppHandle) -> Result<DatabaseLocation, OicopyError> {
+    let (app_data_dir, _) = database_paths(&app)?;
+    let path = match app.try_state::<Arc<DatabasePool>>() {
+        Some(pool) => pool.current_path(),
+        None => database_paths(&app)?.1,
+    };
+    let configured = read_location_file(&app_data_dir);
+    Ok(DatabaseLocation {
+        available: configured.as_ref().map_or(true, |dir| path.starts_with(dir)),
+        is_default: configured.is_none(),
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+// 将数据库移动到指定目录（如外接或加密的磁盘），为空时移回数据目录
+// 只移动数据库文件（包括所有配置），图片文件和密钥仍保存在数据目录中
+#[tauri::command]
+pub async fn set_database_path(app: AppHandle, path: Option<String>) -> Result<DatabaseLocation, OicopyError> {
+    let new_dir = match path.as_deref().map(str::trim).filter(|path| !path.is_empty()) {
+        Some(path) => {
+            let dir = PathBuf::from(path);
+            if !dir.is_absolute() {
+                return Err(OicopyError::Validation(format!("数据库目录必须是绝对路径: {}", path)));
+            }
+            std::fs::create_dir_all(&dir).map_err(|e| OicopyError::Io(format!("创建数据库目录失败: {}", e)))?;
+            Some(dir)
+        }
+        None => None,
+    };
+    ensure_unlocked(&init_database(&app)?)?;
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        execute_write(&app_handle, move |app, conn| relocate_database(app, conn, new_dir))
+    })
+    .await
+    .map_err(|e| format!("移动数据库任务失败: {}", e))??;
+
+    emit_change_event(&app, "clipboard-updated");
+    get_database_path(app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tests::{create_test_database, test_pool};
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("oicopy-location-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("data")).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_move_database_and_back() {
+        let root = temp_root("move");
+        let app_data_dir = root.join("data");
+        let external = root.join("external");
+        let old_path = profile_database_path(&app_data_dir, &active_profile(&app_data_dir));
+        let pool = test_pool(&old_path);
+        let mut conn = pool.open_database_file(&old_path).unwrap();
+        conn.execute_batch("CREATE TABLE marker (value TEXT); INSERT INTO marker VALUES ('已移动');")
+            .unwrap();
+
+        let new_path = move_database(&pool, &mut conn, &app_data_dir, Some(external.clone())).unwrap();
+        assert!(new_path.starts_with(&external));
+        assert_eq!(pool.current_path(), new_path);
+        assert_eq!(read_location_file(&app_data_dir), Some(external.clone()));
+        assert!(!old_path.exists());
+        // 写入线程的连接已替换为新位置的连接
+        let value: String = conn.query_row("SELECT value FROM marker", [], |row| row.get(0)).unwrap();
+        assert_eq!(value, "已移动");
+
+        let back = move_database(&pool, &mut conn, &app_data_dir, None).unwrap();
+        assert_eq!(back, old_path);
+        assert_eq!(read_location_file(&app_data_dir), None);
+        assert!(!new_path.exists());
+
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_move_database_refuses_existing_target() {
+        let root = temp_root("existing");
+        let app_data_dir = root.join("data");
+        let external = root.join("external");
+        let active = active_profile(&app_data_dir);
+        let old_path = profile_database_path(&app_data_dir, &active);
+        let target = profile_database_path(&external, &active);
+        drop(create_test_database(&target));
+        let pool = test_pool(&old_path);
+        let mut conn = pool.open_database_file(&old_path).unwrap();
+
+        // 不覆盖目标目录中已有的数据库，继续使用原来的位置
+        assert!(move_database(&pool, &mut conn, &app_data_dir, Some(external)).is_err());
+        assert_eq!(pool.current_path(), old_path);
+        assert_eq!(read_location_file(&app_data_dir), None);
+        assert!(old_path.exists());
+        assert!(target.exists());
+
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
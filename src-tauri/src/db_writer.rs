@@ -0,0 +1,70 @@
+use crate::db::init_database;
+use rusqlite::Connection;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, sync_channel, Sender};
+use tauri::{AppHandle, Manager};
+
+// 写入任务：在写入线程上使用专用的写入连接执行
+type WriteJob = Box<dyn FnOnce(&AppHandle, &mut Connection) + Send>;
+
+// 数据库写入线程：捕获保存、图标缓存和清理等写操作统一发送到该线程串行执行，
+// 剪贴板监听回调只负责读取剪贴板，不会因等待 SQLite 写锁而阻塞
+pub struct DatabaseWriter {
+    sender: Sender<WriteJob>,
+}
+
+// 启动写入线程并放入 Tauri 托管状态（在连接池初始化后调用一次）
+pub fn start_database_writer(app_handle: &AppHandle) -> Result<(), String> {
+    if app_handle.try_state::<DatabaseWriter>().is_some() {
+        return Ok(());
+    }
+
+    let (sender, receiver) = channel::<WriteJob>();
+    let mut conn = init_database(app_handle)?;
+    let app = app_handle.clone();
+    std::thread::Builder::new()
+        .name("db-writer".to_string())
+        .spawn(move || {
+            for job in receiver {
+                // 单个任务出错不影响后续任务
+                if catch_unwind(AssertUnwindSafe(|| job(&app, &mut *conn))).is_err() {
+                    eprintln!("数据库写入任务异常退出");
+                }
+            }
+            println!("数据库写入线程已退出");
+        })
+        .map_err(|e| format!("启动数据库写入线程失败: {}", e))?;
+
+    app_handle.manage(DatabaseWriter { sender });
+    println!("数据库写入线程已启动");
+    Ok(())
+}
+
+// 提交写入任务，不等待执行结果
+pub fn submit_write<F>(app_handle: &AppHandle, job: F) -> Result<(), String>
+where
+    F: FnOnce(&AppHandle, &mut Connection) + Send + 'static,
+{
+    let writer = app_handle
+        .try_state::<DatabaseWriter>()
+        .ok_or_else(|| "数据库写入线程未启动".to_string())?;
+    writer
+        .sender
+        .send(Box::new(job))
+        .map_err(|_| "数据库写入线程已退出".to_string())
+}
+
+// 提交写入任务并阻塞等待结果，不能在写入线程内调用
+pub fn execute_write<T, F>(app_handle: &AppHandle, job: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&AppHandle, &mut Connection) -> Result<T, String> + Send + 'static,
+{
+    let (reply, result) = sync_channel(1);
+    submit_write(app_handle, move |app, conn| {
+        let _ = reply.send(job(app, conn));
+    })?;
+    result
+        .recv()
+        .map_err(|_| "数据库写入任务未完成".to_string())?
+}
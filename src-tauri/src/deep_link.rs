@@ -0,0 +1,225 @@
+use crate::automation::{paste_nth_item, toggle_monitoring};
+use crate::clipboard_management::{copy_item_by_id, delete_clipboard_item, paste_item_from_shortcut, set_monitoring_paused};
+use crate::db::{get_settings, init_database, AppSettings};
+use crate::local_api::percent_decode;
+use crate::lock_mode::ensure_unlocked;
+use crate::panel_window::open_panel_window;
+use crate::private_mode::{is_private_mode_active, set_private_mode};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter};
+
+// oicopy:// 链接，供快捷指令、Raycast 和脚本触发操作
+// macOS 通过 Info.plist 注册该协议，其他平台由系统以启动参数的形式传入
+pub const SCHEME: &str = "oicopy";
+const DEFAULT_PRIVATE_MINUTES: u32 = 60;
+const MAX_QUERY_CHARS: usize = 500;
+
+#[derive(Debug, PartialEq)]
+pub enum DeepLink {
+    Paste { id: i64 },                // oicopy://paste/<id>
+    PasteIndex { index: u32 },        // oicopy://paste-index/<n>，第 n 条记录（从新到旧）
+    Copy { id: i64 },                 // oicopy://copy/<id>
+    Search { query: String },         // oicopy://search?q=...
+    TogglePrivate { minutes: u32 },   // oicopy://toggle-private?minutes=30
+    Pause,                            // oicopy://pause
+    Resume,                           // oicopy://resume
+    ToggleMonitoring,                 // oicopy://toggle-monitoring
+    Delete { id: i64 },               // oicopy://delete/<id>
+}
+
+impl DeepLink {
+    // 删除等无法撤销的操作，默认禁止通过链接触发
+    fn is_destructive(&self) -> bool {
+        matches!(self, DeepLink::Delete { .. })
+    }
+}
+
+// 任何网页或应用都能打开 oicopy:// 链接，除打开面板搜索外的操作都需要在设置中允许
+fn check_allowed(link: &DeepLink, settings: &AppSettings) -> Result<(), String> {
+    if matches!(link, DeepLink::Search { .. }) {
+        return Ok(());
+    }
+    if link.is_destructive() {
+        if !settings.deep_link_allow_destructive {
+            return Err("设置中未允许通过链接执行删除操作".to_string());
+        }
+    } else if !settings.deep_link_allow_actions {
+        return Err("设置中未允许通过链接执行该操作".to_string());
+    }
+    Ok(())
+}
+
+fn parse_id(segment: Option<&str>) -> Result<i64, String> {
+    let segment = segment.ok_or_else(|| "链接缺少记录 ID".to_string())?;
+    segment
+        .parse::<i64>()
+        .ok()
+        .filter(|id| *id > 0)
+        .ok_or_else(|| format!("无效的记录 ID: {}", segment))
+}
+
+// 解析并校验链接，未知的操作和参数格式错误时返回错误
+pub fn parse_deep_link(url: &str) -> Result<DeepLink, String> {
+    let rest = url
+        .strip_prefix(SCHEME)
+        .and_then(|rest| rest.strip_prefix("://"))
+        .ok_or_else(|| format!("不支持的链接: {}", url))?;
+    let (path, query_string) = rest.split_once('?').unwrap_or((rest, ""));
+    let query: HashMap<String, String> = query_string
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect();
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+    let action = segments.next().unwrap_or_default().to_ascii_lowercase();
+    let argument = segments.next();
+    if segments.next().is_some() {
+        return Err(format!("链接路径无效: {}", url));
+    }
+
+    match action.as_str() {
+        "paste" => Ok(DeepLink::Paste { id: parse_id(argument)? }),
+        "paste-index" => {
+            let index = parse_id(argument)?;
+            Ok(DeepLink::PasteIndex { index: u32::try_from(index).map_err(|_| format!("无效的记录序号: {}", index))? })
+        }
+        "copy" => Ok(DeepLink::Copy { id: parse_id(argument)? }),
+        "delete" => Ok(DeepLink::Delete { id: parse_id(argument)? }),
+        "search" => {
+            let query = query.get("q").map(|q| q.trim().to_string()).unwrap_or_default();
+            if query.chars().count() > MAX_QUERY_CHARS {
+                return Err(format!("搜索内容不能超过 {} 个字符", MAX_QUERY_CHARS));
+            }
+            Ok(DeepLink::Search { query })
+        }
+        "toggle-private" => {
+            let minutes = match query.get("minutes") {
+                Some(value) => value.parse::<u32>().map_err(|_| format!("无效的分钟数: {}", value))?,
+                None => DEFAULT_PRIVATE_MINUTES,
+            };
+            Ok(DeepLink::TogglePrivate { minutes })
+        }
+        "pause" => Ok(DeepLink::Pause),
+        "resume" => Ok(DeepLink::Resume),
+        "toggle-monitoring" => Ok(DeepLink::ToggleMonitoring),
+        _ => Err(format!("未知的链接操作: {}", action)),
+    }
+}
+
+async fn run_deep_link(app: &AppHandle, link: DeepLink) -> Result<(), String> {
+    {
+        let conn = init_database(app)?;
+        check_allowed(&link, &get_settings(&conn)?)?;
+        // 暂停监听和隐私模式只减少采集，锁定时也允许
+        if !matches!(link, DeepLink::Pause | DeepLink::ToggleMonitoring | DeepLink::TogglePrivate { .. }) {
+            ensure_unlocked(&conn)?;
+        }
+    }
+
+    match link {
+        DeepLink::Paste { id } => paste_item_from_shortcut(app, id).await.map_err(|e| e.to_string()),
+        DeepLink::PasteIndex { index } => paste_nth_item(app, index).await.map(|_| ()),
+        DeepLink::Copy { id } => copy_item_by_id(app, id).map_err(String::from),
+        DeepLink::Delete { id } => delete_clipboard_item(app.clone(), id).await.map_err(String::from),
+        DeepLink::Search { query } => {
+            open_panel_window(app.clone(), "copy-panel".to_string())?;
+            app.emit("deep-link-search", query)
+                .map_err(|e| format!("通知面板搜索失败: {}", e))
+        }
+        DeepLink::TogglePrivate { minutes } => {
+            // 已在隐私模式中时结束隐私模式
+            let minutes = if is_private_mode_active() { 0 } else { minutes };
+            set_private_mode(app.clone(), minutes).await.map(|_| ())
+        }
+        DeepLink::Pause => set_monitoring_paused(app, true),
+        DeepLink::Resume => set_monitoring_paused(app, false),
+        DeepLink::ToggleMonitoring => toggle_monitoring(app).map(|_| ()),
+    }
+}
+
+// 处理一个链接，失败时只记录日志并通知前端
+pub fn handle_deep_link(app: &AppHandle, url: &str) {
+    let link = match parse_deep_link(url) {
+        Ok(link) => link,
+        Err(e) => {
+            eprintln!("{}", e);
+            let _ = app.emit("deep-link-failed", e);
+            return;
+        }
+    };
+    println!("处理链接: {:?}", link);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = run_deep_link(&app, link).await {
+            eprintln!("执行链接失败: {}", e);
+            let _ = app.emit("deep-link-failed", e);
+        }
+    });
+}
+
+// Windows 和 Linux 通过启动参数传入链接
+pub fn handle_launch_args(app: &AppHandle) {
+    let prefix = format!("{}://", SCHEME);
+    for arg in std::env::args().skip(1).filter(|arg| arg.starts_with(&prefix)) {
+        handle_deep_link(app, &arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deep_link() {
+        assert_eq!(parse_deep_link("oicopy://paste/42"), Ok(DeepLink::Paste { id: 42 }));
+        assert_eq!(parse_deep_link("oicopy://copy/7/"), Ok(DeepLink::Copy { id: 7 }));
+        assert_eq!(parse_deep_link("oicopy://paste-index/2"), Ok(DeepLink::PasteIndex { index: 2 }));
+        assert_eq!(parse_deep_link("oicopy://toggle-monitoring"), Ok(DeepLink::ToggleMonitoring));
+        assert_eq!(
+            parse_deep_link("oicopy://search?q=hello%20world"),
+            Ok(DeepLink::Search { query: "hello world".to_string() })
+        );
+        assert_eq!(parse_deep_link("oicopy://toggle-private"), Ok(DeepLink::TogglePrivate { minutes: 60 }));
+        assert_eq!(parse_deep_link("oicopy://toggle-private?minutes=15"), Ok(DeepLink::TogglePrivate { minutes: 15 }));
+        assert!(parse_deep_link("oicopy://delete/3").unwrap().is_destructive());
+    }
+
+    #[test]
+    fn test_actions_refused_by_default() {
+        let links = [
+            "oicopy://paste/1",
+            "oicopy://paste-index/1",
+            "oicopy://copy/1",
+            "oicopy://pause",
+            "oicopy://resume",
+            "oicopy://toggle-monitoring",
+            "oicopy://toggle-private",
+            "oicopy://delete/1",
+        ];
+        let defaults = AppSettings::default();
+        for url in links {
+            assert!(check_allowed(&parse_deep_link(url).unwrap(), &defaults).is_err(), "{}", url);
+        }
+        assert!(check_allowed(&parse_deep_link("oicopy://search?q=a").unwrap(), &defaults).is_ok());
+
+        // 允许一般操作后删除仍需单独允许
+        let allowed = AppSettings { deep_link_allow_actions: true, ..AppSettings::default() };
+        for url in &links[..links.len() - 1] {
+            assert!(check_allowed(&parse_deep_link(url).unwrap(), &allowed).is_ok(), "{}", url);
+        }
+        assert!(check_allowed(&parse_deep_link("oicopy://delete/1").unwrap(), &allowed).is_err());
+    }
+
+    #[test]
+    fn test_parse_deep_link_rejects_invalid() {
+        assert!(parse_deep_link("https://paste/1").is_err());
+        assert!(parse_deep_link("oicopy://paste/abc").is_err());
+        assert!(parse_deep_link("oicopy://paste/-1").is_err());
+        assert!(parse_deep_link("oicopy://paste/1/2").is_err());
+        assert!(parse_deep_link("oicopy://format-disk").is_err());
+        assert!(parse_deep_link("oicopy://toggle-private?minutes=x").is_err());
+    }
+}
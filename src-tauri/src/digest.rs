@@ -0,0 +1,191 @@
+use crate::clipboard_content::ContentKind;
+use crate::db::{open_connection, ConnectionRole};
+use chrono::{Duration, NaiveDate};
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+// 识别域名和语言时每条记录最多扫描的字符数
+const SCAN_CHARS: i64 = 8192;
+
+// 摘要中的计数项
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestCount {
+    pub name: String,
+    pub count: usize,
+}
+
+// 某一天的复制摘要
+#[derive(Debug, Serialize)]
+pub struct DailyDigest {
+    pub date: String,
+    pub total_items: usize,
+    pub total_bytes: i64,
+    pub by_type: Vec<DigestCount>,
+    pub domains: Vec<DigestCount>,
+    pub apps: Vec<DigestCount>,
+    pub languages: Vec<DigestCount>,
+}
+
+// 从文本中提取 http(s) 链接的域名（去掉 www. 前缀并转为小写）
+fn extract_domains(text: &str) -> Vec<String> {
+    let mut domains = Vec::new();
+    for (index, _) in text.match_indices("http") {
+        let rest = &text[index..];
+        let after_scheme = match rest
+            .strip_prefix("https://")
+            .or_else(|| rest.strip_prefix("http://"))
+        {
+            Some(after) => after,
+            None => continue,
+        };
+        let host: String = after_scheme
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '.' || *c == '-')
+            .collect::<String>()
+            .to_lowercase();
+        let host = host.trim_start_matches("www.").trim_end_matches('.');
+        if host.contains('.') {
+            domains.push(host.to_string());
+        }
+    }
+    domains
+}
+
+// 根据关键字粗略识别代码语言，无法判断时返回 None
+fn detect_code_language(text: &str) -> Option<&'static str> {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with("#!/bin/bash") || trimmed.starts_with("#!/bin/sh") {
+        return Some("shell");
+    }
+    if trimmed.starts_with("<?php") {
+        return Some("php");
+    }
+
+    // (语言, 特征片段)，命中两个及以上特征才认为是该语言
+    const SIGNATURES: &[(&str, &[&str])] = &[
+        ("rust", &["fn ", "let mut ", "impl ", "pub fn", "::", "-> ", "use crate"]),
+        ("python", &["def ", "import ", "self.", "elif ", "print(", "__init__"]),
+        ("javascript", &["const ", "function ", "=> ", "console.log", "require(", "export default"]),
+        ("typescript", &["interface ", ": string", ": number", "export type ", "as const"]),
+        ("go", &["func ", "package ", ":= ", "fmt.", "go func"]),
+        ("java", &["public class ", "private ", "System.out", "void ", "import java."]),
+        ("sql", &["SELECT ", " FROM ", " WHERE ", "INSERT INTO", "CREATE TABLE"]),
+        ("shell", &["echo ", "export ", "sudo ", "| grep", "&& "]),
+        ("css", &["{", "}", "px;", "color:", "margin:", "display:"]),
+    ];
+
+    SIGNATURES
+        .iter()
+        .map(|(language, patterns)| {
+            let hits = patterns.iter().filter(|pattern| text.contains(*pattern)).count();
+            (*language, hits)
+        })
+        .filter(|(_, hits)| *hits >= 2)
+        .max_by_key(|(_, hits)| *hits)
+        .map(|(language, _)| language)
+}
+
+// 按数量倒序（同数量按名称）输出计数
+fn sorted_counts(counts: HashMap<String, usize>) -> Vec<DigestCount> {
+    let mut counts: Vec<DigestCount> = counts
+        .into_iter()
+        .map(|(name, count)| DigestCount { name, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts
+}
+
+// Tauri命令：获取某一天的复制摘要（date 格式 %Y-%m-%d）
+#[tauri::command]
+pub async fn get_daily_digest(app: AppHandle, date: String) -> Result<DailyDigest, String> {
+    let day = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("无法解析日期 {}: {}", date, e))?;
+    let day_start = day.and_hms_opt(0, 0, 0).ok_or("无效的日期")?;
+    let start_str = day_start.format(TIMESTAMP_FORMAT).to_string();
+    let end_str = (day_start + Duration::days(1)).format(TIMESTAMP_FORMAT).to_string();
+
+    // 聚合查询使用只读连接，不阻塞剪贴板捕获写入
+    let conn = open_connection(&app, ConnectionRole::Analytics)?;
+
+    // 单次查询取出当天所有记录的类型、来源、大小和可扫描的文本
+    let mut stmt = conn
+        .prepare(
+            "SELECT content_type, source_app, length(content),
+                    CASE WHEN content_type = 'text' THEN substr(content, 1, ?3) ELSE preview END
+             FROM clipboard_history
+             WHERE timestamp >= ?1 AND timestamp < ?2",
+        )
+        .map_err(|e| format!("准备摘要查询失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![start_str, end_str, SCAN_CHARS], |row| {
+            Ok((
+                row.get::<_, ContentKind>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+                row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+            ))
+        })
+        .map_err(|e| format!("摘要查询失败: {}", e))?;
+
+    let mut total_items = 0;
+    let mut total_bytes = 0;
+    let mut by_type: HashMap<String, usize> = HashMap::new();
+    let mut domains: HashMap<String, usize> = HashMap::new();
+    let mut apps: HashMap<String, usize> = HashMap::new();
+    let mut languages: HashMap<String, usize> = HashMap::new();
+
+    for row in rows {
+        let (kind, source_app, bytes, text) = row.map_err(|e| format!("处理摘要数据失败: {}", e))?;
+        total_items += 1;
+        total_bytes += bytes;
+        *by_type.entry(kind.as_str().to_string()).or_default() += 1;
+        if let Some(app_name) = source_app.filter(|name| !name.is_empty()) {
+            *apps.entry(app_name).or_default() += 1;
+        }
+
+        // 同一条记录中的重复域名只计一次
+        let mut item_domains = extract_domains(&text);
+        item_domains.sort();
+        item_domains.dedup();
+        for domain in item_domains {
+            *domains.entry(domain).or_default() += 1;
+        }
+
+        if kind == ContentKind::Text {
+            if let Some(language) = detect_code_language(&text) {
+                *languages.entry(language.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    Ok(DailyDigest {
+        date: day.format("%Y-%m-%d").to_string(),
+        total_items,
+        total_bytes,
+        by_type: sorted_counts(by_type),
+        domains: sorted_counts(domains),
+        apps: sorted_counts(apps),
+        languages: sorted_counts(languages),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_domains() {
+        let text = "see https://www.GitHub.com/kissesu/oicopy and http://docs.rs/rusqlite, not ftp://x.y or https://localhost";
+        assert_eq!(extract_domains(text), vec!["github.com", "docs.rs"]);
+    }
+
+    #[test]
+    fn test_detect_code_language() {
+        assert_eq!(detect_code_language("pub fn main() -> Result<(), String> {\n    let mut x = 1;\n}"), Some("rust"));
+        assert_eq!(detect_code_language("def run(self):\n    print(self.name)"), Some("python"));
+        assert_eq!(detect_code_language("just a normal sentence"), None);
+    }
+}
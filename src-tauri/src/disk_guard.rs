@@ -0,0 +1,189 @@
+use crate::clipboard_content::{ClipboardContent, ContentKind};
+use crate::db::{database_paths, get_settings, init_database};
+use crate::notifications::{notify, NotificationEvent};
+use chrono::Local;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::AppHandle;
+
+// 定期检查磁盘剩余空间的间隔
+const DISK_CHECK_INTERVAL_SECS: u64 = 60;
+// 空间不足时仍然保存的小段文本上限
+const SMALL_CAPTURE_BYTES: usize = 16 * 1024;
+// 超过该大小的内容在保存前重新检查剩余空间
+const LARGE_INSERT_BYTES: usize = 1024 * 1024;
+// 诊断记录保留的条数
+const MAX_DIAGNOSTIC_ROWS: i64 = 500;
+
+// 最近一次检查的结果：剩余空间是否低于阈值
+static LOW_DISK_SPACE: AtomicBool = AtomicBool::new(false);
+
+// 获取路径所在磁盘对当前用户可用的空间（字节）
+#[cfg(unix)]
+fn available_disk_bytes(path: &Path) -> Result<u64, String> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|e| format!("无效的路径: {}", e))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(format!("获取磁盘空间失败: {}", std::io::Error::last_os_error()));
+    }
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn available_disk_bytes(_path: &Path) -> Result<u64, String> {
+    Err("当前平台不支持检查磁盘空间".to_string())
+}
+
+// 创建捕获诊断表，记录因磁盘空间不足等原因未保存的内容
+pub fn create_diagnostics_table(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS capture_diagnostics (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            content_type TEXT,
+            content_bytes INTEGER
+        )",
+        [],
+    )
+    .map_err(|e| format!("创建捕获诊断表失败: {}", e))?;
+    Ok(())
+}
+
+fn record_capture_suppression(
+    conn: &Connection,
+    reason: &str,
+    kind: ContentKind,
+    bytes: usize,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO capture_diagnostics (timestamp, reason, content_type, content_bytes) VALUES (?1, ?2, ?3, ?4)",
+        params![Local::now().format("%Y-%m-%d %H:%M:%S").to_string(), reason, kind, bytes as i64],
+    )
+    .map_err(|e| format!("记录捕获诊断失败: {}", e))?;
+    conn.execute(
+        "DELETE FROM capture_diagnostics WHERE id <= (SELECT MAX(id) FROM capture_diagnostics) - ?1",
+        params![MAX_DIAGNOSTIC_ROWS],
+    )
+    .map_err(|e| format!("清理捕获诊断失败: {}", e))?;
+    Ok(())
+}
+
+// 检查剩余空间并更新状态，从充足变为不足时通知用户；返回当前是否空间不足
+pub fn check_disk_space(app_handle: &AppHandle) -> Result<bool, String> {
+    let conn = init_database(app_handle)?;
+    let min_free_mb = get_settings(&conn)?.min_free_disk_mb.max(0) as u64;
+    let (app_data_dir, _) = database_paths(app_handle)?;
+    let available_mb = available_disk_bytes(&app_data_dir)? / (1024 * 1024);
+
+    let low = available_mb < min_free_mb;
+    let was_low = LOW_DISK_SPACE.swap(low, Ordering::SeqCst);
+    if low && !was_low {
+        notify(app_handle, NotificationEvent::LowDiskSpace { available_mb });
+    } else if !low && was_low {
+        println!("磁盘空间已恢复（剩余 {} MB），恢复正常保存", available_mb);
+    }
+    Ok(low)
+}
+
+// 启动后台任务，定期检查磁盘剩余空间
+pub fn start_disk_space_monitor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(DISK_CHECK_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = check_disk_space(&app_handle) {
+                eprintln!("{}", e);
+            }
+        }
+    });
+}
+
+// 磁盘空间不足时，小段文本仍然保存，图片、文件和大段内容跳过
+fn allowed_when_low(kind: ContentKind, bytes: usize) -> bool {
+    !matches!(kind, ContentKind::Image | ContentKind::Files) && bytes <= SMALL_CAPTURE_BYTES
+}
+
+// 保存前检查是否因磁盘空间不足而跳过，跳过时记录诊断并返回原因
+pub fn suppress_capture_for_disk_space(
+    app_handle: &AppHandle,
+    conn: &Connection,
+    content: &ClipboardContent,
+) -> Option<String> {
+    let kind = content.kind();
+    let bytes = content.byte_size();
+    if allowed_when_low(kind, bytes) {
+        return None;
+    }
+
+    // 大内容写入前重新检查，避免在两次定期检查之间写满磁盘
+    let low = if bytes > LARGE_INSERT_BYTES {
+        check_disk_space(app_handle).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            LOW_DISK_SPACE.load(Ordering::SeqCst)
+        })
+    } else {
+        LOW_DISK_SPACE.load(Ordering::SeqCst)
+    };
+    if !low {
+        return None;
+    }
+
+    let reason = "磁盘空间不足".to_string();
+    if let Err(e) = record_capture_suppression(conn, &reason, kind, bytes) {
+        eprintln!("{}", e);
+    }
+    Some(reason)
+}
+
+// 捕获诊断记录
+#[derive(Debug, Serialize)]
+pub struct CaptureDiagnostic {
+    pub timestamp: String,
+    pub reason: String,
+    pub content_type: Option<String>,
+    pub content_bytes: Option<i64>,
+}
+
+// Tauri命令：获取最近未保存内容的诊断记录
+#[tauri::command]
+pub async fn get_capture_diagnostics(app: AppHandle, limit: Option<u32>) -> Result<Vec<CaptureDiagnostic>, String> {
+    let conn = init_database(&app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT timestamp, reason, content_type, content_bytes FROM capture_diagnostics
+             ORDER BY id DESC LIMIT ?1",
+        )
+        .map_err(|e| format!("准备查询捕获诊断失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![limit.unwrap_or(100)], |row| {
+            Ok(CaptureDiagnostic {
+                timestamp: row.get(0)?,
+                reason: row.get(1)?,
+                content_type: row.get(2)?,
+                content_bytes: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("查询捕获诊断失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("处理捕获诊断失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_when_low() {
+        assert!(allowed_when_low(ContentKind::Text, 200));
+        assert!(!allowed_when_low(ContentKind::Text, SMALL_CAPTURE_BYTES + 1));
+        assert!(!allowed_when_low(ContentKind::Image, 10));
+        assert!(!allowed_when_low(ContentKind::Files, 10));
+    }
+}
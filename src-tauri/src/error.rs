@@ -0,0 +1,102 @@
+use crate::performance_optimization::PerformanceError;
+use crate::permissions::FeatureError;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+// 命令返回给前端的错误，序列化为 { kind, message }，前端按 kind 区分错误类型
+#[derive(Debug, thiserror::Error)]
+pub enum OicopyError {
+    #[error("{0}")]
+    Db(String), // 数据库读写失败
+    #[error("{0}")]
+    Clipboard(String), // 读取或写入系统剪贴板失败
+    #[error("{0}")]
+    Permission(String), // 缺少系统权限、权限窗口未完成或处于锁定模式
+    #[error("{0}")]
+    Io(String), // 文件、目录或窗口等系统资源操作失败
+    #[error("{0}")]
+    Analysis(String), // 内容分析失败或超时
+    #[error("{0}")]
+    NotFound(String), // 记录或窗口不存在
+    #[error("{0}")]
+    Validation(String), // 参数无效
+    #[error("内容重复")]
+    Duplicate, // 历史记录中已存在相同内容
+}
+
+impl OicopyError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OicopyError::Db(_) => "db",
+            OicopyError::Clipboard(_) => "clipboard",
+            OicopyError::Permission(_) => "permission",
+            OicopyError::Io(_) => "io",
+            OicopyError::Analysis(_) => "analysis",
+            OicopyError::NotFound(_) => "not_found",
+            OicopyError::Validation(_) => "validation",
+            OicopyError::Duplicate => "duplicate",
+        }
+    }
+}
+
+impl Serialize for OicopyError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("OicopyError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+// 旧代码中的字符串错误大多来自数据库操作，统一归为 Db
+impl From<String> for OicopyError {
+    fn from(message: String) -> Self {
+        OicopyError::Db(message)
+    }
+}
+
+impl From<rusqlite::Error> for OicopyError {
+    fn from(e: rusqlite::Error) -> Self {
+        OicopyError::Db(format!("数据库操作失败: {}", e))
+    }
+}
+
+impl From<std::io::Error> for OicopyError {
+    fn from(e: std::io::Error) -> Self {
+        OicopyError::Io(e.to_string())
+    }
+}
+
+impl From<PerformanceError> for OicopyError {
+    fn from(e: PerformanceError) -> Self {
+        OicopyError::Analysis(e.to_string())
+    }
+}
+
+// 仍返回字符串错误的函数可以直接用 ? 传播
+impl From<OicopyError> for String {
+    fn from(e: OicopyError) -> Self {
+        e.to_string()
+    }
+}
+
+impl From<OicopyError> for FeatureError {
+    fn from(e: OicopyError) -> Self {
+        FeatureError::Failed { message: e.to_string() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_kind_and_message() {
+        let json = serde_json::to_value(OicopyError::Permission("权限不足".to_string())).unwrap();
+        assert_eq!(json["kind"], "permission");
+        assert_eq!(json["message"], "权限不足");
+
+        let json = serde_json::to_value(OicopyError::Duplicate).unwrap();
+        assert_eq!(json["kind"], "duplicate");
+        assert_eq!(json["message"], "内容重复");
+    }
+}
@@ -0,0 +1,199 @@
+use crate::change_log::emit_change_event;
+use crate::clipboard_content::ClipboardContent;
+use crate::content_formats::prune_orphan_content_formats;
+use crate::db::init_database;
+use crate::db_writer::execute_write;
+use crate::error::OicopyError;
+use crate::lock_mode::ensure_unlocked;
+use chrono::{Duration, Local};
+use regex::Regex;
+use rusqlite::{params, Connection};
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+// 自动过期的记录：到期后由定时清理直接永久删除（不进入回收站），适合验证码、临时密码等
+// 过期时间可由规则在捕获时设置（敏感记录和验证码），也可对单条记录手动设置；置顶的记录不会被删除
+
+// 手动设置的过期时长上限：30 天
+const MAX_EXPIRE_MINUTES: i64 = 30 * 24 * 60;
+
+// 常见的接收验证码的应用：短信、邮件、密码管理器和验证器
+const OTP_SOURCE_APPS: &[&str] = &[
+    "com.apple.MobileSMS",
+    "com.apple.mail",
+    "com.apple.Passwords",
+    "com.microsoft.Outlook",
+    "com.tencent.xinWeChat",
+    "ru.keepcoder.Telegram",
+    "org.whispersystems.signal-desktop",
+    "com.1password.1password",
+    "com.agilebits.onepassword7",
+    "com.bitwarden.desktop",
+    "com.authy.authy-mac",
+];
+// 验证码短信或邮件中的关键词（小写）
+const OTP_KEYWORDS: &[&str] = &[
+    "验证码", "校验码", "动态码", "动态密码", "verification code", "security code", "passcode", "one-time", "otp",
+];
+// 带关键词的整条消息最长字符数，更长的文本不按验证码处理
+const MAX_OTP_MESSAGE_CHARS: usize = 200;
+
+fn otp_number_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?:^|[^0-9])[0-9]{4,8}(?:[^0-9]|$)").unwrap())
+}
+
+// 整段内容是否为验证码格式：4~8 位数字，允许中间有一个空格或连字符（如 123 456）
+fn is_code_only(text: &str) -> bool {
+    let digits = text.chars().filter(char::is_ascii_digit).count();
+    let separators = text.chars().filter(|c| *c == ' ' || *c == '-').count();
+    (4..=8).contains(&digits)
+        && separators <= 1
+        && digits + separators == text.chars().count()
+        && !text.starts_with('-')
+        && !text.ends_with('-')
+}
+
+// 内容是否为验证码：单纯的数字很可能是金额、编号或年份，需要有上下文才算验证码
+// 整段是验证码格式且复制自短信、邮件、验证器等应用，或者是包含验证码关键词和 4~8 位数字的短消息
+pub fn is_one_time_code(content: &ClipboardContent, source_bundle_id: Option<&str>) -> bool {
+    let ClipboardContent::Text { text } = content else {
+        return false;
+    };
+    let text = text.trim();
+    if is_code_only(text) {
+        return source_bundle_id.is_some_and(|id| OTP_SOURCE_APPS.iter().any(|app| app.eq_ignore_ascii_case(id)));
+    }
+    if text.chars().count() > MAX_OTP_MESSAGE_CHARS {
+        return false;
+    }
+    let lower = text.to_lowercase();
+    OTP_KEYWORDS.iter().any(|keyword| lower.contains(keyword)) && otp_number_regex().is_match(text)
+}
+
+// N 分钟后的过期时间（与 timestamp 列格式一致）
+pub fn expiry_timestamp(minutes: i64) -> String {
+    (Local::now() + Duration::minutes(minutes))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+// 永久删除已过期的记录（包括过期前已移入回收站的记录），返回删除条数
+pub fn purge_expired_items(conn: &Connection) -> Result<usize, OicopyError> {
+    let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let deleted = conn
+        .execute(
+            "DELETE FROM clipboard_history WHERE expires_at IS NOT NULL AND expires_at <= ?1 AND pinned = 0",
+            params![now],
+        )
+        .map_err(|e| OicopyError::Db(format!("删除过期记录失败: {}", e)))?;
+    let trashed = conn
+        .execute(
+            "DELETE FROM clipboard_trash WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+            params![now],
+        )
+        .map_err(|e| OicopyError::Db(format!("删除回收站中的过期记录失败: {}", e)))?;
+
+    // 同一次复制保存的其他格式也包含原内容，一并删除
+    if deleted + trashed > 0 {
+        if let Err(e) = prune_orphan_content_formats(conn) {
+            eprintln!("{}", e);
+        }
+    }
+    Ok(deleted + trashed)
+}
+
+// 定时清理每次检查时调用，在写入线程上删除过期记录并通知前端
+pub fn purge_expired(app_handle: &AppHandle) {
+    match execute_write(app_handle, |_, conn| purge_expired_items(conn).map_err(String::from)) {
+        Ok(0) => {}
+        Ok(deleted) => {
+            println!("已删除 {} 条过期记录", deleted);
+            emit_change_event(app_handle, "clipboard-updated");
+        }
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+// 设置记录在 minutes 分钟后自动删除，minutes 为空或 0 时取消过期；返回过期时间
+#[tauri::command]
+pub async fn set_item_expiry(app: AppHandle, id: i64, minutes: Option<i64>) -> Result<Option<String>, OicopyError> {
+    let expires_at = match minutes.filter(|minutes| *minutes != 0) {
+        Some(minutes) if !(1..=MAX_EXPIRE_MINUTES).contains(&minutes) => {
+            return Err(OicopyError::Validation(format!("过期时间需在 1 到 {} 分钟之间", MAX_EXPIRE_MINUTES)));
+        }
+        Some(minutes) => Some(expiry_timestamp(minutes)),
+        None => None,
+    };
+
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let changed = conn
+        .execute(
+            "UPDATE clipboard_history SET expires_at = ?1 WHERE id = ?2",
+            params![expires_at, id],
+        )
+        .map_err(|e| OicopyError::Db(format!("设置过期时间失败: {}", e)))?;
+    if changed == 0 {
+        return Err(OicopyError::NotFound(format!("记录 {} 不存在", id)));
+    }
+
+    emit_change_event(&app, "clipboard-updated");
+    Ok(expires_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_one_time_code() {
+        let text = |text: &str| ClipboardContent::Text { text: text.to_string() };
+        let sms = Some("com.apple.MobileSMS");
+        assert!(is_one_time_code(&text("482913"), sms));
+        assert!(is_one_time_code(&text(" 123 456\n"), sms));
+        assert!(is_one_time_code(&text("1234"), Some("com.1password.1password")));
+        assert!(!is_one_time_code(&text("123"), sms));
+        assert!(!is_one_time_code(&text("138-0013-8000"), sms));
+        assert!(!is_one_time_code(&text("-1234"), sms));
+
+        // 没有上下文的数字（金额、编号、年份）不算验证码
+        assert!(!is_one_time_code(&text("2024"), Some("com.apple.Safari")));
+        assert!(!is_one_time_code(&text("482913"), None));
+
+        // 带关键词的短消息
+        assert!(is_one_time_code(&text("【某银行】您的验证码为 482913，5 分钟内有效"), None));
+        assert!(is_one_time_code(&text("Your verification code is 1234."), None));
+        assert!(!is_one_time_code(&text("验证码已发送"), None));
+        assert!(!is_one_time_code(&text("Order 482913 has shipped"), None));
+    }
+
+    #[test]
+    fn test_purge_expired_items_in_trash() {
+        let mut conn = crate::db::tests::test_connection();
+        conn.execute_batch(
+            "INSERT INTO clipboard_history (id, content_type, content, timestamp, expires_at)
+             VALUES (1, 'text', '482913', '2026-01-01 10:00:00', '2999-01-01 00:00:00'),
+                    (2, 'text', '173920', '2026-01-01 10:01:00', '2999-01-01 00:00:00');",
+        )
+        .unwrap();
+        crate::trash::move_to_trash(&mut conn, &[1, 2]).unwrap();
+        let expires_at: Option<String> = conn
+            .query_row("SELECT expires_at FROM clipboard_trash WHERE id = 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(expires_at.as_deref(), Some("2999-01-01 00:00:00"));
+
+        // 回收站中的记录到期后同样永久删除
+        conn.execute("UPDATE clipboard_trash SET expires_at = '2000-01-01 00:00:00' WHERE id = 1", [])
+            .unwrap();
+        purge_expired_items(&conn).unwrap();
+        let remaining: Vec<i64> = conn
+            .prepare("SELECT id FROM clipboard_trash")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(remaining, vec![2]);
+    }
+}
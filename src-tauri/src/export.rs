@@ -0,0 +1,755 @@
+use crate::accessibility::query_accessibility_settings;
+use crate::change_log::emit_change_event;
+use crate::clipboard_content::{generate_preview, ClipboardContent, ContentKind};
+use crate::clipboard_management::{escape_html, html_to_plain_text};
+use crate::db::{get_cached_app_icons, init_database, save_to_database, ClipboardHistoryItem};
+use crate::error::OicopyError;
+use crate::image_store::resolve_image_content;
+use crate::lock_mode::ensure_unlocked;
+use base64::prelude::*;
+use image::{DynamicImage, ImageFormat};
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+// 导出筛选条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportFilter {
+    pub ids: Option<Vec<i64>>,
+    pub content_types: Option<Vec<ContentKind>>,
+    pub source_app: Option<String>,
+    pub start_time: Option<String>, // 格式: %Y-%m-%d %H:%M:%S
+    pub end_time: Option<String>,
+    pub limit: Option<u32>,
+}
+
+// 导出进度事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportProgress {
+    pub current: usize,
+    pub total: usize,
+    pub file_path: Option<String>,
+}
+
+// 图片导出结果
+#[derive(Debug, Serialize)]
+pub struct ImageExportResult {
+    pub exported: usize,
+    pub failed: usize,
+    pub files: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+// 导出的一行历史记录（只包含导出需要的字段）
+struct ExportRow {
+    id: i64,
+    content_type: ContentKind,
+    content: String,
+    preview: String,
+    timestamp: String,
+    source_app: Option<String>,
+    source_bundle_id: Option<String>,
+}
+
+// 按筛选条件查询历史记录
+fn query_export_rows(
+    conn: &Connection,
+    filter: &ExportFilter,
+    content_type: Option<ContentKind>,
+) -> Result<Vec<ExportRow>, String> {
+    let mut conditions = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(kind) = content_type {
+        conditions.push("content_type = ?".to_string());
+        values.push(Value::Text(kind.as_str().to_string()));
+    }
+    if let Some(ref kinds) = filter.content_types {
+        if kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+        conditions.push(format!("content_type IN ({})", vec!["?"; kinds.len()].join(", ")));
+        values.extend(kinds.iter().map(|kind| Value::Text(kind.as_str().to_string())));
+    }
+    if let Some(ref ids) = filter.ids {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        conditions.push(format!("id IN ({})", vec!["?"; ids.len()].join(", ")));
+        values.extend(ids.iter().map(|id| Value::Integer(*id)));
+    }
+    if let Some(ref source_app) = filter.source_app {
+        conditions.push("source_app = ?".to_string());
+        values.push(Value::Text(source_app.clone()));
+    }
+    if let Some(ref start_time) = filter.start_time {
+        conditions.push("timestamp >= ?".to_string());
+        values.push(Value::Text(start_time.clone()));
+    }
+    if let Some(ref end_time) = filter.end_time {
+        conditions.push("timestamp <= ?".to_string());
+        values.push(Value::Text(end_time.clone()));
+    }
+
+    let mut sql = "SELECT id, content_type, content, preview, timestamp, source_app, source_bundle_id FROM clipboard_history".to_string();
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+    sql.push_str(" ORDER BY id DESC");
+    if let Some(limit) = filter.limit {
+        sql.push_str(&format!(" LIMIT {}", limit));
+    }
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("准备导出查询失败: {}", e))?;
+    let rows = stmt
+        .query_map(params_from_iter(values), |row| {
+            Ok(ExportRow {
+                id: row.get(0)?,
+                content_type: row.get(1)?,
+                content: row.get(2)?,
+                preview: row.get(3)?,
+                timestamp: row.get(4)?,
+                source_app: row.get(5)?,
+                source_bundle_id: row.get(6)?,
+            })
+        })
+        .map_err(|e| format!("导出查询失败: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("处理导出数据失败: {}", e))
+}
+
+// 生成可用作文件名的字符串
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            ' ' => '_',
+            _ => c,
+        })
+        .collect()
+}
+
+// 将图片字节按指定格式写入文件
+fn write_image_file(bytes: &[u8], format: ImageFormat, path: &Path) -> Result<(), String> {
+    if format == ImageFormat::Png && image::guess_format(bytes).ok() == Some(ImageFormat::Png) {
+        // 原始数据已是PNG，直接写入
+        return std::fs::write(path, bytes).map_err(|e| format!("写入图片失败: {}", e));
+    }
+
+    let image = image::load_from_memory(bytes).map_err(|e| format!("解码图片失败: {}", e))?;
+    // JPEG 不支持透明通道
+    let image = if format == ImageFormat::Jpeg {
+        DynamicImage::ImageRgb8(image.to_rgb8())
+    } else {
+        image
+    };
+    image
+        .save_with_format(path, format)
+        .map_err(|e| format!("保存图片失败: {}", e))
+}
+
+// 批量导出图片到文件夹
+pub fn export_images_to_dir(
+    app: &AppHandle,
+    conn: &Connection,
+    filter: &ExportFilter,
+    dest_dir: &Path,
+    format: &str,
+) -> Result<ImageExportResult, String> {
+    let image_format = ImageFormat::from_extension(format)
+        .ok_or_else(|| format!("不支持的图片格式: {}", format))?;
+    let extension = image_format.extensions_str().first().copied().unwrap_or(format);
+
+    std::fs::create_dir_all(dest_dir).map_err(|e| format!("创建导出目录失败: {}", e))?;
+
+    let rows = query_export_rows(conn, filter, Some(ContentKind::Image))?;
+    let total = rows.len();
+    let mut result = ImageExportResult {
+        exported: 0,
+        failed: 0,
+        files: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    for (index, row) in rows.into_iter().enumerate() {
+        let file_name = format!(
+            "{}_{}_{}.{}",
+            sanitize_file_name(&row.timestamp),
+            sanitize_file_name(row.source_app.as_deref().unwrap_or("unknown")),
+            row.id,
+            extension
+        );
+        let path: PathBuf = dest_dir.join(file_name);
+
+        let written = match ClipboardContent::from_stored(row.content_type, row.content) {
+            Ok(ClipboardContent::Image { bytes, .. }) => write_image_file(&bytes, image_format, &path),
+            Ok(_) => Err("记录不是图片".to_string()),
+            Err(e) => Err(e),
+        };
+
+        let file_path = match written {
+            Ok(()) => {
+                let file_path = path.to_string_lossy().to_string();
+                result.exported += 1;
+                result.files.push(file_path.clone());
+                Some(file_path)
+            }
+            Err(e) => {
+                result.failed += 1;
+                result.errors.push(format!("记录 {}: {}", row.id, e));
+                None
+            }
+        };
+
+        let _ = app.emit(
+            "image-export-progress",
+            ExportProgress {
+                current: index + 1,
+                total,
+                file_path,
+            },
+        );
+    }
+
+    println!("图片导出完成: 成功 {}, 失败 {}", result.exported, result.failed);
+    Ok(result)
+}
+
+// Tauri命令：批量导出图片
+#[tauri::command]
+pub async fn export_images(
+    app: AppHandle,
+    filter: Option<ExportFilter>,
+    dest_dir: String,
+    format: Option<String>,
+) -> Result<ImageExportResult, String> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let filter = filter.unwrap_or_default();
+    let format = format.unwrap_or_else(|| "png".to_string());
+    export_images_to_dir(&app, &conn, &filter, Path::new(&dest_dir), &format)
+}
+
+// HTML报告导出结果
+#[derive(Debug, Serialize)]
+pub struct ReportExportResult {
+    pub file_path: String,
+    pub item_count: usize,
+    pub day_count: usize,
+}
+
+// 报告中单条文本内容的最大长度
+const REPORT_TEXT_MAX_CHARS: usize = 2000;
+
+const REPORT_STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "PingFang SC", sans-serif; margin: 0; background: #f5f5f7; color: #1d1d1f; }
+header { position: sticky; top: 0; background: rgba(255,255,255,0.92); padding: 16px 32px; border-bottom: 1px solid #ddd; }
+header h1 { font-size: 20px; margin: 0 0 8px; }
+#search { width: 100%; max-width: 480px; padding: 8px 12px; font-size: 14px; border: 1px solid #ccc; border-radius: 8px; }
+main { padding: 16px 32px; }
+section h2 { font-size: 15px; color: #6e6e73; margin: 24px 0 8px; }
+.clip { display: flex; gap: 12px; background: #fff; border-radius: 10px; padding: 12px; margin-bottom: 8px; box-shadow: 0 1px 2px rgba(0,0,0,0.06); }
+.clip img.icon { width: 28px; height: 28px; flex-shrink: 0; }
+.clip .icon-placeholder { width: 28px; height: 28px; flex-shrink: 0; border-radius: 6px; background: #e5e5ea; }
+.clip .body { min-width: 0; flex: 1; }
+.clip .meta { font-size: 12px; color: #86868b; margin-bottom: 4px; }
+.clip pre { margin: 0; white-space: pre-wrap; word-break: break-word; font-size: 13px; font-family: ui-monospace, Menlo, monospace; }
+.clip img.content { max-width: 100%; max-height: 240px; border-radius: 6px; }
+.hidden { display: none; }
+"#;
+
+// 系统开启“增强对比度”时追加的样式
+const HIGH_CONTRAST_STYLE: &str = r#"
+body { background: #fff; color: #000; }
+header { background: #fff; border-bottom: 2px solid #000; }
+#search { border: 2px solid #000; }
+section h2, .clip .meta { color: #000; }
+.clip { border: 2px solid #000; box-shadow: none; }
+"#;
+
+const REPORT_SCRIPT: &str = r#"
+document.getElementById('search').addEventListener('input', function (event) {
+  var query = event.target.value.trim().toLowerCase();
+  document.querySelectorAll('section').forEach(function (section) {
+    var visible = 0;
+    section.querySelectorAll('.clip').forEach(function (clip) {
+      var match = !query || clip.textContent.toLowerCase().indexOf(query) !== -1;
+      clip.classList.toggle('hidden', !match);
+      if (match) visible++;
+    });
+    section.classList.toggle('hidden', visible === 0);
+  });
+});
+"#;
+
+// 渲染单条记录的内容部分
+fn render_clip_body(row: &ExportRow) -> String {
+    match ClipboardContent::from_stored(row.content_type, row.content.clone()) {
+        // content 列中可能是图片文件路径，使用读取出的图片字节
+        Ok(ClipboardContent::Image { bytes, .. }) => format!(
+            r#"<img class="content" src="data:{};base64,{}" alt="{}">"#,
+            image::guess_format(&bytes).map(|format| format.to_mime_type()).unwrap_or("image/png"),
+            BASE64_STANDARD.encode(&bytes),
+            escape_html(&row.preview)
+        ),
+        Ok(ClipboardContent::Files { entries }) => format!(
+            "<pre>{}</pre>",
+            escape_html(&entries.join("\n"))
+        ),
+        Ok(ClipboardContent::Html { raw, .. }) => format!(
+            "<pre>{}</pre>",
+            escape_html(&generate_preview(&html_to_plain_text(&raw), REPORT_TEXT_MAX_CHARS))
+        ),
+        Ok(content) => format!(
+            "<pre>{}</pre>",
+            escape_html(&generate_preview(&content.to_stored(), REPORT_TEXT_MAX_CHARS))
+        ),
+        Err(_) => format!("<pre>{}</pre>", escape_html(&row.preview)),
+    }
+}
+
+// 生成按天分组、可搜索的HTML报告
+fn render_html_report(rows: &[ExportRow], icons: &HashMap<String, String>, high_contrast: bool) -> String {
+    // 按日期分组（最新的日期在前）
+    let mut days: BTreeMap<&str, Vec<&ExportRow>> = BTreeMap::new();
+    for row in rows {
+        let day = row.timestamp.get(..10).unwrap_or(&row.timestamp);
+        days.entry(day).or_default().push(row);
+    }
+
+    let mut sections = String::new();
+    for (day, day_rows) in days.iter().rev() {
+        sections.push_str(&format!(
+            "<section>\n<h2>{} · {} 条</h2>\n",
+            escape_html(day),
+            day_rows.len()
+        ));
+        for row in day_rows {
+            let icon = match row.source_bundle_id.as_ref().and_then(|id| icons.get(id)) {
+                Some(icon) => format!(r#"<img class="icon" src="data:image/png;base64,{}" alt="">"#, icon),
+                None => r#"<div class="icon-placeholder"></div>"#.to_string(),
+            };
+            sections.push_str(&format!(
+                "<div class=\"clip\">{}<div class=\"body\"><div class=\"meta\">{} · {} · {}</div>{}</div></div>\n",
+                icon,
+                escape_html(row.timestamp.get(11..).unwrap_or(&row.timestamp)),
+                escape_html(row.source_app.as_deref().unwrap_or("未知应用")),
+                row.content_type.label(),
+                render_clip_body(row)
+            ));
+        }
+        sections.push_str("</section>\n");
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<title>剪贴板历史报告</title>
+<style>{style}{contrast_style}</style>
+</head>
+<body>
+<header>
+<h1>剪贴板历史报告</h1>
+<div class="meta">生成于 {generated_at} · 共 {count} 条</div>
+<input id="search" type="search" placeholder="搜索内容或来源应用">
+</header>
+<main>
+{sections}</main>
+<script>{script}</script>
+</body>
+</html>
+"#,
+        style = REPORT_STYLE,
+        contrast_style = if high_contrast { HIGH_CONTRAST_STYLE } else { "" },
+        generated_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        count = rows.len(),
+        sections = sections,
+        script = REPORT_SCRIPT
+    )
+}
+
+// Tauri命令：导出HTML报告
+#[tauri::command]
+pub async fn export_html_report(
+    app: AppHandle,
+    filter: Option<ExportFilter>,
+    dest_path: String,
+) -> Result<ReportExportResult, String> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let filter = filter.unwrap_or_default();
+    let rows = query_export_rows(&conn, &filter, None)?;
+
+    let bundle_ids: Vec<String> = rows
+        .iter()
+        .filter_map(|row| row.source_bundle_id.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let icons = get_cached_app_icons(&conn, &bundle_ids)?;
+
+    let high_contrast = query_accessibility_settings().increase_contrast;
+    let html = render_html_report(&rows, &icons, high_contrast);
+    let path = Path::new(&dest_path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    }
+    std::fs::write(path, html).map_err(|e| format!("写入HTML报告失败: {}", e))?;
+
+    let day_count = rows
+        .iter()
+        .map(|row| row.timestamp.get(..10).unwrap_or(&row.timestamp))
+        .collect::<HashSet<_>>()
+        .len();
+    println!("HTML报告导出完成: {} 条记录, {} 天", rows.len(), day_count);
+
+    Ok(ReportExportResult {
+        file_path: path.to_string_lossy().to_string(),
+        item_count: rows.len(),
+        day_count,
+    })
+}
+
+// 历史记录导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryExportFormat {
+    Json,
+    Csv,
+}
+
+// 导出文件中的一条记录
+#[derive(Debug, Serialize)]
+struct ExportRecord {
+    id: i64,
+    content_type: ContentKind,
+    content: String,
+    preview: String,
+    timestamp: String,
+    source_app: Option<String>,
+    source_bundle_id: Option<String>,
+    image_file: Option<String>, // 图片写入旁路文件夹时的相对路径，此时 content 为空
+}
+
+// 历史记录导出结果
+#[derive(Debug, Serialize)]
+pub struct HistoryExportResult {
+    pub file_path: String,
+    pub record_count: usize,
+    pub image_count: usize,
+    pub image_dir: Option<String>,
+}
+
+// CSV 字段转义
+fn csv_field(value: &str) -> String {
+    if value.contains(|c: char| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn render_csv(records: &[ExportRecord]) -> String {
+    let mut csv = String::from(
+        "id,content_type,timestamp,source_app,source_bundle_id,preview,content,image_file\n",
+    );
+    for record in records {
+        let fields = [
+            record.id.to_string(),
+            record.content_type.as_str().to_string(),
+            record.timestamp.clone(),
+            record.source_app.clone().unwrap_or_default(),
+            record.source_bundle_id.clone().unwrap_or_default(),
+            record.preview.clone(),
+            record.content.clone(),
+            record.image_file.clone().unwrap_or_default(),
+        ];
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+// Tauri命令：导出历史记录为 JSON 或 CSV
+#[tauri::command]
+pub async fn export_history(
+    app: AppHandle,
+    path: String,
+    format: HistoryExportFormat,
+    filter: Option<ExportFilter>,
+    images_to_folder: Option<bool>,
+) -> Result<HistoryExportResult, String> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let filter = filter.unwrap_or_default();
+    let rows = query_export_rows(&conn, &filter, None)?;
+
+    let path = PathBuf::from(&path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    }
+
+    // 图片写入与导出文件同名的旁路文件夹（CSV 默认如此，避免单元格中出现超长 base64）
+    let image_dir = if images_to_folder.unwrap_or(format == HistoryExportFormat::Csv) {
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "export".to_string());
+        Some(path.with_file_name(format!("{}_images", stem)))
+    } else {
+        None
+    };
+
+    let mut image_count = 0;
+    let mut records = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut record = ExportRecord {
+            id: row.id,
+            content_type: row.content_type,
+            content: row.content,
+            preview: row.preview,
+            timestamp: row.timestamp,
+            source_app: row.source_app,
+            source_bundle_id: row.source_bundle_id,
+            image_file: None,
+        };
+
+        if let (ContentKind::Image, Some(dir)) = (record.content_type, image_dir.as_ref()) {
+            std::fs::create_dir_all(dir).map_err(|e| format!("创建图片目录失败: {}", e))?;
+            let file_name = format!("{}_{}.png", sanitize_file_name(&record.timestamp), record.id);
+            match ClipboardContent::from_stored(ContentKind::Image, std::mem::take(&mut record.content)) {
+                Ok(ClipboardContent::Image { bytes, .. }) => {
+                    write_image_file(&bytes, ImageFormat::Png, &dir.join(&file_name))?;
+                    let folder = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                    record.image_file = Some(format!("{}/{}", folder, file_name));
+                    image_count += 1;
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("导出图片记录 {} 失败: {}", record.id, e),
+            }
+        } else if record.content_type == ContentKind::Image {
+            // 图片内嵌在导出文件中时以 base64 写出
+            record.content = resolve_image_content(std::mem::take(&mut record.content));
+        }
+        records.push(record);
+    }
+
+    let output = match format {
+        HistoryExportFormat::Json => serde_json::to_string_pretty(&records)
+            .map_err(|e| format!("序列化导出数据失败: {}", e))?,
+        HistoryExportFormat::Csv => render_csv(&records),
+    };
+    std::fs::write(&path, output).map_err(|e| format!("写入导出文件失败: {}", e))?;
+
+    println!("历史记录导出完成: {} 条记录, {} 张图片", records.len(), image_count);
+    Ok(HistoryExportResult {
+        file_path: path.to_string_lossy().to_string(),
+        record_count: records.len(),
+        image_count,
+        image_dir: image_dir.map(|dir| dir.to_string_lossy().to_string()),
+    })
+}
+
+// 导入文件中的一条记录（与 ExportRecord 对应）
+#[derive(Debug, Deserialize)]
+struct ImportRecord {
+    content_type: ContentKind,
+    #[serde(default)]
+    content: String,
+    timestamp: String,
+    #[serde(default)]
+    source_app: Option<String>,
+    #[serde(default)]
+    source_bundle_id: Option<String>,
+    #[serde(default)]
+    image_file: Option<String>,
+}
+
+// 历史记录导入结果
+#[derive(Debug, Serialize)]
+pub struct HistoryImportResult {
+    pub added: usize,
+    pub skipped: usize,
+}
+
+// 解析 CSV 文本（支持引号包裹的字段中包含逗号、换行和转义引号）
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+// 将 CSV 行按表头转换为导入记录
+fn csv_to_records(text: &str) -> Result<Vec<ImportRecord>, String> {
+    let mut rows = parse_csv(text).into_iter();
+    let header = rows.next().ok_or("CSV 文件为空")?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h == name)
+            .ok_or_else(|| format!("CSV 缺少列: {}", name))
+    };
+    let (type_col, content_col, time_col) = (column("content_type")?, column("content")?, column("timestamp")?);
+    let optional_column = |name: &str| header.iter().position(|h| h == name);
+    let (app_col, bundle_col, image_col) = (
+        optional_column("source_app"),
+        optional_column("source_bundle_id"),
+        optional_column("image_file"),
+    );
+
+    let mut records = Vec::new();
+    for (index, row) in rows.enumerate() {
+        let get = |col: usize| row.get(col).cloned().unwrap_or_default();
+        let optional = |col: Option<usize>| col.map(get).filter(|value| !value.is_empty());
+        let content_type = ContentKind::parse(&get(type_col))
+            .ok_or_else(|| format!("第 {} 行内容类型无效: {}", index + 2, get(type_col)))?;
+        records.push(ImportRecord {
+            content_type,
+            content: get(content_col),
+            timestamp: get(time_col),
+            source_app: optional(app_col),
+            source_bundle_id: optional(bundle_col),
+            image_file: optional(image_col),
+        });
+    }
+    Ok(records)
+}
+
+// 校验并还原记录内容，图片从旁路文件夹读取
+fn import_record_content(record: &ImportRecord, base_dir: &Path) -> Result<ClipboardContent, String> {
+    if chrono::NaiveDateTime::parse_from_str(&record.timestamp, "%Y-%m-%d %H:%M:%S").is_err() {
+        return Err(format!("时间格式无效: {}", record.timestamp));
+    }
+
+    let stored = match (&record.image_file, record.content_type) {
+        (Some(image_file), ContentKind::Image) => {
+            let bytes = std::fs::read(base_dir.join(image_file))
+                .map_err(|e| format!("读取图片 {} 失败: {}", image_file, e))?;
+            BASE64_STANDARD.encode(bytes)
+        }
+        _ => record.content.clone(),
+    };
+
+    let content = ClipboardContent::from_stored(record.content_type, stored)?;
+    if content.is_empty() {
+        return Err("内容为空".to_string());
+    }
+    Ok(content)
+}
+
+// Tauri命令：从导出的 JSON/CSV 文件导入历史记录
+#[tauri::command]
+pub async fn import_history(app: AppHandle, path: String) -> Result<HistoryImportResult, String> {
+    let path = PathBuf::from(&path);
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("读取导入文件失败: {}", e))?;
+    let is_csv = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+    let records: Vec<ImportRecord> = if is_csv {
+        csv_to_records(&text)?
+    } else {
+        serde_json::from_str(&text).map_err(|e| format!("导入文件格式无效: {}", e))?
+    };
+    let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    // 先校验全部记录，任何一条无效都不导入
+    let mut items = Vec::with_capacity(records.len());
+    for (index, record) in records.iter().enumerate() {
+        let content = import_record_content(record, &base_dir)
+            .map_err(|e| format!("第 {} 条记录无效: {}", index + 1, e))?;
+        items.push(ClipboardHistoryItem::from_content(
+            &content,
+            record.timestamp.clone(),
+            record.source_app.clone(),
+            record.source_bundle_id.clone(),
+        ));
+    }
+
+    let mut conn = init_database(&app)?;
+
+    ensure_unlocked(&conn)?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+
+    let mut result = HistoryImportResult { added: 0, skipped: 0 };
+    for item in &items {
+        // 通过 content_hash 去重：已存在或文件内重复的记录跳过
+        match save_to_database(&tx, item) {
+            Ok(_) => result.added += 1,
+            Err(OicopyError::Duplicate) => result.skipped += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    tx.commit().map_err(|e| format!("提交导入事务失败: {}", e))?;
+
+    println!("历史记录导入完成: 新增 {}, 跳过 {}", result.added, result.skipped);
+    if result.added > 0 {
+        emit_change_event(&app, "clipboard-updated");
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line1\nline2"), "\"line1\nline2\"");
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let rows = parse_csv("a,b\r\n\"x, y\",\"say \"\"hi\"\"\nagain\"\n");
+        assert_eq!(rows, vec![
+            vec!["a".to_string(), "b".to_string()],
+            vec!["x, y".to_string(), "say \"hi\"\nagain".to_string()],
+        ]);
+    }
+}
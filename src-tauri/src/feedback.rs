@@ -0,0 +1,98 @@
+use crate::db::get_settings;
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+// 触发反馈的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackEvent {
+    Capture, // 新内容已保存到历史记录
+    Paste,   // 历史记录已写回剪贴板
+}
+
+impl FeedbackEvent {
+    // 对应的系统提示音名称（/System/Library/Sounds）
+    fn sound_name(&self) -> &'static std::ffi::CStr {
+        match self {
+            FeedbackEvent::Capture => c"Tink",
+            FeedbackEvent::Paste => c"Pop",
+        }
+    }
+}
+
+// 根据设置播放提示音/触控板震动，设置读取失败时不做任何反馈
+pub fn trigger_feedback(app_handle: &AppHandle, conn: &Connection, event: FeedbackEvent) {
+    let settings = match get_settings(conn) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("读取反馈设置失败: {}", e);
+            return;
+        }
+    };
+
+    if !settings.feedback_sound && !settings.feedback_haptic {
+        return;
+    }
+
+    // AppKit 调用需要在主线程执行
+    let (sound, haptic) = (settings.feedback_sound, settings.feedback_haptic);
+    if let Err(e) = app_handle.run_on_main_thread(move || {
+        if sound {
+            play_sound(event);
+        }
+        if haptic {
+            perform_haptic();
+        }
+    }) {
+        eprintln!("触发反馈失败: {}", e);
+    }
+}
+
+// 通过 NSSound 播放系统提示音
+#[cfg(target_os = "macos")]
+fn play_sound(event: FeedbackEvent) {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+
+    unsafe {
+        let name: *mut AnyObject =
+            msg_send![class!(NSString), stringWithUTF8String: event.sound_name().as_ptr()];
+        if name.is_null() {
+            return;
+        }
+        let sound: *mut AnyObject = msg_send![class!(NSSound), soundNamed: name];
+        if sound.is_null() {
+            eprintln!("未找到系统提示音: {:?}", event.sound_name());
+            return;
+        }
+        let _: bool = msg_send![sound, play];
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn play_sound(_event: FeedbackEvent) {}
+
+// 通过 NSHapticFeedbackManager 触发触控板震动（不支持的设备上无效果）
+#[cfg(target_os = "macos")]
+fn perform_haptic() {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+
+    // NSHapticFeedbackPatternGeneric = 0, NSHapticFeedbackPerformanceTimeDefault = 0
+    const PATTERN_GENERIC: isize = 0;
+    const PERFORMANCE_TIME_DEFAULT: usize = 0;
+
+    unsafe {
+        let performer: *mut AnyObject = msg_send![class!(NSHapticFeedbackManager), defaultPerformer];
+        if performer.is_null() {
+            return;
+        }
+        let _: () = msg_send![
+            performer,
+            performFeedbackPattern: PATTERN_GENERIC,
+            performanceTime: PERFORMANCE_TIME_DEFAULT
+        ];
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn perform_haptic() {}
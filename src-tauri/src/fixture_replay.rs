@@ -0,0 +1,184 @@
+use crate::change_log::emit_change_event;
+use crate::clipboard_content::ContentKind;
+use crate::clipboard_management::{capture_from_source, save_captured_content, ClipboardSource};
+use crate::db::init_database;
+use crate::db_writer::execute_write;
+use crate::error::OicopyError;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+// 剪贴板样本：用户反馈问题时附带的多格式剪贴板内容（JSON 文件）
+#[derive(Debug, Deserialize)]
+pub struct ClipboardFixture {
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub html: Option<String>,
+    #[serde(default)]
+    pub rtf: Option<String>,
+    // PNG 图片的 base64，与剪贴板插件返回的格式一致
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub files: Option<Vec<String>>,
+    #[serde(default)]
+    pub source_app: Option<String>,
+    #[serde(default)]
+    pub source_bundle_id: Option<String>,
+    // 模拟读取失败的格式，用于复现格式降级问题
+    #[serde(default)]
+    pub failing_formats: Vec<ContentKind>,
+}
+
+impl ClipboardFixture {
+    fn read<T: Clone>(&self, kind: ContentKind, value: &Option<T>) -> Result<T, String> {
+        if self.failing_formats.contains(&kind) {
+            return Err(format!("样本设定{}读取失败", kind.label()));
+        }
+        value.clone().ok_or_else(|| format!("样本中没有{}", kind.label()))
+    }
+}
+
+impl ClipboardSource for ClipboardFixture {
+    fn available_kinds(&self) -> Result<Vec<ContentKind>, String> {
+        Ok([
+            (ContentKind::Files, self.files.is_some()),
+            (ContentKind::Image, self.image.is_some()),
+            (ContentKind::Html, self.html.is_some()),
+            (ContentKind::Text, self.text.is_some()),
+            (ContentKind::Rtf, self.rtf.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, available)| *available)
+        .map(|(kind, _)| kind)
+        .collect())
+    }
+
+    fn read_text(&self) -> Result<String, String> {
+        self.read(ContentKind::Text, &self.text)
+    }
+
+    fn read_html(&self) -> Result<String, String> {
+        self.read(ContentKind::Html, &self.html)
+    }
+
+    fn read_rtf(&self) -> Result<String, String> {
+        self.read(ContentKind::Rtf, &self.rtf)
+    }
+
+    fn read_image_base64(&self) -> Result<String, String> {
+        self.read(ContentKind::Image, &self.image)
+    }
+
+    fn read_files(&self) -> Result<Vec<String>, String> {
+        self.read(ContentKind::Files, &self.files)
+    }
+}
+
+// 单个样本的回放结果
+#[derive(Debug, Serialize)]
+pub struct FixtureReplayResult {
+    pub path: String,
+    pub content_type: Option<ContentKind>, // 捕获流程最终选择的格式
+    pub failed_formats: Vec<ContentKind>,
+    pub saved: bool, // false 表示没有可保存的内容或被去重跳过
+    pub error: Option<String>,
+}
+
+// 读取样本路径：目录按文件名顺序回放其中所有 .json 文件
+fn fixture_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+        .map_err(|e| format!("读取样本目录失败: {}", e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn load_fixture(path: &Path) -> Result<ClipboardFixture, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("读取样本文件失败: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("解析样本文件失败: {}", e))
+}
+
+// 通过真实的捕获流程（格式优先级、降级读取、去重保存）回放一个样本
+fn replay_one(app: &AppHandle, path: &Path) -> Result<FixtureReplayResult, String> {
+    let fixture = load_fixture(path)?;
+    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let captured = capture_from_source(
+        &*init_database(app)?,
+        &fixture,
+        timestamp,
+        fixture.source_app.clone(),
+        fixture.source_bundle_id.clone(),
+    )?;
+
+    let mut result = FixtureReplayResult {
+        path: path.display().to_string(),
+        content_type: None,
+        failed_formats: Vec::new(),
+        saved: false,
+        error: None,
+    };
+    if let Some(captured) = captured {
+        result.content_type = Some(captured.content.kind());
+        result.failed_formats = captured.history_item.failed_formats.clone();
+        result.saved = execute_write(app, move |app, conn| Ok(save_captured_content(app, conn, captured)))?;
+    }
+    Ok(result)
+}
+
+// Tauri命令：将剪贴板样本送入捕获流程写入当前数据库（仅在调试模式下可用）
+#[tauri::command]
+pub async fn replay_fixture(app: AppHandle, path: String) -> Result<Vec<FixtureReplayResult>, OicopyError> {
+    let files = fixture_files(Path::new(&path)).map_err(OicopyError::Io)?;
+    if files.is_empty() {
+        return Err(OicopyError::NotFound(format!("{} 中没有样本文件", path)));
+    }
+
+    let app_handle = app.clone();
+    let results = tauri::async_runtime::spawn_blocking(move || {
+        files
+            .iter()
+            .map(|file| {
+                println!("回放剪贴板样本: {}", file.display());
+                replay_one(&app_handle, file).unwrap_or_else(|e| FixtureReplayResult {
+                    path: file.display().to_string(),
+                    content_type: None,
+                    failed_formats: Vec::new(),
+                    saved: false,
+                    error: Some(e),
+                })
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("回放样本失败: {}", e))?;
+
+    if results.iter().any(|result| result.saved) {
+        emit_change_event(&app, "clipboard-updated");
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_source() {
+        let fixture: ClipboardFixture = serde_json::from_str(
+            r#"{"text": "hello", "html": "<b>hello</b>", "failing_formats": ["html"]}"#,
+        )
+        .unwrap();
+        assert_eq!(fixture.available_kinds().unwrap(), vec![ContentKind::Html, ContentKind::Text]);
+        assert_eq!(fixture.read_text().unwrap(), "hello");
+        assert!(fixture.read_html().is_err());
+        assert!(fixture.read_rtf().is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+
+// 默认请求超时（秒）
+const DEFAULT_TIMEOUT_SECS: u32 = 30;
+// 默认响应大小上限
+const DEFAULT_MAX_BYTES: usize = 32 * 1024 * 1024;
+
+// 通过 curl 发起 GET 请求并返回响应文本
+pub fn http_get(url: &str, headers: &[&str]) -> Result<String, String> {
+    http_get_with_timeout(url, headers, DEFAULT_TIMEOUT_SECS, DEFAULT_MAX_BYTES)
+}
+
+// 指定超时时间和大小上限的 GET 请求，返回响应文本
+pub fn http_get_with_timeout(url: &str, headers: &[&str], timeout_secs: u32, max_bytes: usize) -> Result<String, String> {
+    let body = http_get_bytes(url, headers, timeout_secs, max_bytes)?;
+    String::from_utf8(body).map_err(|e| format!("响应不是有效的UTF-8文本: {}", e))
+}
+
+// 指定超时时间和大小上限的 GET 请求，返回原始响应数据
+// 边读边计数，超过上限立即结束请求；--max-filesize 只对声明了 Content-Length 的响应有效
+pub fn http_get_bytes(url: &str, headers: &[&str], timeout_secs: u32, max_bytes: usize) -> Result<Vec<u8>, String> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(format!("不支持的地址: {}", url));
+    }
+
+    let mut command = Command::new("curl");
+    command
+        .arg("-fsSL")
+        .arg("--max-time")
+        .arg(timeout_secs.to_string())
+        .arg("--max-filesize")
+        .arg(max_bytes.to_string());
+    // https 请求跟随重定向时不允许降级为 http
+    if url.starts_with("https://") {
+        command.arg("--proto-redir").arg("=https");
+    }
+    for header in headers {
+        command.arg("-H").arg(header);
+    }
+
+    let mut child = command
+        .arg(url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("执行 curl 失败: {}", e))?;
+
+    let mut body = Vec::new();
+    let read = match child.stdout.take() {
+        Some(stdout) => stdout.take(max_bytes as u64 + 1).read_to_end(&mut body),
+        None => Ok(0),
+    };
+    if body.len() > max_bytes {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(format!("响应超过 {} 字节", max_bytes));
+    }
+    read.map_err(|e| format!("读取响应失败: {}", e))?;
+
+    let output = child.wait_with_output().map_err(|e| format!("执行 curl 失败: {}", e))?;
+    if !output.status.success() {
+        let error_msg = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("请求失败: {}", error_msg.trim()));
+    }
+
+    Ok(body)
+}
+
+// 请求的认证方式
+pub enum HttpAuth<'a> {
+    None,
+    Basic { username: &'a str, password: &'a str },
+    // S3 兼容存储使用 curl 内置的 AWS SigV4 签名
+    AwsSigV4 { region: &'a str, access_key: &'a str, secret_key: &'a str },
+}
+
+// 响应状态码和内容
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+// curl 配置文件中的字符串需要转义引号和反斜杠
+fn quote_config_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// 通用请求：返回状态码而不是把 4xx/5xx 当作错误，由调用方按状态码处理
+// 账号密码通过标准输入传给 curl，避免出现在进程列表中；请求内容先写入临时文件
+pub fn http_request(
+    method: &str,
+    url: &str,
+    headers: &[&str],
+    auth: &HttpAuth,
+    body: Option<&[u8]>,
+    timeout_secs: u32,
+) -> Result<HttpResponse, String> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(format!("不支持的地址: {}", url));
+    }
+
+    let mut command = Command::new("curl");
+    command
+        .arg("-sS")
+        .arg("--max-time")
+        .arg(timeout_secs.to_string())
+        .arg("-X")
+        .arg(method)
+        .arg("-w")
+        .arg("\n%{http_code}")
+        .arg("-K")
+        .arg("-");
+    for header in headers {
+        command.arg("-H").arg(header);
+    }
+    let user = match auth {
+        HttpAuth::None => None,
+        HttpAuth::Basic { username, password } => Some(format!("{}:{}", username, password)),
+        HttpAuth::AwsSigV4 { region, access_key, secret_key } => {
+            command.arg("--aws-sigv4").arg(format!("aws:amz:{}:s3", region));
+            Some(format!("{}:{}", access_key, secret_key))
+        }
+    };
+
+    let body_path = match body {
+        Some(body) => {
+            let path = std::env::temp_dir().join(format!(
+                "oicopy-upload-{}-{}",
+                std::process::id(),
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or_default()
+            ));
+            std::fs::write(&path, body).map_err(|e| format!("写入请求数据失败: {}", e))?;
+            command.arg("--data-binary").arg(format!("@{}", path.display()));
+            Some(path)
+        }
+        None => None,
+    };
+
+    let output = (|| {
+        let mut child = command
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("执行 curl 失败: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let config = user.map(|user| format!("user = {}\n", quote_config_value(&user))).unwrap_or_default();
+            stdin
+                .write_all(config.as_bytes())
+                .map_err(|e| format!("执行 curl 失败: {}", e))?;
+        }
+        child.wait_with_output().map_err(|e| format!("执行 curl 失败: {}", e))
+    })();
+    if let Some(path) = body_path {
+        let _ = std::fs::remove_file(path);
+    }
+    let output = output?;
+
+    if !output.status.success() {
+        let error_msg = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("请求失败: {}", error_msg.trim()));
+    }
+
+    // 输出末尾是 -w 追加的状态码
+    let mut body = output.stdout;
+    let split = body.iter().rposition(|&b| b == b'\n').ok_or_else(|| "无法读取响应状态".to_string())?;
+    let status = std::str::from_utf8(&body[split + 1..])
+        .ok()
+        .and_then(|code| code.trim().parse::<u16>().ok())
+        .ok_or_else(|| "无法读取响应状态".to_string())?;
+    body.truncate(split);
+    Ok(HttpResponse { status, body })
+}
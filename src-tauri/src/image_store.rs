@@ -0,0 +1,471 @@
+use crate::db::{init_database, set_item_thumbnail};
+use crate::db_encryption::{apply_key, database_key};
+use crate::profiles::other_profile_databases;
+use crate::sync_crypto::{derive_key, from_hex, hmac_sha256, open, seal, to_hex, KEY_SIZE};
+use base64::prelude::*;
+use rusqlite::{params, Connection, OpenFlags};
+use std::collections::HashSet;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::AppHandle;
+
+// 图片文件保存在应用数据目录下的 blobs 目录，数据库中只保存相对路径
+// 文件内容用由数据库密钥派生的密钥加密，文件名是内容的 HMAC，不会泄露图片内容
+const BLOBS_DIR: &str = "blobs";
+const BLOB_MAGIC: &[u8; 5] = b"OICB1";
+const ORPHAN_GRACE_PERIOD: Duration = Duration::from_secs(10 * 60);
+// 列表中使用的缩略图最长边
+const THUMBNAIL_SIZE: u32 = 256;
+
+// 应用数据目录和图片加密密钥（由 init_database 设置）
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+static BLOB_KEY: OnceLock<[u8; KEY_SIZE]> = OnceLock::new();
+
+// 已保存的图片文件
+#[derive(Debug, Clone)]
+pub struct StoredImage {
+    pub reference: String, // 相对于应用数据目录的路径，如 blobs/ab/<sha256>.png
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+// 设置图片存储根目录和加密密钥（由数据库密钥派生），并确保 blobs 目录存在
+pub fn init_image_store(app_data_dir: &Path, database_key: &str) -> Result<(), String> {
+    std::fs::create_dir_all(app_data_dir.join(BLOBS_DIR))
+        .map_err(|e| format!("创建图片存储目录失败: {}", e))?;
+    let _ = BLOB_KEY.set(derive_key(&from_hex(database_key)?, "oicopy-image-blob"));
+    let _ = DATA_DIR.set(app_data_dir.to_path_buf());
+    Ok(())
+}
+
+fn data_dir() -> Result<&'static Path, String> {
+    DATA_DIR
+        .get()
+        .map(PathBuf::as_path)
+        .ok_or_else(|| "图片存储尚未初始化".to_string())
+}
+
+fn blob_key() -> Result<&'static [u8; KEY_SIZE], String> {
+    BLOB_KEY.get().ok_or_else(|| "图片存储尚未初始化".to_string())
+}
+
+fn is_encrypted_blob(data: &[u8]) -> bool {
+    data.starts_with(BLOB_MAGIC)
+}
+
+fn encrypt_blob(key: &[u8], bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut data = BLOB_MAGIC.to_vec();
+    data.extend_from_slice(&seal(key, bytes)?);
+    Ok(data)
+}
+
+// 先写临时文件再重命名，避免中途失败留下不完整的图片
+fn write_file_atomically(path: &Path, data: &[u8]) -> Result<(), String> {
+    let temp_path = path.with_extension("tmp");
+    std::fs::write(&temp_path, data).map_err(|e| format!("写入图片文件失败: {}", e))?;
+    std::fs::rename(&temp_path, path).map_err(|e| format!("保存图片文件失败: {}", e))
+}
+
+// content 列中保存的是否为图片文件路径（旧版本保存的是 base64）
+pub fn is_blob_reference(stored: &str) -> bool {
+    stored.starts_with("blobs/") && !stored.contains("..")
+}
+
+// 读取图片尺寸（只解析文件头，不解码整张图片）
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+// 将图片字节加密后写入 blobs 目录，相同内容只保存一份
+pub fn store_image_blob(bytes: &[u8], format: &str) -> Result<StoredImage, String> {
+    write_blob(data_dir()?, blob_key()?, bytes, format)
+}
+
+fn write_blob(root: &Path, key: &[u8], bytes: &[u8], format: &str) -> Result<StoredImage, String> {
+    let hash = to_hex(&hmac_sha256(key, bytes));
+    let extension = if !format.is_empty() && format.chars().all(|c| c.is_ascii_alphanumeric()) {
+        format.to_ascii_lowercase()
+    } else {
+        "png".to_string()
+    };
+    let reference = format!("{}/{}/{}.{}", BLOBS_DIR, &hash[..2], hash, extension);
+
+    let path = root.join(&reference);
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建图片目录失败: {}", e))?;
+        }
+        write_file_atomically(&path, &encrypt_blob(key, bytes)?)?;
+    }
+
+    let (width, height) = image_dimensions(bytes).unzip();
+    Ok(StoredImage { reference, width, height })
+}
+
+// 按相对路径读取并解密图片字节
+pub fn load_image_blob(reference: &str) -> Result<Vec<u8>, String> {
+    read_blob(data_dir()?, blob_key()?, reference)
+}
+
+fn read_blob(root: &Path, key: &[u8], reference: &str) -> Result<Vec<u8>, String> {
+    if !is_blob_reference(reference) {
+        return Err(format!("无效的图片路径: {}", reference));
+    }
+    let data = std::fs::read(root.join(reference)).map_err(|e| format!("读取图片文件 {} 失败: {}", reference, e))?;
+    if !is_encrypted_blob(&data) {
+        // 启用加密之前保存的图片，由 encrypt_legacy_blobs 在后台加密
+        return Ok(data);
+    }
+    open(key, &data[BLOB_MAGIC.len()..]).map_err(|_| format!("解密图片文件 {} 失败", reference))
+}
+
+// 图片文件大小（字节），文件不存在时返回 None
+pub fn blob_size(reference: &str) -> Option<u64> {
+    let path = data_dir().ok()?.join(reference);
+    std::fs::metadata(path).ok().map(|meta| meta.len())
+}
+
+// 从图片路径推断格式
+pub fn blob_format(reference: &str) -> String {
+    Path::new(reference)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("png")
+        .to_string()
+}
+
+// 将 content 列中的图片路径还原为前端使用的 base64，旧数据原样返回
+pub fn resolve_image_content(stored: String) -> String {
+    if !is_blob_reference(&stored) {
+        return stored;
+    }
+    match load_image_blob(&stored) {
+        Ok(bytes) => BASE64_STANDARD.encode(bytes),
+        Err(e) => {
+            eprintln!("{}", e);
+            String::new()
+        }
+    }
+}
+
+// 生成最长边不超过 THUMBNAIL_SIZE 的 PNG 缩略图（base64），小图直接使用原图
+pub fn generate_thumbnail(bytes: &[u8]) -> Result<String, String> {
+    let image = image::load_from_memory(bytes).map_err(|e| format!("解码图片失败: {}", e))?;
+    if image.width() <= THUMBNAIL_SIZE
+        && image.height() <= THUMBNAIL_SIZE
+        && image::guess_format(bytes).ok() == Some(image::ImageFormat::Png)
+    {
+        return Ok(BASE64_STANDARD.encode(bytes));
+    }
+
+    let mut output = Cursor::new(Vec::new());
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .write_to(&mut output, image::ImageFormat::Png)
+        .map_err(|e| format!("生成缩略图失败: {}", e))?;
+    Ok(BASE64_STANDARD.encode(output.into_inner()))
+}
+
+// 为还没有缩略图的图片记录补生成缩略图，返回生成数量
+pub fn backfill_thumbnails(conn: &Connection) -> Result<usize, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, content FROM clipboard_history WHERE content_type = 'image' AND thumbnail IS NULL")
+        .map_err(|e| format!("准备缩略图查询失败: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("查询缺少缩略图的记录失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取缺少缩略图的记录失败: {}", e))?;
+
+    let mut generated = 0;
+    for (id, content) in rows {
+        let bytes = if is_blob_reference(&content) {
+            load_image_blob(&content)
+        } else {
+            BASE64_STANDARD
+                .decode(content.trim())
+                .map_err(|e| format!("解码图像数据失败: {}", e))
+        };
+        match bytes.and_then(|bytes| generate_thumbnail(&bytes)) {
+            Ok(thumbnail) => {
+                set_item_thumbnail(conn, id, &thumbnail)?;
+                generated += 1;
+            }
+            Err(e) => eprintln!("为记录 {} 生成缩略图失败: {}", id, e),
+        }
+    }
+
+    if generated > 0 {
+        println!("已为 {} 张图片补生成缩略图", generated);
+    }
+    Ok(generated)
+}
+
+// 将旧版本以 base64 保存在数据库中的图片迁移为文件，返回迁移条数
+pub fn migrate_inline_images(conn: &Connection) -> Result<usize, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, content FROM clipboard_history WHERE content_type = 'image' AND content NOT LIKE 'blobs/%'")
+        .map_err(|e| format!("准备图片迁移查询失败: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("查询待迁移图片失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取待迁移图片失败: {}", e))?;
+
+    let mut migrated = 0;
+    for (id, content) in rows {
+        let bytes = match BASE64_STANDARD.decode(content.trim()) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("记录 {} 的图片数据无法解码，跳过迁移: {}", id, e);
+                continue;
+            }
+        };
+        let stored = store_image_blob(&bytes, "png")?;
+        conn.execute(
+            "UPDATE clipboard_history SET content = ?1, image_width = ?2, image_height = ?3 WHERE id = ?4",
+            params![stored.reference, stored.width, stored.height, id],
+        )
+        .map_err(|e| format!("更新图片记录失败: {}", e))?;
+        migrated += 1;
+    }
+
+    if migrated > 0 {
+        println!("已将 {} 张图片从数据库迁移为文件", migrated);
+    }
+    Ok(migrated)
+}
+
+// 数据库中引用的图片路径（包括回收站中的记录和其他格式）
+fn referenced_blobs(conn: &Connection) -> Result<HashSet<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT content FROM clipboard_history WHERE content_type = 'image' AND content LIKE 'blobs/%'
+             UNION SELECT content FROM clipboard_trash WHERE content_type = 'image' AND content LIKE 'blobs/%'
+             UNION SELECT content FROM content_formats WHERE kind = 'image' AND content LIKE 'blobs/%'",
+        )
+        .map_err(|e| format!("准备图片引用查询失败: {}", e))?;
+    let referenced = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("查询图片引用失败: {}", e))?
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("读取图片引用失败: {}", e))?;
+    Ok(referenced)
+}
+
+// 所有配置的数据库中引用的图片路径：各配置共用 blobs 目录，只看当前数据库会误删其他配置的图片
+// 其他配置的数据库无法读取时返回错误，宁可不清理也不误删
+fn referenced_blobs_all_profiles(conn: &Connection) -> Result<HashSet<String>, String> {
+    let mut referenced = referenced_blobs(conn)?;
+    let Some(db_path) = conn.path().filter(|path| !path.is_empty()) else {
+        return Ok(referenced);
+    };
+    for path in other_profile_databases(Path::new(db_path)) {
+        let other = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+            .map_err(|e| format!("打开配置数据库 {} 失败: {}", path.display(), e))?;
+        if let Some(key) = database_key() {
+            apply_key(&other, key)?;
+        }
+        referenced.extend(referenced_blobs(&other)?);
+    }
+    Ok(referenced)
+}
+
+// blobs 目录下的图片文件及其相对路径（不含写入中的临时文件）
+fn blob_files(root: &Path) -> Vec<(std::fs::DirEntry, String)> {
+    let Ok(shards) = std::fs::read_dir(root.join(BLOBS_DIR)) else {
+        return Vec::new();
+    };
+    shards
+        .flatten()
+        .filter_map(|shard| std::fs::read_dir(shard.path()).ok())
+        .flat_map(|files| files.flatten())
+        .filter_map(|file| {
+            let path = file.path();
+            if path.extension().is_some_and(|ext| ext == "tmp") {
+                return None;
+            }
+            let reference = path.strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/");
+            Some((file, reference))
+        })
+        .collect()
+}
+
+// 删除不在 referenced 中的图片文件，返回删除数量
+fn remove_unreferenced_blobs(root: &Path, referenced: &HashSet<String>) -> usize {
+    let mut removed = 0;
+    for (file, reference) in blob_files(root) {
+        // 刚写入的文件可能属于尚未插入数据库的记录，暂不删除
+        let recently_written = file
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .map(|age| age < ORPHAN_GRACE_PERIOD)
+            .unwrap_or(true);
+        if recently_written || referenced.contains(&reference) {
+            continue;
+        }
+        if std::fs::remove_file(file.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+// 删除不再被任何配置中的记录（包括回收站中的记录）引用的图片文件，返回删除数量
+pub fn prune_orphan_blobs(conn: &Connection) -> Result<usize, String> {
+    let referenced = referenced_blobs_all_profiles(conn)?;
+    let removed = remove_unreferenced_blobs(data_dir()?, &referenced);
+    if removed > 0 {
+        println!("已删除 {} 个未被引用的图片文件", removed);
+    }
+    Ok(removed)
+}
+
+// 将启用加密之前保存的明文图片文件原地加密（路径不变，数据库无需修改），返回加密数量
+fn encrypt_legacy_blobs(root: &Path, key: &[u8]) -> Result<usize, String> {
+    let mut encrypted = 0;
+    for (file, reference) in blob_files(root) {
+        let path = file.path();
+        let data = std::fs::read(&path).map_err(|e| format!("读取图片文件 {} 失败: {}", reference, e))?;
+        if is_encrypted_blob(&data) {
+            continue;
+        }
+        write_file_atomically(&path, &encrypt_blob(key, &data)?)?;
+        encrypted += 1;
+    }
+    if encrypted > 0 {
+        println!("已加密 {} 个旧版本保存的图片文件", encrypted);
+    }
+    Ok(encrypted)
+}
+
+// 启动时在后台迁移旧版本的内联图片、加密旧的图片文件，并为旧图片补生成缩略图
+pub fn start_inline_image_migration(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let result = init_database(&app_handle).map_err(String::from).and_then(|conn| {
+            migrate_inline_images(&conn)?;
+            encrypt_legacy_blobs(data_dir()?, blob_key()?)?;
+            backfill_thumbnails(&conn)
+        });
+        if let Err(e) = result {
+            eprintln!("图片迁移失败: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tests::{create_test_database, test_connection};
+    use crate::db::DATABASE_FILE_NAME;
+    use std::time::SystemTime;
+
+    const KEY: [u8; KEY_SIZE] = [3u8; KEY_SIZE];
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("oicopy-blob-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join(BLOBS_DIR)).unwrap();
+        root
+    }
+
+    // 把文件修改时间改到宽限期之前，使其可以被清理
+    fn make_old(root: &Path, reference: &str) {
+        let file = std::fs::File::options().write(true).open(root.join(reference)).unwrap();
+        file.set_modified(SystemTime::now() - ORPHAN_GRACE_PERIOD * 2).unwrap();
+    }
+
+    #[test]
+    fn test_store_and_load_encrypted_blob() {
+        let root = temp_root("store");
+        let bytes = b"\x89PNG fake image bytes".to_vec();
+        let stored = write_blob(&root, &KEY, &bytes, "png").unwrap();
+        assert!(is_blob_reference(&stored.reference));
+        assert!(stored.reference.ends_with(".png"));
+
+        // 磁盘上的文件已加密，文件名也不是内容的 SHA-256
+        let on_disk = std::fs::read(root.join(&stored.reference)).unwrap();
+        assert!(is_encrypted_blob(&on_disk));
+        assert!(!on_disk.windows(bytes.len()).any(|window| window == bytes.as_slice()));
+        assert_eq!(read_blob(&root, &KEY, &stored.reference).unwrap(), bytes);
+        assert!(read_blob(&root, &[4u8; KEY_SIZE], &stored.reference).is_err());
+        assert!(read_blob(&root, &KEY, "blobs/../secret.png").is_err());
+
+        // 相同内容只保存一份
+        assert_eq!(write_blob(&root, &KEY, &bytes, "png").unwrap().reference, stored.reference);
+        assert_eq!(blob_files(&root).len(), 1);
+
+        // 旧版本的明文文件可以直接读取，加密后内容不变
+        let legacy = "blobs/ab/legacy.png";
+        std::fs::create_dir_all(root.join("blobs/ab")).unwrap();
+        std::fs::write(root.join(legacy), b"legacy bytes").unwrap();
+        assert_eq!(read_blob(&root, &KEY, legacy).unwrap(), b"legacy bytes");
+        assert_eq!(encrypt_legacy_blobs(&root, &KEY).unwrap(), 1);
+        assert!(is_encrypted_blob(&std::fs::read(root.join(legacy)).unwrap()));
+        assert_eq!(read_blob(&root, &KEY, legacy).unwrap(), b"legacy bytes");
+        assert_eq!(encrypt_legacy_blobs(&root, &KEY).unwrap(), 0);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_prune_unreferenced_blobs() {
+        let root = temp_root("prune");
+        let conn = test_connection();
+        let kept = write_blob(&root, &KEY, b"kept image", "png").unwrap().reference;
+        let orphan = write_blob(&root, &KEY, b"orphan image", "png").unwrap().reference;
+        let recent = write_blob(&root, &KEY, b"recent image", "png").unwrap().reference;
+        conn.execute(
+            "INSERT INTO clipboard_history (content_type, content, timestamp) VALUES ('image', ?1, '2024-01-01 00:00:00')",
+            params![kept],
+        )
+        .unwrap();
+        make_old(&root, &kept);
+        make_old(&root, &orphan);
+
+        // 只删除未被引用且已过宽限期的文件
+        assert_eq!(remove_unreferenced_blobs(&root, &referenced_blobs(&conn).unwrap()), 1);
+        assert!(root.join(&kept).exists());
+        assert!(!root.join(&orphan).exists());
+        assert!(root.join(&recent).exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_prune_keeps_blobs_used_by_other_profiles() {
+        let root = temp_root("profiles");
+        let default_db = create_test_database(&root.join(DATABASE_FILE_NAME));
+        let work_db = create_test_database(&root.join("profiles").join("work.db"));
+        let shared = write_blob(&root, &KEY, b"shared image", "png").unwrap().reference;
+        make_old(&root, &shared);
+        for conn in [&default_db, &work_db] {
+            conn.execute(
+                "INSERT INTO clipboard_history (content_type, content, timestamp) VALUES ('image', ?1, '2024-01-01 00:00:00')",
+                params![shared],
+            )
+            .unwrap();
+        }
+
+        // 默认配置删除记录后，work 配置仍在使用该图片
+        default_db.execute("DELETE FROM clipboard_history", []).unwrap();
+        assert!(referenced_blobs(&default_db).unwrap().is_empty());
+        assert_eq!(remove_unreferenced_blobs(&root, &referenced_blobs_all_profiles(&default_db).unwrap()), 0);
+        assert!(root.join(&shared).exists());
+
+        // 两个配置都不再引用后才删除
+        work_db.execute("DELETE FROM clipboard_history", []).unwrap();
+        assert_eq!(remove_unreferenced_blobs(&root, &referenced_blobs_all_profiles(&default_db).unwrap()), 1);
+        assert!(!root.join(&shared).exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
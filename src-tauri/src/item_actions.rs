@@ -0,0 +1,196 @@
+use crate::clipboard_content::ClipboardContent;
+use crate::db::{get_history_item, init_database};
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+
+// 面板中可对记录执行的快捷操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemActionKind {
+    OpenUrl,           // 在默认浏览器中打开链接
+    RevealFile,        // 在访达中显示文件
+    ConvertToMarkdown, // HTML/RTF 转为 Markdown
+    RunTransform,      // 对文本执行转换（大小写、去空白等）
+    Share,             // 通过系统共享菜单分享
+}
+
+impl ItemActionKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ItemActionKind::OpenUrl => "打开链接",
+            ItemActionKind::RevealFile => "在访达中显示",
+            ItemActionKind::ConvertToMarkdown => "转为 Markdown",
+            ItemActionKind::RunTransform => "文本转换",
+            ItemActionKind::Share => "分享",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemAction {
+    pub kind: ItemActionKind,
+    pub label: &'static str,
+    pub target: Option<String>, // 操作对象（链接或文件路径），无需时为空
+}
+
+impl ItemAction {
+    fn new(kind: ItemActionKind, target: Option<String>) -> Self {
+        Self {
+            kind,
+            label: kind.label(),
+            target,
+        }
+    }
+}
+
+// 文本整体是否为一个 http(s) 链接，是则返回去掉首尾空白的链接
+pub fn detect_url(text: &str) -> Option<String> {
+    let trimmed = text.trim();
+    let is_url = (trimmed.starts_with("https://") || trimmed.starts_with("http://"))
+        && trimmed.len() > "https://".len()
+        && !trimmed.chars().any(char::is_whitespace);
+    is_url.then(|| trimmed.to_string())
+}
+
+// 根据内容类型计算可用的操作
+pub fn actions_for_content(content: &ClipboardContent) -> Vec<ItemAction> {
+    let mut actions = Vec::new();
+    match content {
+        ClipboardContent::Text { text } => {
+            if let Some(url) = detect_url(text) {
+                actions.push(ItemAction::new(ItemActionKind::OpenUrl, Some(url)));
+            }
+            actions.push(ItemAction::new(ItemActionKind::RunTransform, None));
+        }
+        ClipboardContent::Html { .. } | ClipboardContent::Rtf { .. } => {
+            actions.push(ItemAction::new(ItemActionKind::ConvertToMarkdown, None));
+            actions.push(ItemAction::new(ItemActionKind::RunTransform, None));
+        }
+        ClipboardContent::Files { entries } => {
+            actions.push(ItemAction::new(ItemActionKind::RevealFile, entries.first().cloned()));
+        }
+        ClipboardContent::Image { .. } => {}
+    }
+    actions.push(ItemAction::new(ItemActionKind::Share, None));
+    actions
+}
+
+// Tauri命令：获取记录可用的快捷操作
+#[tauri::command]
+pub async fn get_item_actions(app: AppHandle, id: i64) -> Result<Vec<ItemAction>, String> {
+    let conn = init_database(&app)?;
+    let content = get_history_item(&conn, id)?
+        .ok_or_else(|| format!("记录 {} 不存在", id))?
+        .to_content()?;
+    Ok(actions_for_content(&content))
+}
+
+// 将文件列表中的条目转换为本地路径（file:// 形式需要解码百分号转义）
+fn entry_to_path(entry: &str) -> PathBuf {
+    match entry.strip_prefix("file://") {
+        Some(path) => PathBuf::from(decode_percent(path)),
+        None => PathBuf::from(entry),
+    }
+}
+
+// 解码百分号转义（如 %20）
+fn decode_percent(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// Tauri命令：在默认浏览器中打开记录中的链接
+#[tauri::command]
+pub async fn open_item_url(app: AppHandle, id: i64) -> Result<String, String> {
+    let conn = init_database(&app)?;
+    let content = get_history_item(&conn, id)?
+        .ok_or_else(|| format!("记录 {} 不存在", id))?
+        .to_content()?;
+
+    let url = match content {
+        ClipboardContent::Text { ref text } => detect_url(text),
+        _ => None,
+    }
+    .ok_or_else(|| "该记录不是有效的链接".to_string())?;
+
+    app.opener()
+        .open_url(url.as_str(), None::<&str>)
+        .map_err(|e| format!("打开链接失败: {}", e))?;
+    println!("已在浏览器中打开链接: {}", url);
+    Ok(url)
+}
+
+// Tauri命令：在访达中显示记录中的文件（多个文件时全部选中）
+#[tauri::command]
+pub async fn reveal_item_in_finder(app: AppHandle, id: i64) -> Result<usize, String> {
+    let conn = init_database(&app)?;
+    let content = get_history_item(&conn, id)?
+        .ok_or_else(|| format!("记录 {} 不存在", id))?
+        .to_content()?;
+
+    let ClipboardContent::Files { entries } = content else {
+        return Err("该记录不是文件".to_string());
+    };
+
+    // 只显示仍然存在的文件
+    let paths: Vec<PathBuf> = entries
+        .iter()
+        .map(|entry| entry_to_path(entry))
+        .filter(|path| path.exists())
+        .collect();
+    if paths.is_empty() {
+        return Err("文件已被移动或删除".to_string());
+    }
+
+    app.opener()
+        .reveal_items_in_dir(&paths)
+        .map_err(|e| format!("在访达中显示文件失败: {}", e))?;
+    Ok(paths.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(content: ClipboardContent) -> Vec<ItemActionKind> {
+        actions_for_content(&content).into_iter().map(|action| action.kind).collect()
+    }
+
+    #[test]
+    fn test_actions_by_content_type() {
+        assert_eq!(
+            kinds(ClipboardContent::Text { text: " https://example.com/a?b=1 \n".to_string() }),
+            vec![ItemActionKind::OpenUrl, ItemActionKind::RunTransform, ItemActionKind::Share]
+        );
+        assert_eq!(
+            kinds(ClipboardContent::Text { text: "see https://example.com".to_string() }),
+            vec![ItemActionKind::RunTransform, ItemActionKind::Share]
+        );
+        assert_eq!(
+            kinds(ClipboardContent::Files { entries: vec!["/tmp/a.txt".to_string()] }),
+            vec![ItemActionKind::RevealFile, ItemActionKind::Share]
+        );
+    }
+
+    #[test]
+    fn test_entry_to_path() {
+        assert_eq!(entry_to_path("file:///Users/me/My%20File.txt"), PathBuf::from("/Users/me/My File.txt"));
+        assert_eq!(entry_to_path("/tmp/50%20off.txt"), PathBuf::from("/tmp/50%20off.txt"));
+    }
+}
@@ -0,0 +1,175 @@
+// 通过 Bonjour 在局域网中广播和发现其他设备，使用系统自带的 dns-sd 命令
+use serde::Serialize;
+
+pub const SERVICE_TYPE: &str = "_oicopy._tcp";
+
+// 局域网中发现的设备
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredDevice {
+    pub device_id: String,
+    pub name: String,
+    pub address: String, // host:port
+}
+
+// 从 dns-sd -B 的输出中提取新出现的服务实例名
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_browse_line(line: &str) -> Option<String> {
+    let mut fields = line.split_whitespace();
+    let _timestamp = fields.next()?;
+    if fields.next()? != "Add" {
+        return None;
+    }
+    let _flags = fields.next()?;
+    let _interface = fields.next()?;
+    let _domain = fields.next()?;
+    if !fields.next()?.starts_with(SERVICE_TYPE) {
+        return None;
+    }
+    let name = fields.collect::<Vec<_>>().join(" ");
+    (!name.is_empty()).then_some(name)
+}
+
+// 从 dns-sd -L 的输出中提取 host:port
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_resolve_line(line: &str) -> Option<String> {
+    let (_, rest) = line.split_once("can be reached at ")?;
+    let address = rest.split_whitespace().next()?;
+    let (host, port) = address.rsplit_once(':')?;
+    port.parse::<u16>().ok()?;
+    Some(format!("{}:{}", host.trim_end_matches('.'), port))
+}
+
+// 从 TXT 记录中提取设备 ID（id=...）
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_txt_device_id(line: &str) -> Option<String> {
+    line.split_whitespace()
+        .find_map(|field| field.strip_prefix("id="))
+        .map(str::to_string)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::mpsc::{channel, Receiver};
+    use std::time::{Duration, Instant};
+
+    // 广播本机服务，返回的子进程结束时停止广播
+    pub fn advertise(name: &str, port: u16, device_id: &str) -> Result<Child, String> {
+        Command::new("dns-sd")
+            .args(["-R", name, SERVICE_TYPE, "local", &port.to_string(), &format!("id={}", device_id)])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("启动 Bonjour 广播失败: {}", e))
+    }
+
+    // dns-sd 不会自行退出，逐行读取输出直到超时后结束进程
+    fn read_lines(args: &[&str]) -> Result<(Child, Receiver<String>), String> {
+        let mut child = Command::new("dns-sd")
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("执行 dns-sd 失败: {}", e))?;
+        let stdout = child.stdout.take().ok_or_else(|| "读取 dns-sd 输出失败".to_string())?;
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok((child, receiver))
+    }
+
+    fn resolve(name: &str, timeout: Duration) -> Option<(String, Option<String>)> {
+        let (mut child, lines) = read_lines(&["-L", name, SERVICE_TYPE, "local"]).ok()?;
+        let deadline = Instant::now() + timeout;
+        let mut address = None;
+        let mut device_id = None;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            let Ok(line) = lines.recv_timeout(remaining) else {
+                break;
+            };
+            if address.is_none() {
+                address = parse_resolve_line(&line);
+            } else if let Some(id) = parse_txt_device_id(&line) {
+                device_id = Some(id);
+                break;
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+        address.map(|address| (address, device_id))
+    }
+
+    // 浏览局域网中的服务并逐个解析地址，排除本机
+    pub fn discover(own_device_id: &str, timeout: Duration) -> Result<Vec<DiscoveredDevice>, String> {
+        let (mut child, lines) = read_lines(&["-B", SERVICE_TYPE, "local"])?;
+        let deadline = Instant::now() + timeout;
+        let mut names = Vec::new();
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match lines.recv_timeout(remaining) {
+                Ok(line) => {
+                    if let Some(name) = parse_browse_line(&line).filter(|name| !names.contains(name)) {
+                        names.push(name);
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+
+        Ok(names
+            .into_iter()
+            .filter_map(|name| {
+                let (address, device_id) = resolve(&name, timeout)?;
+                let device_id = device_id?;
+                (device_id != own_device_id).then_some(DiscoveredDevice { device_id, name, address })
+            })
+            .collect())
+    }
+}
+
+// 其他平台没有系统自带的 Bonjour 命令，只能通过地址手动连接
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::*;
+    use std::process::Child;
+    use std::time::Duration;
+
+    pub fn advertise(_name: &str, _port: u16, _device_id: &str) -> Result<Child, String> {
+        Err("当前平台不支持 Bonjour 广播".to_string())
+    }
+
+    pub fn discover(_own_device_id: &str, _timeout: Duration) -> Result<Vec<DiscoveredDevice>, String> {
+        Err("当前平台不支持 Bonjour 发现".to_string())
+    }
+}
+
+pub use platform::{advertise, discover};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_browse_line() {
+        let line = " 9:41:01.123  Add        3   4 local.               _oicopy._tcp.        MacBook Pro";
+        assert_eq!(parse_browse_line(line), Some("MacBook Pro".to_string()));
+        assert_eq!(parse_browse_line("Browsing for _oicopy._tcp.local"), None);
+        let removed = " 9:41:05.000  Rmv        0   4 local.               _oicopy._tcp.        MacBook Pro";
+        assert_eq!(parse_browse_line(removed), None);
+    }
+
+    #[test]
+    fn test_parse_resolve_output() {
+        let line = " 9:41:01.200  MacBook\\032Pro._oicopy._tcp.local. can be reached at MacBook-Pro.local.:50123 (interface 4)";
+        assert_eq!(parse_resolve_line(line), Some("MacBook-Pro.local:50123".to_string()));
+        assert_eq!(parse_txt_device_id(" id=abc123"), Some("abc123".to_string()));
+    }
+}
@@ -0,0 +1,961 @@
+use crate::change_log::emit_change_event;
+use crate::clipboard_content::{ClipboardContent, ContentKind};
+use crate::clipboard_management::write_clipboard_content;
+use crate::cloud_sync::SYNC_STATE_PENDING;
+use crate::db::{get_device_id, get_settings, init_database, open_connection, save_settings, save_to_database, ClipboardHistoryItem, ConnectionRole};
+use crate::db_writer::execute_write;
+use crate::error::OicopyError;
+use crate::lan_discovery::{advertise, discover, DiscoveredDevice};
+use crate::lock_mode::ensure_unlocked;
+use crate::notifications::{notify, NotificationEvent};
+use crate::sensitive::{detect_sensitive_content, masked_preview, SensitiveDataMode};
+use crate::sync_crypto::{
+    constant_time_eq, derive_key, from_hex, hmac_sha256, open, random_bytes, seal, stretch_key, to_hex, KEY_SIZE,
+};
+use chrono::Local;
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+// 定时与已配对设备同步的间隔
+const SYNC_INTERVAL_SECS: u64 = 5 * 60;
+// 配对码有效期
+const PAIRING_CODE_TTL: Duration = Duration::from_secs(120);
+// 配对码字符集（去掉容易混淆的 0/O、1/I/L）
+const PAIRING_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+const PAIRING_CODE_LEN: usize = 8;
+// 由配对码派生密钥时的迭代次数，增加离线猜测配对码的成本
+const PAIRING_KEY_ROUNDS: u32 = 100_000;
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(30);
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+const MAX_FRAME_BYTES: usize = 64 * 1024 * 1024;
+// 握手完成前（未加密）的消息上限：握手消息都很小，防止未认证的连接声明超大长度占用内存
+const MAX_HANDSHAKE_FRAME_BYTES: usize = 4 * 1024;
+// 同时进行的握手数上限，超出时直接关闭新连接
+const MAX_PENDING_HANDSHAKES: usize = 8;
+// 每批同步的记录数和数据量上限
+const BATCH_ITEMS: usize = 200;
+const BATCH_BYTES: usize = 16 * 1024 * 1024;
+
+// 正在运行的同步服务
+struct LanSyncServer {
+    port: u16,
+    stop: Arc<AtomicBool>,
+    advertiser: Option<Child>,
+}
+
+static SERVER: Lazy<Mutex<Option<LanSyncServer>>> = Lazy::new(|| Mutex::new(None));
+// 当前有效的配对码及生成时间，配对成功或失败一次后作废
+static PAIRING_CODE: Lazy<Mutex<Option<(String, Instant)>>> = Lazy::new(|| Mutex::new(None));
+
+// 已配对的设备
+#[derive(Debug, Serialize)]
+pub struct PairedDevice {
+    pub device_id: String,
+    pub name: String,
+    pub address: Option<String>, // 最近一次连接或发现的地址
+    pub last_synced_at: Option<String>,
+}
+
+// 局域网同步状态
+#[derive(Debug, Serialize)]
+pub struct LanSyncStatus {
+    pub enabled: bool,
+    pub device_id: String,
+    pub device_name: String,
+    pub port: Option<u16>,
+    pub pairing_code: Option<String>,
+    pub paired_devices: Vec<PairedDevice>,
+}
+
+// 同步结果
+#[derive(Debug, Default, Serialize)]
+pub struct LanSyncResult {
+    pub devices_synced: usize,
+    pub received: usize,
+    pub sent: usize,
+    pub errors: Vec<String>,
+}
+
+// 同步的一条记录，文件列表是本机路径，不参与同步
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncItem {
+    pub content: ClipboardContent,
+    pub timestamp: String,
+    pub source_app: Option<String>,
+    pub source_bundle_id: Option<String>,
+    pub device_id: Option<String>,
+    #[serde(default)]
+    pub sensitive: bool, // 敏感记录在对方设备上同样只显示打码的预览
+    #[serde(default)]
+    pub expires_at: Option<String>, // 自动过期的记录在对方设备上同样到期删除
+}
+
+// 握手消息（明文），握手完成后的消息都经过加密
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Handshake {
+    Hello { device_id: String, name: String, nonce: String, pairing: bool },
+    Challenge { device_id: String, name: String, nonce: String },
+    Proof { proof: String },
+    Welcome { sealed: String },
+    Rejected { reason: String },
+}
+
+// 会话消息：请求方拉取对方的新记录，再推送本机的新记录；
+// 或者直接发送一条记录（Send），对方写入剪贴板后回复 Delivered
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionMessage {
+    Pull { since_id: i64 },
+    Items { items: Vec<SyncItem>, last_id: i64, has_more: bool },
+    Stored { added: usize },
+    Send { item: SyncItem },
+    Delivered,
+    Failed { reason: String },
+    Done,
+}
+
+// 创建已配对设备表，共享密钥保存在加密的数据库中
+pub fn create_sync_peers_table(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sync_peers (
+            device_id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            address TEXT,
+            last_pulled_id INTEGER NOT NULL DEFAULT 0,
+            last_pushed_id INTEGER NOT NULL DEFAULT 0,
+            last_synced_at TEXT
+        );",
+    )
+    .map_err(|e| format!("创建同步设备表失败: {}", e))
+}
+
+fn now_string() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+// 本机名称，用于 Bonjour 广播和配对时显示
+pub fn device_name() -> String {
+    #[cfg(target_os = "macos")]
+    {
+        if let Ok(output) = std::process::Command::new("scutil").args(["--get", "ComputerName"]).output() {
+            let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if output.status.success() && !name.is_empty() {
+                return name;
+            }
+        }
+    }
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "OiCopy".to_string())
+}
+
+fn list_paired_devices(conn: &Connection) -> Result<Vec<PairedDevice>, String> {
+    let mut stmt = conn
+        .prepare("SELECT device_id, name, address, last_synced_at FROM sync_peers ORDER BY name")
+        .map_err(|e| format!("查询已配对设备失败: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(PairedDevice {
+                device_id: row.get(0)?,
+                name: row.get(1)?,
+                address: row.get(2)?,
+                last_synced_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("查询已配对设备失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取已配对设备失败: {}", e))
+}
+
+pub fn peer_secret(conn: &Connection, device_id: &str) -> Result<Option<Vec<u8>>, String> {
+    let secret: Option<String> = conn
+        .query_row("SELECT secret FROM sync_peers WHERE device_id = ?1", params![device_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("查询配对设备失败: {}", e))?;
+    secret.map(|secret| from_hex(&secret)).transpose()
+}
+
+fn peer_cursors(conn: &Connection, device_id: &str) -> Result<(i64, i64), String> {
+    conn.query_row(
+        "SELECT last_pulled_id, last_pushed_id FROM sync_peers WHERE device_id = ?1",
+        params![device_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .map_err(|e| format!("查询同步进度失败: {}", e))
+}
+
+// 连接后附带的帧读写，握手完成后设置会话密钥，之后的帧都加密传输
+pub struct Channel {
+    stream: TcpStream,
+    key: Option<[u8; KEY_SIZE]>,
+}
+
+impl Channel {
+    fn new(stream: TcpStream) -> Result<Self, String> {
+        stream
+            .set_read_timeout(Some(NETWORK_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(NETWORK_TIMEOUT)))
+            .map_err(|e| format!("设置连接超时失败: {}", e))?;
+        Ok(Self { stream, key: None })
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) -> Result<(), String> {
+        let json = serde_json::to_vec(message).map_err(|e| format!("序列化同步消息失败: {}", e))?;
+        let payload = match &self.key {
+            Some(key) => seal(key, &json)?,
+            None => json,
+        };
+        if payload.len() > MAX_FRAME_BYTES {
+            return Err("同步消息过大".to_string());
+        }
+        self.stream
+            .write_all(&(payload.len() as u32).to_be_bytes())
+            .and_then(|_| self.stream.write_all(&payload))
+            .map_err(|e| format!("发送同步消息失败: {}", e))
+    }
+
+    pub fn recv<T: DeserializeOwned>(&mut self) -> Result<T, String> {
+        let mut len = [0u8; 4];
+        self.stream
+            .read_exact(&mut len)
+            .map_err(|e| format!("接收同步消息失败: {}", e))?;
+        let len = u32::from_be_bytes(len) as usize;
+        let limit = if self.key.is_some() { MAX_FRAME_BYTES } else { MAX_HANDSHAKE_FRAME_BYTES };
+        if len > limit {
+            return Err("同步消息过大".to_string());
+        }
+        let mut payload = vec![0u8; len];
+        self.stream
+            .read_exact(&mut payload)
+            .map_err(|e| format!("接收同步消息失败: {}", e))?;
+        let json = match &self.key {
+            Some(key) => open(key, &payload)?,
+            None => payload,
+        };
+        serde_json::from_slice(&json).map_err(|e| format!("同步消息格式无效: {}", e))
+    }
+}
+
+fn client_proof(key: &[u8], client_nonce: &str, server_nonce: &str) -> [u8; 32] {
+    hmac_sha256(key, format!("client:{}:{}", client_nonce, server_nonce).as_bytes())
+}
+
+fn session_key(key: &[u8], client_nonce: &str, server_nonce: &str) -> [u8; KEY_SIZE] {
+    derive_key(key, &format!("oicopy-session:{}:{}", client_nonce, server_nonce))
+}
+
+// 以客户端随机数作盐，每次配对尝试的密钥都不同，无法预先计算配对码对应的密钥
+fn pairing_key(code: &str, client_nonce: &str) -> [u8; KEY_SIZE] {
+    let salt = format!("oicopy-pairing:{}", client_nonce);
+    stretch_key(code.to_uppercase().as_bytes(), salt.as_bytes(), PAIRING_KEY_ROUNDS)
+}
+
+fn generate_pairing_code() -> Result<String, String> {
+    Ok(random_bytes(PAIRING_CODE_LEN)?
+        .into_iter()
+        .map(|b| PAIRING_ALPHABET[b as usize % PAIRING_ALPHABET.len()] as char)
+        .collect())
+}
+
+// 取出有效的配对码，取出后即作废，每个配对码只能尝试一次
+fn take_pairing_code() -> Option<String> {
+    PAIRING_CODE
+        .lock()
+        .ok()?
+        .take()
+        .filter(|(_, created)| created.elapsed() < PAIRING_CODE_TTL)
+        .map(|(code, _)| code)
+}
+
+// 身份信息：本机设备 ID 和名称
+struct LocalDevice {
+    device_id: String,
+    name: String,
+}
+
+fn local_device(conn: &Connection) -> Result<LocalDevice, String> {
+    Ok(LocalDevice { device_id: get_device_id(conn)?, name: device_name() })
+}
+
+pub fn connect(address: &str) -> Result<Channel, String> {
+    let addr = address
+        .to_socket_addrs()
+        .map_err(|e| format!("解析设备地址失败: {}", e))?
+        .next()
+        .ok_or_else(|| format!("无法解析设备地址: {}", address))?;
+    let stream = TcpStream::connect_timeout(&addr, NETWORK_TIMEOUT).map_err(|e| format!("连接设备失败: {}", e))?;
+    Channel::new(stream)
+}
+
+// 与对方交换随机数并证明双方持有相同的密钥；key_for 由对方设备 ID 和本端随机数得到密钥，pairing 为 true 时由配对码派生
+// 返回对方的 Challenge 信息和服务端发来的加密数据（解密后）
+fn client_handshake(
+    channel: &mut Channel,
+    local: &LocalDevice,
+    pairing: bool,
+    key_for: impl FnOnce(&str, &str) -> Result<Vec<u8>, String>,
+) -> Result<(String, String, Vec<u8>), String> {
+    let client_nonce = to_hex(&random_bytes(16)?);
+    channel.send(&Handshake::Hello {
+        device_id: local.device_id.clone(),
+        name: local.name.clone(),
+        nonce: client_nonce.clone(),
+        pairing,
+    })?;
+    let (device_id, name, server_nonce) = match channel.recv()? {
+        Handshake::Challenge { device_id, name, nonce } => (device_id, name, nonce),
+        Handshake::Rejected { reason } => return Err(reason),
+        _ => return Err("同步握手失败".to_string()),
+    };
+    let key = key_for(&device_id, &client_nonce)?;
+    channel.send(&Handshake::Proof { proof: to_hex(&client_proof(&key, &client_nonce, &server_nonce)) })?;
+    let sealed = match channel.recv()? {
+        Handshake::Welcome { sealed } => sealed,
+        Handshake::Rejected { reason } => return Err(reason),
+        _ => return Err("同步握手失败".to_string()),
+    };
+    let session = session_key(&key, &client_nonce, &server_nonce);
+    // 能解密说明对方持有相同的密钥
+    let payload = open(&session, &from_hex(&sealed)?).map_err(|_| "对方设备验证失败".to_string())?;
+    channel.key = Some(session);
+    Ok((device_id, name, payload))
+}
+
+// 连接已配对的设备并完成握手，返回加密的会话
+pub fn open_session(conn: &Connection, address: &str, expected_device_id: &str) -> Result<Channel, String> {
+    let local = local_device(conn)?;
+    let mut channel = connect(address)?;
+    client_handshake(&mut channel, &local, false, |device_id, _| {
+        if device_id != expected_device_id {
+            return Err("该地址上的设备与配对的设备不一致".to_string());
+        }
+        peer_secret(conn, device_id)?.ok_or_else(|| "设备未配对".to_string())
+    })?;
+    Ok(channel)
+}
+
+// 正在握手的连接数
+static PENDING_HANDSHAKES: AtomicUsize = AtomicUsize::new(0);
+
+// 握手名额，握手完成或连接结束时释放
+struct HandshakeSlot;
+
+impl HandshakeSlot {
+    fn acquire() -> Option<Self> {
+        PENDING_HANDSHAKES
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                (count < MAX_PENDING_HANDSHAKES).then_some(count + 1)
+            })
+            .ok()
+            .map(|_| HandshakeSlot)
+    }
+}
+
+impl Drop for HandshakeSlot {
+    fn drop(&mut self) {
+        PENDING_HANDSHAKES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// 处理一个连接：完成握手后处理会话消息
+fn handle_connection(app: &AppHandle, stream: TcpStream, slot: HandshakeSlot) -> Result<(), String> {
+    let mut channel = Channel::new(stream)?;
+    let conn = open_connection(app, ConnectionRole::Analytics)?;
+    let local = local_device(&init_database(app)?)?;
+
+    let (client_id, client_name, client_nonce, pairing) = match channel.recv()? {
+        Handshake::Hello { device_id, name, nonce, pairing } => (device_id, name, nonce, pairing),
+        _ => return Err("同步握手失败".to_string()),
+    };
+    let key = if pairing {
+        match take_pairing_code() {
+            Some(code) => pairing_key(&code, &client_nonce).to_vec(),
+            None => {
+                channel.send(&Handshake::Rejected { reason: "配对码已失效，请在对方设备上重新生成".to_string() })?;
+                return Ok(());
+            }
+        }
+    } else {
+        match peer_secret(&conn, &client_id)? {
+            Some(secret) => secret,
+            None => {
+                channel.send(&Handshake::Rejected { reason: "设备未配对".to_string() })?;
+                return Ok(());
+            }
+        }
+    };
+    if let Err(e) = ensure_unlocked(&conn) {
+        channel.send(&Handshake::Rejected { reason: e.to_string() })?;
+        return Ok(());
+    }
+
+    let server_nonce = to_hex(&random_bytes(16)?);
+    channel.send(&Handshake::Challenge {
+        device_id: local.device_id.clone(),
+        name: local.name.clone(),
+        nonce: server_nonce.clone(),
+    })?;
+    let proof = match channel.recv()? {
+        Handshake::Proof { proof } => from_hex(&proof)?,
+        _ => return Err("同步握手失败".to_string()),
+    };
+    if !constant_time_eq(&proof, &client_proof(&key, &client_nonce, &server_nonce)) {
+        channel.send(&Handshake::Rejected { reason: if pairing { "配对码错误" } else { "设备验证失败" }.to_string() })?;
+        return Ok(());
+    }
+
+    let session = session_key(&key, &client_nonce, &server_nonce);
+    if pairing {
+        // 配对成功：生成共享密钥，用配对码派生的会话密钥加密后发给对方
+        let secret = random_bytes(KEY_SIZE)?;
+        channel.send(&Handshake::Welcome { sealed: to_hex(&seal(&session, &secret)?) })?;
+        // 对方的监听端口在下次发现设备时获得
+        save_peer(app, client_id, client_name, secret, None)?;
+        return Ok(());
+    }
+    channel.send(&Handshake::Welcome { sealed: to_hex(&seal(&session, b"ok")?) })?;
+    channel.key = Some(session);
+    drop(slot);
+
+    loop {
+        match channel.recv::<SessionMessage>()? {
+            SessionMessage::Pull { since_id } => {
+                let (items, last_id, has_more) = load_items_since(&conn, since_id, &client_id)?;
+                channel.send(&SessionMessage::Items { items, last_id, has_more })?;
+            }
+            SessionMessage::Items { items, .. } => {
+                let added = store_items(app, items, &client_id, SYNC_STATE_PENDING)?;
+                channel.send(&SessionMessage::Stored { added })?;
+            }
+            SessionMessage::Send { item } => match receive_item(app, item, &client_id, &client_name) {
+                Ok(()) => channel.send(&SessionMessage::Delivered)?,
+                Err(reason) => channel.send(&SessionMessage::Failed { reason })?,
+            },
+            SessionMessage::Done => break,
+            SessionMessage::Stored { .. } | SessionMessage::Delivered | SessionMessage::Failed { .. } => {
+                return Err("同步消息顺序错误".to_string())
+            }
+        }
+    }
+    let _ = execute_write(app, move |_, conn| {
+        conn.execute(
+            "UPDATE sync_peers SET last_synced_at = ?1 WHERE device_id = ?2",
+            params![now_string(), client_id],
+        )
+        .map_err(|e| format!("更新同步时间失败: {}", e))
+    });
+    Ok(())
+}
+
+fn save_peer(app: &AppHandle, device_id: String, name: String, secret: Vec<u8>, address: Option<String>) -> Result<(), String> {
+    execute_write(app, move |_, conn| {
+        conn.execute(
+            "INSERT INTO sync_peers (device_id, name, secret, address) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(device_id) DO UPDATE SET name = excluded.name, secret = excluded.secret,
+                 address = COALESCE(excluded.address, address), last_pulled_id = 0, last_pushed_id = 0",
+            params![device_id, name, to_hex(&secret), address],
+        )
+        .map_err(|e| format!("保存配对设备失败: {}", e))?;
+        println!("已与设备配对: {} ({})", name, device_id);
+        Ok(())
+    })
+}
+
+// 读取 since_id 之后的记录，排除对方设备采集的记录和文件列表
+fn load_items_since(conn: &Connection, since_id: i64, peer_id: &str) -> Result<(Vec<SyncItem>, i64, bool), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, content_type, content, timestamp, source_app, source_bundle_id, device_id, sensitive, expires_at
+             FROM clipboard_history
+             WHERE id > ?1 AND content_type != ?2 AND (device_id IS NULL OR device_id != ?3)
+             ORDER BY id LIMIT ?4",
+        )
+        .map_err(|e| format!("查询同步记录失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![since_id, ContentKind::Files, peer_id, BATCH_ITEMS as i64], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, ContentKind>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, bool>(7)?,
+                row.get::<_, Option<String>>(8)?,
+            ))
+        })
+        .map_err(|e| format!("查询同步记录失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取同步记录失败: {}", e))?;
+
+    let row_count = rows.len();
+    let mut items = Vec::new();
+    let mut last_id = since_id;
+    let mut bytes = 0;
+    let mut truncated = false;
+    for (id, kind, stored, timestamp, source_app, source_bundle_id, device_id, sensitive, expires_at) in rows {
+        let content = match ClipboardContent::from_stored(kind, stored) {
+            Ok(content) => content,
+            Err(e) => {
+                // 图片文件丢失等无法读取的记录跳过
+                eprintln!("读取同步记录 {} 失败: {}", id, e);
+                last_id = id;
+                continue;
+            }
+        };
+        bytes += content.byte_size();
+        if bytes > BATCH_BYTES && !items.is_empty() {
+            truncated = true;
+            break;
+        }
+        items.push(SyncItem { content, timestamp, source_app, source_bundle_id, device_id, sensitive, expires_at });
+        last_id = id;
+    }
+    Ok((items, last_id, truncated || row_count == BATCH_ITEMS))
+}
+
+// 保存收到的记录，通过 content_hash 去重：本机已有的内容跳过
+// sync_state 为新记录的云同步状态
+pub fn store_items(app: &AppHandle, items: Vec<SyncItem>, peer_id: &str, sync_state: i64) -> Result<usize, String> {
+    if items.is_empty() {
+        return Ok(0);
+    }
+    let peer_id = peer_id.to_string();
+    let added = execute_write(app, move |_, conn| {
+        // 对方可能是旧版本或关闭了识别，按本机设置再识别一次敏感内容
+        let detect_sensitive = SensitiveDataMode::parse(&get_settings(conn)?.sensitive_data_mode) != Some(SensitiveDataMode::Off);
+        let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let tx = conn.transaction().map_err(|e| format!("开启事务失败: {}", e))?;
+        let mut added = 0;
+        for item in items {
+            // 已经过期的记录不再保存
+            if item.expires_at.as_deref().is_some_and(|expires_at| expires_at <= now.as_str()) {
+                continue;
+            }
+            let sensitive = item.sensitive || (detect_sensitive && detect_sensitive_content(&item.content).is_some());
+            let mut record = ClipboardHistoryItem::from_content(&item.content, item.timestamp, item.source_app, item.source_bundle_id);
+            record.device_id = item.device_id.or_else(|| Some(peer_id.clone()));
+            if sensitive {
+                record.sensitive = true;
+                record.preview = Some(masked_preview(&item.content));
+            }
+            record.expires_at = item.expires_at;
+            match save_to_database(&tx, &record) {
+                Ok(id) => {
+                    if sync_state != SYNC_STATE_PENDING {
+                        tx.execute("UPDATE clipboard_history SET sync_state = ?1 WHERE id = ?2", params![sync_state, id])
+                            .map_err(|e| format!("保存同步状态失败: {}", e))?;
+                    }
+                    added += 1;
+                }
+                Err(OicopyError::Duplicate) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        tx.commit().map_err(|e| format!("提交同步记录失败: {}", e))?;
+        Ok(added)
+    })?;
+    if added > 0 {
+        emit_change_event(app, "clipboard-updated");
+    }
+    Ok(added)
+}
+
+// 收到其他设备发送的一条记录：保存到历史记录并写入剪贴板
+fn receive_item(app: &AppHandle, item: SyncItem, peer_id: &str, peer_name: &str) -> Result<(), String> {
+    if item.content.kind() == ContentKind::Files {
+        return Err("无法接收文件列表".to_string());
+    }
+    let content = item.content.clone();
+    // 内容已存在时不重复保存，仍然写入剪贴板
+    store_items(app, vec![item], peer_id, SYNC_STATE_PENDING)?;
+    let clipboard_state = app.state::<tauri_plugin_clipboard::Clipboard>();
+    write_clipboard_content(&clipboard_state, &content)?;
+    println!("已将 {} 发送的{}写入剪贴板", peer_name, content.kind().label());
+    notify(app, NotificationEvent::ItemReceived { from: peer_name.to_string() });
+    Ok(())
+}
+
+// 读取一条要发送的记录
+fn load_send_item(conn: &Connection, id: i64) -> Result<SyncItem, String> {
+    let (kind, stored, timestamp, source_app, source_bundle_id, device_id, sensitive, expires_at) = conn
+        .query_row(
+            "SELECT content_type, content, timestamp, source_app, source_bundle_id, device_id, sensitive, expires_at
+             FROM clipboard_history WHERE id = ?1",
+            params![id],
+            |row| {
+                Ok((
+                    row.get::<_, ContentKind>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, bool>(6)?,
+                    row.get::<_, Option<String>>(7)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| format!("查询记录失败: {}", e))?
+        .ok_or_else(|| format!("记录不存在: {}", id))?;
+    if kind == ContentKind::Files {
+        return Err("文件列表是本机路径，无法发送到其他设备".to_string());
+    }
+    let content = ClipboardContent::from_stored(kind, stored)?;
+    Ok(SyncItem { content, timestamp, source_app, source_bundle_id, device_id, sensitive, expires_at })
+}
+
+// 与一台已配对的设备同步：先拉取对方的新记录，再推送本机的新记录
+fn sync_with_peer(app: &AppHandle, device_id: &str, address: &str) -> Result<(usize, usize), String> {
+    let conn = open_connection(app, ConnectionRole::Analytics)?;
+    let (mut last_pulled_id, mut last_pushed_id) = peer_cursors(&conn, device_id)?;
+    let mut channel = open_session(&init_database(app)?, address, device_id)?;
+    let (mut received, mut sent) = (0, 0);
+
+    loop {
+        channel.send(&SessionMessage::Pull { since_id: last_pulled_id })?;
+        let SessionMessage::Items { items, last_id, has_more } = channel.recv::<SessionMessage>()? else {
+            return Err("同步消息顺序错误".to_string());
+        };
+        received += store_items(app, items, device_id, SYNC_STATE_PENDING)?;
+        last_pulled_id = last_id;
+        if !has_more {
+            break;
+        }
+    }
+
+    loop {
+        let (items, last_id, has_more) = load_items_since(&conn, last_pushed_id, device_id)?;
+        if !items.is_empty() {
+            channel.send(&SessionMessage::Items { items, last_id, has_more })?;
+            let SessionMessage::Stored { added } = channel.recv::<SessionMessage>()? else {
+                return Err("同步消息顺序错误".to_string());
+            };
+            sent += added;
+        }
+        last_pushed_id = last_id;
+        if !has_more {
+            break;
+        }
+    }
+    let _ = channel.send(&SessionMessage::Done);
+
+    let (device_id, address) = (device_id.to_string(), address.to_string());
+    execute_write(app, move |_, conn| {
+        conn.execute(
+            "UPDATE sync_peers SET last_pulled_id = ?1, last_pushed_id = ?2, last_synced_at = ?3, address = ?4
+             WHERE device_id = ?5",
+            params![last_pulled_id, last_pushed_id, now_string(), address, device_id],
+        )
+        .map_err(|e| format!("保存同步进度失败: {}", e))
+    })?;
+    Ok((received, sent))
+}
+
+// 发现局域网中的设备，并更新已配对设备的地址
+fn discover_devices(app: &AppHandle) -> Result<Vec<DiscoveredDevice>, String> {
+    let own_device_id = get_device_id(&init_database(app)?)?;
+    let devices = discover(&own_device_id, DISCOVERY_TIMEOUT)?;
+    let updates: Vec<(String, String)> = devices.iter().map(|d| (d.device_id.clone(), d.address.clone())).collect();
+    execute_write(app, move |_, conn| {
+        for (device_id, address) in updates {
+            conn.execute("UPDATE sync_peers SET address = ?1 WHERE device_id = ?2", params![address, device_id])
+                .map_err(|e| format!("更新设备地址失败: {}", e))?;
+        }
+        Ok(())
+    })?;
+    Ok(devices)
+}
+
+// 与所有已知地址的已配对设备同步，单台设备失败不影响其他设备
+fn sync_all(app: &AppHandle) -> Result<LanSyncResult, String> {
+    if let Err(e) = discover_devices(app) {
+        println!("发现局域网设备失败，使用上次的地址同步: {}", e);
+    }
+    let peers = list_paired_devices(&open_connection(app, ConnectionRole::Analytics)?)?;
+    let mut result = LanSyncResult::default();
+    for peer in peers {
+        let Some(address) = peer.address.as_deref() else {
+            continue;
+        };
+        match sync_with_peer(app, &peer.device_id, address) {
+            Ok((received, sent)) => {
+                result.devices_synced += 1;
+                result.received += received;
+                result.sent += sent;
+            }
+            Err(e) => result.errors.push(format!("{}: {}", peer.name, e)),
+        }
+    }
+    if result.devices_synced > 0 {
+        println!("局域网同步完成: {:?}", result);
+    }
+    Ok(result)
+}
+
+// 启动同步服务：监听随机端口并通过 Bonjour 广播
+fn start_server(app: &AppHandle) -> Result<u16, String> {
+    let mut server = SERVER.lock().map_err(|_| "同步服务状态锁已失效".to_string())?;
+    if let Some(server) = server.as_ref() {
+        return Ok(server.port);
+    }
+
+    let listener = TcpListener::bind("0.0.0.0:0").map_err(|e| format!("启动同步服务失败: {}", e))?;
+    // 非阻塞接受连接，定期检查停止标志
+    listener.set_nonblocking(true).map_err(|e| format!("启动同步服务失败: {}", e))?;
+    let port = listener.local_addr().map_err(|e| format!("启动同步服务失败: {}", e))?.port();
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let app_handle = app.clone();
+    let stop_flag = Arc::clone(&stop);
+    std::thread::Builder::new()
+        .name("lan-sync".to_string())
+        .spawn(move || {
+            while !stop_flag.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, address)) => {
+                        let Some(slot) = HandshakeSlot::acquire() else {
+                            eprintln!("同步握手连接过多，已拒绝来自 {} 的连接", address);
+                            continue;
+                        };
+                        let app = app_handle.clone();
+                        std::thread::spawn(move || {
+                            let _ = stream.set_nonblocking(false);
+                            if let Err(e) = handle_connection(&app, stream, slot) {
+                                eprintln!("处理同步连接失败: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(200));
+                    }
+                    Err(e) => eprintln!("接受同步连接失败: {}", e),
+                }
+            }
+            println!("局域网同步服务已停止");
+        })
+        .map_err(|e| format!("启动同步服务失败: {}", e))?;
+
+    let device_id = get_device_id(&init_database(app)?)?;
+    let advertiser = match advertise(&device_name(), port, &device_id) {
+        Ok(child) => Some(child),
+        Err(e) => {
+            // 无法广播时对方仍可通过地址手动连接
+            eprintln!("{}", e);
+            None
+        }
+    };
+    *server = Some(LanSyncServer { port, stop, advertiser });
+    println!("局域网同步服务已启动，端口: {}", port);
+    Ok(port)
+}
+
+fn stop_server() {
+    if let Some(mut server) = SERVER.lock().ok().and_then(|mut server| server.take()) {
+        server.stop.store(true, Ordering::SeqCst);
+        if let Some(mut advertiser) = server.advertiser.take() {
+            let _ = advertiser.kill();
+            let _ = advertiser.wait();
+        }
+    }
+    if let Ok(mut code) = PAIRING_CODE.lock() {
+        *code = None;
+    }
+}
+
+fn server_port() -> Option<u16> {
+    SERVER.lock().ok()?.as_ref().map(|server| server.port)
+}
+
+// 启动时按设置开启同步服务，并定时与已配对设备同步
+pub fn start_lan_sync(app_handle: AppHandle) {
+    let enabled = init_database(&app_handle)
+        .ok()
+        .and_then(|conn| get_settings(&conn).ok())
+        .is_some_and(|settings| settings.lan_sync_enabled);
+    if enabled {
+        if let Err(e) = start_server(&app_handle) {
+            eprintln!("{}", e);
+        }
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(SYNC_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if server_port().is_none() {
+                continue;
+            }
+            let app = app_handle.clone();
+            match tauri::async_runtime::spawn_blocking(move || sync_all(&app)).await {
+                Ok(Ok(result)) => {
+                    for error in result.errors {
+                        eprintln!("局域网同步失败: {}", error);
+                    }
+                }
+                Ok(Err(e)) => eprintln!("局域网同步失败: {}", e),
+                Err(e) => eprintln!("局域网同步任务失败: {}", e),
+            }
+        }
+    });
+}
+
+// 获取局域网同步状态命令
+#[tauri::command]
+pub async fn get_lan_sync_status(app: AppHandle) -> Result<LanSyncStatus, OicopyError> {
+    let conn = init_database(&app)?;
+    let pairing_code = PAIRING_CODE
+        .lock()
+        .ok()
+        .and_then(|code| code.clone())
+        .filter(|(_, created)| created.elapsed() < PAIRING_CODE_TTL)
+        .map(|(code, _)| code);
+    Ok(LanSyncStatus {
+        enabled: get_settings(&conn)?.lan_sync_enabled,
+        device_id: get_device_id(&conn)?,
+        device_name: device_name(),
+        port: server_port(),
+        pairing_code,
+        paired_devices: list_paired_devices(&conn)?,
+    })
+}
+
+// 开启或关闭局域网同步命令
+#[tauri::command]
+pub async fn set_lan_sync_enabled(app: AppHandle, enabled: bool) -> Result<LanSyncStatus, OicopyError> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let mut settings = get_settings(&conn)?;
+    settings.lan_sync_enabled = enabled;
+    save_settings(&conn, &settings)?;
+    drop(conn);
+
+    if enabled {
+        start_server(&app).map_err(OicopyError::Io)?;
+    } else {
+        stop_server();
+    }
+    get_lan_sync_status(app).await
+}
+
+// 发现局域网中的设备命令
+#[tauri::command]
+pub async fn discover_lan_devices(app: AppHandle) -> Result<Vec<DiscoveredDevice>, OicopyError> {
+    let devices = tauri::async_runtime::spawn_blocking(move || discover_devices(&app))
+        .await
+        .map_err(|e| format!("发现设备任务失败: {}", e))??;
+    Ok(devices)
+}
+
+// 生成配对码命令：在对方设备上输入该配对码完成配对
+#[tauri::command]
+pub async fn start_lan_pairing() -> Result<String, OicopyError> {
+    if server_port().is_none() {
+        return Err(OicopyError::Validation("请先开启局域网同步".to_string()));
+    }
+    let code = generate_pairing_code()?;
+    *PAIRING_CODE.lock().map_err(|_| "配对状态锁已失效".to_string())? = Some((code.clone(), Instant::now()));
+    Ok(code)
+}
+
+// 与指定地址（host:port）的设备配对命令，code 为对方设备上显示的配对码
+#[tauri::command]
+pub async fn pair_lan_device(app: AppHandle, address: String, code: String) -> Result<PairedDevice, OicopyError> {
+    let code = code.trim().to_uppercase();
+    if code.len() != PAIRING_CODE_LEN {
+        return Err(OicopyError::Validation("配对码格式错误".to_string()));
+    }
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let local = local_device(&conn)?;
+    drop(conn);
+
+    let app_handle = app.clone();
+    let device = tauri::async_runtime::spawn_blocking(move || {
+        let mut channel = connect(&address)?;
+        let (device_id, name, secret) =
+            client_handshake(&mut channel, &local, true, |_, client_nonce| Ok(pairing_key(&code, client_nonce).to_vec()))?;
+        if secret.len() != KEY_SIZE {
+            return Err("配对失败: 共享密钥无效".to_string());
+        }
+        save_peer(&app_handle, device_id.clone(), name.clone(), secret, Some(address.clone()))?;
+        Ok(PairedDevice { device_id, name, address: Some(address), last_synced_at: None })
+    })
+    .await
+    .map_err(|e| format!("配对任务失败: {}", e))??;
+    Ok(device)
+}
+
+// 发送到设备命令：将一条记录立即发送到已配对的设备并写入对方的剪贴板，不同步其他历史记录
+#[tauri::command]
+pub async fn send_item_to_device(app: AppHandle, id: i64, device_id: String) -> Result<(), OicopyError> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let item = load_send_item(&conn, id)?;
+    drop(conn);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let find_address = |app: &AppHandle| -> Result<Option<String>, String> {
+            let conn = open_connection(app, ConnectionRole::Analytics)?;
+            Ok(list_paired_devices(&conn)?
+                .into_iter()
+                .find(|peer| peer.device_id == device_id)
+                .ok_or_else(|| "设备未配对".to_string())?
+                .address)
+        };
+        // 没有记录地址时先发现一次设备
+        let address = match find_address(&app)? {
+            Some(address) => address,
+            None => {
+                discover_devices(&app)?;
+                find_address(&app)?.ok_or_else(|| "找不到该设备，请确认对方已开启局域网同步".to_string())?
+            }
+        };
+
+        let mut channel = open_session(&init_database(&app)?, &address, &device_id)?;
+        channel.send(&SessionMessage::Send { item })?;
+        let result = match channel.recv::<SessionMessage>()? {
+            SessionMessage::Delivered => Ok(()),
+            SessionMessage::Failed { reason } => Err(reason),
+            _ => Err("同步消息顺序错误".to_string()),
+        };
+        let _ = channel.send(&SessionMessage::Done);
+        result
+    })
+    .await
+    .map_err(|e| format!("发送任务失败: {}", e))??;
+    Ok(())
+}
+
+// 取消配对命令
+#[tauri::command]
+pub async fn unpair_lan_device(app: AppHandle, device_id: String) -> Result<(), OicopyError> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    conn.execute("DELETE FROM sync_peers WHERE device_id = ?1", params![device_id])
+        .map_err(|e| format!("取消配对失败: {}", e))?;
+    Ok(())
+}
+
+// 立即与已配对设备同步命令
+#[tauri::command]
+pub async fn sync_lan_now(app: AppHandle) -> Result<LanSyncResult, OicopyError> {
+    ensure_unlocked(&init_database(&app)?)?;
+    let result = tauri::async_runtime::spawn_blocking(move || sync_all(&app))
+        .await
+        .map_err(|e| format!("同步任务失败: {}", e))??;
+    Ok(result)
+}
@@ -1,21 +1,116 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+mod accessibility;
+mod automation;
+mod backup;
+mod change_log;
+mod classifier;
+mod cleanup_schedule;
+mod clipboard_content;
 mod clipboard_management;
+mod cloud_sync;
+mod color;
+mod content_formats;
+pub mod control_socket;
 mod db;
+mod db_encryption;
+mod db_integrity;
+mod db_location;
+mod db_writer;
+mod deep_link;
+mod digest;
+mod disk_guard;
+mod error;
+mod expiry;
+mod export;
+mod feedback;
+mod http;
+mod image_store;
+mod item_actions;
+mod lan_discovery;
+mod lan_sync;
+mod local_api;
+mod lock_mode;
+mod markdown;
+mod notifications;
 mod panel_window;
+mod paste;
+mod pasteboard;
+mod pattern_packs;
+mod permissions;
+mod print;
+mod private_mode;
+mod profiles;
+mod quick_capture;
+mod retention;
+mod rtf;
+mod screen_lock;
+mod search_ranking;
+mod sensitive;
 mod settings;
+mod shortcut;
+mod sync_crypto;
+mod timeline;
+mod tracking_params;
+mod trash;
+mod url_metadata;
 mod app_info;
+#[cfg(target_os = "linux")]
+mod app_info_linux;
+#[cfg(target_os = "windows")]
+mod app_info_windows;
 mod performance_optimization;
+mod updater;
 #[cfg(debug_assertions)]
 pub mod test_db;
+#[cfg(debug_assertions)]
+mod fixture_replay;
 
-use crate::clipboard_management::{get_clipboard_history, setup_clipboard_monitor};
-use crate::panel_window::{setup_panel_window, open_panel_window, hide_panel_window, toggle_panel_window};
-use crate::settings::{get_app_settings, save_app_settings, cleanup_old_history_command, clear_all_history_command, get_data_count, emit_data_cleared_event};
-use crate::app_info::{get_current_app_info, get_app_icon_by_bundle_id};
-use crate::db::{get_database_stats, perform_maintenance, cleanup_by_limit, cleanup_by_size, perform_smart_cleanup, analyze_database_performance, DatabaseStats, MaintenanceResult, SmartCleanupResult, PerformanceAnalysis};
-use tauri::{Manager, AppHandle, Wry, WindowEvent};
-use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut, ShortcutState};
-use tauri::menu::{Menu, MenuItem};
+use crate::clipboard_management::{get_clipboard_history, get_clipboard_history_page, list_source_apps, search_clipboard_history, setup_clipboard_monitor, edit_item_content, redact_item, get_current_clipboard_summary, reorder_pinned, pin_clipboard_item, unpin_clipboard_item, get_clipboard_at, restore_clipboard_at, copy_item_to_clipboard, copy_color_as, paste_item, paste_as_markdown, get_item_html, paste_and_clear, get_item_full_content, get_clipboard_item_content, is_monitoring_paused, set_monitoring_paused, copy_item_by_id, delete_clipboard_item, delete_clipboard_items, delete_history_by_filter, paste_item_from_shortcut, add_tag_to_item, remove_tag_from_item, list_tags, set_item_note};
+use crate::expiry::set_item_expiry;
+use crate::panel_window::{setup_panel_window, open_panel_window, hide_panel_window, toggle_panel_window, open_quick_capture_window, get_panel_layout, set_panel_layout};
+use crate::settings::{get_app_settings, save_app_settings, get_content_priority, set_content_priority, get_analysis_config, set_analysis_config, apply_saved_analysis_config, get_cleanup_limits, set_cleanup_limits, get_cleanup_schedule, set_cleanup_schedule, get_device_id_command, get_excluded_apps_command, add_excluded_app_command, remove_excluded_app_command, cleanup_old_history_command, clear_all_history_command, get_data_count, emit_data_cleared_event};
+use crate::app_info::{get_current_app_info, get_app_icon_by_bundle_id, get_app_icons, refresh_app_icons};
+use crate::export::{export_images, export_html_report, export_history, import_history};
+use crate::timeline::get_capture_timeline;
+use crate::retention::{default_policies, RetentionPolicy};
+use crate::trash::{get_trash_items, restore_clipboard_item, empty_trash, purge_trash};
+use crate::digest::get_daily_digest;
+use crate::disk_guard::{get_capture_diagnostics, start_disk_space_monitor};
+use crate::print::print_item;
+use crate::profiles::{current_profiles, list_profiles, create_profile, switch_profile};
+use crate::private_mode::{get_private_mode, set_private_mode, is_private_mode_active};
+use crate::quick_capture::create_manual_item;
+#[cfg(debug_assertions)]
+use crate::fixture_replay::replay_fixture;
+use crate::change_log::{emit_change_event, get_changes_since, prune_change_log};
+use crate::cleanup_schedule::{system_idle_seconds, CleanupSchedule};
+use crate::lock_mode::{ensure_unlocked, get_lock_status, lock_history, unlock_history};
+use crate::item_actions::{get_item_actions, open_item_url, reveal_item_in_finder};
+use crate::shortcut::{get_shortcut, set_shortcut, is_panel_shortcut, register_saved_shortcut, item_for_shortcut, register_item_shortcuts, bind_item_shortcut, get_item_shortcuts};
+use crate::backup::{get_backup_config, set_backup_config, list_backups, create_backup_now, restore_from_backup, start_backup_scheduler};
+use crate::accessibility::{get_accessibility_settings, start_accessibility_watcher};
+use crate::content_formats::prune_orphan_content_formats;
+use crate::image_store::{prune_orphan_blobs, start_inline_image_migration};
+use crate::permissions::{get_feature_permissions, open_permission_settings};
+use crate::updater::{check_for_updates, start_update_checker};
+use crate::notifications::{notify, NotificationEvent};
+use crate::pattern_packs::{start_pattern_pack_watcher, update_pattern_pack, get_pattern_pack_info, get_analysis_rules, save_analysis_rules};
+use crate::clipboard_content::ContentKind;
+use crate::db::{init_database, setup_database_pool, get_recent_previews, get_database_stats, perform_maintenance, cleanup_by_limit, cleanup_by_size, list_largest_items, delete_items_over_size, perform_smart_cleanup, analyze_database_performance, DatabaseStats, MaintenanceResult, SmartCleanupResult, PerformanceAnalysis, LargeItem};
+use crate::db_integrity::{check_integrity, stage_recovery, IntegrityReport, RecoveryResult};
+use crate::db_location::{get_database_path, set_database_path};
+use crate::cloud_sync::{get_cloud_sync_status, set_cloud_sync_config, cloud_sync_push_now, cloud_sync_pull_now, start_cloud_sync};
+use crate::local_api::{get_local_api_status, set_local_api_config, regenerate_local_api_token, start_local_api};
+use crate::control_socket::start_control_socket;
+use crate::screen_lock::start_screen_lock_watch;
+use crate::deep_link::{handle_deep_link, handle_launch_args};
+use crate::lan_sync::{get_lan_sync_status, set_lan_sync_enabled, discover_lan_devices, start_lan_pairing, pair_lan_device, unpair_lan_device, sync_lan_now, send_item_to_device, start_lan_sync};
+use crate::db_writer::{execute_write, start_database_writer, submit_write};
+use crate::error::OicopyError;
+use tauri::{Manager, AppHandle, Listener, Wry, WindowEvent};
+use tauri_plugin_global_shortcut::ShortcutState;
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::tray::{MouseButton, TrayIconBuilder, TrayIconEvent};
 
 // 处理系统托盘事件
@@ -44,7 +139,7 @@ fn handle_tray_event(app: &AppHandle<Wry>, event: TrayIconEvent) {
                 Err(e) => {
                     println!("Failed to open copy panel from tray: {}", e);
                     // 如果权限不足，打开权限设置窗口
-                    if e.contains("权限不足") {
+                    if matches!(e, OicopyError::Permission(_)) {
                         let _ = open_panel_window(app.clone(), "check-permissions".to_string());
                     }
                 }
@@ -56,12 +151,176 @@ fn handle_tray_event(app: &AppHandle<Wry>, event: TrayIconEvent) {
 
 // 创建系统托盘菜单
 fn create_tray_menu(app: &AppHandle<Wry>) -> Result<Menu<Wry>, Box<dyn std::error::Error>> {
+    let pause_item = CheckMenuItem::with_id(
+        app,
+        "toggle_pause",
+        "暂停监听",
+        true,
+        is_monitoring_paused(app),
+        None::<&str>,
+    )?;
+    let recent_menu = Submenu::with_id(app, "recent", "最近复制", true)?;
+    let profile_menu = Submenu::with_id(app, "profile", "配置", true)?;
+    let new_snippet_item = MenuItem::with_id(app, "new_snippet", "新建片段…", true, None::<&str>)?;
     let settings_item = MenuItem::with_id(app, "settings", "设置", true, None::<&str>)?;
     let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&settings_item, &quit_item])?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &new_snippet_item,
+            &recent_menu,
+            &pause_item,
+            &PredefinedMenuItem::separator(app)?,
+            &profile_menu,
+            &settings_item,
+            &quit_item,
+        ],
+    )?;
+    // 保存菜单项引用，切换暂停状态后同步勾选状态
+    app.manage(PauseMenuItem(pause_item));
+    app.manage(RecentSubmenu(recent_menu));
+    app.manage(ProfileSubmenu(profile_menu));
+    refresh_recent_menu(app);
+    refresh_profile_menu(app);
     Ok(menu)
 }
 
+// 配置菜单项的 ID 前缀，后接配置名
+const PROFILE_ITEM_PREFIX: &str = "profile:";
+
+// 托盘菜单中的“配置”子菜单，标题显示当前配置
+struct ProfileSubmenu(Submenu<Wry>);
+
+// 用已有的配置重建“配置”子菜单，勾选当前配置
+fn refresh_profile_menu(app: &AppHandle<Wry>) {
+    let Some(submenu) = app.try_state::<ProfileSubmenu>() else {
+        return;
+    };
+    let (names, active) = match current_profiles(app) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            eprintln!("刷新托盘配置菜单失败: {}", e);
+            return;
+        }
+    };
+
+    let _ = submenu.0.set_text(format!("配置：{}", active));
+    if let Ok(items) = submenu.0.items() {
+        for item in items {
+            let _ = submenu.0.remove(&item);
+        }
+    }
+    for name in names {
+        let item_id = format!("{}{}", PROFILE_ITEM_PREFIX, name);
+        match CheckMenuItem::with_id(app, item_id, &name, true, name == active, None::<&str>) {
+            Ok(item) => {
+                let _ = submenu.0.append(&item);
+            }
+            Err(e) => eprintln!("创建托盘菜单项失败: {}", e),
+        }
+    }
+}
+
+// 托盘菜单中“最近复制”子菜单显示的记录数
+const TRAY_RECENT_ITEMS: u32 = 10;
+// 子菜单中预览文字的最大字符数
+const TRAY_PREVIEW_CHARS: usize = 40;
+// 最近记录菜单项的 ID 前缀，后接记录 ID
+const RECENT_ITEM_PREFIX: &str = "recent:";
+
+// 托盘菜单中的“最近复制”子菜单
+struct RecentSubmenu(Submenu<Wry>);
+
+// 取预览的第一行非空文字作为菜单标题，过长时截断
+fn tray_item_title(kind: ContentKind, preview: &str) -> String {
+    let line = preview.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("");
+    if line.is_empty() {
+        return format!("[{}]", kind.label());
+    }
+    if line.chars().count() > TRAY_PREVIEW_CHARS {
+        let truncated: String = line.chars().take(TRAY_PREVIEW_CHARS).collect();
+        format!("{}…", truncated)
+    } else {
+        line.to_string()
+    }
+}
+
+// 用最新的历史记录重建“最近复制”子菜单
+fn refresh_recent_menu(app: &AppHandle<Wry>) {
+    let Some(submenu) = app.try_state::<RecentSubmenu>() else {
+        return;
+    };
+    let recent = match init_database(app).and_then(|conn| get_recent_previews(&conn, TRAY_RECENT_ITEMS)) {
+        Ok(recent) => recent,
+        Err(e) => {
+            eprintln!("刷新托盘最近记录失败: {}", e);
+            return;
+        }
+    };
+
+    if let Ok(items) = submenu.0.items() {
+        for item in items {
+            let _ = submenu.0.remove(&item);
+        }
+    }
+    if recent.is_empty() {
+        if let Ok(empty_item) = MenuItem::with_id(app, "recent_empty", "暂无记录", false, None::<&str>) {
+            let _ = submenu.0.append(&empty_item);
+        }
+        return;
+    }
+    for (id, kind, preview) in recent {
+        let item_id = format!("{}{}", RECENT_ITEM_PREFIX, id);
+        match MenuItem::with_id(app, item_id, tray_item_title(kind, &preview), true, None::<&str>) {
+            Ok(item) => {
+                let _ = submenu.0.append(&item);
+            }
+            Err(e) => eprintln!("创建托盘菜单项失败: {}", e),
+        }
+    }
+}
+
+// 托盘菜单中的“暂停监听”菜单项
+struct PauseMenuItem(CheckMenuItem<Wry>);
+
+// 根据暂停监听和隐私模式状态更新托盘图标、提示文字和菜单勾选状态
+fn update_tray_status(app: &AppHandle<Wry>) {
+    let paused = is_monitoring_paused(app);
+    let private = is_private_mode_active();
+    if let Some(item) = app.try_state::<PauseMenuItem>() {
+        let _ = item.0.set_checked(paused);
+    }
+
+    let Some(tray) = app.tray_by_id("main-tray") else {
+        return;
+    };
+    let tooltip = if paused {
+        "OiCopy - 剪贴板管理器（已暂停监听）"
+    } else if private {
+        "OiCopy - 剪贴板管理器（隐私模式）"
+    } else {
+        "OiCopy - 剪贴板管理器"
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+
+    // 暂停或隐私模式时使用半透明图标
+    if let Some(icon) = app.default_window_icon() {
+        let icon = if paused || private {
+            let rgba: Vec<u8> = icon
+                .rgba()
+                .chunks(4)
+                .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3] / 3])
+                .collect();
+            Image::new_owned(rgba, icon.width(), icon.height())
+        } else {
+            icon.clone().to_owned()
+        };
+        if let Err(e) = tray.set_icon(Some(icon)) {
+            eprintln!("更新托盘图标失败: {}", e);
+        }
+    }
+}
+
 // 处理托盘菜单事件
 fn handle_menu_event(app: &AppHandle<Wry>, event: tauri::menu::MenuEvent) {
     match event.id().as_ref() {
@@ -72,29 +331,58 @@ fn handle_menu_event(app: &AppHandle<Wry>, event: tauri::menu::MenuEvent) {
                 let _ = settings_window.set_focus();
             }
         }
+        "new_snippet" => {
+            // 打开快速记录窗口，输入的内容直接保存到历史记录
+            if let Err(e) = open_quick_capture_window(app) {
+                eprintln!("{}", e);
+            }
+        }
+        "toggle_pause" => {
+            let paused = !is_monitoring_paused(app);
+            if let Err(e) = set_monitoring_paused(app, paused) {
+                eprintln!("切换剪贴板监听状态失败: {}", e);
+            }
+            update_tray_status(app);
+        }
         "quit" => {
             println!("Quitting application...");
             app.exit(0);
         }
+        id if id.starts_with(PROFILE_ITEM_PREFIX) => {
+            // 点击配置：切换到该配置，完成后重建菜单（点击会改变勾选状态，切换失败时需要恢复）
+            let app = app.clone();
+            let name = id[PROFILE_ITEM_PREFIX.len()..].to_string();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = switch_profile(app.clone(), name).await {
+                    eprintln!("切换配置失败: {}", e);
+                }
+                refresh_profile_menu(&app);
+            });
+        }
+        id if id.starts_with(RECENT_ITEM_PREFIX) => {
+            // 点击最近记录：写回剪贴板
+            if let Ok(item_id) = id[RECENT_ITEM_PREFIX.len()..].parse::<i64>() {
+                if let Err(e) = copy_item_by_id(app, item_id) {
+                    eprintln!("从托盘复制记录失败: {}", e);
+                }
+            }
+        }
         _ => {}
     }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default()
         .plugin(
+            // 快捷键在 setup 中按设置注册，可通过 set_shortcut 修改
             tauri_plugin_global_shortcut::Builder::new()
-                .with_shortcuts(["command+shift+v"])
-                .expect("REASON")
                 .with_handler(move |app, shortcut, event| {
-                    let csv_shortcut =
-                        Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyV);
                     println!("{:?}", shortcut);
-                    if shortcut == &csv_shortcut {
+                    if is_panel_shortcut(shortcut) {
                         match event.state() {
                             ShortcutState::Pressed => {
-                                println!("Command+Shift+V Pressed!");
+                                println!("Panel shortcut {} Pressed!", shortcut.into_string());
                                 
                                 // 首先检查 check-permissions 是否正在显示
                                 if let Some(setting_win) = app.app_handle().get_webview_window("check-permissions") {
@@ -114,7 +402,7 @@ pub fn run() {
                                     Err(e) => {
                                         println!("Failed to open copy panel via shortcut: {}", e);
                                         // 如果权限不足，打开权限设置窗口
-                                        if e.contains("权限") {
+                                        if matches!(e, OicopyError::Permission(_)) {
                                             println!("Opening check-permissions due to permission issues");
                                             let _ = open_panel_window(app.app_handle().clone(), "check-permissions".to_string());
                                         }
@@ -122,17 +410,33 @@ pub fn run() {
                                 }
                             }
                             ShortcutState::Released => {
-                                println!("Command+Shift+V Released!");
+                                println!("Panel shortcut {} Released!", shortcut.into_string());
                             }
                         }
+                    } else if let Some(item_id) = item_for_shortcut(shortcut) {
+                        // 记录快捷键：松开按键后再粘贴，避免修饰键仍按下时影响模拟的 Cmd+V
+                        if event.state() == ShortcutState::Released {
+                            let app_handle = app.app_handle().clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = paste_item_from_shortcut(&app_handle, item_id).await {
+                                    eprintln!("通过快捷键粘贴记录 {} 失败: {}", item_id, e);
+                                }
+                            });
+                        }
                     }
                 })
                 .build(),
         )
         .plugin(tauri_plugin_macos_permissions::init())
         .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_clipboard::init())
-        .plugin(tauri_nspanel::init()) // 将 NSPanel 插件放在最后初始化
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard::init());
+
+    // 将 NSPanel 插件放在最后初始化，其他平台使用普通的无边框窗口
+    #[cfg(target_os = "macos")]
+    let builder = builder.plugin(tauri_nspanel::init());
+
+    builder
         .on_window_event(|window, event| {
             // 处理窗口事件并记录所有窗口的状态
             match event {
@@ -169,6 +473,24 @@ pub fn run() {
         .setup(|app| {
             // 立即隐藏 Dock 图标，只在系统托盘显示
             let _ = app.set_dock_visibility(false);
+
+            // 初始化数据库连接池（建表和迁移只在这里执行一次）
+            setup_database_pool(&app.app_handle())?;
+            // 启动数据库写入线程，捕获保存和定时清理都在该线程上执行
+            start_database_writer(&app.app_handle())?;
+            // 为旧记录补充内容类别和 RTF 文本预览
+            submit_write(&app.app_handle(), |_, conn| {
+                match crate::db::backfill_categories(conn) {
+                    Ok(updated) if updated > 0 => println!("已为 {} 条旧记录补充内容类别", updated),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("{}", e),
+                }
+                match crate::db::backfill_rtf_previews(conn) {
+                    Ok(updated) if updated > 0 => println!("已为 {} 条 RTF 记录生成文本预览", updated),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("{}", e),
+                }
+            })?;
             
             // 创建系统托盘菜单
             let tray_menu = create_tray_menu(&app.app_handle())
@@ -185,6 +507,29 @@ pub fn run() {
                 })
                 .build(app)
                 .expect("Failed to create tray icon");
+            update_tray_status(&app.app_handle());
+
+            // 隐私模式开启或结束时更新托盘状态
+            let tray_handle = app.app_handle().clone();
+            app.listen("private-mode-changed", move |_| {
+                update_tray_status(&tray_handle);
+            });
+
+            // 切换或新建配置时刷新托盘“配置”子菜单
+            for event in ["profile-changed", "profiles-updated"] {
+                let profile_handle = app.app_handle().clone();
+                app.listen(event, move |_| {
+                    refresh_profile_menu(&profile_handle);
+                });
+            }
+
+            // 历史记录变化时刷新托盘“最近复制”子菜单
+            for event in ["clipboard-updated", "data-cleared"] {
+                let recent_handle = app.app_handle().clone();
+                app.listen(event, move |_| {
+                    refresh_recent_menu(&recent_handle);
+                });
+            }
             
             // 只在 macOS 下初始化 NSPanel，并且只对 copy-panel 窗口
             #[cfg(target_os = "macos")]
@@ -205,11 +550,60 @@ pub fn run() {
                 
                 let _ = setup_panel_window(&app.app_handle());
             }
+            // 注册唤出面板的全局快捷键
+            if let Err(e) = register_saved_shortcut(&app.app_handle()) {
+                eprintln!("{}", e);
+            }
+            // 注册记录快捷键（须在唤出面板快捷键之后，以便检测冲突）
+            if let Err(e) = register_item_shortcuts(&app.app_handle()) {
+                eprintln!("{}", e);
+            }
+            
+            // 加载检测模式包并监听其变化
+            start_pattern_pack_watcher(app.app_handle().clone());
+            // 应用保存的 HTML 分析配置
+            if let Err(e) = apply_saved_analysis_config(&app.app_handle()) {
+                eprintln!("加载分析配置失败: {}", e);
+            }
+            
+            // 将旧版本保存在数据库中的图片迁移为文件
+            start_inline_image_migration(app.app_handle().clone());
+            
             let _ = setup_clipboard_monitor(app.app_handle().clone()).ok();
             
             // 启动定时清理任务
             start_cleanup_scheduler(app.app_handle().clone());
             
+            // 启动定时备份任务
+            start_backup_scheduler(app.app_handle().clone());
+            
+            // 按设置开启局域网同步服务
+            start_lan_sync(app.app_handle().clone());
+            
+            // 启动云同步定时任务（未开启时不会访问网络）
+            start_cloud_sync(app.app_handle().clone());
+            
+            // 按设置开启本机 HTTP/WebSocket 接口
+            start_local_api(app.app_handle().clone());
+            
+            // 启动命令行工具使用的控制 socket
+            start_control_socket(app.app_handle().clone());
+            
+            // 检测锁屏和睡眠，按设置暂停监听或删除最近的记录
+            start_screen_lock_watch(app.app_handle().clone());
+            
+            // 处理启动参数中的 oicopy:// 链接
+            handle_launch_args(app.app_handle());
+            
+            // 定期检查磁盘剩余空间，不足时暂停保存大内容
+            start_disk_space_monitor(app.app_handle().clone());
+            
+            // 启动后台更新检查
+            start_update_checker(app.app_handle().clone());
+            
+            // 监听系统辅助功能显示设置变化
+            start_accessibility_watcher(app.app_handle().clone());
+            
             Ok(())
             // let app_handler = app.app_handle();
             // // 这里调用一次即可
@@ -235,39 +629,181 @@ pub fn run() {
             hide_panel_window,
             toggle_panel_window,
             get_clipboard_history,
+            get_clipboard_history_page,
+            list_source_apps,
+            search_clipboard_history,
+            add_tag_to_item,
+            remove_tag_from_item,
+            list_tags,
+            set_item_note,
+            set_item_expiry,
+            get_item_full_content,
+            get_clipboard_item_content,
+            get_item_actions,
+            get_lock_status,
+            lock_history,
+            unlock_history,
+            open_item_url,
+            reveal_item_in_finder,
+            pin_clipboard_item,
+            unpin_clipboard_item,
+            delete_clipboard_item,
+            delete_clipboard_items,
+            delete_history_by_filter,
+            get_trash_items,
+            restore_clipboard_item,
+            empty_trash,
+            get_clipboard_at,
+            restore_clipboard_at,
+            get_capture_timeline,
+            get_daily_digest,
+            get_capture_diagnostics,
+            print_item,
+            get_changes_since,
+            get_private_mode,
+            set_private_mode,
+            create_manual_item,
+            get_shortcut,
+            set_shortcut,
+            bind_item_shortcut,
+            get_item_shortcuts,
+            get_accessibility_settings,
+            copy_item_to_clipboard,
+            copy_color_as,
+            paste_item,
+            paste_as_markdown,
+            get_item_html,
+            paste_and_clear,
+            edit_item_content,
+            redact_item,
+            get_current_clipboard_summary,
+            reorder_pinned,
+            export_images,
+            export_html_report,
+            export_history,
+            import_history,
+            update_pattern_pack,
+            get_pattern_pack_info,
+            get_analysis_rules,
+            save_analysis_rules,
+            check_for_updates,
             get_app_settings,
             save_app_settings,
+            get_content_priority,
+            set_content_priority,
+            get_analysis_config,
+            set_analysis_config,
+            get_cleanup_limits,
+            set_cleanup_limits,
+            get_cleanup_schedule,
+            set_cleanup_schedule,
+            get_panel_layout,
+            set_panel_layout,
+            get_device_id_command,
+            get_excluded_apps_command,
+            add_excluded_app_command,
+            remove_excluded_app_command,
             cleanup_old_history_command,
             clear_all_history_command,
             get_data_count,
             emit_data_cleared_event,
             get_current_app_info,
             get_app_icon_by_bundle_id,
+            get_app_icons,
+            refresh_app_icons,
+            get_feature_permissions,
+            open_permission_settings,
             get_database_statistics,
             perform_database_maintenance,
             cleanup_database_by_limit,
             cleanup_database_by_size,
+            get_largest_items,
+            delete_items_larger_than,
+            check_database_integrity,
+            repair_database,
+            get_backup_config,
+            set_backup_config,
+            list_backups,
+            create_backup_now,
+            restore_from_backup,
+            get_database_path,
+            set_database_path,
+            list_profiles,
+            create_profile,
+            switch_profile,
+            get_lan_sync_status,
+            set_lan_sync_enabled,
+            discover_lan_devices,
+            start_lan_pairing,
+            pair_lan_device,
+            unpair_lan_device,
+            sync_lan_now,
+            send_item_to_device,
+            get_cloud_sync_status,
+            set_cloud_sync_config,
+            cloud_sync_push_now,
+            cloud_sync_pull_now,
+            get_local_api_status,
+            set_local_api_config,
+            regenerate_local_api_token,
             perform_smart_cleanup_command,
             analyze_database_performance_command,
-            test_database_optimization_command
+            test_database_optimization_command,
+            replay_fixture
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, _event| {
+            // macOS 通过系统事件传入 oicopy:// 链接
+            #[cfg(target_os = "macos")]
+            {
+                if let tauri::RunEvent::Opened { urls } = _event {
+                    for url in urls {
+                        handle_deep_link(_app, url.as_str());
+                    }
+                }
+            }
+        });
 }
 
 // 启动定时清理任务
 fn start_cleanup_scheduler(app_handle: AppHandle<Wry>) {
+    use chrono::Timelike;
+
     tauri::async_runtime::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600)); // 每小时执行一次
+        // 每分钟检查一次计划，设置修改后无需重启即可生效；因免打扰或用户忙碌跳过的清理会在之后补做
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        let mut last_run = std::time::Instant::now();
         
         loop {
             interval.tick().await;
+
+            // 过期记录每次检查都删除，不受清理间隔、免打扰和空闲条件限制
+            let handle = app_handle.clone();
+            let _ = tauri::async_runtime::spawn_blocking(move || crate::expiry::purge_expired(&handle)).await;
+            
+            let Some(schedule) = init_database(&app_handle)
+                .ok()
+                .and_then(|conn| crate::db::get_settings(&conn).ok())
+                .map(|settings| CleanupSchedule::from_settings(&settings))
+            else {
+                continue;
+            };
+            if last_run.elapsed().as_secs() < schedule.interval_minutes as u64 * 60 {
+                continue;
+            }
+            let hour = chrono::Local::now().hour() as i64;
+            if let Some(reason) = schedule.blocked_reason(hour, system_idle_seconds()) {
+                println!("暂不执行自动清理: {}", reason);
+                continue;
+            }
+            last_run = std::time::Instant::now();
             
             // 执行清理任务
             match perform_auto_cleanup(&app_handle).await {
                 Ok(deleted_count) => {
                     if deleted_count > 0 {
-                        println!("自动清理了 {} 条过期记录", deleted_count);
+                        notify(&app_handle, NotificationEvent::CleanupCompleted { deleted: deleted_count });
                     }
                 }
                 Err(e) => {
@@ -278,67 +814,165 @@ fn start_cleanup_scheduler(app_handle: AppHandle<Wry>) {
     });
 }
 
-// 执行自动清理
+// 执行自动清理：交给数据库写入线程执行，不与捕获保存争用写锁
 async fn perform_auto_cleanup(app_handle: &AppHandle<Wry>) -> Result<usize, String> {
-    use crate::db::{init_database, get_settings, cleanup_old_history};
-    
-    let conn = init_database(app_handle)?;
-    let settings = get_settings(&conn)?;
-    cleanup_old_history(&conn, settings.retention_days)
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        execute_write(&app_handle, |_, conn| run_auto_cleanup(conn))
+    })
+    .await
+    .map_err(|e| format!("自动清理任务失败: {}", e))?
+}
+
+fn run_auto_cleanup(conn: &rusqlite::Connection) -> Result<usize, String> {
+    use crate::db::{get_settings, cleanup_old_history, prune_orphan_item_tags};
+
+    let settings = get_settings(conn)?;
+    let mut deleted = cleanup_old_history(conn, settings.retention_days)?;
+
+    // 按设置的条数和大小上限清理（置顶项除外），0 表示不限制
+    if settings.cleanup_max_records > 0 {
+        deleted += cleanup_by_limit(conn, settings.cleanup_max_records)?;
+    }
+    if settings.cleanup_max_size_mb > 0.0 {
+        deleted += cleanup_by_size(conn, settings.cleanup_max_size_mb)?;
+    }
+
+    // 永久删除回收站中超过保留期的记录
+    match purge_trash(conn, settings.trash_retention_days) {
+        Ok(purged) if purged > 0 => println!("已从回收站永久删除 {} 条记录", purged),
+        Ok(_) => {}
+        Err(e) => eprintln!("{}", e),
+    }
+
+    // 其他格式可能引用图片文件，需先于图片文件清理
+    if let Err(e) = prune_orphan_content_formats(conn) {
+        eprintln!("{}", e);
+    }
+
+    // 清理后删除不再被引用的图片文件
+    if let Err(e) = prune_orphan_blobs(conn) {
+        eprintln!("清理图片文件失败: {}", e);
+    }
+    if let Err(e) = prune_change_log(conn) {
+        eprintln!("{}", e);
+    }
+    if let Err(e) = prune_orphan_item_tags(conn) {
+        eprintln!("{}", e);
+    }
+    Ok(deleted)
 }
 
 // Tauri命令：获取数据库统计信息
 #[tauri::command]
-async fn get_database_statistics(app: AppHandle) -> Result<DatabaseStats, String> {
-    use crate::db::init_database;
+async fn get_database_statistics(app: AppHandle) -> Result<DatabaseStats, OicopyError> {
+    use crate::db::{open_connection, ConnectionRole};
     
-    let conn = init_database(&app)?;
+    // 统计查询使用只读连接，不阻塞剪贴板捕获写入
+    let conn = open_connection(&app, ConnectionRole::Analytics)?;
     get_database_stats(&conn)
 }
 
 // Tauri命令：执行数据库维护
 #[tauri::command]
-async fn perform_database_maintenance(app: AppHandle) -> Result<MaintenanceResult, String> {
+async fn perform_database_maintenance(app: AppHandle) -> Result<MaintenanceResult, OicopyError> {
     use crate::db::init_database;
     
     let conn = init_database(&app)?;
+    
+    ensure_unlocked(&conn)?;
     perform_maintenance(&conn)
 }
 
 // Tauri命令：按数量限制清理数据库
 #[tauri::command]
-async fn cleanup_database_by_limit(app: AppHandle, max_records: i64) -> Result<usize, String> {
+async fn cleanup_database_by_limit(app: AppHandle, max_records: i64) -> Result<usize, OicopyError> {
     use crate::db::init_database;
     
     let conn = init_database(&app)?;
+    
+    ensure_unlocked(&conn)?;
     cleanup_by_limit(&conn, max_records)
 }
 
 // Tauri命令：按大小限制清理数据库
 #[tauri::command]
-async fn cleanup_database_by_size(app: AppHandle, max_size_mb: f64) -> Result<usize, String> {
+async fn cleanup_database_by_size(app: AppHandle, max_size_mb: f64) -> Result<usize, OicopyError> {
     use crate::db::init_database;
     
     let conn = init_database(&app)?;
+    
+    ensure_unlocked(&conn)?;
     cleanup_by_size(&conn, max_size_mb)
 }
 
+// Tauri命令：获取占用空间最大的记录
+#[tauri::command]
+async fn get_largest_items(app: AppHandle, limit: Option<u32>) -> Result<Vec<LargeItem>, OicopyError> {
+    use crate::db::init_database;
+    
+    let conn = init_database(&app)?;
+    list_largest_items(&conn, limit.unwrap_or(20) as usize)
+}
+
+// Tauri命令：删除超过指定大小（字节，至少 1KB）的记录，置顶项除外
+#[tauri::command]
+async fn delete_items_larger_than(app: AppHandle, bytes: i64) -> Result<usize, OicopyError> {
+    use crate::db::init_database;
+    
+    let conn = init_database(&app)?;
+    
+    ensure_unlocked(&conn)?;
+    let deleted = delete_items_over_size(&conn, bytes.max(1024))?;
+    if deleted > 0 {
+        emit_change_event(&app, "clipboard-updated");
+    }
+    Ok(deleted)
+}
+
+// Tauri命令：检查数据库完整性，full 为 true 时执行完整但较慢的 integrity_check
+#[tauri::command]
+async fn check_database_integrity(app: AppHandle, full: Option<bool>) -> Result<IntegrityReport, OicopyError> {
+    use crate::db::init_database;
+    
+    let conn = init_database(&app)?;
+    check_integrity(&conn, full.unwrap_or(false))
+}
+
+// Tauri命令：将可读取的数据导出到新的数据库文件，重启应用后替换当前数据库
+#[tauri::command]
+async fn repair_database(app: AppHandle) -> Result<RecoveryResult, OicopyError> {
+    use crate::db::init_database;
+    
+    let conn = init_database(&app)?;
+    
+    ensure_unlocked(&conn)?;
+    stage_recovery(&conn)
+}
+
 // Tauri命令：执行智能清理
 #[tauri::command]
-async fn perform_smart_cleanup_command(app: AppHandle) -> Result<SmartCleanupResult, String> {
+async fn perform_smart_cleanup_command(app: AppHandle) -> Result<SmartCleanupResult, OicopyError> {
     use crate::db::init_database;
     
     let conn = init_database(&app)?;
+    
+    ensure_unlocked(&conn)?;
     perform_smart_cleanup(&conn)
 }
 
 // Tauri命令：分析数据库性能
 #[tauri::command]
-async fn analyze_database_performance_command(app: AppHandle) -> Result<PerformanceAnalysis, String> {
+async fn analyze_database_performance_command(
+    app: AppHandle,
+    retention_policies: Option<Vec<RetentionPolicy>>,
+) -> Result<PerformanceAnalysis, OicopyError> {
     use crate::db::init_database;
     
     let conn = init_database(&app)?;
-    analyze_database_performance(&conn)
+    // 未指定候选保留策略时模拟常用的天数和条数
+    let policies = retention_policies.unwrap_or_else(default_policies);
+    analyze_database_performance(&conn, &policies)
 }
 
 // Tauri命令：测试数据库优化（仅在调试模式下可用）
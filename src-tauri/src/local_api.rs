@@ -0,0 +1,540 @@
+use crate::change_log::current_change_seq;
+use crate::clipboard_content::ContentKind;
+use crate::clipboard_management::{get_clipboard_history, get_item_full_content, is_monitoring_paused, search_clipboard_history};
+use crate::db::{get_settings, init_database, open_connection, save_settings, ConnectionRole};
+use crate::error::OicopyError;
+use crate::lock_mode::ensure_unlocked;
+use crate::sync_crypto::{constant_time_eq, random_bytes, to_hex};
+use base64::prelude::*;
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Listener};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::{Role, WebSocketConfig};
+use tungstenite::{Message, WebSocket};
+
+// 本机接口：只监听 127.0.0.1，提供只读的历史记录查询（HTTP）和新记录推送（WebSocket）
+// 所有请求都需要携带令牌：Authorization: Bearer <token>
+// 浏览器无法给 WebSocket 设置请求头，只有 /v1/events 允许在地址中附加 ?token=<token>
+const MAX_REQUEST_HEAD_BYTES: usize = 16 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// WebSocket 空闲时定期发送 ping，及时发现已断开的连接
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+// WebSocket 读取超时，超时后检查是否有新记录需要推送
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const MAX_PAGE_SIZE: u32 = 500;
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_REQUEST_HEADERS: usize = 64;
+const MAX_CLIENT_MESSAGE_BYTES: usize = 64 * 1024;
+const EVENTS_PATH: &str = "/v1/events";
+
+// 正在运行的接口服务
+struct ApiServer {
+    port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+static SERVER: Lazy<Mutex<Option<ApiServer>>> = Lazy::new(|| Mutex::new(None));
+
+// 本机接口状态
+#[derive(Debug, Serialize)]
+pub struct LocalApiStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    pub token: Option<String>,
+    pub base_url: String,
+}
+
+// 解析后的请求
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>, // 名称统一为小写
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    fn query_u32(&self, name: &str) -> Option<u32> {
+        self.query.get(name).and_then(|value| value.parse().ok())
+    }
+}
+
+// 推送给 WebSocket 客户端的新记录摘要
+#[derive(Debug, Serialize)]
+struct ItemAddedEvent {
+    #[serde(rename = "type")]
+    event_type: &'static str,
+    id: i64,
+    content_type: ContentKind,
+    preview: Option<String>,
+    timestamp: String,
+    source_app: Option<String>,
+    source_bundle_id: Option<String>,
+}
+
+// 解码查询参数（%XX 和表示空格的 +）
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// 解析请求行和请求头
+fn parse_request(head: &[u8]) -> Option<Request> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_REQUEST_HEADERS];
+    let mut parsed = httparse::Request::new(&mut headers);
+    if !parsed.parse(head).ok()?.is_complete() {
+        return None;
+    }
+    let method = parsed.method?.to_string();
+    let target = parsed.path?;
+    let (path, query_string) = target.split_once('?').unwrap_or((target, ""));
+    let query = query_string
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect();
+    let headers = parsed
+        .headers
+        .iter()
+        .map(|header| (header.name.to_ascii_lowercase(), String::from_utf8_lossy(header.value).trim().to_string()))
+        .collect();
+    Some(Request { method, path: path.to_string(), query, headers })
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, reason: &str, body: &str) -> Result<(), String> {
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(response.as_bytes())
+        .map_err(|e| format!("发送响应失败: {}", e))
+}
+
+fn write_json<T: Serialize>(stream: &mut TcpStream, value: &T) -> Result<(), String> {
+    let body = serde_json::to_string(value).map_err(|e| format!("序列化响应失败: {}", e))?;
+    write_response(stream, 200, "OK", &body)
+}
+
+fn write_error(stream: &mut TcpStream, status: u16, reason: &str, message: &str) -> Result<(), String> {
+    write_response(stream, status, reason, &serde_json::json!({ "error": message }).to_string())
+}
+
+fn write_command_error(stream: &mut TcpStream, error: OicopyError) -> Result<(), String> {
+    match error {
+        OicopyError::NotFound(message) => write_error(stream, 404, "Not Found", &message),
+        OicopyError::Permission(message) => write_error(stream, 403, "Forbidden", &message),
+        error => write_error(stream, 500, "Internal Server Error", &error.to_string()),
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Option<Request>, String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_HEAD_BYTES {
+            return Ok(None);
+        }
+        let n = stream.read(&mut buf).map_err(|e| format!("读取请求失败: {}", e))?;
+        if n == 0 {
+            return Ok(None);
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    Ok(parse_request(&head))
+}
+
+fn is_authorized(request: &Request, token: &str) -> bool {
+    // 地址中的令牌会出现在浏览器历史和日志里，只在 WebSocket 握手时接受
+    let query_token = || {
+        (request.path.trim_end_matches('/') == EVENTS_PATH)
+            .then(|| request.query.get("token").map(String::as_str))
+            .flatten()
+    };
+    let provided = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(query_token)
+        .unwrap_or_default();
+    !token.is_empty() && constant_time_eq(provided.trim().as_bytes(), token.as_bytes())
+}
+
+// 处理一个连接：校验令牌后按路径分发
+fn handle_connection(app: &AppHandle, mut stream: TcpStream) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(REQUEST_TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(REQUEST_TIMEOUT)))
+        .map_err(|e| format!("设置连接超时失败: {}", e))?;
+    let Some(request) = read_request(&mut stream)? else {
+        return write_error(&mut stream, 400, "Bad Request", "请求格式错误");
+    };
+
+    // 每次请求都读取最新的令牌，重新生成后旧令牌立即失效
+    let token = init_database(app)
+        .and_then(|conn| get_settings(&conn))
+        .ok()
+        .and_then(|settings| settings.local_api_token)
+        .unwrap_or_default();
+    if !is_authorized(&request, &token) {
+        return write_error(&mut stream, 401, "Unauthorized", "令牌无效");
+    }
+    if request.method != "GET" {
+        return write_error(&mut stream, 405, "Method Not Allowed", "接口只支持 GET 请求");
+    }
+    if let Err(e) = ensure_unlocked(&init_database(app)?) {
+        return write_error(&mut stream, 403, "Forbidden", &e.to_string());
+    }
+
+    let limit = Some(request.query_u32("limit").unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE));
+    let offset = request.query_u32("offset");
+    let path = request.path.trim_end_matches('/');
+    match path {
+        "/v1/status" => {
+            let conn = open_connection(app, ConnectionRole::Analytics)?;
+            let items: i64 = conn
+                .query_row("SELECT COUNT(*) FROM clipboard_history", [], |row| row.get(0))
+                .map_err(|e| format!("统计记录失败: {}", e))?;
+            write_json(
+                &mut stream,
+                &serde_json::json!({
+                    "version": app.package_info().version.to_string(),
+                    "items": items,
+                    "monitoring_paused": is_monitoring_paused(app),
+                }),
+            )
+        }
+        "/v1/items" => {
+            let result = tauri::async_runtime::block_on(get_clipboard_history(
+                app.clone(),
+                limit,
+                offset,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ));
+            match result {
+                Ok(items) => write_json(&mut stream, &items),
+                Err(e) => write_command_error(&mut stream, e),
+            }
+        }
+        "/v1/search" => {
+            let query = request.query.get("q").cloned().unwrap_or_default();
+            match tauri::async_runtime::block_on(search_clipboard_history(app.clone(), query, limit, offset, None)) {
+                Ok(items) => write_json(&mut stream, &items),
+                Err(e) => write_command_error(&mut stream, e),
+            }
+        }
+        EVENTS_PATH => {
+            let is_upgrade = request.header("upgrade").is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+            match request.header("sec-websocket-key") {
+                Some(key) if is_upgrade => {
+                    let key = key.to_string();
+                    serve_events(app, stream, &key)
+                }
+                _ => write_error(&mut stream, 400, "Bad Request", "需要 WebSocket 连接"),
+            }
+        }
+        _ => match path.strip_prefix("/v1/items/").map(str::parse::<i64>) {
+            Some(Ok(id)) => match tauri::async_runtime::block_on(get_item_full_content(app.clone(), id)) {
+                Ok(item) => write_json(&mut stream, &item),
+                Err(e) => write_command_error(&mut stream, e),
+            },
+            _ => write_error(&mut stream, 404, "Not Found", "接口不存在"),
+        },
+    }
+}
+
+// 读取 seq 之后新增的记录
+fn added_items_since(conn: &Connection, seq: i64) -> Result<Vec<ItemAddedEvent>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT h.id, h.content_type, h.preview, h.timestamp, h.source_app, h.source_bundle_id
+             FROM change_log c JOIN clipboard_history h ON h.id = c.item_id
+             WHERE c.seq > ?1 AND c.change_kind = 'added'
+             ORDER BY c.seq",
+        )
+        .map_err(|e| format!("查询新记录失败: {}", e))?;
+    let rows = stmt
+        .query_map(params![seq], |row| {
+            Ok(ItemAddedEvent {
+                event_type: "item_added",
+                id: row.get(0)?,
+                content_type: row.get(1)?,
+                preview: row.get(2)?,
+                timestamp: row.get(3)?,
+                source_app: row.get(4)?,
+                source_bundle_id: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("查询新记录失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("读取新记录失败: {}", e))
+}
+
+fn is_timeout(error: &tungstenite::Error) -> bool {
+    matches!(error, tungstenite::Error::Io(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut))
+}
+
+// 完成 WebSocket 握手后，每当有新记录保存时推送记录摘要
+fn serve_events(app: &AppHandle, mut stream: TcpStream, key: &str) -> Result<(), String> {
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.trim().as_bytes())
+    );
+    stream
+        .write_all(response.as_bytes())
+        .and_then(|_| stream.set_read_timeout(Some(EVENT_POLL_INTERVAL)))
+        .map_err(|e| format!("WebSocket 握手失败: {}", e))?;
+
+    let conn = open_connection(app, ConnectionRole::Analytics)?;
+    let mut last_seq = current_change_seq(&conn)?;
+    let (sender, receiver) = channel();
+    let listener = app.listen("clipboard-updated", move |_| {
+        let _ = sender.send(());
+    });
+
+    // 帧的编解码、掩码和 ping/pong/close 的应答交给 tungstenite 处理
+    let config = WebSocketConfig::default()
+        .max_message_size(Some(MAX_CLIENT_MESSAGE_BYTES))
+        .max_frame_size(Some(MAX_CLIENT_MESSAGE_BYTES));
+    let mut socket = WebSocket::from_raw_socket(stream, Role::Server, Some(config));
+    let send = |socket: &mut WebSocket<TcpStream>, message: Message| {
+        socket.send(message).map_err(|e| format!("发送 WebSocket 数据失败: {}", e))
+    };
+    let mut last_ping = Instant::now();
+    let result = (|| loop {
+        match socket.read() {
+            // 客户端的消息不需要处理，ping 和 close 已由 tungstenite 自动应答
+            Ok(_) => {}
+            Err(e) if is_timeout(&e) => {}
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => return Ok(()),
+            Err(e) => return Err(format!("读取 WebSocket 数据失败: {}", e)),
+        }
+        if !socket.can_write() {
+            continue;
+        }
+
+        if receiver.try_iter().count() > 0 {
+            // 锁定期间不推送内容，解锁后也不补发
+            let seq = current_change_seq(&conn)?;
+            if ensure_unlocked(&conn).is_ok() {
+                for event in added_items_since(&conn, last_seq)? {
+                    let json = serde_json::to_string(&event).map_err(|e| format!("序列化事件失败: {}", e))?;
+                    send(&mut socket, Message::text(json))?;
+                }
+            }
+            last_seq = seq;
+        }
+        if last_ping.elapsed() >= PING_INTERVAL {
+            send(&mut socket, Message::Ping(Default::default()))?;
+            last_ping = Instant::now();
+        }
+    })();
+
+    app.unlisten(listener);
+    let _ = socket.get_ref().shutdown(Shutdown::Both);
+    result
+}
+
+// 启动接口服务，端口被占用时返回错误
+fn start_server(app: &AppHandle, port: u16) -> Result<(), String> {
+    let mut server = SERVER.lock().map_err(|_| "接口服务状态锁已失效".to_string())?;
+    if let Some(running) = server.as_ref() {
+        if running.port == port {
+            return Ok(());
+        }
+        running.stop.store(true, Ordering::SeqCst);
+        *server = None;
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|e| format!("启动本机接口失败（端口 {}）: {}", port, e))?;
+    // 非阻塞接受连接，定期检查停止标志
+    listener.set_nonblocking(true).map_err(|e| format!("启动本机接口失败: {}", e))?;
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let app_handle = app.clone();
+    let stop_flag = Arc::clone(&stop);
+    std::thread::Builder::new()
+        .name("local-api".to_string())
+        .spawn(move || {
+            while !stop_flag.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let app = app_handle.clone();
+                        std::thread::spawn(move || {
+                            let _ = stream.set_nonblocking(false);
+                            if let Err(e) = handle_connection(&app, stream) {
+                                eprintln!("处理本机接口请求失败: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(100));
+                    }
+                    Err(e) => eprintln!("接受本机接口连接失败: {}", e),
+                }
+            }
+            println!("本机接口已停止");
+        })
+        .map_err(|e| format!("启动本机接口失败: {}", e))?;
+
+    *server = Some(ApiServer { port, stop });
+    println!("本机接口已启动: http://127.0.0.1:{}", port);
+    Ok(())
+}
+
+fn stop_server() {
+    if let Some(server) = SERVER.lock().ok().and_then(|mut server| server.take()) {
+        server.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+fn generate_token() -> Result<String, String> {
+    Ok(to_hex(&random_bytes(24)?))
+}
+
+fn valid_port(port: i64) -> Result<u16, String> {
+    u16::try_from(port)
+        .ok()
+        .filter(|port| *port >= 1024)
+        .ok_or_else(|| format!("端口必须在 1024 到 65535 之间: {}", port))
+}
+
+// 启动时按设置开启本机接口
+pub fn start_local_api(app_handle: AppHandle) {
+    let Some(settings) = init_database(&app_handle).ok().and_then(|conn| get_settings(&conn).ok()) else {
+        return;
+    };
+    if !settings.local_api_enabled {
+        return;
+    }
+    if let Err(e) = valid_port(settings.local_api_port).and_then(|port| start_server(&app_handle, port)) {
+        eprintln!("{}", e);
+    }
+}
+
+fn api_status(app: &AppHandle) -> Result<LocalApiStatus, String> {
+    let settings = get_settings(&init_database(app)?)?;
+    let port = valid_port(settings.local_api_port).unwrap_or_default();
+    let running = SERVER.lock().ok().is_some_and(|server| server.is_some());
+    Ok(LocalApiStatus {
+        enabled: settings.local_api_enabled,
+        running,
+        port,
+        token: settings.local_api_token,
+        base_url: format!("http://127.0.0.1:{}/v1", port),
+    })
+}
+
+// 获取本机接口状态命令
+#[tauri::command]
+pub async fn get_local_api_status(app: AppHandle) -> Result<LocalApiStatus, OicopyError> {
+    Ok(api_status(&app)?)
+}
+
+// 开启或关闭本机接口命令，首次开启时生成访问令牌
+#[tauri::command]
+pub async fn set_local_api_config(app: AppHandle, enabled: bool, port: Option<i64>) -> Result<LocalApiStatus, OicopyError> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let mut settings = get_settings(&conn)?;
+    let port = valid_port(port.unwrap_or(settings.local_api_port)).map_err(OicopyError::Validation)?;
+    if enabled {
+        // 先启动服务，端口不可用时不保存设置
+        start_server(&app, port).map_err(OicopyError::Io)?;
+        if settings.local_api_token.is_none() {
+            settings.local_api_token = Some(generate_token()?);
+        }
+    } else {
+        stop_server();
+    }
+    settings.local_api_enabled = enabled;
+    settings.local_api_port = port as i64;
+    save_settings(&conn, &settings)?;
+    drop(conn);
+    Ok(api_status(&app)?)
+}
+
+// 重新生成访问令牌命令，旧令牌立即失效（已建立的 WebSocket 连接不受影响）
+#[tauri::command]
+pub async fn regenerate_local_api_token(app: AppHandle) -> Result<LocalApiStatus, OicopyError> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let mut settings = get_settings(&conn)?;
+    settings.local_api_token = Some(generate_token()?);
+    save_settings(&conn, &settings)?;
+    drop(conn);
+    Ok(api_status(&app)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let request = parse_request(
+            b"GET /v1/search?q=hello+w%C3%B6rld&limit=5&token=abc HTTP/1.1\r\nHost: 127.0.0.1\r\nAuthorization: Bearer xyz\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/v1/search");
+        assert_eq!(request.query.get("q").map(String::as_str), Some("hello wörld"));
+        assert_eq!(request.query_u32("limit"), Some(5));
+        assert_eq!(request.header("authorization"), Some("Bearer xyz"));
+        assert!(is_authorized(&request, "xyz"));
+        assert!(!is_authorized(&request, "abc"));
+        assert!(!is_authorized(&request, ""));
+        assert!(parse_request(b"GET /v1/status HTTP/1.1\r\nHost").is_none());
+    }
+
+    #[test]
+    fn test_query_token_only_for_websocket() {
+        let request = parse_request(b"GET /v1/items?token=abc HTTP/1.1\r\n\r\n").unwrap();
+        assert!(!is_authorized(&request, "abc"));
+        let request = parse_request(b"GET /v1/events?token=abc HTTP/1.1\r\nUpgrade: websocket\r\n\r\n").unwrap();
+        assert!(is_authorized(&request, "abc"));
+    }
+}
@@ -0,0 +1,172 @@
+use crate::db::{get_settings, init_database, save_settings};
+use crate::error::OicopyError;
+use crate::sync_crypto::{constant_time_eq, from_hex, random_bytes, stretch_key, to_hex};
+use rusqlite::Connection;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+
+// 密码错误后的等待时间，减缓暴力尝试
+const FAILED_UNLOCK_DELAY_MS: u64 = 1000;
+// 密码哈希的迭代次数：解锁密码通常很短，需要增加从设置表离线猜测的成本
+const PASSCODE_ROUNDS: u32 = 600_000;
+const PASSCODE_SCHEME: &str = "pbkdf2";
+
+// 锁定状态
+#[derive(Debug, Clone, Serialize)]
+pub struct LockStatus {
+    pub locked: bool,
+    pub has_passcode: bool,
+}
+
+// 旧版本保存的单次加盐 SHA-256，只用于验证，解锁成功后升级为新格式
+fn legacy_hash_passcode(salt: &str, passcode: &str) -> Vec<u8> {
+    Sha256::digest(format!("{}:{}", salt, passcode).as_bytes()).to_vec()
+}
+
+// 生成带随机盐的密码哈希（pbkdf2$迭代次数$盐$哈希）
+fn create_passcode_hash_with_rounds(passcode: &str, rounds: u32) -> Result<String, String> {
+    let salt = random_bytes(16).map_err(|e| format!("生成密码盐失败: {}", e))?;
+    let hash = stretch_key(passcode.as_bytes(), &salt, rounds);
+    Ok(format!("{}${}${}${}", PASSCODE_SCHEME, rounds, to_hex(&salt), to_hex(&hash)))
+}
+
+fn create_passcode_hash(passcode: &str) -> Result<String, String> {
+    create_passcode_hash_with_rounds(passcode, PASSCODE_ROUNDS)
+}
+
+// 是否为旧格式（salt$sha256）的密码哈希
+fn is_legacy_passcode_hash(stored: &str) -> bool {
+    !stored.starts_with(&format!("{}$", PASSCODE_SCHEME))
+}
+
+fn verify_passcode(stored: &str, passcode: &str) -> bool {
+    let parts: Vec<&str> = stored.split('$').collect();
+    match parts.as_slice() {
+        [PASSCODE_SCHEME, rounds, salt, hash] => {
+            let (Ok(rounds), Ok(salt), Ok(hash)) = (rounds.parse::<u32>(), from_hex(salt), from_hex(hash)) else {
+                return false;
+            };
+            rounds > 0 && constant_time_eq(&stretch_key(passcode.as_bytes(), &salt, rounds), &hash)
+        }
+        [salt, hash] => match from_hex(hash) {
+            Ok(hash) => constant_time_eq(&legacy_hash_passcode(salt, passcode), &hash),
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
+
+// 锁定状态下拒绝会修改数据或泄露内容的操作，返回 Permission 错误供前端按 kind 区分
+pub fn ensure_unlocked(conn: &Connection) -> Result<(), OicopyError> {
+    if get_settings(conn)?.history_locked {
+        return Err(OicopyError::Permission("剪贴板历史已锁定，请先解锁".to_string()));
+    }
+    Ok(())
+}
+
+fn emit_lock_changed(app: &AppHandle, status: &LockStatus) {
+    if let Err(e) = app.emit("lock-status-changed", status) {
+        eprintln!("通知前端锁定状态变化失败: {}", e);
+    }
+}
+
+// Tauri命令：获取锁定状态
+#[tauri::command]
+pub async fn get_lock_status(app: AppHandle) -> Result<LockStatus, String> {
+    let conn = init_database(&app)?;
+    let settings = get_settings(&conn)?;
+    Ok(LockStatus {
+        locked: settings.history_locked,
+        has_passcode: settings.lock_passcode_hash.is_some(),
+    })
+}
+
+// Tauri命令：锁定历史记录；传入密码时设置新的解锁密码（传空字符串清除密码）
+#[tauri::command]
+pub async fn lock_history(app: AppHandle, passcode: Option<String>) -> Result<LockStatus, String> {
+    let conn = init_database(&app)?;
+    let mut settings = get_settings(&conn)?;
+    if settings.history_locked {
+        return Err("剪贴板历史已处于锁定状态".to_string());
+    }
+
+    if let Some(passcode) = passcode {
+        settings.lock_passcode_hash = if passcode.is_empty() {
+            None
+        } else {
+            Some(create_passcode_hash(&passcode)?)
+        };
+    }
+    settings.history_locked = true;
+    save_settings(&conn, &settings)?;
+
+    let status = LockStatus {
+        locked: true,
+        has_passcode: settings.lock_passcode_hash.is_some(),
+    };
+    println!("剪贴板历史已锁定");
+    emit_lock_changed(&app, &status);
+    Ok(status)
+}
+
+// Tauri命令：解锁历史记录，设置了密码时需要验证
+#[tauri::command]
+pub async fn unlock_history(app: AppHandle, passcode: Option<String>) -> Result<LockStatus, String> {
+    let conn = init_database(&app)?;
+    let mut settings = get_settings(&conn)?;
+    if !settings.history_locked {
+        return Ok(LockStatus {
+            locked: false,
+            has_passcode: settings.lock_passcode_hash.is_some(),
+        });
+    }
+
+    if let Some(ref stored) = settings.lock_passcode_hash {
+        let passcode = passcode.unwrap_or_default();
+        if !verify_passcode(stored, &passcode) {
+            tokio::time::sleep(tokio::time::Duration::from_millis(FAILED_UNLOCK_DELAY_MS)).await;
+            return Err("解锁密码错误".to_string());
+        }
+        if is_legacy_passcode_hash(stored) {
+            settings.lock_passcode_hash = Some(create_passcode_hash(&passcode)?);
+        }
+    }
+
+    settings.history_locked = false;
+    save_settings(&conn, &settings)?;
+
+    let status = LockStatus {
+        locked: false,
+        has_passcode: settings.lock_passcode_hash.is_some(),
+    };
+    println!("剪贴板历史已解锁");
+    emit_lock_changed(&app, &status);
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passcode_hash_round_trip() {
+        let stored = create_passcode_hash_with_rounds("1234", 1000).unwrap();
+        assert!(!is_legacy_passcode_hash(&stored));
+        assert!(verify_passcode(&stored, "1234"));
+        assert!(!verify_passcode(&stored, "12345"));
+        assert!(!verify_passcode("corrupted", "1234"));
+        assert!(!verify_passcode(&stored.replace("$1000$", "$0$"), "1234"));
+        // 每次生成的盐不同
+        assert_ne!(stored, create_passcode_hash_with_rounds("1234", 1000).unwrap());
+    }
+
+    #[test]
+    fn test_legacy_passcode_hash() {
+        let salt = "0011aabb";
+        let stored = format!("{}${}", salt, to_hex(&legacy_hash_passcode(salt, "1234")));
+        assert!(is_legacy_passcode_hash(&stored));
+        assert!(verify_passcode(&stored, "1234"));
+        assert!(!verify_passcode(&stored, "4321"));
+    }
+}
@@ -0,0 +1,332 @@
+use crate::clipboard_management::decode_html_entities;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+// 转换前整体去掉的元素（连同内容）
+static RE_DROPPED: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?is)<head\b.*?</head\s*>|<script\b.*?</script\s*>|<style\b.*?</style\s*>|<noscript\b.*?</noscript\s*>|<!--.*?-->|<meta\b[^>]*>",
+    )
+    .unwrap()
+});
+static RE_TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<(/?)([a-zA-Z][a-zA-Z0-9]*)([^>]*)>").unwrap());
+static RE_HREF: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)\bhref\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#).unwrap());
+static RE_LANGUAGE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\b(?:language|lang)-([a-z0-9_+#-]+)").unwrap());
+static RE_BLANK_LINES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n[ \t]*\n(?:[ \t]*\n)+").unwrap());
+static RE_SPACES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
+
+// 列表层级：有序列表记录下一个序号
+enum ListKind {
+    Unordered,
+    Ordered(usize),
+}
+
+struct MarkdownWriter {
+    out: String,
+    lists: Vec<ListKind>,
+    links: Vec<Option<String>>, // 每个 <a> 的链接，无 href 时为空
+    link_starts: Vec<usize>,    // 链接文字在输出中的起始位置
+    quote_depth: usize,
+    pre_depth: usize,
+    inline_code: bool,
+}
+
+impl MarkdownWriter {
+    fn new() -> Self {
+        Self {
+            out: String::new(),
+            lists: Vec::new(),
+            links: Vec::new(),
+            link_starts: Vec::new(),
+            quote_depth: 0,
+            pre_depth: 0,
+            inline_code: false,
+        }
+    }
+
+    // 新行的前缀：引用标记和列表缩进
+    fn line_prefix(&self) -> String {
+        let mut prefix = "> ".repeat(self.quote_depth);
+        prefix.push_str(&"   ".repeat(self.lists.len().saturating_sub(1)));
+        prefix
+    }
+
+    // 输出为空或以空白结尾时，后续文本的前导空白可以省略
+    fn ends_with_space(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with([' ', '\n'])
+    }
+
+    fn newline(&mut self) {
+        let trimmed_len = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed_len);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    // 块级元素前后留一个空行
+    fn block_break(&mut self) {
+        self.newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn start_line(&mut self) {
+        if self.out.is_empty() || self.out.ends_with('\n') {
+            let prefix = self.line_prefix();
+            self.out.push_str(&prefix);
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let text = decode_html_entities(text);
+        if self.pre_depth > 0 {
+            let prefix = self.line_prefix();
+            for (i, line) in text.split('\n').enumerate() {
+                if i > 0 {
+                    self.out.push('\n');
+                    self.out.push_str(&prefix);
+                }
+                self.out.push_str(line);
+            }
+            return;
+        }
+
+        // 普通文本折叠空白，不产生连续空格
+        let collapsed = RE_SPACES.replace_all(&text, " ");
+        let collapsed = if self.ends_with_space() { collapsed.trim_start() } else { &collapsed };
+        if collapsed.is_empty() {
+            return;
+        }
+        self.start_line();
+        self.out.push_str(collapsed);
+    }
+
+    fn open_tag(&mut self, name: &str, attrs: &str) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block_break();
+                self.start_line();
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            "p" | "div" | "section" | "article" | "table" | "header" | "footer" => self.block_break(),
+            "tr" => self.newline(),
+            "td" | "th" => {
+                if !self.ends_with_space() {
+                    self.out.push(' ');
+                }
+            }
+            "br" => {
+                if self.pre_depth > 0 {
+                    self.out.push('\n');
+                } else {
+                    self.newline();
+                }
+            }
+            "hr" => {
+                self.block_break();
+                self.start_line();
+                self.out.push_str("---");
+                self.block_break();
+            }
+            "blockquote" => {
+                self.block_break();
+                self.quote_depth += 1;
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.block_break();
+                } else {
+                    self.newline();
+                }
+                self.lists.push(if name == "ol" { ListKind::Ordered(1) } else { ListKind::Unordered });
+            }
+            "li" => {
+                self.newline();
+                let depth = self.lists.len();
+                let mut prefix = "> ".repeat(self.quote_depth);
+                prefix.push_str(&"   ".repeat(depth.saturating_sub(1)));
+                let marker = match self.lists.last_mut() {
+                    Some(ListKind::Ordered(next)) => {
+                        let marker = format!("{}. ", next);
+                        *next += 1;
+                        marker
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.out.push_str(&prefix);
+                self.out.push_str(&marker);
+            }
+            "pre" => {
+                self.block_break();
+                self.start_line();
+                let language = RE_LANGUAGE
+                    .captures(attrs)
+                    .map(|caps| caps[1].to_lowercase())
+                    .unwrap_or_default();
+                self.out.push_str("```");
+                self.out.push_str(&language);
+                self.out.push('\n');
+                self.out.push_str(&self.line_prefix());
+                self.pre_depth += 1;
+            }
+            "code" => {
+                if self.pre_depth > 0 {
+                    // <pre><code class="language-x"> 的语言写在围栏上
+                    if let Some(caps) = RE_LANGUAGE.captures(attrs) {
+                        if self.out.ends_with("```\n") {
+                            self.out.truncate(self.out.len() - 1);
+                            self.out.push_str(&caps[1].to_lowercase());
+                            self.out.push('\n');
+                        }
+                    }
+                } else {
+                    self.start_line();
+                    self.out.push('`');
+                    self.inline_code = true;
+                }
+            }
+            "strong" | "b" => {
+                self.start_line();
+                self.out.push_str("**");
+            }
+            "em" | "i" => {
+                self.start_line();
+                self.out.push('*');
+            }
+            "a" => {
+                let href = RE_HREF.captures(attrs).and_then(|caps| {
+                    caps.get(1)
+                        .or_else(|| caps.get(2))
+                        .or_else(|| caps.get(3))
+                        .map(|m| decode_html_entities(m.as_str().trim()))
+                });
+                self.start_line();
+                self.link_starts.push(self.out.len());
+                if href.is_some() {
+                    self.out.push('[');
+                }
+                self.links.push(href);
+            }
+            _ => {}
+        }
+    }
+
+    fn close_tag(&mut self, name: &str) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "div" | "section" | "article" | "table" | "header"
+            | "footer" => self.block_break(),
+            "tr" => self.newline(),
+            "blockquote" => {
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.block_break();
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block_break();
+                } else {
+                    self.newline();
+                }
+            }
+            "li" => self.newline(),
+            "pre" => {
+                if self.pre_depth > 0 {
+                    self.pre_depth -= 1;
+                    self.newline();
+                    self.start_line();
+                    self.out.push_str("```");
+                    self.block_break();
+                }
+            }
+            "code" => {
+                if self.inline_code {
+                    self.out.push('`');
+                    self.inline_code = false;
+                }
+            }
+            "strong" | "b" => self.out.push_str("**"),
+            "em" | "i" => self.out.push('*'),
+            "a" => {
+                let start = self.link_starts.pop();
+                if let Some(Some(href)) = self.links.pop() {
+                    let text = start.map(|start| self.out[start + 1..].trim().to_string()).unwrap_or_default();
+                    // 链接文字为空时直接使用链接
+                    if text.is_empty() {
+                        if let Some(start) = start {
+                            self.out.truncate(start);
+                        }
+                        self.out.push_str(&format!("<{}>", href));
+                    } else {
+                        self.out.push_str(&format!("]({})", href));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> String {
+        RE_BLANK_LINES.replace_all(&self.out, "\n\n").trim().to_string()
+    }
+}
+
+// 将 HTML 转换为 Markdown，支持标题、列表、链接、引用、粗体/斜体和代码块
+pub fn html_to_markdown(html: &str) -> String {
+    let html = RE_DROPPED.replace_all(html, "");
+    let mut writer = MarkdownWriter::new();
+    let mut last = 0;
+
+    for caps in RE_TAG.captures_iter(&html) {
+        let whole = caps.get(0).unwrap();
+        writer.push_text(&html[last..whole.start()]);
+        last = whole.end();
+
+        let name = caps[2].to_lowercase();
+        if caps[1].is_empty() {
+            writer.open_tag(&name, &caps[3]);
+        } else {
+            writer.close_tag(&name);
+        }
+    }
+    writer.push_text(&html[last..]);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = r#"<html><head><title>t</title></head><body>
+            <h2>Install</h2>
+            <p>Run the <strong>installer</strong> from <a href="https://example.com/dl?a=1&amp;b=2">the site</a>.</p>
+            <ul><li>first</li><li>second<ol><li>nested</li></ol></li></ul>
+            <pre><code class="language-rust">fn main() {
+    println!("&lt;hi&gt;");
+}</code></pre>
+        </body></html>"#;
+
+        assert_eq!(
+            html_to_markdown(html),
+            "## Install\n\n\
+             Run the **installer** from [the site](https://example.com/dl?a=1&b=2).\n\n\
+             - first\n\
+             - second\n   1. nested\n\n\
+             ```rust\nfn main() {\n    println!(\"<hi>\");\n}\n```"
+        );
+    }
+
+    #[test]
+    fn test_inline_code_and_quote() {
+        assert_eq!(
+            html_to_markdown("<blockquote><p>Use <code>cargo build</code></p></blockquote>"),
+            "> Use `cargo build`"
+        );
+        assert_eq!(html_to_markdown(r#"<a href="https://x.dev"></a>"#), "<https://x.dev>");
+    }
+}
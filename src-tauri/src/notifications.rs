@@ -0,0 +1,101 @@
+use crate::db::{get_settings, init_database, AppSettings};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+// 需要提示用户的重要事件
+#[derive(Debug, Clone)]
+pub enum NotificationEvent {
+    MonitorRestarted,                   // 剪贴板监听意外停止并已重新启动
+    CleanupCompleted { deleted: usize }, // 自动清理删除了记录
+    LowDiskSpace { available_mb: u64 },  // 磁盘空间不足，暂停保存大内容
+    ItemReceived { from: String },       // 收到其他设备发送的内容并已写入剪贴板
+}
+
+impl NotificationEvent {
+    // 事件对应的通知开关
+    fn is_enabled(&self, settings: &AppSettings) -> bool {
+        match self {
+            NotificationEvent::MonitorRestarted => settings.notify_monitor_restart,
+            NotificationEvent::CleanupCompleted { .. } => settings.notify_cleanup,
+            // 影响捕获结果，始终提示
+            NotificationEvent::LowDiskSpace { .. } => true,
+            // 剪贴板被其他设备替换，始终提示
+            NotificationEvent::ItemReceived { .. } => true,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            NotificationEvent::MonitorRestarted => "剪贴板监听已恢复",
+            NotificationEvent::CleanupCompleted { .. } => "自动清理完成",
+            NotificationEvent::LowDiskSpace { .. } => "磁盘空间不足",
+            NotificationEvent::ItemReceived { .. } => "收到其他设备的内容",
+        }
+    }
+
+    fn body(&self) -> String {
+        match self {
+            NotificationEvent::MonitorRestarted => {
+                "剪贴板监听意外停止，已自动重新启动".to_string()
+            }
+            NotificationEvent::CleanupCompleted { deleted } => {
+                format!("已删除 {} 条过期记录", format_count(*deleted))
+            }
+            NotificationEvent::LowDiskSpace { available_mb } => {
+                format!("剩余 {} MB，已暂停保存图片、文件和大段内容", format_count(*available_mb as usize))
+            }
+            NotificationEvent::ItemReceived { from } => {
+                format!("已将 {} 发送的内容写入剪贴板", from)
+            }
+        }
+    }
+}
+
+// 千位分隔的数字（1200 -> 1,200）
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::new();
+    for (index, c) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(c);
+    }
+    formatted
+}
+
+// 按设置发送系统通知，同时保留日志输出
+pub fn notify(app_handle: &AppHandle, event: NotificationEvent) {
+    println!("{}: {}", event.title(), event.body());
+
+    let enabled = init_database(app_handle)
+        .and_then(|conn| get_settings(&conn))
+        .map(|settings| event.is_enabled(&settings))
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+
+    if let Err(e) = app_handle
+        .notification()
+        .builder()
+        .title(event.title())
+        .body(event.body())
+        .show()
+    {
+        eprintln!("发送系统通知失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1200), "1,200");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+}
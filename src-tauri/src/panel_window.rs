@@ -1,7 +1,14 @@
 #![allow(deprecated)]
-use tauri::{AppHandle, Manager, Wry};
+use crate::app_info::remember_frontmost_app;
+use crate::db::{get_settings, init_database, save_settings, AppSettings};
+use crate::error::OicopyError;
+use crate::lock_mode::ensure_unlocked;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Monitor, WebviewUrl, WebviewWindow, WebviewWindowBuilder, Wry};
+#[cfg(target_os = "macos")]
 use tauri_nspanel::{tauri_panel, CollectionBehavior, PanelLevel, WebviewWindowExt};
 
+#[cfg(target_os = "macos")]
 tauri_panel!(MyPanel {
     config: {
         canBecomeKeyWindow: true,
@@ -13,6 +20,276 @@ tauri_panel!(MyPanel {
     }
 });
 
+// 面板尺寸的允许范围（逻辑像素）
+const PANEL_HEIGHT_RANGE: std::ops::RangeInclusive<f64> = 200.0..=800.0;
+const PANEL_WIDTH_RANGE: std::ops::RangeInclusive<f64> = 280.0..=800.0;
+
+// Spotlight 模式下浮动面板的尺寸（逻辑像素）和顶部留白占屏幕高度的比例
+const SPOTLIGHT_WIDTH: f64 = 680.0;
+const SPOTLIGHT_HEIGHT: f64 = 440.0;
+const SPOTLIGHT_TOP_RATIO: f64 = 0.2;
+
+// 面板展示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PanelMode {
+    #[default]
+    Dock, // 停靠在屏幕边缘的整条面板
+    Spotlight, // 屏幕中上方居中的浮动搜索框
+}
+
+impl PanelMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "dock" => Some(PanelMode::Dock),
+            "spotlight" => Some(PanelMode::Spotlight),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PanelMode::Dock => "dock",
+            PanelMode::Spotlight => "spotlight",
+        }
+    }
+}
+
+// 面板停靠的屏幕边缘
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PanelEdge {
+    Bottom,
+    Top,
+    Left,
+    Right,
+}
+
+impl PanelEdge {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "bottom" => Some(PanelEdge::Bottom),
+            "top" => Some(PanelEdge::Top),
+            "left" => Some(PanelEdge::Left),
+            "right" => Some(PanelEdge::Right),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PanelEdge::Bottom => "bottom",
+            PanelEdge::Top => "top",
+            PanelEdge::Left => "left",
+            PanelEdge::Right => "right",
+        }
+    }
+}
+
+// 面板布局：顶部/底部停靠时使用高度（宽度与屏幕相同），左侧/右侧停靠时使用宽度（高度与屏幕相同）
+// Spotlight 模式使用固定尺寸，忽略停靠边缘和尺寸
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PanelLayout {
+    #[serde(default)]
+    pub mode: PanelMode,
+    pub edge: PanelEdge,
+    pub height: f64,
+    pub width: f64,
+}
+
+impl PanelLayout {
+    fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            mode: PanelMode::parse(&settings.panel_mode).unwrap_or_default(),
+            edge: PanelEdge::parse(&settings.panel_edge).unwrap_or(PanelEdge::Bottom),
+            height: settings.panel_height.clamp(*PANEL_HEIGHT_RANGE.start(), *PANEL_HEIGHT_RANGE.end()),
+            width: settings.panel_width.clamp(*PANEL_WIDTH_RANGE.start(), *PANEL_WIDTH_RANGE.end()),
+        }
+    }
+}
+
+// 面板显示在哪个显示器上
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelMonitor {
+    Cursor,        // 鼠标所在的显示器
+    FocusedWindow, // 当前焦点窗口所在的显示器
+    Primary,       // 主显示器
+}
+
+impl PanelMonitor {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "cursor" => Some(PanelMonitor::Cursor),
+            "focused_window" => Some(PanelMonitor::FocusedWindow),
+            "primary" => Some(PanelMonitor::Primary),
+            _ => None,
+        }
+    }
+}
+
+// 读取面板相关设置，数据库不可用时使用默认值
+fn panel_settings(app: &AppHandle<Wry>) -> AppSettings {
+    init_database(app)
+        .ok()
+        .and_then(|conn| get_settings(&conn).ok())
+        .unwrap_or_default()
+}
+
+fn cursor_monitor(win: &WebviewWindow) -> Option<Monitor> {
+    let cursor = win.cursor_position().ok()?;
+    win.monitor_from_point(cursor.x, cursor.y).ok().flatten()
+}
+
+// macOS：NSScreen.mainScreen 为当前接收键盘输入的窗口所在屏幕，需在面板激活前查询
+#[cfg(target_os = "macos")]
+fn focused_window_monitor(win: &WebviewWindow) -> Option<Monitor> {
+    use objc2::rc::autoreleasepool;
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use objc2_foundation::NSRect;
+
+    // Cocoa 坐标原点在主屏幕左下角，转换为以左上角为原点的逻辑坐标
+    let (x, y) = autoreleasepool(|_| unsafe {
+        let screen: *mut AnyObject = msg_send![class!(NSScreen), mainScreen];
+        let screens: *mut AnyObject = msg_send![class!(NSScreen), screens];
+        if screen.is_null() || screens.is_null() {
+            return None;
+        }
+        let primary: *mut AnyObject = msg_send![screens, firstObject];
+        if primary.is_null() {
+            return None;
+        }
+        let frame: NSRect = msg_send![screen, frame];
+        let primary_frame: NSRect = msg_send![primary, frame];
+        Some((
+            frame.origin.x + frame.size.width / 2.0,
+            primary_frame.size.height - (frame.origin.y + frame.size.height / 2.0),
+        ))
+    })?;
+
+    win.available_monitors().ok()?.into_iter().find(|monitor| {
+        let scale_factor = monitor.scale_factor();
+        let position = monitor.position().to_logical::<f64>(scale_factor);
+        let size = monitor.size().to_logical::<f64>(scale_factor);
+        x >= position.x && x < position.x + size.width && y >= position.y && y < position.y + size.height
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn focused_window_monitor(win: &WebviewWindow) -> Option<Monitor> {
+    let (x, y) = crate::app_info_windows::foreground_window_center()?;
+    win.monitor_from_point(x, y).ok().flatten()
+}
+
+// Linux：Wayland 不提供其他应用的窗口位置，使用鼠标所在的显示器
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn focused_window_monitor(win: &WebviewWindow) -> Option<Monitor> {
+    cursor_monitor(win)
+}
+
+// 面板显示所在的显示器，按设置选择，无法确定时使用主显示器
+fn target_monitor(win: &WebviewWindow, settings: &AppSettings) -> Option<Monitor> {
+    let monitor = match PanelMonitor::parse(&settings.panel_monitor).unwrap_or(PanelMonitor::Cursor) {
+        PanelMonitor::Cursor => cursor_monitor(win),
+        PanelMonitor::FocusedWindow => focused_window_monitor(win),
+        PanelMonitor::Primary => None,
+    };
+    monitor.or_else(|| win.primary_monitor().ok().flatten())
+}
+
+// 将面板停靠到显示器的指定边缘，Spotlight 模式下居中放置
+// 显示器位置和尺寸为物理像素，先移动到目标显示器，再按该显示器的缩放比例设置尺寸
+fn dock_panel(win: &WebviewWindow, monitor: &Monitor, layout: &PanelLayout) {
+    let scale_factor = monitor.scale_factor();
+    let screen_size = monitor.size();
+    let screen_position = monitor.position();
+    let (screen_x, screen_y) = (screen_position.x as f64, screen_position.y as f64);
+    let (screen_width, screen_height) = (screen_size.width as f64, screen_size.height as f64);
+
+    let panel_height = (layout.height * scale_factor).round().min(screen_height);
+    let panel_width = (layout.width * scale_factor).round().min(screen_width);
+    let (x, y, width, height) = match (layout.mode, layout.edge) {
+        (PanelMode::Spotlight, _) => {
+            let width = (SPOTLIGHT_WIDTH * scale_factor).round().min(screen_width);
+            let height = (SPOTLIGHT_HEIGHT * scale_factor).round().min(screen_height);
+            let x = screen_x + ((screen_width - width) / 2.0).round();
+            let y = screen_y + (screen_height * SPOTLIGHT_TOP_RATIO).round().min(screen_height - height);
+            (x, y, width, height)
+        }
+        (PanelMode::Dock, PanelEdge::Bottom) => {
+            (screen_x, screen_y + screen_height - panel_height, screen_width, panel_height)
+        }
+        (PanelMode::Dock, PanelEdge::Top) => (screen_x, screen_y, screen_width, panel_height),
+        (PanelMode::Dock, PanelEdge::Left) => (screen_x, screen_y, panel_width, screen_height),
+        (PanelMode::Dock, PanelEdge::Right) => {
+            (screen_x + screen_width - panel_width, screen_y, panel_width, screen_height)
+        }
+    };
+
+    let _ = win.set_position(tauri::Position::Physical(tauri::PhysicalPosition::new(x as i32, y as i32)));
+    let _ = win.set_size(tauri::Size::Physical(tauri::PhysicalSize::new(width as u32, height as u32)));
+    println!(
+        "Panel placed ({} / {}) on {:?}: {}x{} at physical ({}, {}), scale {}",
+        layout.mode.as_str(),
+        layout.edge.as_str(),
+        monitor.name(),
+        width,
+        height,
+        x,
+        y,
+        scale_factor
+    );
+}
+
+// 显示面板前重新停靠（显示器和布局可能已变化）
+fn position_panel(win: &WebviewWindow) {
+    let settings = panel_settings(win.app_handle());
+    match target_monitor(win, &settings) {
+        Some(monitor) => dock_panel(win, &monitor, &PanelLayout::from_settings(&settings)),
+        None => println!("Warning: no monitor found, keeping panel position"),
+    }
+}
+
+// 显示面板并获得焦点
+#[cfg(target_os = "macos")]
+fn show_panel(win: &WebviewWindow) {
+    if let Ok(panel) = win.to_panel::<MyPanel>() {
+        // 第一步：显示面板并成为关键窗口
+        let _ = panel.show_and_make_key();
+        // 第二步：确保窗口获得焦点
+        let _ = win.set_focus();
+        // 第三步：再次尝试成为关键窗口
+        let _ = panel.show_and_make_key();
+
+        // 验证焦点状态
+        match win.is_focused() {
+            Ok(focused) => {
+                println!("NSPanel focus status: {}", focused);
+                if !focused {
+                    println!("NSPanel not focused, trying additional focus methods");
+                    let _ = win.set_focus();
+                    let _ = panel.show_and_make_key();
+                }
+            }
+            Err(e) => println!("Failed to check NSPanel focus: {:?}", e),
+        }
+    } else {
+        println!("Failed to convert to NSPanel, using regular window methods");
+        let _ = win.show();
+        let _ = win.set_focus();
+    }
+}
+
+// 其他平台使用置顶的无边框窗口代替 NSPanel
+#[cfg(not(target_os = "macos"))]
+fn show_panel(win: &WebviewWindow) {
+    let _ = win.set_always_on_top(true);
+    let _ = win.show();
+    let _ = win.unminimize();
+    let _ = win.set_focus();
+}
+
+#[cfg(target_os = "macos")]
 pub fn setup_panel_window(app: &AppHandle<Wry>) {
     // 明确检查窗口标签，只对 copy-panel 窗口进行 NSPanel 转换
     if let Some(win) = app.get_webview_window("copy-panel") {
@@ -37,45 +314,7 @@ pub fn setup_panel_window(app: &AppHandle<Wry>) {
                     );
 
                     // 设置位置和大小
-                    let screen = win.primary_monitor().unwrap().unwrap();
-                    let screen_width = screen.size().width;
-                    let screen_height = screen.size().height;
-                    let scale_factor = screen.scale_factor();
-
-                    // 使用逻辑尺寸
-                    let logical_screen_width = screen_width as f64 / scale_factor;
-                    let logical_screen_height = screen_height as f64 / scale_factor;
-
-                    let panel_height = 332.0;
-                    let panel_width = logical_screen_width;
-
-                    // 使用物理坐标来设置位置，确保面板在屏幕底部
-                    let physical_x = 0.0;
-                    let physical_y = screen_height as f64 - (panel_height * scale_factor);
-
-                    println!(
-                        "Physical Screen: {}x{}, Scale: {}",
-                        screen_width, screen_height, scale_factor
-                    );
-                    println!(
-                        "Logical Screen: {}x{}",
-                        logical_screen_width, logical_screen_height
-                    );
-                    println!(
-                        "Panel: {}x{} at physical ({}, {})",
-                        panel_width, panel_height, physical_x, physical_y
-                    );
-
-                    win.set_size(tauri::Size::Logical(tauri::LogicalSize::new(
-                        panel_width,
-                        panel_height,
-                    )))
-                    .unwrap();
-                    win.set_position(tauri::Position::Physical(tauri::PhysicalPosition::new(
-                        physical_x as i32,
-                        physical_y as i32,
-                    )))
-                    .unwrap();
+                    position_panel(&win);
 
                     // 获取设置后的实际位置
                     if let Ok(position) = win.outer_position() {
@@ -123,8 +362,59 @@ pub fn setup_panel_window(app: &AppHandle<Wry>) {
     }
 }
 
+// Windows/Linux：没有 NSPanel，将 copy-panel 配置为不出现在任务栏的置顶无边框窗口
+#[cfg(not(target_os = "macos"))]
+pub fn setup_panel_window(app: &AppHandle<Wry>) {
+    let Some(win) = app.get_webview_window("copy-panel") else {
+        println!("Warning: copy-panel window not found during setup");
+        return;
+    };
+
+    let _ = win.set_decorations(false);
+    let _ = win.set_always_on_top(true);
+    let _ = win.set_skip_taskbar(true);
+    let _ = win.set_resizable(false);
+    position_panel(&win);
+    let _ = win.hide();
+    println!("Borderless panel window setup completed for copy-panel");
+}
+
+// 快速记录窗口的标签和尺寸
+pub const QUICK_CAPTURE_LABEL: &str = "quick-capture";
+const QUICK_CAPTURE_WIDTH: f64 = 420.0;
+const QUICK_CAPTURE_HEIGHT: f64 = 260.0;
+
+// 打开快速记录窗口（首次打开时创建，之后复用），用于不经过剪贴板直接新建记录
+pub fn open_quick_capture_window(app: &AppHandle) -> Result<(), OicopyError> {
+    let win = match app.get_webview_window(QUICK_CAPTURE_LABEL) {
+        Some(win) => win,
+        None => WebviewWindowBuilder::new(
+            app,
+            QUICK_CAPTURE_LABEL,
+            WebviewUrl::App("index.html#/quick-capture".into()),
+        )
+        .title("新建片段")
+        .inner_size(QUICK_CAPTURE_WIDTH, QUICK_CAPTURE_HEIGHT)
+        .resizable(false)
+        .minimizable(false)
+        .maximizable(false)
+        .always_on_top(true)
+        .center()
+        .visible(false)
+        .build()
+        .map_err(|e| OicopyError::Io(format!("创建快速记录窗口失败: {}", e)))?,
+    };
+
+    // 快速记录窗口是普通窗口，不进行 NSPanel 转换
+    let _ = win.center();
+    let _ = win.show();
+    let _ = win.set_focus();
+    println!("Quick capture window shown");
+    Ok(())
+}
+
 #[tauri::command]
-pub fn open_panel_window(app: AppHandle, panel_name: String) -> Result<(), String> {
+pub fn open_panel_window(app: AppHandle, panel_name: String) -> Result<(), OicopyError> {
     match panel_name.as_str() {
         "copy-panel" => {
             if let Some(win) = app.get_webview_window("copy-panel") {
@@ -136,38 +426,14 @@ pub fn open_panel_window(app: AppHandle, panel_name: String) -> Result<(), Strin
                         println!(
                             "User should complete permission setup in check-permissions first."
                         );
-                        return Err("权限设置窗口正在显示，请先完成权限授权".into());
+                        return Err(OicopyError::Permission("权限设置窗口正在显示，请先完成权限授权".into()));
                     }
                 }
 
                 // 如果面板已经显示，确保它获得焦点
                 if win.is_visible().unwrap_or(false) {
-                    println!("NSPanel is already visible, ensuring it has focus");
-
-                    if let Ok(panel) = win.to_panel::<MyPanel>() {
-                        // 强制成为关键窗口并获得焦点
-                        let _ = panel.show_and_make_key();
-                        let _ = win.set_focus();
-                        let _ = panel.show_and_make_key(); // 再次确保
-
-                        // 验证焦点状态
-                        match win.is_focused() {
-                            Ok(focused) => {
-                                println!("NSPanel focus status: {}", focused);
-                                if !focused {
-                                    println!(
-                                        "NSPanel not focused, trying additional focus methods"
-                                    );
-                                    // 额外的焦点尝试
-                                    let _ = win.set_focus();
-                                    let _ = panel.show_and_make_key();
-                                }
-                            }
-                            Err(e) => println!("Failed to check NSPanel focus: {:?}", e),
-                        }
-                    } else {
-                        let _ = win.set_focus();
-                    }
+                    println!("Panel is already visible, ensuring it has focus");
+                    show_panel(&win);
                     return Ok(());
                 }
 
@@ -175,58 +441,19 @@ pub fn open_panel_window(app: AppHandle, panel_name: String) -> Result<(), Strin
                 if let Some(check_permissions_win) = app.get_webview_window("check-permissions") {
                     if check_permissions_win.is_visible().unwrap_or(false) {
                         println!("FINAL CHECK FAILED: check-permissions became visible, aborting copy-panel display");
-                        return Err("权限设置窗口已显示，取消主面板显示".into());
+                        return Err(OicopyError::Permission("权限设置窗口已显示，取消主面板显示".into()));
                     }
                 }
 
-                // 在显示之前重新设置位置
-                let screen = win.primary_monitor().unwrap().unwrap();
-                let screen_height = screen.size().height;
-                let scale_factor = screen.scale_factor();
-
-                let panel_height = 332.0;
-                let physical_x = 0.0;
-                let physical_y = screen_height as f64 - (panel_height * scale_factor);
-
-                let _ = win.set_position(tauri::Position::Physical(tauri::PhysicalPosition::new(
-                    physical_x as i32,
-                    physical_y as i32,
-                )));
-                println!(
-                    "Opening NSPanel at physical position: ({}, {})",
-                    physical_x, physical_y
-                );
-
-                if let Ok(panel) = win.to_panel::<MyPanel>() {
-                    // 强制显示并获得焦点
-                    println!("Attempting to show NSPanel and make it key window");
-
-                    // 第一步：显示面板
-                    let _ = panel.show_and_make_key();
+                // 面板会激活本应用，需在显示之前记录前台应用，供 paste_item 粘贴时重新激活
+                remember_frontmost_app(&app.config().identifier);
 
-                    // 第二步：确保窗口获得焦点
-                    let _ = win.set_focus();
-
-                    // 第三步：再次尝试成为关键窗口
-                    let _ = panel.show_and_make_key();
-
-                    // 第四步：使用 Tauri 的焦点方法
-                    let _ = win.set_focus();
-
-                    // 验证焦点状态
-                    match win.is_focused() {
-                        Ok(focused) => println!("NSPanel focus status after setup: {}", focused),
-                        Err(e) => println!("Failed to check NSPanel focus status: {:?}", e),
-                    }
-
-                    println!("NSPanel shown with enhanced focus attempts");
-                } else {
-                    println!("Failed to convert to NSPanel, using regular window methods");
-                    let _ = win.show();
-                    let _ = win.set_focus();
-                }
+                // 在显示之前重新设置位置
+                position_panel(&win);
+                show_panel(&win);
+                println!("Panel shown with enhanced focus attempts");
             } else {
-                return Err("copy-panel不存在".into());
+                return Err(OicopyError::NotFound("copy-panel不存在".into()));
             }
         }
         "check-permissions" => {
@@ -249,7 +476,7 @@ pub fn open_panel_window(app: AppHandle, panel_name: String) -> Result<(), Strin
 
                 println!("Setting panel window shown as regular window");
             } else {
-                return Err("check-permissions不存在".into());
+                return Err(OicopyError::NotFound("check-permissions不存在".into()));
             }
         }
         "settings" => {
@@ -259,81 +486,113 @@ pub fn open_panel_window(app: AppHandle, panel_name: String) -> Result<(), Strin
                 let _ = win.set_focus();
                 println!("Settings window shown");
             } else {
-                return Err("settings不存在".into());
+                return Err(OicopyError::NotFound("settings不存在".into()));
             }
         }
-        _ => return Err(format!("未知面板：{}", panel_name)),
+        QUICK_CAPTURE_LABEL => open_quick_capture_window(&app)?,
+        _ => return Err(OicopyError::NotFound(format!("未知面板：{}", panel_name))),
     }
     Ok(())
 }
 
 #[tauri::command]
-pub fn hide_panel_window(app: AppHandle, panel_name: String) -> Result<(), String> {
+pub fn hide_panel_window(app: AppHandle, panel_name: String) -> Result<(), OicopyError> {
     match panel_name.as_str() {
         "copy-panel" => {
             if let Some(win) = app.get_webview_window("copy-panel") {
                 let _ = win.hide();
             } else {
-                return Err("copy-panel不存在".into());
+                return Err(OicopyError::NotFound("copy-panel不存在".into()));
             }
         }
         "check-permissions" => {
             if let Some(win) = app.get_webview_window("check-permissions") {
                 let _ = win.hide();
             } else {
-                return Err("check-permissions不存在".into());
+                return Err(OicopyError::NotFound("check-permissions不存在".into()));
             }
         }
         "settings" => {
             if let Some(win) = app.get_webview_window("settings") {
                 let _ = win.hide();
             } else {
-                return Err("settings不存在".into());
+                return Err(OicopyError::NotFound("settings不存在".into()));
             }
         }
-        _ => return Err(format!("未知面板：{}", panel_name)),
+        QUICK_CAPTURE_LABEL => {
+            if let Some(win) = app.get_webview_window(QUICK_CAPTURE_LABEL) {
+                let _ = win.hide();
+            } else {
+                return Err(OicopyError::NotFound("quick-capture不存在".into()));
+            }
+        }
+        _ => return Err(OicopyError::NotFound(format!("未知面板：{}", panel_name))),
     }
     Ok(())
 }
 
 #[tauri::command]
-pub fn toggle_panel_window(app: AppHandle, panel_name: String) -> Result<(), String> {
+pub fn toggle_panel_window(app: AppHandle, panel_name: String) -> Result<(), OicopyError> {
     match panel_name.as_str() {
         "copy-panel" => {
             if let Some(win) = app.get_webview_window("copy-panel") {
                 if win.is_visible().unwrap_or(false) {
                     let _ = win.hide();
                 } else {
+                    remember_frontmost_app(&app.config().identifier);
+
                     // 在显示之前重新设置位置
-                    let screen = win.primary_monitor().unwrap().unwrap();
-                    let _screen_width = screen.size().width as f64 / screen.scale_factor();
-                    let screen_height = screen.size().height as f64 / screen.scale_factor();
-
-                    let panel_height = 332.0;
-                    let x = 0.0;
-                    let y = screen_height - panel_height;
-
-                    let _ = win
-                        .set_position(tauri::Position::Logical(tauri::LogicalPosition::new(x, y)));
-                    println!("Toggling panel at position: ({}, {})", x, y);
-
-                    if let Ok(panel) = win.to_panel::<MyPanel>() {
-                        // 强制显示并获得焦点
-                        let _ = panel.show_and_make_key();
-                        let _ = win.set_focus();
-                        let _ = panel.show_and_make_key();
-                        println!("NSPanel toggled and focused");
-                    } else {
-                        let _ = win.show();
-                        let _ = win.set_focus();
-                        println!("Regular window toggled and focused");
-                    }
+                    position_panel(&win);
+                    show_panel(&win);
+                    println!("Panel toggled and focused");
                 }
             } else {
-                return Err("copy-panel不存在".into());
+                return Err(OicopyError::NotFound("copy-panel不存在".into()));
             }
         }
-        _ => return Err(format!("未知面板：{}", panel_name)),
+        _ => return Err(OicopyError::NotFound(format!("未知面板：{}", panel_name))),
     }
     Ok(())
 }
+
+// 获取面板布局命令
+#[tauri::command]
+pub async fn get_panel_layout(app: AppHandle) -> Result<PanelLayout, OicopyError> {
+    let conn = init_database(&app)?;
+    let settings = get_settings(&conn)?;
+    Ok(PanelLayout::from_settings(&settings))
+}
+
+// 设置面板展示方式、停靠边缘和尺寸命令，保存后立即重新定位面板并通知前端切换界面
+#[tauri::command]
+pub async fn set_panel_layout(app: AppHandle, layout: PanelLayout) -> Result<PanelLayout, OicopyError> {
+    if !PANEL_HEIGHT_RANGE.contains(&layout.height) {
+        return Err(OicopyError::Validation(format!(
+            "面板高度必须在 {} 到 {} 之间",
+            PANEL_HEIGHT_RANGE.start(),
+            PANEL_HEIGHT_RANGE.end()
+        )));
+    }
+    if !PANEL_WIDTH_RANGE.contains(&layout.width) {
+        return Err(OicopyError::Validation(format!(
+            "面板宽度必须在 {} 到 {} 之间",
+            PANEL_WIDTH_RANGE.start(),
+            PANEL_WIDTH_RANGE.end()
+        )));
+    }
+
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let mut settings = get_settings(&conn)?;
+    settings.panel_mode = layout.mode.as_str().to_string();
+    settings.panel_edge = layout.edge.as_str().to_string();
+    settings.panel_height = layout.height;
+    settings.panel_width = layout.width;
+    save_settings(&conn, &settings)?;
+
+    if let Some(win) = app.get_webview_window("copy-panel") {
+        position_panel(&win);
+    }
+    let _ = app.emit("panel-layout-changed", &layout);
+    Ok(layout)
+}
@@ -0,0 +1,49 @@
+// 模拟 Cmd+V 按键，将剪贴板内容粘贴到前台应用（需要辅助功能权限）
+#[cfg(target_os = "macos")]
+pub fn synthesize_paste() -> Result<(), String> {
+    use std::ffi::c_void;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceCreate(state_id: i32) -> *mut c_void;
+        fn CGEventCreateKeyboardEvent(source: *mut c_void, keycode: u16, key_down: bool) -> *mut c_void;
+        fn CGEventSetFlags(event: *mut c_void, flags: u64);
+        fn CGEventPost(tap: u32, event: *mut c_void);
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(cf: *const c_void);
+    }
+
+    const HID_SYSTEM_STATE: i32 = 1; // kCGEventSourceStateHIDSystemState
+    const HID_EVENT_TAP: u32 = 0; // kCGHIDEventTap
+    const KEY_V: u16 = 0x09; // kVK_ANSI_V
+    const FLAG_COMMAND: u64 = 0x0010_0000; // kCGEventFlagMaskCommand
+
+    unsafe {
+        let source = CGEventSourceCreate(HID_SYSTEM_STATE);
+        if source.is_null() {
+            return Err("创建键盘事件源失败".to_string());
+        }
+
+        for key_down in [true, false] {
+            let event = CGEventCreateKeyboardEvent(source, KEY_V, key_down);
+            if event.is_null() {
+                CFRelease(source);
+                return Err("创建键盘事件失败".to_string());
+            }
+            CGEventSetFlags(event, FLAG_COMMAND);
+            CGEventPost(HID_EVENT_TAP, event);
+            CFRelease(event);
+        }
+
+        CFRelease(source);
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn synthesize_paste() -> Result<(), String> {
+    Err("当前平台不支持模拟粘贴".to_string())
+}
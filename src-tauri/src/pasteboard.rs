@@ -0,0 +1,275 @@
+use crate::image_store::load_image_blob;
+use image::ImageFormat;
+use std::io::Cursor;
+use std::sync::Mutex;
+
+// nspasteboard.org 约定的标记类型：密码管理器等应用用它们声明内容不应被记录
+pub const CONCEALED_TYPE: &str = "org.nspasteboard.ConcealedType";
+pub const TRANSIENT_TYPE: &str = "org.nspasteboard.TransientType";
+
+// 本应用延迟提供数据时附带的标记类型，读取带该标记的剪贴板会触发图片数据生成
+pub const PROMISE_MARKER_TYPE: &str = "com.oicopy.promise";
+
+// 写回剪贴板时不小于该大小的图片只登记文件路径，目标应用请求时才读取
+pub const PROMISE_MIN_BYTES: u64 = 8 * 1024 * 1024;
+
+// 当前剪贴板上等待目标应用请求的图片
+struct PendingImage {
+    reference: String,
+    format: String,
+}
+
+static PENDING_IMAGE: Mutex<Option<PendingImage>> = Mutex::new(None);
+
+// 当前剪贴板是否带有隐藏/临时标记
+pub fn has_privacy_marker() -> bool {
+    [CONCEALED_TYPE, TRANSIENT_TYPE]
+        .iter()
+        .any(|marker| pasteboard_has_type(marker))
+}
+
+// 当前剪贴板是否为本应用延迟提供的内容
+pub fn has_promise_marker() -> bool {
+    pasteboard_has_type(PROMISE_MARKER_TYPE)
+}
+
+// 图片格式对应的剪贴板类型
+fn image_type_for_format(format: &str) -> &'static str {
+    match format.to_ascii_lowercase().as_str() {
+        "jpg" | "jpeg" => "public.jpeg",
+        "tif" | "tiff" => "public.tiff",
+        "gif" => "com.compuserve.gif",
+        _ => "public.png",
+    }
+}
+
+// 延迟提供的图片声明的类型：原始格式在前，另外提供 PNG 和 TIFF 供只接受这两种格式的应用使用
+fn promised_image_types(format: &str) -> Vec<&'static str> {
+    let mut types = vec![image_type_for_format(format)];
+    for fallback in ["public.png", "public.tiff"] {
+        if !types.contains(&fallback) {
+            types.push(fallback);
+        }
+    }
+    types
+}
+
+// 按目标应用请求的类型生成图片数据，与原始格式相同时直接使用文件内容
+fn encode_promised_image(bytes: Vec<u8>, format: &str, requested: &str) -> Result<Vec<u8>, String> {
+    if requested == image_type_for_format(format) {
+        return Ok(bytes);
+    }
+    let image_format = match requested {
+        "public.png" => ImageFormat::Png,
+        "public.tiff" => ImageFormat::Tiff,
+        _ => return Err(format!("不支持的图片类型: {}", requested)),
+    };
+    let image = image::load_from_memory(&bytes).map_err(|e| format!("解码图片失败: {}", e))?;
+    let mut output = Cursor::new(Vec::new());
+    image
+        .write_to(&mut output, image_format)
+        .map_err(|e| format!("转换图片格式失败: {}", e))?;
+    Ok(output.into_inner())
+}
+
+// 读取等待中的图片并按请求的类型生成数据
+fn materialize_pending_image(requested: &str) -> Result<Vec<u8>, String> {
+    let (reference, format) = PENDING_IMAGE
+        .lock()
+        .map_err(|_| "读取待提供图片失败".to_string())?
+        .as_ref()
+        .map(|pending| (pending.reference.clone(), pending.format.clone()))
+        .ok_or_else(|| "没有待提供的图片".to_string())?;
+    encode_promised_image(load_image_blob(&reference)?, &format, requested)
+}
+
+// 以延迟提供的方式将图片写入剪贴板：只声明图片类型，目标应用实际请求时才读取图片文件
+#[cfg(target_os = "macos")]
+pub fn write_image_promise(reference: &str, format: &str) -> Result<(), String> {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+
+    let provider = promise_provider()?;
+    *PENDING_IMAGE
+        .lock()
+        .map_err(|_| "登记待提供图片失败".to_string())? = Some(PendingImage {
+        reference: reference.to_string(),
+        format: format.to_string(),
+    });
+
+    unsafe {
+        let pasteboard: *mut AnyObject = msg_send![class!(NSPasteboard), generalPasteboard];
+        if pasteboard.is_null() {
+            return Err("无法访问系统剪贴板".to_string());
+        }
+
+        let types: *mut AnyObject = msg_send![class!(NSMutableArray), array];
+        for pasteboard_type in promised_image_types(format) {
+            let _: () = msg_send![types, addObject: ns_string(pasteboard_type)?];
+        }
+
+        let item: *mut AnyObject = msg_send![class!(NSPasteboardItem), alloc];
+        let item: *mut AnyObject = msg_send![item, init];
+        let provided: bool = msg_send![item, setDataProvider: provider, forTypes: types];
+        // 标记类型直接写入空数据，监听器据此跳过读取
+        let empty: *mut AnyObject = msg_send![class!(NSData), data];
+        let _: bool = msg_send![item, setData: empty, forType: ns_string(PROMISE_MARKER_TYPE)?];
+
+        let items: *mut AnyObject = msg_send![class!(NSArray), arrayWithObject: item];
+        let _: isize = msg_send![pasteboard, clearContents];
+        let written: bool = msg_send![pasteboard, writeObjects: items];
+        let _: () = msg_send![item, release];
+
+        if !provided || !written {
+            return Err("写入延迟提供的图片失败".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn write_image_promise(_reference: &str, _format: &str) -> Result<(), String> {
+    Err("当前平台不支持延迟写入剪贴板".to_string())
+}
+
+// 创建 NSString（调用方所在的自动释放池负责释放）
+#[cfg(target_os = "macos")]
+unsafe fn ns_string(value: &str) -> Result<*mut objc2::runtime::AnyObject, String> {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use std::ffi::CString;
+
+    let value = CString::new(value).map_err(|e| format!("转换字符串失败: {}", e))?;
+    let string: *mut AnyObject = msg_send![class!(NSString), stringWithUTF8String: value.as_ptr()];
+    if string.is_null() {
+        return Err("创建字符串失败".to_string());
+    }
+    Ok(string)
+}
+
+// 延迟提供数据的对象，整个应用共用一个实例
+#[cfg(target_os = "macos")]
+fn promise_provider() -> Result<*mut objc2::runtime::AnyObject, String> {
+    use std::sync::OnceLock;
+
+    static PROVIDER: OnceLock<usize> = OnceLock::new();
+    let provider = *PROVIDER.get_or_init(|| unsafe { create_promise_provider() } as usize);
+    if provider == 0 {
+        return Err("创建剪贴板数据提供者失败".to_string());
+    }
+    Ok(provider as *mut objc2::runtime::AnyObject)
+}
+
+// 注册实现 NSPasteboardItemDataProvider 的类并创建实例
+#[cfg(target_os = "macos")]
+unsafe fn create_promise_provider() -> *mut objc2::runtime::AnyObject {
+    use objc2::runtime::{AnyObject, AnyProtocol, ClassBuilder, Sel};
+    use objc2::{class, msg_send, sel};
+
+    let Some(mut builder) = ClassBuilder::new(c"OicopyImagePromiseProvider", class!(NSObject)) else {
+        return std::ptr::null_mut();
+    };
+    if let Some(protocol) = AnyProtocol::get(c"NSPasteboardItemDataProvider") {
+        builder.add_protocol(protocol);
+    }
+    builder.add_method(
+        sel!(pasteboard:item:provideDataForType:),
+        provide_promised_data as extern "C-unwind" fn(&AnyObject, Sel, *mut AnyObject, *mut AnyObject, *mut AnyObject),
+    );
+    let class = builder.register();
+    msg_send![class, new]
+}
+
+// 目标应用请求某种类型时由系统在主线程回调
+#[cfg(target_os = "macos")]
+extern "C-unwind" fn provide_promised_data(
+    _this: &objc2::runtime::AnyObject,
+    _cmd: objc2::runtime::Sel,
+    _pasteboard: *mut objc2::runtime::AnyObject,
+    item: *mut objc2::runtime::AnyObject,
+    pasteboard_type: *mut objc2::runtime::AnyObject,
+) {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use std::ffi::{c_char, CStr};
+
+    unsafe {
+        let name: *const c_char = msg_send![pasteboard_type, UTF8String];
+        if name.is_null() {
+            return;
+        }
+        let requested = CStr::from_ptr(name).to_string_lossy().into_owned();
+        match materialize_pending_image(&requested) {
+            Ok(bytes) => {
+                let data: *mut AnyObject = msg_send![
+                    class!(NSData),
+                    dataWithBytes: bytes.as_ptr() as *const std::ffi::c_void,
+                    length: bytes.len()
+                ];
+                let _: bool = msg_send![item, setData: data, forType: pasteboard_type];
+                println!("已按需提供剪贴板图片数据 ({}, {} 字节)", requested, bytes.len());
+            }
+            Err(e) => eprintln!("提供剪贴板图片数据失败: {}", e),
+        }
+    }
+}
+
+// 检查通用剪贴板中是否包含指定类型
+#[cfg(target_os = "macos")]
+fn pasteboard_has_type(pasteboard_type: &str) -> bool {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use std::ffi::CString;
+
+    let Ok(type_name) = CString::new(pasteboard_type) else {
+        return false;
+    };
+
+    unsafe {
+        let pasteboard: *mut AnyObject = msg_send![class!(NSPasteboard), generalPasteboard];
+        if pasteboard.is_null() {
+            return false;
+        }
+        let types: *mut AnyObject = msg_send![pasteboard, types];
+        if types.is_null() {
+            return false;
+        }
+        let name: *mut AnyObject =
+            msg_send![class!(NSString), stringWithUTF8String: type_name.as_ptr()];
+        if name.is_null() {
+            return false;
+        }
+        let contains: bool = msg_send![types, containsObject: name];
+        contains
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn pasteboard_has_type(_pasteboard_type: &str) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_promised_image_types() {
+        assert_eq!(promised_image_types("png"), vec!["public.png", "public.tiff"]);
+        assert_eq!(promised_image_types("JPG"), vec!["public.jpeg", "public.png", "public.tiff"]);
+    }
+
+    #[test]
+    fn test_encode_promised_image() {
+        let mut png = Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(2, 2)
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+        let png = png.into_inner();
+
+        assert_eq!(encode_promised_image(png.clone(), "png", "public.png").unwrap(), png);
+        let tiff = encode_promised_image(png.clone(), "png", "public.tiff").unwrap();
+        assert_eq!(image::guess_format(&tiff).unwrap(), ImageFormat::Tiff);
+        assert!(encode_promised_image(png, "png", "public.heic").is_err());
+    }
+}
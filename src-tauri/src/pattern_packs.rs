@@ -0,0 +1,252 @@
+use crate::clipboard_management::get_optimized_analyzer;
+use crate::error::OicopyError;
+use crate::http::http_get;
+use crate::performance_optimization::{AnalysisRule, PatternPack, BUILTIN_PATTERN_PACK_VERSION};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager, Runtime};
+
+const BUILTIN_PACK_FILE: &str = "builtin.json";
+// 用户编辑的规则表，存在时完整替代其他模式包
+const USER_RULES_FILE: &str = "user_rules.json";
+
+// 已加载模式包的概要信息
+#[derive(Debug, Clone, Serialize)]
+pub struct PatternPackInfo {
+    pub name: String,
+    pub version: u32,
+    pub application_count: usize,
+    pub applications: Vec<String>,
+}
+
+impl From<&PatternPack> for PatternPackInfo {
+    fn from(pack: &PatternPack) -> Self {
+        Self {
+            name: pack.name.clone(),
+            version: pack.version,
+            application_count: pack.applications.len(),
+            applications: pack.applications.iter().map(|a| a.name.clone()).collect(),
+        }
+    }
+}
+
+// 模式包目录
+fn pattern_pack_dir<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|_| "无法获取应用数据目录".to_string())?;
+    let dir = app_data_dir.join("pattern_packs");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建模式包目录失败: {}", e))?;
+    Ok(dir)
+}
+
+// 写出内置模式包（文件不存在或版本较旧时）
+fn ensure_builtin_pack(dir: &Path) -> Result<(), String> {
+    let path = dir.join(BUILTIN_PACK_FILE);
+    let up_to_date = read_pack_file(&path)
+        .map(|pack| pack.version >= BUILTIN_PATTERN_PACK_VERSION)
+        .unwrap_or(false);
+
+    if !up_to_date {
+        let json = serde_json::to_string_pretty(&PatternPack::builtin())
+            .map_err(|e| format!("序列化内置模式包失败: {}", e))?;
+        std::fs::write(&path, json).map_err(|e| format!("写入内置模式包失败: {}", e))?;
+        println!("已写出内置模式包: {:?}", path);
+    }
+    Ok(())
+}
+
+// 读取并解析单个模式包文件
+fn read_pack_file(path: &Path) -> Result<PatternPack, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| format!("读取模式包失败: {}", e))?;
+    parse_pack(&data)
+}
+
+fn parse_pack(data: &str) -> Result<PatternPack, String> {
+    let pack: PatternPack =
+        serde_json::from_str(data).map_err(|e| format!("解析模式包失败: {}", e))?;
+    pack.validate()?;
+    Ok(pack.normalize())
+}
+
+// 列出目录中的模式包文件：内置包在前，用户规则在最后，其余按文件名排序
+fn list_pack_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    files.sort_by_key(|path| {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        (name != BUILTIN_PACK_FILE, name == USER_RULES_FILE, name)
+    });
+    files
+}
+
+// 加载并合并目录中的全部模式包
+fn load_packs(dir: &Path) -> PatternPack {
+    let mut merged: Option<PatternPack> = None;
+
+    for path in list_pack_files(dir) {
+        match read_pack_file(&path) {
+            // 用户规则表是完整的规则集合（可以删除内置规则），直接替代
+            Ok(pack) if path.ends_with(USER_RULES_FILE) => merged = Some(pack),
+            Ok(pack) => {
+                merged = Some(match merged.take() {
+                    Some(mut base) => {
+                        base.merge(pack);
+                        base
+                    }
+                    None => pack,
+                });
+            }
+            // 单个文件损坏不影响其他模式包
+            Err(e) => eprintln!("跳过模式包 {:?}: {}", path, e),
+        }
+    }
+
+    merged.unwrap_or_else(PatternPack::builtin)
+}
+
+// 目录指纹：文件名、大小和修改时间，用于检测变化
+fn dir_fingerprint(dir: &Path) -> Vec<(PathBuf, u64, Option<SystemTime>)> {
+    list_pack_files(dir)
+        .into_iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(&path).ok();
+            let len = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+            let modified = metadata.and_then(|m| m.modified().ok());
+            (path, len, modified)
+        })
+        .collect()
+}
+
+// 重新加载模式包并应用到分析器
+fn reload_packs(dir: &Path) -> PatternPack {
+    let pack = load_packs(dir);
+    println!(
+        "模式包已加载: 版本 {}, {} 个应用签名",
+        pack.version,
+        pack.applications.len()
+    );
+    get_optimized_analyzer().set_pattern_pack(pack.clone());
+    pack
+}
+
+// 启动模式包监听任务（定期检查目录变化并热更新）
+pub fn start_pattern_pack_watcher<R: Runtime>(app_handle: AppHandle<R>) {
+    let dir = match pattern_pack_dir(&app_handle) {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("模式包监听启动失败: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = ensure_builtin_pack(&dir) {
+        eprintln!("{}", e);
+    }
+    reload_packs(&dir);
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+        let mut last_fingerprint = dir_fingerprint(&dir);
+
+        loop {
+            interval.tick().await;
+
+            let fingerprint = dir_fingerprint(&dir);
+            if fingerprint != last_fingerprint {
+                println!("检测到模式包变化，重新加载");
+                reload_packs(&dir);
+                last_fingerprint = fingerprint;
+            }
+        }
+    });
+}
+
+// 生成模式包文件名
+fn pack_file_name(pack: &PatternPack) -> String {
+    let name: String = pack
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    // 避免覆盖内置模式包
+    if name.is_empty() || name == "builtin" {
+        "community.json".to_string()
+    } else {
+        format!("{}.json", name)
+    }
+}
+
+// Tauri命令：从指定地址更新社区模式包
+#[tauri::command]
+pub async fn update_pattern_pack(app: AppHandle, url: String) -> Result<PatternPackInfo, String> {
+    // 模式包中的正则会直接用于分析剪贴板内容，只接受 https 地址，防止下载过程被篡改
+    if !url.trim_start().to_ascii_lowercase().starts_with("https://") {
+        return Err(format!("模式包地址必须使用 https: {}", url));
+    }
+    let data = http_get(url.trim(), &[]).map_err(|e| format!("下载模式包失败: {}", e))?;
+    let pack = parse_pack(&data)?;
+
+    let dir = pattern_pack_dir(&app)?;
+    let path = dir.join(pack_file_name(&pack));
+    std::fs::write(&path, &data).map_err(|e| format!("保存模式包失败: {}", e))?;
+    println!("已保存模式包 {} (版本 {}) 到 {:?}", pack.name, pack.version, path);
+
+    // 立即生效，不等待下一次轮询
+    let merged = reload_packs(&dir);
+    Ok(PatternPackInfo::from(&merged))
+}
+
+// Tauri命令：获取当前生效的模式包信息
+#[tauri::command]
+pub fn get_pattern_pack_info() -> PatternPackInfo {
+    PatternPackInfo::from(get_optimized_analyzer().pattern_pack().as_ref())
+}
+
+// Tauri命令：获取当前生效的分析规则表
+#[tauri::command]
+pub fn get_analysis_rules() -> Vec<AnalysisRule> {
+    get_optimized_analyzer().pattern_pack().to_rules()
+}
+
+// Tauri命令：保存分析规则表并立即生效，传入空列表恢复为模式包中的规则
+#[tauri::command]
+pub async fn save_analysis_rules(app: AppHandle, rules: Vec<AnalysisRule>) -> Result<Vec<AnalysisRule>, OicopyError> {
+    let dir = pattern_pack_dir(&app).map_err(OicopyError::Io)?;
+    let path = dir.join(USER_RULES_FILE);
+
+    if rules.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| OicopyError::Io(format!("删除规则表失败: {}", e)))?;
+        }
+        println!("已恢复模式包中的分析规则");
+        return Ok(reload_packs(&dir).to_rules());
+    }
+
+    for rule in &rules {
+        if rule.pattern.trim().is_empty() {
+            return Err(OicopyError::Validation("规则的模式不能为空".to_string()));
+        }
+        if !(0.0..=10.0).contains(&rule.score) {
+            return Err(OicopyError::Validation(format!("规则 {} 的分值必须在 0 到 10 之间", rule.pattern)));
+        }
+    }
+    let pack = PatternPack::from_rules("user_rules", &rules, &get_optimized_analyzer().pattern_pack());
+    pack.validate().map_err(OicopyError::Validation)?;
+
+    let json = serde_json::to_string_pretty(&pack).map_err(|e| format!("序列化规则表失败: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| OicopyError::Io(format!("保存规则表失败: {}", e)))?;
+    println!("已保存 {} 条分析规则到 {:?}", rules.len(), path);
+
+    // 立即生效，不等待下一次轮询
+    Ok(reload_packs(&dir).to_rules())
+}
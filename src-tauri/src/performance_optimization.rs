@@ -1,6 +1,7 @@
+use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::fmt;
 
 // 性能优化相关错误类型
@@ -31,8 +32,7 @@ impl fmt::Display for PerformanceError {
 impl std::error::Error for PerformanceError {}
 
 // 分析配置
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AnalysisConfig {
     pub similarity_threshold: f64,        // 默认: 0.95
     pub analysis_timeout_ms: u64,         // 默认: 200
@@ -136,14 +136,265 @@ impl PerformanceMonitor {
     }
 }
 
+// 应用程序特征签名
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSignature {
+    pub name: String,
+    pub patterns: Vec<String>,      // 任意一个模式命中即视为该应用
+    #[serde(default)]
+    pub redundancy_bonus: f64,      // 冗余评分阶段的附加分
+    #[serde(default)]
+    pub detection_bonus: f64,       // 检测到该应用后的附加冗余分
+}
+
+// 冗余指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedundancyIndicator {
+    pub pattern: String,
+    pub score: f64,
+}
+
+// 分析规则：模式命中时增加冗余分，标注应用时同时作为该应用的识别特征
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub score: f64,
+    #[serde(default)]
+    pub app: Option<String>,
+}
+
+// 新应用的默认附加分（与内置规则中等强度的应用一致）
+const DEFAULT_APP_REDUNDANCY_BONUS: f64 = 1.0;
+const DEFAULT_APP_DETECTION_BONUS: f64 = 1.5;
+
+// 检测模式包（可从数据文件加载并热更新）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternPack {
+    pub version: u32,
+    #[serde(default)]
+    pub name: String,
+    pub applications: Vec<AppSignature>,
+    #[serde(default)]
+    pub redundancy_indicators: Vec<RedundancyIndicator>,
+}
+
+// 内置模式包版本，内置规则变化时递增
+pub const BUILTIN_PATTERN_PACK_VERSION: u32 = 2;
+
+impl PatternPack {
+    // 内置检测规则
+    pub fn builtin() -> Self {
+        let signature = |name: &str, patterns: &[&str], redundancy_bonus: f64, detection_bonus: f64| AppSignature {
+            name: name.to_string(),
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            redundancy_bonus,
+            detection_bonus,
+        };
+        let indicator = |pattern: &str, score: f64| RedundancyIndicator {
+            pattern: pattern.to_string(),
+            score,
+        };
+
+        Self {
+            version: BUILTIN_PATTERN_PACK_VERSION,
+            name: "builtin".to_string(),
+            applications: vec![
+                signature("ChatGPT", &["data-testid=\"conversation-turn", "markdown prose w-full"], 2.0, 3.0),
+                // Google Docs 复制内容外层带 docs-internal-guid，且充满内联样式
+                signature("GoogleDocs", &["docs-internal-guid"], 1.5, 2.0),
+                // Notion 导出的块带有 data-block-id / notion- 前缀类名
+                signature("Notion", &["data-block-id", "notion-", "notion.so"], 0.5, 1.0),
+                // Slack 消息的富文本容器
+                signature("Slack", &["p-rich_text_section", "c-message_kit", "c-mrkdwn__"], 2.0, 2.5),
+                signature("MicrosoftOffice", &["mso-", "xmlns:o=", "<!--[if"], 1.5, 2.5),
+                signature("AppleEcosystem", &["apple-converted-space", "webkit-"], 1.0, 1.5),
+            ],
+            redundancy_indicators: vec![
+                indicator("mso-", 3.0),
+                indicator("microsoft", 2.5),
+                indicator("office", 2.5),
+                indicator("xmlns:o=", 3.5),
+                indicator("<!--[if", 3.5),
+                indicator("apple-converted-space", 2.5),
+                indicator("webkit-", 2.0),
+                indicator("chatgpt", 3.0),
+                indicator("conversation-turn", 4.0),
+                indicator("data-testid=\"conversation", 4.0),
+                indicator("docs-internal-guid", 3.0),
+                indicator("font-variant-east-asian", 1.5),
+                indicator("p-rich_text_section", 3.0),
+                indicator("c-message_kit", 2.5),
+                indicator("c-mrkdwn__", 2.0),
+            ],
+        }
+    }
+
+    // 校验模式包内容
+    pub fn validate(&self) -> Result<(), String> {
+        if self.applications.is_empty() {
+            return Err("模式包中没有任何应用签名".to_string());
+        }
+        for app in &self.applications {
+            if app.name.trim().is_empty() {
+                return Err("应用签名名称不能为空".to_string());
+            }
+            if app.patterns.iter().all(|p| p.trim().is_empty()) {
+                return Err(format!("应用签名 {} 没有有效的模式", app.name));
+            }
+        }
+        Ok(())
+    }
+
+    // 模式统一转为小写，与分析时的小写 HTML 匹配
+    pub fn normalize(mut self) -> Self {
+        for app in &mut self.applications {
+            app.patterns = app
+                .patterns
+                .iter()
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .collect();
+        }
+        for indicator in &mut self.redundancy_indicators {
+            indicator.pattern = indicator.pattern.to_lowercase();
+        }
+        self
+    }
+
+    // 合并另一个模式包：同名应用以后者为准，冗余指标按模式去重
+    pub fn merge(&mut self, other: PatternPack) {
+        for app in other.applications {
+            match self.applications.iter_mut().find(|a| a.name == app.name) {
+                Some(existing) => *existing = app,
+                None => self.applications.push(app),
+            }
+        }
+        for indicator in other.redundancy_indicators {
+            match self
+                .redundancy_indicators
+                .iter_mut()
+                .find(|i| i.pattern == indicator.pattern)
+            {
+                Some(existing) => *existing = indicator,
+                None => self.redundancy_indicators.push(indicator),
+            }
+        }
+        self.version = self.version.max(other.version);
+    }
+
+    pub fn application(&self, name: &str) -> Option<&AppSignature> {
+        self.applications.iter().find(|a| a.name == name)
+    }
+
+    // 展开为规则表：冗余指标在前，只用于识别应用的模式记为 0 分
+    pub fn to_rules(&self) -> Vec<AnalysisRule> {
+        let app_for = |pattern: &str| {
+            self.applications
+                .iter()
+                .find(|app| app.patterns.iter().any(|p| p == pattern))
+                .map(|app| app.name.clone())
+        };
+        let mut rules: Vec<AnalysisRule> = self
+            .redundancy_indicators
+            .iter()
+            .map(|indicator| AnalysisRule {
+                pattern: indicator.pattern.clone(),
+                score: indicator.score,
+                app: app_for(&indicator.pattern),
+            })
+            .collect();
+        for app in &self.applications {
+            for pattern in &app.patterns {
+                if !self.redundancy_indicators.iter().any(|i| &i.pattern == pattern) {
+                    rules.push(AnalysisRule {
+                        pattern: pattern.clone(),
+                        score: 0.0,
+                        app: Some(app.name.clone()),
+                    });
+                }
+            }
+        }
+        rules
+    }
+
+    // 由规则表构建模式包，已有应用沿用 base 中的附加分
+    pub fn from_rules(name: &str, rules: &[AnalysisRule], base: &PatternPack) -> Self {
+        let mut pack = Self {
+            version: base.version,
+            name: name.to_string(),
+            applications: Vec::new(),
+            redundancy_indicators: Vec::new(),
+        };
+        for rule in rules {
+            if rule.score > 0.0 {
+                pack.redundancy_indicators.push(RedundancyIndicator {
+                    pattern: rule.pattern.clone(),
+                    score: rule.score,
+                });
+            }
+            let Some(app_name) = rule.app.as_deref().map(str::trim).filter(|name| !name.is_empty()) else {
+                continue;
+            };
+            match pack.applications.iter_mut().find(|app| app.name == app_name) {
+                Some(app) => app.patterns.push(rule.pattern.clone()),
+                None => {
+                    let existing = base.application(app_name);
+                    pack.applications.push(AppSignature {
+                        name: app_name.to_string(),
+                        patterns: vec![rule.pattern.clone()],
+                        redundancy_bonus: existing.map_or(DEFAULT_APP_REDUNDANCY_BONUS, |app| app.redundancy_bonus),
+                        detection_bonus: existing.map_or(DEFAULT_APP_DETECTION_BONUS, |app| app.detection_bonus),
+                    });
+                }
+            }
+        }
+        pack.normalize()
+    }
+}
+
 // 优化的内容分析器
 pub struct OptimizedContentAnalyzer {
-    config: AnalysisConfig,
+    config: RwLock<AnalysisConfig>,
+    pattern_pack: RwLock<Arc<PatternPack>>,
 }
 
 impl OptimizedContentAnalyzer {
     pub fn new(config: AnalysisConfig) -> Self {
-        Self { config }
+        Self {
+            config: RwLock::new(config),
+            pattern_pack: RwLock::new(Arc::new(PatternPack::builtin())),
+        }
+    }
+
+    // 获取当前的分析配置
+    pub fn config(&self) -> AnalysisConfig {
+        self.config
+            .read()
+            .map(|config| config.clone())
+            .unwrap_or_default()
+    }
+
+    // 替换分析配置（设置修改后热更新）
+    pub fn set_config(&self, config: AnalysisConfig) {
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
+    }
+
+    // 获取当前使用的模式包
+    pub fn pattern_pack(&self) -> Arc<PatternPack> {
+        self.pattern_pack
+            .read()
+            .map(|pack| Arc::clone(&pack))
+            .unwrap_or_else(|_| Arc::new(PatternPack::builtin()))
+    }
+
+    // 替换模式包（热更新）
+    pub fn set_pattern_pack(&self, pack: PatternPack) {
+        if let Ok(mut current) = self.pattern_pack.write() {
+            *current = Arc::new(pack);
+        }
     }
 
     // 带性能监控的内容分析
@@ -151,7 +402,8 @@ impl OptimizedContentAnalyzer {
     where
         F: FnOnce(&str, &PerformanceMonitor) -> Result<R, PerformanceError>,
     {
-        let monitor = PerformanceMonitor::new(&self.config);
+        let config = self.config();
+        let monitor = PerformanceMonitor::new(&config);
         
         // 检查内容大小
         monitor.check_content_size(content)?;
@@ -162,7 +414,7 @@ impl OptimizedContentAnalyzer {
         // 记录完成
         monitor.record_completion();
         
-        if self.config.log_analysis_details {
+        if config.log_analysis_details {
             println!("Analysis completed in {}ms", monitor.start_time.elapsed().as_millis());
         }
         
@@ -343,34 +595,20 @@ impl OptimizedContentAnalyzer {
     ) -> Result<Option<String>, PerformanceError> {
         monitor.check_timeout()?;
 
-        if !self.config.enable_app_detection {
+        if !self.config().enable_app_detection {
             return Ok(None);
         }
 
-        // 快速检测常见模式
         let html_lower = html.to_lowercase();
-        
-        // 检查ChatGPT模式
-        if html_lower.contains("data-testid=\"conversation-turn") 
-            || html_lower.contains("markdown prose w-full") {
-            return Ok(Some("ChatGPT".to_string()));
-        }
-
-        monitor.check_timeout()?;
+        let pack = self.pattern_pack();
 
-        // 检查Office模式
-        if html_lower.contains("mso-") 
-            || html_lower.contains("xmlns:o=") 
-            || html_lower.contains("<!--[if") {
-            return Ok(Some("MicrosoftOffice".to_string()));
-        }
-
-        monitor.check_timeout()?;
+        // 按模式包中的顺序逐个检测应用签名
+        for app in &pack.applications {
+            if app.patterns.iter().any(|pattern| html_lower.contains(pattern.as_str())) {
+                return Ok(Some(app.name.clone()));
+            }
 
-        // 检查Apple模式
-        if html_lower.contains("apple-converted-space") 
-            || html_lower.contains("webkit-") {
-            return Ok(Some("AppleEcosystem".to_string()));
+            monitor.check_timeout()?;
         }
 
         Ok(None)
@@ -653,6 +891,75 @@ mod tests {
         assert_eq!(detected, Some("ChatGPT".to_string()));
     }
 
+    #[test]
+    fn test_source_specific_app_detection() {
+        let analyzer = OptimizedContentAnalyzer::new(AnalysisConfig::default());
+        let samples = [
+            (r#"<b style="font-weight:normal;" id="docs-internal-guid-1a2b"><p>Hi</p></b>"#, "GoogleDocs"),
+            (r#"<div data-block-id="8f2c"><p>Notes</p></div>"#, "Notion"),
+            (r#"<div class="p-rich_text_section">deploy done</div>"#, "Slack"),
+        ];
+
+        for (html, expected) in samples {
+            let detected = analyzer.analyze_with_monitoring(html, |html, monitor| {
+                analyzer.detect_application_optimized(html, monitor)
+            });
+            assert_eq!(detected.unwrap(), Some(expected.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_pattern_pack_merge_overrides_by_name() {
+        let mut pack = PatternPack::builtin();
+        let community = PatternPack {
+            version: 7,
+            name: "community".to_string(),
+            applications: vec![AppSignature {
+                name: "ChatGPT".to_string(),
+                patterns: vec!["DATA-MESSAGE-AUTHOR-ROLE".to_string()],
+                redundancy_bonus: 2.0,
+                detection_bonus: 3.0,
+            }],
+            redundancy_indicators: Vec::new(),
+        }
+        .normalize();
+
+        pack.merge(community);
+        assert_eq!(pack.version, 7);
+        assert_eq!(pack.application("ChatGPT").unwrap().patterns, vec!["data-message-author-role".to_string()]);
+
+        let analyzer = OptimizedContentAnalyzer::new(AnalysisConfig::default());
+        analyzer.set_pattern_pack(pack);
+        let result = analyzer.analyze_with_monitoring("<div data-message-author-role=\"user\">hi</div>", |html, monitor| {
+            analyzer.detect_application_optimized(html, monitor)
+        });
+        assert_eq!(result.unwrap(), Some("ChatGPT".to_string()));
+    }
+
+    #[test]
+    fn test_pattern_pack_rules_round_trip() {
+        let builtin = PatternPack::builtin();
+        let mut rules = builtin.to_rules();
+        assert!(rules.contains(&AnalysisRule {
+            pattern: "docs-internal-guid".to_string(),
+            score: 3.0,
+            app: Some("GoogleDocs".to_string()),
+        }));
+
+        rules.push(AnalysisRule {
+            pattern: "Feishu-Doc".to_string(),
+            score: 2.0,
+            app: Some("Feishu".to_string()),
+        });
+        let pack = PatternPack::from_rules("user_rules", &rules, &builtin);
+        assert_eq!(pack.application("Feishu").unwrap().patterns, vec!["feishu-doc".to_string()]);
+        assert_eq!(
+            pack.application("ChatGPT").unwrap().redundancy_bonus,
+            builtin.application("ChatGPT").unwrap().redundancy_bonus
+        );
+        assert_eq!(pack.redundancy_indicators.len(), builtin.redundancy_indicators.len() + 1);
+    }
+
     #[test]
     fn test_benchmark_suite() {
         let config = AnalysisConfig {
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+
+// 系统权限（对应“系统设置 > 隐私与安全性”中的条目）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    Accessibility,
+    FullDiskAccess,
+}
+
+impl Capability {
+    // 直接打开对应设置面板的链接
+    pub fn settings_url(&self) -> &'static str {
+        match self {
+            Capability::Accessibility => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility"
+            }
+            Capability::FullDiskAccess => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles"
+            }
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Capability::Accessibility => "辅助功能",
+            Capability::FullDiskAccess => "完全磁盘访问",
+        }
+    }
+
+    pub async fn is_granted(&self) -> bool {
+        match self {
+            Capability::Accessibility => {
+                tauri_plugin_macos_permissions::check_accessibility_permission().await
+            }
+            Capability::FullDiskAccess => {
+                tauri_plugin_macos_permissions::check_full_disk_access_permission().await
+            }
+        }
+    }
+}
+
+// 依赖系统权限的功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    PasteBack,           // 模拟按键粘贴到前台应用
+    SourceAppDetection,  // 通过 NSWorkspace 获取前台应用
+    WindowTitleCapture,  // 读取前台窗口标题
+}
+
+impl Feature {
+    pub const ALL: [Feature; 3] = [
+        Feature::PasteBack,
+        Feature::SourceAppDetection,
+        Feature::WindowTitleCapture,
+    ];
+
+    // 功能所需的权限矩阵
+    pub fn required_capabilities(&self) -> &'static [Capability] {
+        match self {
+            Feature::PasteBack => &[Capability::Accessibility],
+            // NSWorkspace 查询前台应用不需要额外权限
+            Feature::SourceAppDetection => &[],
+            Feature::WindowTitleCapture => &[Capability::Accessibility],
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Feature::PasteBack => "粘贴到前台应用",
+            Feature::SourceAppDetection => "识别来源应用",
+            Feature::WindowTitleCapture => "记录窗口标题",
+        }
+    }
+}
+
+// 受权限控制的命令返回的错误，前端可根据 kind 跳转到对应设置面板
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FeatureError {
+    NeedsPermission {
+        feature: Feature,
+        capability: Capability,
+        settings_url: &'static str,
+        message: String,
+    },
+    // 粘贴前需要用户确认（内容过大或目标为终端），确认后带 confirmed 重新调用
+    NeedsConfirmation {
+        reason: String,
+        line_count: usize,
+        byte_count: usize,
+        target_app: String,
+        message: String,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl From<String> for FeatureError {
+    fn from(message: String) -> Self {
+        FeatureError::Failed { message }
+    }
+}
+
+impl std::fmt::Display for FeatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureError::NeedsPermission { message, .. }
+            | FeatureError::NeedsConfirmation { message, .. }
+            | FeatureError::Failed { message } => f.write_str(message),
+        }
+    }
+}
+
+// 检查功能所需的全部权限，返回第一个缺失的权限
+pub async fn require_feature(feature: Feature) -> Result<(), FeatureError> {
+    for capability in feature.required_capabilities() {
+        if !capability.is_granted().await {
+            return Err(FeatureError::NeedsPermission {
+                feature,
+                capability: *capability,
+                settings_url: capability.settings_url(),
+                message: format!("{}需要{}权限", feature.label(), capability.label()),
+            });
+        }
+    }
+    Ok(())
+}
+
+// 单个功能的权限状态
+#[derive(Debug, Serialize)]
+pub struct FeatureStatus {
+    pub feature: Feature,
+    pub label: &'static str,
+    pub required: Vec<Capability>,
+    pub missing: Vec<Capability>,
+    pub available: bool,
+}
+
+// Tauri命令：获取所有功能的权限状态
+#[tauri::command]
+pub async fn get_feature_permissions() -> Vec<FeatureStatus> {
+    let mut statuses = Vec::new();
+    for feature in Feature::ALL {
+        let mut missing = Vec::new();
+        for capability in feature.required_capabilities() {
+            if !capability.is_granted().await {
+                missing.push(*capability);
+            }
+        }
+        statuses.push(FeatureStatus {
+            feature,
+            label: feature.label(),
+            required: feature.required_capabilities().to_vec(),
+            available: missing.is_empty(),
+            missing,
+        });
+    }
+    statuses
+}
+
+// Tauri命令：打开指定权限的系统设置面板
+#[tauri::command]
+pub fn open_permission_settings(capability: Capability) -> Result<(), String> {
+    use std::process::Command;
+
+    Command::new("open")
+        .arg(capability.settings_url())
+        .status()
+        .map_err(|e| format!("打开系统设置失败: {}", e))?;
+    Ok(())
+}
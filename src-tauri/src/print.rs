@@ -0,0 +1,141 @@
+use crate::clipboard_content::ClipboardContent;
+use crate::db::{get_history_item, init_database};
+use crate::lock_mode::ensure_unlocked;
+use tauri::AppHandle;
+
+// 交给系统打印的文档
+enum PrintDocument {
+    Text(String),
+    Html(String),
+    Rtf(String),
+    Image(Vec<u8>),
+}
+
+impl PrintDocument {
+    fn from_content(content: ClipboardContent) -> Self {
+        match content {
+            ClipboardContent::Text { text } => PrintDocument::Text(text),
+            ClipboardContent::Html { sanitized, .. } => PrintDocument::Html(sanitized),
+            ClipboardContent::Rtf { rtf } => PrintDocument::Rtf(rtf),
+            ClipboardContent::Image { bytes, .. } => PrintDocument::Image(bytes),
+            // 文件列表打印为路径清单
+            ClipboardContent::Files { entries } => PrintDocument::Text(entries.join("\n")),
+        }
+    }
+}
+
+// 打印的页面内容宽度和初始高度（pt，对应 A4 去掉页边距），文本视图会按内容自动增高
+#[cfg(target_os = "macos")]
+const PAGE_CONTENT_WIDTH: f64 = 483.0;
+#[cfg(target_os = "macos")]
+const PAGE_CONTENT_HEIGHT: f64 = 700.0;
+
+// 通过 NSPrintOperation 弹出系统打印面板，必须在主线程调用
+#[cfg(target_os = "macos")]
+fn run_print_operation(document: PrintDocument) -> Result<(), String> {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use objc2_foundation::{NSPoint, NSRect, NSSize};
+    use std::ffi::CString;
+
+    unsafe {
+        let ns_data = |bytes: &[u8]| -> *mut AnyObject {
+            msg_send![class!(NSData), dataWithBytes: bytes.as_ptr() as *const std::ffi::c_void, length: bytes.len()]
+        };
+        let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(PAGE_CONTENT_WIDTH, PAGE_CONTENT_HEIGHT));
+
+        let empty_attributes = std::ptr::null_mut::<AnyObject>();
+        let attributed: *mut AnyObject = match document {
+            PrintDocument::Image(ref bytes) => {
+                let image: *mut AnyObject = msg_send![class!(NSImage), alloc];
+                let image: *mut AnyObject = msg_send![image, initWithData: ns_data(bytes)];
+                if image.is_null() {
+                    return Err("无法解析图片，打印失败".to_string());
+                }
+                let size: NSSize = msg_send![image, size];
+                let view: *mut AnyObject = msg_send![class!(NSImageView), alloc];
+                let view: *mut AnyObject =
+                    msg_send![view, initWithFrame: NSRect::new(NSPoint::new(0.0, 0.0), size)];
+                let _: () = msg_send![view, setImage: image];
+                let _: () = msg_send![image, release];
+                return run_view_print(view, true);
+            }
+            PrintDocument::Html(ref html) => {
+                let attributed: *mut AnyObject = msg_send![class!(NSAttributedString), alloc];
+                msg_send![attributed, initWithHTML: ns_data(html.as_bytes()), documentAttributes: empty_attributes]
+            }
+            PrintDocument::Rtf(ref rtf) => {
+                let attributed: *mut AnyObject = msg_send![class!(NSAttributedString), alloc];
+                msg_send![attributed, initWithRTF: ns_data(rtf.as_bytes()), documentAttributes: empty_attributes]
+            }
+            PrintDocument::Text(ref text) => {
+                let text = CString::new(text.replace('\0', ""))
+                    .map_err(|e| format!("转换打印文本失败: {}", e))?;
+                let string: *mut AnyObject =
+                    msg_send![class!(NSString), stringWithUTF8String: text.as_ptr()];
+                let attributed: *mut AnyObject = msg_send![class!(NSAttributedString), alloc];
+                msg_send![attributed, initWithString: string]
+            }
+        };
+        if attributed.is_null() {
+            return Err("无法解析内容格式，打印失败".to_string());
+        }
+
+        let view: *mut AnyObject = msg_send![class!(NSTextView), alloc];
+        let view: *mut AnyObject = msg_send![view, initWithFrame: frame];
+        let storage: *mut AnyObject = msg_send![view, textStorage];
+        let _: () = msg_send![storage, setAttributedString: attributed];
+        let _: () = msg_send![attributed, release];
+        let _: () = msg_send![view, sizeToFit];
+        run_view_print(view, false)
+    }
+}
+
+// 打印视图并在结束后释放；fit_to_page 为 true 时缩放到一页内
+#[cfg(target_os = "macos")]
+unsafe fn run_view_print(view: *mut objc2::runtime::AnyObject, fit_to_page: bool) -> Result<(), String> {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+
+    // NSPrintingPaginationMode.fit
+    const FIT_PAGINATION: isize = 1;
+
+    // 菜单栏应用没有前台窗口，先激活应用让打印面板显示在最前
+    let app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+    let _: () = msg_send![app, activateIgnoringOtherApps: true];
+
+    let operation: *mut AnyObject = msg_send![class!(NSPrintOperation), printOperationWithView: view];
+    if fit_to_page {
+        let info: *mut AnyObject = msg_send![operation, printInfo];
+        let _: () = msg_send![info, setHorizontalPagination: FIT_PAGINATION];
+        let _: () = msg_send![info, setVerticalPagination: FIT_PAGINATION];
+    }
+    let printed: bool = msg_send![operation, runOperation];
+    let _: () = msg_send![view, release];
+    println!("打印操作结束: {}", if printed { "已打印" } else { "已取消" });
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn run_print_operation(_document: PrintDocument) -> Result<(), String> {
+    Err("当前平台不支持打印".to_string())
+}
+
+// Tauri命令：通过系统打印面板打印记录（文本、HTML、RTF、图片或文件清单）
+#[tauri::command]
+pub async fn print_item(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let content = get_history_item(&conn, id)?
+        .ok_or_else(|| format!("记录 {} 不存在", id))?
+        .to_content()?;
+    let document = PrintDocument::from_content(content);
+
+    // AppKit 视图只能在主线程创建
+    app.run_on_main_thread(move || {
+        if let Err(e) = run_print_operation(document) {
+            eprintln!("打印记录失败: {}", e);
+        }
+    })
+    .map_err(|e| format!("打印记录失败: {}", e))
+}
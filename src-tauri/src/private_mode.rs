@@ -0,0 +1,97 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// 隐私模式状态：结束时间和计时器代数（重新设置后旧计时器失效）
+struct PrivateModeState {
+    until: Option<Instant>,
+    generation: u64,
+}
+
+static PRIVATE_MODE: Mutex<PrivateModeState> = Mutex::new(PrivateModeState {
+    until: None,
+    generation: 0,
+});
+
+// 隐私模式最长持续时间（分钟）
+const MAX_PRIVATE_MINUTES: u32 = 24 * 60;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrivateModeStatus {
+    pub active: bool,
+    pub remaining_seconds: u64,
+}
+
+fn current_status() -> PrivateModeStatus {
+    let remaining = PRIVATE_MODE
+        .lock()
+        .ok()
+        .and_then(|state| state.until)
+        .map(|until| until.saturating_duration_since(Instant::now()))
+        .unwrap_or_default();
+    PrivateModeStatus {
+        active: !remaining.is_zero(),
+        remaining_seconds: remaining.as_secs(),
+    }
+}
+
+// 隐私模式下继续监听剪贴板，但不保存任何内容
+pub fn is_private_mode_active() -> bool {
+    current_status().active
+}
+
+fn emit_private_mode_changed(app: &AppHandle) {
+    if let Err(e) = app.emit("private-mode-changed", current_status()) {
+        eprintln!("通知前端隐私模式变化失败: {}", e);
+    }
+}
+
+// Tauri命令：获取隐私模式状态
+#[tauri::command]
+pub async fn get_private_mode() -> Result<PrivateModeStatus, String> {
+    Ok(current_status())
+}
+
+// Tauri命令：开启隐私模式，到时自动恢复保存；duration_minutes 为 0 时立即结束
+#[tauri::command]
+pub async fn set_private_mode(app: AppHandle, duration_minutes: u32) -> Result<PrivateModeStatus, String> {
+    if duration_minutes > MAX_PRIVATE_MINUTES {
+        return Err(format!("隐私模式最长 {} 分钟", MAX_PRIVATE_MINUTES));
+    }
+
+    let generation = {
+        let mut state = PRIVATE_MODE
+            .lock()
+            .map_err(|e| format!("获取隐私模式状态失败: {}", e))?;
+        state.generation += 1;
+        state.until = (duration_minutes > 0)
+            .then(|| Instant::now() + Duration::from_secs(duration_minutes as u64 * 60));
+        state.generation
+    };
+
+    if duration_minutes > 0 {
+        println!("已开启隐私模式，{} 分钟后自动恢复", duration_minutes);
+        let app_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(duration_minutes as u64 * 60)).await;
+            // 期间重新设置过隐私模式时由新的计时器负责
+            let expired = match PRIVATE_MODE.lock() {
+                Ok(mut state) if state.generation == generation => {
+                    state.until = None;
+                    true
+                }
+                _ => false,
+            };
+            if expired {
+                println!("隐私模式已结束，恢复保存剪贴板内容");
+                emit_private_mode_changed(&app_handle);
+            }
+        });
+    } else {
+        println!("已关闭隐私模式");
+    }
+
+    emit_private_mode_changed(&app);
+    Ok(current_status())
+}
@@ -0,0 +1,265 @@
+use crate::change_log::emit_change_event;
+use crate::db::{database_paths, get_settings, init_database, save_settings, DatabasePool, DATABASE_FILE_NAME};
+use crate::db_encryption::sidecar_path;
+use crate::db_writer::execute_write;
+use crate::error::OicopyError;
+use crate::lock_mode::ensure_unlocked;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+
+// 默认配置使用原来的数据库文件，其他配置的数据库保存在数据库目录下的 profiles 中
+pub const DEFAULT_PROFILE: &str = "default";
+const PROFILES_DIR: &str = "profiles";
+// 当前配置保存在数据目录下的文件中，打开数据库之前就需要读取
+const ACTIVE_PROFILE_FILE: &str = "active_profile";
+const MAX_PROFILE_NAME_CHARS: usize = 32;
+
+// 历史记录配置（如工作、个人），每个配置使用独立的数据库文件
+#[derive(Debug, Serialize)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+    pub path: String,
+}
+
+// 配置名用作文件名，只允许字母、数字、- 和 _
+pub fn validate_profile_name(name: &str) -> Result<(), String> {
+    let count = name.chars().count();
+    if count == 0 || count > MAX_PROFILE_NAME_CHARS {
+        return Err(format!("配置名长度必须在 1 到 {} 个字符之间", MAX_PROFILE_NAME_CHARS));
+    }
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("配置名只能包含字母、数字、- 和 _: {}", name));
+    }
+    Ok(())
+}
+
+// 当前使用的配置，未设置或无效时使用默认配置
+pub fn active_profile(app_data_dir: &Path) -> String {
+    std::fs::read_to_string(app_data_dir.join(ACTIVE_PROFILE_FILE))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| validate_profile_name(name).is_ok())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+fn save_active_profile(app_data_dir: &Path, name: &str) -> Result<(), String> {
+    let path = app_data_dir.join(ACTIVE_PROFILE_FILE);
+    if name == DEFAULT_PROFILE {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("保存当前配置失败: {}", e)),
+            _ => Ok(()),
+        };
+    }
+    std::fs::write(&path, name).map_err(|e| format!("保存当前配置失败: {}", e))
+}
+
+// 配置对应的数据库文件
+pub fn profile_database_path(db_dir: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        db_dir.join(DATABASE_FILE_NAME)
+    } else {
+        db_dir.join(PROFILES_DIR).join(format!("{}.db", name))
+    }
+}
+
+// 数据库目录中已有的配置，默认配置始终在最前
+pub fn list_profile_names(db_dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(db_dir.join(PROFILES_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter_map(|entry| {
+                    let file_name = entry.file_name().to_string_lossy().to_string();
+                    let name = file_name.strip_suffix(".db")?;
+                    validate_profile_name(name).ok().map(|_| name.to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    names
+}
+
+// 与 db_path 位于同一数据库目录下的其他配置的数据库文件
+pub fn other_profile_databases(db_path: &Path) -> Vec<PathBuf> {
+    let db_dir = if db_path.file_name().is_some_and(|name| name == DATABASE_FILE_NAME) {
+        db_path.parent()
+    } else {
+        db_path.parent().and_then(Path::parent)
+    };
+    let Some(db_dir) = db_dir else {
+        return Vec::new();
+    };
+    list_profile_names(db_dir)
+        .into_iter()
+        .map(|name| profile_database_path(db_dir, &name))
+        .filter(|path| path != db_path && path.exists())
+        .collect()
+}
+
+// 移动数据库目录时一并移动未使用的配置（当前配置由调用方通过备份 API 复制）
+pub fn move_inactive_profiles(old_dir: &Path, new_dir: &Path, active: &str) -> Result<(), String> {
+    for name in list_profile_names(old_dir) {
+        if name == active {
+            continue;
+        }
+        let old_path = profile_database_path(old_dir, &name);
+        let new_path = profile_database_path(new_dir, &name);
+        if !old_path.exists() {
+            continue;
+        }
+        if new_path.exists() {
+            return Err(format!("目标目录中已存在配置 {} 的数据库文件", name));
+        }
+        if let Some(parent) = new_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+        }
+        for suffix in ["", "-wal", "-shm"] {
+            let from = sidecar_path(&old_path, suffix);
+            if from.exists() {
+                move_file(&from, &sidecar_path(&new_path, suffix))
+                    .map_err(|e| format!("移动配置 {} 的数据库文件失败: {}", name, e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// 跨磁盘时无法直接改名，改为复制后删除
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to)?;
+    std::fs::remove_file(from)
+}
+
+// 已有的配置和当前配置
+pub fn current_profiles(app: &AppHandle) -> Result<(Vec<String>, String), String> {
+    let (_, db_dir, active) = profile_context(app)?;
+    Ok((list_profile_names(&db_dir), active))
+}
+
+// 当前数据库目录和当前配置
+fn profile_context(app: &AppHandle) -> Result<(PathBuf, PathBuf, String), String> {
+    let (app_data_dir, db_path) = database_paths(app)?;
+    let active = active_profile(&app_data_dir);
+    let mut db_dir = db_path.parent().map(Path::to_path_buf).unwrap_or_else(|| app_data_dir.clone());
+    if active != DEFAULT_PROFILE {
+        db_dir.pop();
+    }
+    Ok((app_data_dir, db_dir, active))
+}
+
+fn profile_info(db_dir: &Path, name: &str, active: &str) -> ProfileInfo {
+    ProfileInfo {
+        name: name.to_string(),
+        active: name == active,
+        path: profile_database_path(db_dir, name).to_string_lossy().to_string(),
+    }
+}
+
+// 列出配置命令
+#[tauri::command]
+pub async fn list_profiles(app: AppHandle) -> Result<Vec<ProfileInfo>, OicopyError> {
+    let (_, db_dir, active) = profile_context(&app)?;
+    Ok(list_profile_names(&db_dir)
+        .iter()
+        .map(|name| profile_info(&db_dir, name, &active))
+        .collect())
+}
+
+// 创建配置命令：新配置沿用当前配置的设置，历史记录为空
+#[tauri::command]
+pub async fn create_profile(app: AppHandle, name: String) -> Result<ProfileInfo, OicopyError> {
+    let name = name.trim().to_string();
+    validate_profile_name(&name).map_err(OicopyError::Validation)?;
+    let (_, db_dir, active) = profile_context(&app)?;
+    let path = profile_database_path(&db_dir, &name);
+    if list_profile_names(&db_dir).contains(&name) || path.exists() {
+        return Err(OicopyError::Validation(format!("配置已存在: {}", name)));
+    }
+
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let settings = get_settings(&conn)?;
+    let pool = app
+        .try_state::<Arc<DatabasePool>>()
+        .ok_or_else(|| OicopyError::Db("数据库连接池未初始化".to_string()))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| OicopyError::Io(format!("创建配置目录失败: {}", e)))?;
+    }
+    let profile_conn = pool.open_database_file(&path)?;
+    save_settings(&profile_conn, &settings)?;
+
+    println!("已创建配置: {}", name);
+    let _ = app.emit("profiles-updated", &name);
+    Ok(profile_info(&db_dir, &name, &active))
+}
+
+// 切换配置命令：在写入线程上切换数据库文件，之后的捕获保存到新配置
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, name: String) -> Result<ProfileInfo, OicopyError> {
+    let (app_data_dir, db_dir, active) = profile_context(&app)?;
+    if name == active {
+        return Ok(profile_info(&db_dir, &name, &active));
+    }
+    if !list_profile_names(&db_dir).contains(&name) {
+        return Err(OicopyError::NotFound(format!("配置不存在: {}", name)));
+    }
+    ensure_unlocked(&init_database(&app)?)?;
+
+    let path = profile_database_path(&db_dir, &name);
+    let profile = name.clone();
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        execute_write(&app_handle, move |app, conn| {
+            let pool = app
+                .try_state::<Arc<DatabasePool>>()
+                .ok_or_else(|| "数据库连接池未初始化".to_string())?;
+            save_active_profile(&app_data_dir, &profile)?;
+            match pool.switch_path(path) {
+                Ok(new_conn) => {
+                    *conn = new_conn;
+                    Ok(())
+                }
+                Err(e) => {
+                    let _ = save_active_profile(&app_data_dir, &active);
+                    Err(e.into())
+                }
+            }
+        })
+    })
+    .await
+    .map_err(|e| format!("切换配置任务失败: {}", e))??;
+
+    println!("已切换到配置: {}", name);
+    let _ = app.emit("profile-changed", &name);
+    emit_change_event(&app, "clipboard-updated");
+    Ok(profile_info(&db_dir, &name, &name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_profile_name() {
+        assert!(validate_profile_name("work").is_ok());
+        assert!(validate_profile_name("个人_2").is_ok());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("../work").is_err());
+        assert!(validate_profile_name(&"a".repeat(MAX_PROFILE_NAME_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_profile_database_path() {
+        let dir = Path::new("/data");
+        assert_eq!(profile_database_path(dir, DEFAULT_PROFILE), dir.join(DATABASE_FILE_NAME));
+        assert_eq!(profile_database_path(dir, "work"), dir.join("profiles").join("work.db"));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::change_log::emit_change_event;
+use crate::clipboard_content::{ClipboardContent, ContentKind};
+use crate::db::{add_item_tags, get_device_id, save_to_database, ClipboardHistoryItem};
+use crate::db_writer::execute_write;
+use crate::error::OicopyError;
+use chrono::Local;
+use tauri::AppHandle;
+
+// 手动记录的来源名称
+const MANUAL_SOURCE: &str = "快速记录";
+
+// 构建手动输入的内容，只支持文本类格式
+fn build_manual_content(content: String, kind: ContentKind) -> Result<ClipboardContent, String> {
+    if content.trim().is_empty() {
+        return Err("内容不能为空".to_string());
+    }
+    match kind {
+        ContentKind::Text | ContentKind::Html | ContentKind::Rtf => ClipboardContent::from_stored(kind, content),
+        _ => Err(format!("不支持手动创建{}内容", kind.label())),
+    }
+}
+
+// Tauri命令：不经过系统剪贴板，直接在历史记录中新建一条内容，返回新记录ID
+#[tauri::command]
+pub async fn create_manual_item(
+    app: AppHandle,
+    content: String,
+    content_type: Option<ContentKind>,
+    tags: Option<Vec<String>>,
+) -> Result<i64, String> {
+    let content = build_manual_content(content, content_type.unwrap_or(ContentKind::Text))?;
+    let mut item = ClipboardHistoryItem::from_content(
+        &content,
+        Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        Some(MANUAL_SOURCE.to_string()),
+        None,
+    );
+    let tags = tags.unwrap_or_default();
+
+    // 与捕获保存一样交给数据库写入线程执行
+    let app_handle = app.clone();
+    let id = tauri::async_runtime::spawn_blocking(move || {
+        execute_write(&app_handle, move |_, conn| {
+            item.device_id = get_device_id(conn).ok();
+            let id = save_to_database(conn, &item).map_err(|e| match e {
+                OicopyError::Duplicate => "历史记录中已存在相同内容".to_string(),
+                e => e.to_string(),
+            })?;
+            add_item_tags(conn, id, &tags)?;
+            Ok(id)
+        })
+    })
+    .await
+    .map_err(|e| format!("新建记录失败: {}", e))??;
+
+    println!("已手动新建{}记录，ID: {}", content.kind().label(), id);
+    emit_change_event(&app, "clipboard-updated");
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_manual_content() {
+        assert!(build_manual_content("  \n".to_string(), ContentKind::Text).is_err());
+        assert!(build_manual_content("a.png".to_string(), ContentKind::Image).is_err());
+        let content = build_manual_content("<b>hi</b>".to_string(), ContentKind::Html).unwrap();
+        assert_eq!(content.kind(), ContentKind::Html);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::image_store::{blob_size, is_blob_reference};
+use chrono::{Duration, Local, NaiveDateTime};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// 候选保留策略：按天数、按条数，或两者同时生效（与自动清理一致，置顶项不受影响）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub max_age_days: Option<i32>,
+    pub max_records: Option<i64>,
+}
+
+impl RetentionPolicy {
+    fn label(&self) -> String {
+        match (self.max_age_days, self.max_records) {
+            (Some(days), Some(records)) => format!("{} 天 / {} 条", days, records),
+            (Some(days), None) => format!("{} 天", days),
+            (None, Some(records)) => format!("{} 条", records),
+            (None, None) => "不清理".to_string(),
+        }
+    }
+}
+
+// 默认模拟的策略
+pub fn default_policies() -> Vec<RetentionPolicy> {
+    let by_age = [30, 60, 90, 180].map(|days| RetentionPolicy { max_age_days: Some(days), max_records: None });
+    let by_count = [10_000, 50_000].map(|records| RetentionPolicy { max_age_days: None, max_records: Some(records) });
+    by_age.into_iter().chain(by_count).collect()
+}
+
+// 单个策略的模拟结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionSimulation {
+    pub label: String,
+    pub policy: RetentionPolicy,
+    pub remaining_records: i64,
+    pub removed_records: i64,
+    pub remaining_bytes: i64,
+    pub removed_bytes: i64,
+}
+
+// 用于模拟的记录信息
+#[derive(Debug, Clone)]
+pub struct RetentionRow {
+    pub timestamp: NaiveDateTime,
+    pub bytes: i64,
+    pub pinned: bool,
+}
+
+// 读取所有记录的时间、大小和置顶状态，按时间从新到旧排序
+// 图片内容保存在文件中，大小按文件实际大小计算
+pub fn load_retention_rows(conn: &Connection) -> Result<Vec<RetentionRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT timestamp, content_type,
+                    CASE WHEN content_type = 'image' AND content LIKE 'blobs/%' THEN content END,
+                    length(content) + COALESCE(length(preview), 0) + COALESCE(length(thumbnail), 0),
+                    pinned
+             FROM clipboard_history ORDER BY timestamp DESC",
+        )
+        .map_err(|e| format!("准备保留策略模拟查询失败: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<i64>>(3)?.unwrap_or(0),
+                row.get::<_, bool>(4)?,
+            ))
+        })
+        .map_err(|e| format!("保留策略模拟查询失败: {}", e))?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        let (timestamp, blob, bytes, pinned) = row.map_err(|e| format!("处理模拟数据失败: {}", e))?;
+        let Ok(timestamp) = NaiveDateTime::parse_from_str(&timestamp, TIMESTAMP_FORMAT) else {
+            continue;
+        };
+        let blob_bytes = blob
+            .filter(|reference| is_blob_reference(reference))
+            .and_then(|reference| blob_size(&reference))
+            .unwrap_or(0) as i64;
+        result.push(RetentionRow { timestamp, bytes: bytes + blob_bytes, pinned });
+    }
+    Ok(result)
+}
+
+// 模拟策略的效果：先按天数删除过期记录，再只保留最新的 max_records 条非置顶记录
+// rows 需按时间从新到旧排序
+pub fn simulate_policy(rows: &[RetentionRow], now: NaiveDateTime, policy: &RetentionPolicy) -> RetentionSimulation {
+    let cutoff = policy.max_age_days.map(|days| now - Duration::days(days as i64));
+    let mut kept_unpinned = 0;
+    let mut simulation = RetentionSimulation {
+        label: policy.label(),
+        policy: policy.clone(),
+        remaining_records: 0,
+        removed_records: 0,
+        remaining_bytes: 0,
+        removed_bytes: 0,
+    };
+
+    for row in rows {
+        let keep = row.pinned || {
+            let within_age = cutoff.is_none_or(|cutoff| row.timestamp >= cutoff);
+            let within_count = policy.max_records.is_none_or(|max| kept_unpinned < max);
+            if within_age && within_count {
+                kept_unpinned += 1;
+                true
+            } else {
+                false
+            }
+        };
+        if keep {
+            simulation.remaining_records += 1;
+            simulation.remaining_bytes += row.bytes;
+        } else {
+            simulation.removed_records += 1;
+            simulation.removed_bytes += row.bytes;
+        }
+    }
+    simulation
+}
+
+// 根据实际数据分布模拟多个保留策略
+pub fn simulate_retention(conn: &Connection, policies: &[RetentionPolicy]) -> Result<Vec<RetentionSimulation>, String> {
+    let rows = load_retention_rows(conn)?;
+    let now = Local::now().naive_local();
+    Ok(policies.iter().map(|policy| simulate_policy(&rows, now, policy)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(days_ago: i64, bytes: i64, pinned: bool, now: NaiveDateTime) -> RetentionRow {
+        RetentionRow { timestamp: now - Duration::days(days_ago), bytes, pinned }
+    }
+
+    #[test]
+    fn test_simulate_policy() {
+        let now = NaiveDateTime::parse_from_str("2025-06-01 12:00:00", TIMESTAMP_FORMAT).unwrap();
+        let rows = vec![
+            row(1, 100, false, now),
+            row(10, 200, false, now),
+            row(40, 300, true, now),
+            row(50, 400, false, now),
+            row(100, 500, false, now),
+        ];
+
+        let by_age = simulate_policy(&rows, now, &RetentionPolicy { max_age_days: Some(30), max_records: None });
+        assert_eq!((by_age.remaining_records, by_age.removed_records), (3, 2));
+        assert_eq!((by_age.remaining_bytes, by_age.removed_bytes), (600, 900));
+
+        let by_count = simulate_policy(&rows, now, &RetentionPolicy { max_age_days: None, max_records: Some(2) });
+        assert_eq!((by_count.remaining_records, by_count.removed_bytes), (3, 900));
+
+        let both = simulate_policy(&rows, now, &RetentionPolicy { max_age_days: Some(60), max_records: Some(1) });
+        assert_eq!((both.remaining_records, both.remaining_bytes), (2, 400));
+        assert_eq!(both.label, "60 天 / 1 条");
+    }
+}
@@ -0,0 +1,293 @@
+use crate::clipboard_management::escape_html;
+
+// 不包含正文的目标组（字体表、颜色表、样式表、文档信息、图片等），整组跳过
+const SKIPPED_DESTINATIONS: &[&str] = &[
+    "fonttbl", "colortbl", "stylesheet", "listtable", "listoverridetable", "info", "pict", "object",
+    "header", "footer", "footnote", "themedata", "colorschememapping", "latentstyles", "datastore",
+    "generator", "expandedcolortbl", "rsidtbl", "xmlnstbl", "mmathPr",
+];
+
+// 解析出的片段：文本附带当时的字符格式
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct CharFormat {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Text(String, CharFormat),
+    Break,     // \line，段内换行
+    Paragraph, // \par，段落结束
+}
+
+// 每个组 {} 保存的解析状态
+#[derive(Clone, Copy)]
+struct GroupState {
+    format: CharFormat,
+    skip: bool,
+    unicode_skip: usize, // \ucN：\uN 之后需要跳过的替代字符数
+}
+
+struct RtfParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    stack: Vec<GroupState>,
+    state: GroupState,
+    pieces: Vec<Piece>,
+    pending_skip: usize,
+    high_surrogate: Option<u16>,
+}
+
+impl<'a> RtfParser<'a> {
+    fn new(rtf: &'a str) -> Self {
+        Self {
+            bytes: rtf.as_bytes(),
+            pos: 0,
+            stack: Vec::new(),
+            state: GroupState { format: CharFormat::default(), skip: false, unicode_skip: 1 },
+            pieces: Vec::new(),
+            pending_skip: 0,
+            high_surrogate: None,
+        }
+    }
+
+    fn push_char(&mut self, c: char) {
+        if self.state.skip {
+            return;
+        }
+        // \uN 后面的替代字符（通常是 ?）不输出
+        if self.pending_skip > 0 {
+            self.pending_skip -= 1;
+            return;
+        }
+        let format = self.state.format;
+        match self.pieces.last_mut() {
+            Some(Piece::Text(text, last)) if *last == format => text.push(c),
+            _ => self.pieces.push(Piece::Text(c.to_string(), format)),
+        }
+    }
+
+    fn push_piece(&mut self, piece: Piece) {
+        if !self.state.skip {
+            self.pending_skip = 0;
+            self.pieces.push(piece);
+        }
+    }
+
+    // 读取控制字（字母部分和可选的数字参数），定界空格一并消耗
+    fn read_control_word(&mut self) -> (String, Option<i32>) {
+        let start = self.pos;
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_alphabetic() {
+            self.pos += 1;
+        }
+        let word = String::from_utf8_lossy(&self.bytes[start..self.pos]).into_owned();
+
+        let param_start = self.pos;
+        if self.pos < self.bytes.len() && self.bytes[self.pos] == b'-' {
+            self.pos += 1;
+        }
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        let param = std::str::from_utf8(&self.bytes[param_start..self.pos])
+            .ok()
+            .and_then(|param| param.parse::<i32>().ok());
+
+        if self.pos < self.bytes.len() && self.bytes[self.pos] == b' ' {
+            self.pos += 1;
+        }
+        (word, param)
+    }
+
+    fn handle_control_word(&mut self, word: &str, param: Option<i32>, group_start: bool) {
+        // 组开头的目标控制字决定整组是否为正文
+        if group_start && SKIPPED_DESTINATIONS.contains(&word) {
+            self.state.skip = true;
+            return;
+        }
+        let enabled = param != Some(0);
+        match word {
+            "par" | "sect" | "page" => self.push_piece(Piece::Paragraph),
+            "line" => self.push_piece(Piece::Break),
+            "tab" => self.push_char('\t'),
+            "cell" => self.push_char(' '),
+            "row" => self.push_piece(Piece::Break),
+            "emdash" => self.push_char('—'),
+            "endash" => self.push_char('–'),
+            "bullet" => self.push_char('•'),
+            "lquote" => self.push_char('‘'),
+            "rquote" => self.push_char('’'),
+            "ldblquote" => self.push_char('“'),
+            "rdblquote" => self.push_char('”'),
+            "b" => self.state.format.bold = enabled,
+            "i" => self.state.format.italic = enabled,
+            "ul" => self.state.format.underline = enabled,
+            "ulnone" => self.state.format.underline = false,
+            "plain" => self.state.format = CharFormat::default(),
+            "uc" => self.state.unicode_skip = param.unwrap_or(1).max(0) as usize,
+            "u" => {
+                if let Some(code) = param {
+                    // 参数为有符号 16 位整数，超出 0x7FFF 的字符以负数表示
+                    let unit = if code < 0 { (code + 65536) as u16 } else { code as u16 };
+                    self.push_utf16_unit(unit);
+                    self.pending_skip = self.state.unicode_skip;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 处理 \uN 的 UTF-16 码元，代理对需要组合成一个字符
+    fn push_utf16_unit(&mut self, unit: u16) {
+        if (0xD800..0xDC00).contains(&unit) {
+            self.high_surrogate = Some(unit);
+            return;
+        }
+        let decoded = match self.high_surrogate.take() {
+            Some(high) => char::decode_utf16([high, unit]).next(),
+            None => char::decode_utf16([unit]).next(),
+        };
+        if let Some(Ok(c)) = decoded {
+            self.push_char(c);
+        }
+    }
+
+    fn parse(mut self) -> Vec<Piece> {
+        let mut group_start = false;
+        while self.pos < self.bytes.len() {
+            let byte = self.bytes[self.pos];
+            match byte {
+                b'{' => {
+                    self.pos += 1;
+                    self.stack.push(self.state);
+                    group_start = true;
+                    continue;
+                }
+                b'}' => {
+                    self.pos += 1;
+                    if let Some(state) = self.stack.pop() {
+                        self.state = state;
+                    }
+                }
+                b'\\' => {
+                    self.pos += 1;
+                    let Some(&next) = self.bytes.get(self.pos) else { break };
+                    if next.is_ascii_alphabetic() {
+                        let (word, param) = self.read_control_word();
+                        self.handle_control_word(&word, param, group_start);
+                    } else {
+                        self.pos += 1;
+                        match next {
+                            // {\*\destination ...} 为可忽略的扩展目标
+                            b'*' if group_start => {
+                                self.state.skip = true;
+                            }
+                            b'\'' => {
+                                let hex = self.bytes.get(self.pos..self.pos + 2);
+                                self.pos += 2;
+                                // 按 Latin-1 近似解码单字节字符，macOS 生成的 RTF 对非 ASCII 字符使用 \uN
+                                if let Some(byte) = hex
+                                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                                {
+                                    self.push_char(byte as char);
+                                }
+                            }
+                            b'\n' | b'\r' => self.push_piece(Piece::Paragraph),
+                            b'~' => self.push_char('\u{a0}'),
+                            b'-' => {}
+                            b'_' => self.push_char('-'),
+                            other => self.push_char(other as char),
+                        }
+                    }
+                }
+                b'\r' | b'\n' => self.pos += 1,
+                _ => {
+                    // 普通文本按 UTF-8 读取完整字符
+                    let rest = &self.bytes[self.pos..];
+                    let len = rest.iter().position(|b| matches!(b, b'{' | b'}' | b'\\' | b'\r' | b'\n')).unwrap_or(rest.len());
+                    let text = String::from_utf8_lossy(&rest[..len]).into_owned();
+                    self.pos += len;
+                    for c in text.chars() {
+                        self.push_char(c);
+                    }
+                }
+            }
+            group_start = false;
+        }
+        self.pieces
+    }
+}
+
+// 提取 RTF 中可读的纯文本，用于预览和搜索
+pub fn rtf_to_text(rtf: &str) -> String {
+    let mut text = String::new();
+    for piece in RtfParser::new(rtf).parse() {
+        match piece {
+            Piece::Text(part, _) => text.push_str(&part),
+            Piece::Break | Piece::Paragraph => text.push('\n'),
+        }
+    }
+    text.trim().to_string()
+}
+
+// 将 RTF 转换为简单的 HTML（段落、换行、粗体/斜体/下划线），用于面板渲染和格式转换
+pub fn rtf_to_html(rtf: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph = String::new();
+    for piece in RtfParser::new(rtf).parse() {
+        match piece {
+            Piece::Text(part, format) => {
+                let mut part = escape_html(&part);
+                if format.underline {
+                    part = format!("<u>{}</u>", part);
+                }
+                if format.italic {
+                    part = format!("<em>{}</em>", part);
+                }
+                if format.bold {
+                    part = format!("<strong>{}</strong>", part);
+                }
+                paragraph.push_str(&part);
+            }
+            Piece::Break => paragraph.push_str("<br>"),
+            Piece::Paragraph => {
+                html.push_str(&format!("<p>{}</p>", paragraph));
+                paragraph.clear();
+            }
+        }
+    }
+    if !paragraph.trim().is_empty() {
+        html.push_str(&format!("<p>{}</p>", paragraph));
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r"{\rtf1\ansi\ansicpg1252\cocoartf2761
+{\fonttbl\f0\fswiss\fcharset0 Helvetica;}
+{\colortbl;\red255\green255\blue255;}
+{\*\expandedcolortbl;;}
+\f0\fs24 \cf0 Hello \b bold\b0  world\
+\uc0\u20320 \u22909  caf\'e9\par
+}";
+
+    #[test]
+    fn test_rtf_to_text() {
+        assert_eq!(rtf_to_text(SAMPLE), "Hello bold world\n你好 café");
+        assert_eq!(rtf_to_text(r"{\rtf1 a\u-10179?\u-8704?b}"), "a😀b");
+    }
+
+    #[test]
+    fn test_rtf_to_html() {
+        assert_eq!(
+            rtf_to_html(SAMPLE),
+            "<p>Hello <strong>bold</strong> world</p><p>你好 café</p>"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::change_log::emit_change_event;
+use crate::content_formats::prune_orphan_content_formats;
+use crate::db::{get_settings, init_database};
+use crate::db_writer::execute_write;
+use crate::error::OicopyError;
+use crate::image_store::prune_orphan_blobs;
+use chrono::{Duration, Local};
+use rusqlite::{params, Connection};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::AppHandle;
+
+// 锁屏或睡眠时保护历史记录（多人共用电脑）：按设置暂停保存，或删除最近一段时间内复制的记录
+
+// 因锁屏暂停保存：只保存在内存中，不影响用户手动暂停的状态，重启后也不会保持
+static PAUSED_BY_LOCK: AtomicBool = AtomicBool::new(false);
+
+// 锁屏/睡眠时的处理方式（与设置 lock_clear_action 的取值一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenLockAction {
+    Off,   // 不处理
+    Pause, // 锁屏期间不保存新内容，解锁后恢复
+    Purge, // 删除最近 lock_purge_minutes 分钟内复制的记录（置顶项除外）
+}
+
+impl ScreenLockAction {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(ScreenLockAction::Off),
+            "pause" => Some(ScreenLockAction::Pause),
+            "purge" => Some(ScreenLockAction::Purge),
+            _ => None,
+        }
+    }
+}
+
+// 是否因锁屏暂停保存剪贴板内容
+pub fn is_paused_by_screen_lock() -> bool {
+    PAUSED_BY_LOCK.load(Ordering::Relaxed)
+}
+
+// 永久删除 minutes 分钟内复制的记录（包括已移入回收站的），返回删除条数
+pub fn purge_recent_items(conn: &Connection, minutes: i64) -> Result<usize, OicopyError> {
+    let cutoff = (Local::now() - Duration::minutes(minutes))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    let deleted = conn
+        .execute(
+            "DELETE FROM clipboard_history WHERE timestamp >= ?1 AND pinned = 0",
+            params![cutoff],
+        )
+        .map_err(|e| OicopyError::Db(format!("删除最近的记录失败: {}", e)))?
+        + conn
+            .execute("DELETE FROM clipboard_trash WHERE timestamp >= ?1", params![cutoff])
+            .map_err(|e| OicopyError::Db(format!("删除回收站中最近的记录失败: {}", e)))?;
+    // 同一次复制保存的其他格式和图片文件也包含原内容，一并删除
+    if deleted > 0 {
+        if let Err(e) = prune_orphan_content_formats(conn) {
+            eprintln!("{}", e);
+        }
+        if let Err(e) = prune_orphan_blobs(conn) {
+            eprintln!("清理图片文件失败: {}", e);
+        }
+    }
+    Ok(deleted)
+}
+
+// 屏幕锁定或即将睡眠时执行设置的处理
+fn on_screen_locked(app_handle: &AppHandle, sleeping: bool) -> Result<(), String> {
+    let settings = get_settings(&init_database(app_handle)?)?;
+    match ScreenLockAction::parse(&settings.lock_clear_action).unwrap_or(ScreenLockAction::Off) {
+        ScreenLockAction::Off => {}
+        // 睡眠期间剪贴板不会变化，只在锁屏时暂停
+        ScreenLockAction::Pause if sleeping => {}
+        ScreenLockAction::Pause => {
+            PAUSED_BY_LOCK.store(true, Ordering::Relaxed);
+            println!("屏幕已锁定，暂停保存剪贴板内容");
+        }
+        ScreenLockAction::Purge => {
+            let minutes = settings.lock_purge_minutes.max(1);
+            let deleted = execute_write(app_handle, move |_, conn| {
+                purge_recent_items(conn, minutes).map_err(String::from)
+            })?;
+            println!("屏幕{}，已删除最近 {} 分钟内的 {} 条记录", if sleeping { "睡眠" } else { "锁定" }, minutes, deleted);
+            if deleted > 0 {
+                emit_change_event(app_handle, "clipboard-updated");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+#[derive(Debug, Clone, Copy)]
+enum ScreenEvent {
+    Locked,
+    Unlocked,
+    WillSleep,
+}
+
+// 通知在主线程上回调，数据库操作放到后台线程执行
+#[cfg(target_os = "macos")]
+fn handle_screen_event(app_handle: &AppHandle, event: ScreenEvent) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        let result = match event {
+            ScreenEvent::Locked => on_screen_locked(&app_handle, false),
+            ScreenEvent::WillSleep => on_screen_locked(&app_handle, true),
+            ScreenEvent::Unlocked => {
+                if PAUSED_BY_LOCK.swap(false, Ordering::Relaxed) {
+                    println!("屏幕已解锁，恢复保存剪贴板内容");
+                }
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("锁屏处理失败: {}", e);
+        }
+    });
+}
+
+// 注册锁屏、解锁（分布式通知）和睡眠（NSWorkspace 通知）的观察者，在 setup 中调用
+#[cfg(target_os = "macos")]
+pub fn start_screen_lock_watch(app_handle: AppHandle) {
+    use block2::RcBlock;
+    use objc2::rc::autoreleasepool;
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use std::ffi::CStr;
+    use std::ptr::NonNull;
+
+    autoreleasepool(|_| unsafe {
+        let distributed: *mut AnyObject = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+        let workspace: *mut AnyObject = msg_send![class!(NSWorkspace), sharedWorkspace];
+        let workspace_center: *mut AnyObject = msg_send![workspace, notificationCenter];
+        let observers: [(*mut AnyObject, &CStr, ScreenEvent); 3] = [
+            (distributed, c"com.apple.screenIsLocked", ScreenEvent::Locked),
+            (distributed, c"com.apple.screenIsUnlocked", ScreenEvent::Unlocked),
+            (workspace_center, c"NSWorkspaceWillSleepNotification", ScreenEvent::WillSleep),
+        ];
+        for (center, name, event) in observers {
+            if center.is_null() {
+                eprintln!("启动锁屏检测失败: 无法访问通知中心");
+                continue;
+            }
+            let app_handle = app_handle.clone();
+            let block = RcBlock::new(move |_notification: NonNull<AnyObject>| handle_screen_event(&app_handle, event));
+            let name: *mut AnyObject = msg_send![class!(NSString), stringWithUTF8String: name.as_ptr()];
+            // 通知中心会复制并持有回调，观察者在应用运行期间一直有效
+            let _: *mut AnyObject = msg_send![
+                center,
+                addObserverForName: name,
+                object: std::ptr::null_mut::<AnyObject>(),
+                queue: std::ptr::null_mut::<AnyObject>(),
+                usingBlock: &*block
+            ];
+        }
+    });
+}
+
+// 其他平台没有锁屏通知，低频检查系统时间与单调时钟的差值发现睡眠（睡眠时单调时钟停止计时），唤醒后执行处理
+#[cfg(not(target_os = "macos"))]
+pub fn start_screen_lock_watch(app_handle: AppHandle) {
+    use std::time::{Duration as StdDuration, Instant, SystemTime};
+
+    const POLL_INTERVAL: StdDuration = StdDuration::from_secs(30);
+    const SLEEP_GAP: StdDuration = StdDuration::from_secs(60);
+
+    let spawned = std::thread::Builder::new()
+        .name("screen-lock-watch".to_string())
+        .spawn(move || {
+            let mut last_tick = (Instant::now(), SystemTime::now());
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                let now = (Instant::now(), SystemTime::now());
+                let wall_elapsed = now.1.duration_since(last_tick.1).unwrap_or_default();
+                let slept = wall_elapsed.saturating_sub(now.0 - last_tick.0) > SLEEP_GAP;
+                last_tick = now;
+                if slept {
+                    if let Err(e) = on_screen_locked(&app_handle, true) {
+                        eprintln!("锁屏处理失败: {}", e);
+                    }
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("启动锁屏检测失败: {}", e);
+    }
+}
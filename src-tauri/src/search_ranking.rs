@@ -0,0 +1,161 @@
+// 搜索结果排序：在 FTS 相关度基础上，提升与查询语言一致的记录，降低二进制样内容和超大记录的权重
+
+// 参与重排的候选数量为请求数量的倍数，上限防止一次读取过多记录
+const CANDIDATE_FACTOR: u32 = 3;
+const MAX_CANDIDATES: u32 = 500;
+// 语言判断和二进制检测使用的内容前缀长度（字符）
+pub const SAMPLE_CHARS: i64 = 1024;
+// 超过该大小的记录视为超大记录
+const GIANT_CONTENT_BYTES: i64 = 256 * 1024;
+
+const LANGUAGE_MATCH_BOOST: f64 = 1.5;
+const BINARY_PENALTY: f64 = 0.2;
+const GIANT_PENALTY: f64 = 0.5;
+
+// 文本的主要书写系统
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Script {
+    Han,      // 中日文汉字
+    Kana,     // 日文假名
+    Hangul,   // 韩文
+    Cyrillic, // 西里尔字母
+    Latin,    // 拉丁字母
+}
+
+fn char_script(c: char) -> Option<Script> {
+    match c as u32 {
+        0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0xF900..=0xFAFF => Some(Script::Han),
+        0x3040..=0x30FF => Some(Script::Kana),
+        0xAC00..=0xD7AF | 0x1100..=0x11FF => Some(Script::Hangul),
+        0x0400..=0x04FF => Some(Script::Cyrillic),
+        _ if c.is_ascii_alphabetic() => Some(Script::Latin),
+        0x00C0..=0x024F => Some(Script::Latin),
+        _ => None,
+    }
+}
+
+// 识别文本中占比最高的书写系统，出现假名时视为日文
+pub fn dominant_script(text: &str) -> Option<Script> {
+    let mut counts = [0usize; 5];
+    for script in text.chars().filter_map(char_script) {
+        counts[script as usize] += 1;
+    }
+    if counts[Script::Kana as usize] > 0 {
+        return Some(Script::Kana);
+    }
+    [Script::Han, Script::Hangul, Script::Cyrillic, Script::Latin]
+        .into_iter()
+        .filter(|script| counts[*script as usize] > 0)
+        .max_by_key(|script| counts[*script as usize])
+}
+
+// 内容是否像二进制或编码数据（控制字符多，或长段无空白的 base64/十六进制串）
+pub fn looks_binary(sample: &str) -> bool {
+    let total = sample.chars().count();
+    if total < 64 {
+        return false;
+    }
+    let control = sample
+        .chars()
+        .filter(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+        .count();
+    if control * 10 > total {
+        return true;
+    }
+    let longest_token = sample.split_whitespace().map(|token| token.len()).max().unwrap_or(0);
+    let encoded = sample
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '-' | '_'))
+        .count();
+    longest_token * 10 >= total * 9 && encoded * 10 >= total * 9
+}
+
+// 重排所需的候选记录信息
+#[derive(Debug)]
+pub struct RankCandidate {
+    pub fts_rank: Option<f64>, // FTS5 bm25 得分（越小越相关），LIKE 查询时为空
+    pub content_bytes: i64,
+    pub sample: String,
+}
+
+// 计算排序得分，越大越靠前
+pub fn score(candidate: &RankCandidate, query_script: Option<Script>) -> f64 {
+    let mut score = match candidate.fts_rank {
+        Some(rank) => (-rank).max(0.0) + 1.0,
+        None => 1.0,
+    };
+    if query_script.is_some() && dominant_script(&candidate.sample) == query_script {
+        score *= LANGUAGE_MATCH_BOOST;
+    }
+    if looks_binary(&candidate.sample) {
+        score *= BINARY_PENALTY;
+    }
+    if candidate.content_bytes > GIANT_CONTENT_BYTES {
+        score *= GIANT_PENALTY;
+    }
+    score
+}
+
+// 需要从数据库读取的候选数量
+pub fn candidate_limit(limit: u32, offset: u32) -> u32 {
+    (limit + offset).saturating_mul(CANDIDATE_FACTOR).min(MAX_CANDIDATES).max(limit + offset)
+}
+
+// 按得分稳定排序（得分相同时保持数据库返回的顺序），再截取分页范围
+pub fn rank_results<T>(
+    query: &str,
+    results: Vec<(T, RankCandidate)>,
+    limit: u32,
+    offset: u32,
+) -> Vec<T> {
+    let query_script = dominant_script(query);
+    let mut scored: Vec<(f64, T)> = results
+        .into_iter()
+        .map(|(item, candidate)| (score(&candidate, query_script), item))
+        .collect();
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    scored
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .map(|(_, item)| item)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(sample: &str, bytes: i64) -> RankCandidate {
+        RankCandidate {
+            fts_rank: Some(-1.0),
+            content_bytes: bytes,
+            sample: sample.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_dominant_script() {
+        assert_eq!(dominant_script("剪贴板 history"), Some(Script::Han));
+        assert_eq!(dominant_script("コピー履歴"), Some(Script::Kana));
+        assert_eq!(dominant_script("hello мир world"), Some(Script::Latin));
+        assert_eq!(dominant_script("123 !?"), None);
+    }
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(looks_binary(&"iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk".repeat(3)));
+        assert!(!looks_binary(&"the quick brown fox jumps over the lazy dog ".repeat(3)));
+    }
+
+    #[test]
+    fn test_rank_results() {
+        let results = vec![
+            ("base64", candidate(&"QUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVo=".repeat(4), 200)),
+            ("english", candidate("meeting notes for the 会议 agenda", 40)),
+            ("giant", candidate("会议纪要 会议纪要", 1024 * 1024)),
+            ("chinese", candidate("明天的会议纪要", 30)),
+        ];
+        assert_eq!(rank_results("会议", results, 10, 0), vec!["chinese", "english", "giant", "base64"]);
+    }
+}
@@ -1,27 +1,362 @@
-use crate::db::{init_database, get_settings, save_settings, cleanup_old_history, AppSettings};
+use crate::change_log::emit_change_event;
+use crate::cleanup_schedule::CleanupSchedule;
+use crate::clipboard_content::{normalize_content_priority, ContentKind, DEFAULT_CONTENT_PRIORITY};
+use crate::db::{init_database, get_settings, save_settings, cleanup_old_history, get_device_id, get_excluded_apps, add_excluded_app, remove_excluded_app, AppSettings, ExcludedApp};
+use crate::clipboard_management::get_optimized_analyzer;
+use crate::error::OicopyError;
+use crate::lock_mode::ensure_unlocked;
+use crate::panel_window::PanelMonitor;
+use crate::performance_optimization::AnalysisConfig;
+use crate::screen_lock::ScreenLockAction;
+use crate::sensitive::SensitiveDataMode;
+use crate::trash::move_all_to_trash;
+use crate::updater::UpdateChannel;
+use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
 // 获取应用设置命令
 #[tauri::command]
 pub async fn get_app_settings(app: AppHandle) -> Result<AppSettings, String> {
     let conn = init_database(&app)?;
-    get_settings(&conn)
+    get_settings(&conn).map_err(String::from)
 }
 
 // 保存应用设置命令
 #[tauri::command]
-pub async fn save_app_settings(app: AppHandle, retention_days: i32) -> Result<(), String> {
+pub async fn save_app_settings(
+    app: AppHandle,
+    retention_days: i32,
+    feedback_sound: Option<bool>,
+    feedback_haptic: Option<bool>,
+    auto_check_updates: Option<bool>,
+    update_channel: Option<String>,
+    notify_monitor_restart: Option<bool>,
+    notify_cleanup: Option<bool>,
+    paste_confirm_enabled: Option<bool>,
+    paste_confirm_max_lines: Option<i64>,
+    paste_confirm_max_bytes: Option<i64>,
+    min_free_disk_mb: Option<i64>,
+    trash_retention_days: Option<i32>,
+    fetch_url_metadata: Option<bool>,
+    strip_tracking_params: Option<bool>,
+    panel_monitor: Option<String>,
+    deep_link_allow_destructive: Option<bool>,
+    deep_link_allow_actions: Option<bool>,
+    sensitive_data_mode: Option<String>,
+    search_sensitive_items: Option<bool>,
+    ephemeral_expire_minutes: Option<i64>,
+    lock_clear_action: Option<String>,
+    lock_purge_minutes: Option<i64>,
+) -> Result<(), String> {
     let conn = init_database(&app)?;
-    let settings = AppSettings { retention_days };
-    save_settings(&conn, &settings)
+    ensure_unlocked(&conn)?;
+    if let Some(ref channel) = update_channel {
+        if UpdateChannel::parse(channel).is_none() {
+            return Err(format!("未知的更新通道: {}", channel));
+        }
+    }
+    if let Some(ref monitor) = panel_monitor {
+        if PanelMonitor::parse(monitor).is_none() {
+            return Err(format!("未知的面板显示器选项: {}", monitor));
+        }
+    }
+    if let Some(ref mode) = sensitive_data_mode {
+        if SensitiveDataMode::parse(mode).is_none() {
+            return Err(format!("未知的敏感内容处理方式: {}", mode));
+        }
+    }
+    if let Some(ref action) = lock_clear_action {
+        if ScreenLockAction::parse(action).is_none() {
+            return Err(format!("未知的锁屏处理方式: {}", action));
+        }
+    }
+
+    // 未传入的选项保持原值
+    let current = get_settings(&conn)?;
+    let settings = AppSettings {
+        retention_days,
+        feedback_sound: feedback_sound.unwrap_or(current.feedback_sound),
+        feedback_haptic: feedback_haptic.unwrap_or(current.feedback_haptic),
+        auto_check_updates: auto_check_updates.unwrap_or(current.auto_check_updates),
+        update_channel: update_channel.unwrap_or(current.update_channel),
+        notify_monitor_restart: notify_monitor_restart.unwrap_or(current.notify_monitor_restart),
+        notify_cleanup: notify_cleanup.unwrap_or(current.notify_cleanup),
+        paste_confirm_enabled: paste_confirm_enabled.unwrap_or(current.paste_confirm_enabled),
+        paste_confirm_max_lines: paste_confirm_max_lines.unwrap_or(current.paste_confirm_max_lines),
+        paste_confirm_max_bytes: paste_confirm_max_bytes.unwrap_or(current.paste_confirm_max_bytes),
+        // 快捷键需要重新注册，只能通过 set_shortcut 修改
+        global_shortcut: current.global_shortcut,
+        // 锁定状态和密码只能通过 lock_history/unlock_history 修改
+        history_locked: current.history_locked,
+        lock_passcode_hash: current.lock_passcode_hash,
+        // 暂停状态由托盘菜单切换
+        monitoring_paused: current.monitoring_paused,
+        min_free_disk_mb: min_free_disk_mb.unwrap_or(current.min_free_disk_mb).max(0),
+        trash_retention_days: trash_retention_days.unwrap_or(current.trash_retention_days).max(1),
+        fetch_url_metadata: fetch_url_metadata.unwrap_or(current.fetch_url_metadata),
+        strip_tracking_params: strip_tracking_params.unwrap_or(current.strip_tracking_params),
+        // 格式优先级通过 set_content_priority 修改
+        content_priority: current.content_priority,
+        // 分析配置通过 set_analysis_config 修改，需要同时更新分析器
+        analysis_similarity_threshold: current.analysis_similarity_threshold,
+        analysis_timeout_ms: current.analysis_timeout_ms,
+        analysis_max_content_size: current.analysis_max_content_size,
+        analysis_app_detection: current.analysis_app_detection,
+        analysis_redundancy_scoring: current.analysis_redundancy_scoring,
+        analysis_log_details: current.analysis_log_details,
+        panel_monitor: panel_monitor.unwrap_or(current.panel_monitor),
+        // 清理上限通过 set_cleanup_limits 修改
+        cleanup_max_records: current.cleanup_max_records,
+        cleanup_max_size_mb: current.cleanup_max_size_mb,
+        // 清理计划通过 set_cleanup_schedule 修改
+        auto_cleanup_enabled: current.auto_cleanup_enabled,
+        cleanup_interval_minutes: current.cleanup_interval_minutes,
+        cleanup_quiet_start_hour: current.cleanup_quiet_start_hour,
+        cleanup_quiet_end_hour: current.cleanup_quiet_end_hour,
+        cleanup_only_when_idle: current.cleanup_only_when_idle,
+        // 备份设置通过 set_backup_config 修改
+        backup_enabled: current.backup_enabled,
+        backup_interval_hours: current.backup_interval_hours,
+        backup_keep_count: current.backup_keep_count,
+        backup_folder: current.backup_folder,
+        // 局域网同步通过 set_lan_sync_enabled 开关，需要同时启动或停止同步服务
+        lan_sync_enabled: current.lan_sync_enabled,
+        // 本机接口通过 set_local_api_config 修改，需要同时重启接口服务
+        local_api_enabled: current.local_api_enabled,
+        local_api_port: current.local_api_port,
+        local_api_token: current.local_api_token,
+        deep_link_allow_destructive: deep_link_allow_destructive.unwrap_or(current.deep_link_allow_destructive),
+        deep_link_allow_actions: deep_link_allow_actions.unwrap_or(current.deep_link_allow_actions),
+        sensitive_data_mode: sensitive_data_mode.unwrap_or(current.sensitive_data_mode),
+        search_sensitive_items: search_sensitive_items.unwrap_or(current.search_sensitive_items),
+        ephemeral_expire_minutes: ephemeral_expire_minutes.unwrap_or(current.ephemeral_expire_minutes).max(0),
+        lock_clear_action: lock_clear_action.unwrap_or(current.lock_clear_action),
+        lock_purge_minutes: lock_purge_minutes.unwrap_or(current.lock_purge_minutes).max(1),
+        // 面板布局通过 set_panel_layout 修改，需要同时重新定位面板
+        panel_mode: current.panel_mode,
+        panel_edge: current.panel_edge,
+        panel_height: current.panel_height,
+        panel_width: current.panel_width,
+    };
+    save_settings(&conn, &settings).map_err(String::from)
+}
+
+// 捕获格式优先级
+#[derive(Debug, Serialize)]
+pub struct ContentPriority {
+    pub order: Vec<ContentKind>, // 生效的优先级顺序
+    pub custom: bool,            // false 表示使用默认顺序（HTML 与纯文本由内容分析决定）
+}
+
+impl ContentPriority {
+    fn from_setting(order: Option<Vec<ContentKind>>) -> Self {
+        match order {
+            Some(order) => Self { order, custom: true },
+            None => Self {
+                order: DEFAULT_CONTENT_PRIORITY.to_vec(),
+                custom: false,
+            },
+        }
+    }
+}
+
+// 获取捕获格式优先级命令
+#[tauri::command]
+pub async fn get_content_priority(app: AppHandle) -> Result<ContentPriority, OicopyError> {
+    let conn = init_database(&app)?;
+    let settings = get_settings(&conn)?;
+    Ok(ContentPriority::from_setting(settings.content_priority))
+}
+
+// 设置捕获格式优先级命令，传入空值恢复默认的智能判断
+#[tauri::command]
+pub async fn set_content_priority(
+    app: AppHandle,
+    order: Option<Vec<ContentKind>>,
+) -> Result<ContentPriority, OicopyError> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let order = order
+        .map(|order| normalize_content_priority(&order))
+        .transpose()
+        .map_err(OicopyError::Validation)?;
+
+    let mut settings = get_settings(&conn)?;
+    settings.content_priority = order.clone();
+    save_settings(&conn, &settings)?;
+    println!("捕获格式优先级已更新: {:?}", order);
+
+    Ok(ContentPriority::from_setting(order))
+}
+
+// 由设置构建 HTML 分析配置
+fn analysis_config_from_settings(settings: &AppSettings) -> AnalysisConfig {
+    AnalysisConfig {
+        similarity_threshold: settings.analysis_similarity_threshold,
+        analysis_timeout_ms: settings.analysis_timeout_ms.max(0) as u64,
+        max_content_size: settings.analysis_max_content_size.max(0) as usize,
+        enable_app_detection: settings.analysis_app_detection,
+        enable_redundancy_scoring: settings.analysis_redundancy_scoring,
+        log_analysis_details: settings.analysis_log_details,
+    }
+}
+
+// 启动时将保存的分析配置应用到分析器
+pub fn apply_saved_analysis_config(app: &AppHandle) -> Result<(), String> {
+    let conn = init_database(app)?;
+    let settings = get_settings(&conn)?;
+    get_optimized_analyzer().set_config(analysis_config_from_settings(&settings));
+    Ok(())
+}
+
+// 获取 HTML 分析配置命令
+#[tauri::command]
+pub async fn get_analysis_config(app: AppHandle) -> Result<AnalysisConfig, OicopyError> {
+    let conn = init_database(&app)?;
+    let settings = get_settings(&conn)?;
+    Ok(analysis_config_from_settings(&settings))
+}
+
+// 保存 HTML 分析配置命令，保存后立即生效
+#[tauri::command]
+pub async fn set_analysis_config(app: AppHandle, config: AnalysisConfig) -> Result<AnalysisConfig, OicopyError> {
+    if !(0.0..=1.0).contains(&config.similarity_threshold) {
+        return Err(OicopyError::Validation("相似度阈值必须在 0 到 1 之间".to_string()));
+    }
+    if !(10..=10_000).contains(&config.analysis_timeout_ms) {
+        return Err(OicopyError::Validation("分析超时必须在 10 到 10000 毫秒之间".to_string()));
+    }
+    if !(1024..=64 * 1024 * 1024).contains(&config.max_content_size) {
+        return Err(OicopyError::Validation("分析内容大小上限必须在 1KB 到 64MB 之间".to_string()));
+    }
+
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let mut settings = get_settings(&conn)?;
+    settings.analysis_similarity_threshold = config.similarity_threshold;
+    settings.analysis_timeout_ms = config.analysis_timeout_ms as i64;
+    settings.analysis_max_content_size = config.max_content_size as i64;
+    settings.analysis_app_detection = config.enable_app_detection;
+    settings.analysis_redundancy_scoring = config.enable_redundancy_scoring;
+    settings.analysis_log_details = config.log_analysis_details;
+    save_settings(&conn, &settings)?;
+
+    get_optimized_analyzer().set_config(config.clone());
+    println!("HTML 分析配置已更新: {:?}", config);
+    Ok(config)
+}
+
+// 自动清理上限，0 表示不限制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupLimits {
+    pub max_records: i64,
+    pub max_size_mb: f64,
+}
+
+// 获取自动清理上限命令
+#[tauri::command]
+pub async fn get_cleanup_limits(app: AppHandle) -> Result<CleanupLimits, OicopyError> {
+    let conn = init_database(&app)?;
+    let settings = get_settings(&conn)?;
+    Ok(CleanupLimits {
+        max_records: settings.cleanup_max_records,
+        max_size_mb: settings.cleanup_max_size_mb,
+    })
+}
+
+// 保存自动清理上限命令，智能清理和定时清理都按该上限执行
+#[tauri::command]
+pub async fn set_cleanup_limits(app: AppHandle, limits: CleanupLimits) -> Result<CleanupLimits, OicopyError> {
+    if limits.max_records < 0 || (1..100).contains(&limits.max_records) {
+        return Err(OicopyError::Validation("记录数上限必须为 0（不限制）或不少于 100".to_string()));
+    }
+    if !limits.max_size_mb.is_finite() || limits.max_size_mb < 0.0 || (limits.max_size_mb > 0.0 && limits.max_size_mb < 10.0) {
+        return Err(OicopyError::Validation("数据库大小上限必须为 0（不限制）或不少于 10MB".to_string()));
+    }
+
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let mut settings = get_settings(&conn)?;
+    settings.cleanup_max_records = limits.max_records;
+    settings.cleanup_max_size_mb = limits.max_size_mb;
+    save_settings(&conn, &settings)?;
+
+    println!("自动清理上限已更新: {:?}", limits);
+    Ok(limits)
+}
+
+// 获取自动清理计划命令
+#[tauri::command]
+pub async fn get_cleanup_schedule(app: AppHandle) -> Result<CleanupSchedule, OicopyError> {
+    let conn = init_database(&app)?;
+    let settings = get_settings(&conn)?;
+    Ok(CleanupSchedule::from_settings(&settings))
+}
+
+// 保存自动清理计划命令（间隔、开关、免打扰时段、仅空闲时执行），定时任务每分钟读取，无需重启
+#[tauri::command]
+pub async fn set_cleanup_schedule(app: AppHandle, schedule: CleanupSchedule) -> Result<CleanupSchedule, OicopyError> {
+    schedule.validate().map_err(OicopyError::Validation)?;
+
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let mut settings = get_settings(&conn)?;
+    settings.auto_cleanup_enabled = schedule.enabled;
+    settings.cleanup_interval_minutes = schedule.interval_minutes;
+    settings.cleanup_quiet_start_hour = schedule.quiet_start_hour;
+    settings.cleanup_quiet_end_hour = schedule.quiet_end_hour;
+    settings.cleanup_only_when_idle = schedule.only_when_idle;
+    save_settings(&conn, &settings)?;
+
+    println!("自动清理计划已更新: {:?}", schedule);
+    Ok(schedule)
+}
+
+// 获取本机设备ID命令
+#[tauri::command]
+pub async fn get_device_id_command(app: AppHandle) -> Result<String, String> {
+    let conn = init_database(&app)?;
+    get_device_id(&conn).map_err(String::from)
+}
+
+// 获取排除应用列表命令
+#[tauri::command]
+pub async fn get_excluded_apps_command(app: AppHandle) -> Result<Vec<ExcludedApp>, String> {
+    let conn = init_database(&app)?;
+    get_excluded_apps(&conn).map_err(String::from)
+}
+
+// 添加排除应用命令
+#[tauri::command]
+pub async fn add_excluded_app_command(
+    app: AppHandle,
+    bundle_id: String,
+    app_name: Option<String>,
+) -> Result<(), String> {
+    let bundle_id = bundle_id.trim();
+    if bundle_id.is_empty() {
+        return Err("Bundle ID不能为空".to_string());
+    }
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    add_excluded_app(&conn, bundle_id, app_name.as_deref()).map_err(String::from)
+}
+
+// 移除排除应用命令
+#[tauri::command]
+pub async fn remove_excluded_app_command(app: AppHandle, bundle_id: String) -> Result<bool, String> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    remove_excluded_app(&conn, &bundle_id).map_err(String::from)
 }
 
 // 清理过期历史记录命令
 #[tauri::command]
 pub async fn cleanup_old_history_command(app: AppHandle) -> Result<usize, String> {
     let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
     let settings = get_settings(&conn)?;
-    cleanup_old_history(&conn, settings.retention_days)
+    cleanup_old_history(&conn, settings.retention_days).map_err(String::from)
 }
 
 // 获取数据计数命令
@@ -42,31 +377,17 @@ pub async fn get_data_count(app: AppHandle) -> Result<usize, String> {
 // 清理所有历史记录命令
 #[tauri::command]
 pub async fn clear_all_history_command(app: AppHandle) -> Result<usize, String> {
-    use crate::db::init_database;
-    use rusqlite::params;
-    
-    let conn = init_database(&app)?;
-    
-    // 先查询要删除的记录数量
-    let count_query = "SELECT COUNT(*) FROM clipboard_history";
-    let deleted_count: i64 = conn
-        .query_row(count_query, params![], |row| row.get(0))
-        .map_err(|e| format!("查询记录数量失败: {}", e))?;
+    let mut conn = init_database(&app)?;
     
-    // 执行删除操作
-    conn.execute("DELETE FROM clipboard_history", params![])
-        .map_err(|e| format!("清理所有历史记录失败: {}", e))?;
+    ensure_unlocked(&conn)?;
     
-    Ok(deleted_count as usize)
+    // 移入回收站，误操作时仍可恢复
+    move_all_to_trash(&mut conn)
 }
 
 // 发送数据清理事件
 #[tauri::command]
 pub async fn emit_data_cleared_event(app: AppHandle) -> Result<(), String> {
-    use tauri::Emitter;
-    
-    app.emit("data-cleared", ())
-        .map_err(|e| format!("发送数据清理事件失败: {}", e))?;
-    
+    emit_change_event(&app, "data-cleared");
     Ok(())
 }
@@ -0,0 +1,312 @@
+use crate::db::{get_history_item, get_settings, init_database, save_settings};
+use crate::lock_mode::ensure_unlocked;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+// 当前注册的唤出面板快捷键
+static PANEL_SHORTCUT: Mutex<Option<Shortcut>> = Mutex::new(None);
+
+// 当前注册的记录快捷键 -> 记录ID
+static ITEM_SHORTCUTS: OnceLock<Mutex<HashMap<Shortcut, i64>>> = OnceLock::new();
+
+fn item_shortcuts() -> &'static Mutex<HashMap<Shortcut, i64>> {
+    ITEM_SHORTCUTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// 解析快捷键字符串（如 "command+shift+v"）
+fn parse_shortcut(value: &str) -> Result<Shortcut, String> {
+    value
+        .trim()
+        .parse::<Shortcut>()
+        .map_err(|e| format!("无效的快捷键 {}: {}", value, e))
+}
+
+// 是否为唤出面板的快捷键
+pub fn is_panel_shortcut(shortcut: &Shortcut) -> bool {
+    PANEL_SHORTCUT
+        .lock()
+        .map(|current| current.as_ref() == Some(shortcut))
+        .unwrap_or(false)
+}
+
+fn set_panel_shortcut(shortcut: Option<Shortcut>) {
+    if let Ok(mut current) = PANEL_SHORTCUT.lock() {
+        *current = shortcut;
+    }
+}
+
+// 启动时注册设置中保存的快捷键，失败时退回默认快捷键
+pub fn register_saved_shortcut(app_handle: &AppHandle) -> Result<(), String> {
+    let conn = init_database(app_handle)?;
+    let settings = get_settings(&conn)?;
+
+    let shortcut = match parse_shortcut(&settings.global_shortcut) {
+        Ok(shortcut) => shortcut,
+        Err(e) => {
+            eprintln!("{}，使用默认快捷键", e);
+            parse_shortcut("command+shift+v")?
+        }
+    };
+    app_handle
+        .global_shortcut()
+        .register(shortcut)
+        .map_err(|e| format!("注册快捷键 {} 失败: {}", shortcut.into_string(), e))?;
+    set_panel_shortcut(Some(shortcut));
+    println!("已注册唤出面板快捷键: {}", shortcut.into_string());
+    Ok(())
+}
+
+// Tauri命令：获取唤出面板的快捷键
+#[tauri::command]
+pub async fn get_shortcut(app: AppHandle) -> Result<String, String> {
+    let conn = init_database(&app)?;
+    Ok(get_settings(&conn)?.global_shortcut)
+}
+
+// Tauri命令：修改唤出面板的快捷键，注册失败（被占用）时恢复原快捷键
+#[tauri::command]
+pub async fn set_shortcut(app: AppHandle, shortcut: String) -> Result<String, String> {
+    ensure_unlocked(&init_database(&app)?)?;
+    let new_shortcut = parse_shortcut(&shortcut)?;
+    let old_shortcut = PANEL_SHORTCUT.lock().ok().and_then(|current| *current);
+    if old_shortcut == Some(new_shortcut) {
+        return Ok(new_shortcut.into_string());
+    }
+
+    let global_shortcut = app.global_shortcut();
+    if global_shortcut.is_registered(new_shortcut) {
+        return Err(format!("快捷键 {} 已被本应用的其他功能占用", new_shortcut.into_string()));
+    }
+
+    if let Some(old) = old_shortcut {
+        global_shortcut
+            .unregister(old)
+            .map_err(|e| format!("注销原快捷键失败: {}", e))?;
+    }
+
+    if let Err(e) = global_shortcut.register(new_shortcut) {
+        // 新快捷键可能已被其他应用占用，恢复原快捷键
+        if let Some(old) = old_shortcut {
+            if let Err(restore_error) = global_shortcut.register(old) {
+                eprintln!("恢复原快捷键失败: {}", restore_error);
+                set_panel_shortcut(None);
+            }
+        }
+        return Err(format!(
+            "快捷键 {} 注册失败，可能已被其他应用占用: {}",
+            new_shortcut.into_string(),
+            e
+        ));
+    }
+    set_panel_shortcut(Some(new_shortcut));
+
+    let conn = init_database(&app)?;
+    let mut settings = get_settings(&conn)?;
+    settings.global_shortcut = new_shortcut.into_string();
+    save_settings(&conn, &settings)?;
+
+    println!("唤出面板快捷键已修改为: {}", settings.global_shortcut);
+    Ok(settings.global_shortcut)
+}
+
+// 创建记录快捷键表：每条记录最多绑定一个快捷键，同一快捷键只能绑定一条记录
+pub fn create_item_shortcuts_table(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS item_shortcuts (
+            item_id INTEGER PRIMARY KEY,
+            accelerator TEXT NOT NULL UNIQUE
+        )",
+        [],
+    )
+    .map_err(|e| format!("创建记录快捷键表失败: {}", e))?;
+    Ok(())
+}
+
+// 快捷键绑定的记录（不是记录快捷键时返回 None）
+pub fn item_for_shortcut(shortcut: &Shortcut) -> Option<i64> {
+    item_shortcuts()
+        .lock()
+        .ok()
+        .and_then(|shortcuts| shortcuts.get(shortcut).copied())
+}
+
+// 启动时注册已保存的记录快捷键，单个注册失败不影响其他快捷键
+pub fn register_item_shortcuts(app_handle: &AppHandle) -> Result<(), String> {
+    let conn = init_database(app_handle)?;
+    let mut stmt = conn
+        .prepare("SELECT item_id, accelerator FROM item_shortcuts")
+        .map_err(|e| format!("准备查询记录快捷键失败: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("查询记录快捷键失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("处理记录快捷键失败: {}", e))?;
+
+    let global_shortcut = app_handle.global_shortcut();
+    let mut shortcuts = item_shortcuts()
+        .lock()
+        .map_err(|e| format!("注册记录快捷键失败: {}", e))?;
+    for (item_id, accelerator) in rows {
+        let shortcut = match parse_shortcut(&accelerator) {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                eprintln!("{}", e);
+                continue;
+            }
+        };
+        if is_panel_shortcut(&shortcut) || global_shortcut.is_registered(shortcut) {
+            eprintln!("记录 {} 的快捷键 {} 与其他快捷键冲突，跳过注册", item_id, accelerator);
+            continue;
+        }
+        match global_shortcut.register(shortcut) {
+            Ok(()) => {
+                shortcuts.insert(shortcut, item_id);
+            }
+            Err(e) => eprintln!("注册记录 {} 的快捷键 {} 失败: {}", item_id, accelerator, e),
+        }
+    }
+    println!("已注册 {} 个记录快捷键", shortcuts.len());
+    Ok(())
+}
+
+// 检查快捷键是否可以绑定到指定记录，冲突时返回原因
+fn check_item_shortcut_conflict(
+    shortcut: &Shortcut,
+    item_id: i64,
+    bindings: &HashMap<Shortcut, i64>,
+) -> Result<(), String> {
+    if is_panel_shortcut(shortcut) {
+        return Err(format!("快捷键 {} 已用于唤出面板", shortcut.into_string()));
+    }
+    match bindings.get(shortcut) {
+        Some(bound) if *bound != item_id => Err(format!(
+            "快捷键 {} 已绑定到记录 {}",
+            shortcut.into_string(),
+            bound
+        )),
+        _ => Ok(()),
+    }
+}
+
+// 记录快捷键绑定信息
+#[derive(Debug, Serialize)]
+pub struct ItemShortcut {
+    pub item_id: i64,
+    pub accelerator: String,
+    pub preview: Option<String>, // 记录已被删除时为空
+}
+
+// Tauri命令：获取所有记录快捷键
+#[tauri::command]
+pub async fn get_item_shortcuts(app: AppHandle) -> Result<Vec<ItemShortcut>, String> {
+    let conn = init_database(&app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.item_id, s.accelerator, h.preview FROM item_shortcuts s
+             LEFT JOIN clipboard_history h ON h.id = s.item_id
+             ORDER BY s.item_id",
+        )
+        .map_err(|e| format!("准备查询记录快捷键失败: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ItemShortcut {
+                item_id: row.get(0)?,
+                accelerator: row.get(1)?,
+                preview: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("查询记录快捷键失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("处理记录快捷键失败: {}", e))
+}
+
+// Tauri命令：为置顶记录绑定全局快捷键，按下后直接粘贴该记录；accelerator 为空时解除绑定
+// 与唤出面板快捷键、其他记录或其他应用的快捷键冲突时返回错误，原有绑定保持不变
+#[tauri::command]
+pub async fn bind_item_shortcut(
+    app: AppHandle,
+    id: i64,
+    accelerator: Option<String>,
+) -> Result<Option<String>, String> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let new_shortcut = match accelerator.as_deref().map(str::trim) {
+        Some(value) if !value.is_empty() => Some(parse_shortcut(value)?),
+        _ => None,
+    };
+
+    // 未置顶的记录可能被自动清理，只允许为置顶记录绑定
+    if new_shortcut.is_some() {
+        let item = get_history_item(&conn, id)?.ok_or_else(|| format!("记录 {} 不存在", id))?;
+        if !item.pinned {
+            return Err("请先置顶该记录再绑定快捷键".to_string());
+        }
+    }
+
+    let global_shortcut = app.global_shortcut();
+    let mut bindings = item_shortcuts()
+        .lock()
+        .map_err(|e| format!("绑定记录快捷键失败: {}", e))?;
+    let old_shortcut = bindings
+        .iter()
+        .find(|(_, item_id)| **item_id == id)
+        .map(|(shortcut, _)| *shortcut);
+    if old_shortcut.is_some() && old_shortcut == new_shortcut {
+        return Ok(new_shortcut.map(|shortcut| shortcut.into_string()));
+    }
+
+    if let Some(shortcut) = new_shortcut {
+        check_item_shortcut_conflict(&shortcut, id, &bindings)?;
+        if global_shortcut.is_registered(shortcut) {
+            return Err(format!("快捷键 {} 已被本应用的其他功能占用", shortcut.into_string()));
+        }
+        global_shortcut.register(shortcut).map_err(|e| {
+            format!("快捷键 {} 注册失败，可能已被其他应用占用: {}", shortcut.into_string(), e)
+        })?;
+    }
+
+    if let Some(old) = old_shortcut {
+        if let Err(e) = global_shortcut.unregister(old) {
+            eprintln!("注销原记录快捷键失败: {}", e);
+        }
+        bindings.remove(&old);
+    }
+
+    match new_shortcut {
+        Some(shortcut) => {
+            conn.execute(
+                "INSERT OR REPLACE INTO item_shortcuts (item_id, accelerator) VALUES (?1, ?2)",
+                params![id, shortcut.into_string()],
+            )
+            .map_err(|e| format!("保存记录快捷键失败: {}", e))?;
+            bindings.insert(shortcut, id);
+            println!("记录 {} 已绑定快捷键 {}", id, shortcut.into_string());
+        }
+        None => {
+            conn.execute("DELETE FROM item_shortcuts WHERE item_id = ?1", params![id])
+                .map_err(|e| format!("删除记录快捷键失败: {}", e))?;
+            println!("记录 {} 已解除快捷键绑定", id);
+        }
+    }
+    Ok(new_shortcut.map(|shortcut| shortcut.into_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_item_shortcut_conflict() {
+        let shortcut = parse_shortcut("command+alt+1").unwrap();
+        let mut bindings = HashMap::new();
+        assert!(check_item_shortcut_conflict(&shortcut, 1, &bindings).is_ok());
+
+        bindings.insert(shortcut, 1);
+        assert!(check_item_shortcut_conflict(&shortcut, 1, &bindings).is_ok());
+        assert!(check_item_shortcut_conflict(&shortcut, 2, &bindings).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+// 同步数据加密：XChaCha20-Poly1305 认证加密，子密钥用 HKDF-SHA256 派生，口令和配对码用 PBKDF2-HMAC-SHA256 加盐派生
+// 封装格式：nonce(24) || 密文 || tag(16)
+pub const NONCE_SIZE: usize = 24;
+pub const TAG_SIZE: usize = 16;
+pub const KEY_SIZE: usize = 32;
+
+// 读取系统随机数（各平台统一使用系统提供的安全随机源）
+pub fn random_bytes(len: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0u8; len];
+    getrandom::fill(&mut bytes).map_err(|e| format!("生成随机数失败: {}", e))?;
+    Ok(bytes)
+}
+
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+// 由主密钥和用途标签派生子密钥（HKDF-SHA256），不同用途的密钥互不相关
+pub fn derive_key(key: &[u8], label: &str) -> [u8; KEY_SIZE] {
+    let mut derived = [0u8; KEY_SIZE];
+    Hkdf::<Sha256>::new(None, key)
+        .expand(label.as_bytes(), &mut derived)
+        .expect("HKDF 输出长度有效");
+    derived
+}
+
+// PBKDF2 加盐迭代派生密钥，增加暴力猜测口令或配对码的成本；盐值每次都应不同
+pub fn stretch_key(secret: &[u8], salt: &[u8], rounds: u32) -> [u8; KEY_SIZE] {
+    pbkdf2::pbkdf2_hmac_array::<Sha256, KEY_SIZE>(secret, salt, rounds)
+}
+
+// 比较耗时与内容无关，避免通过响应时间猜测校验值
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+// 加密并附加校验值
+pub fn seal(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = XChaCha20Poly1305::new_from_slice(key).map_err(|_| "加密密钥长度无效".to_string())?;
+    let nonce = random_bytes(NONCE_SIZE)?;
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .map_err(|_| "加密数据失败".to_string())?;
+    let mut sealed = nonce;
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+// 校验并解密，密钥错误或数据被篡改时返回错误
+pub fn open(key: &[u8], sealed: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < NONCE_SIZE + TAG_SIZE {
+        return Err("加密数据长度无效".to_string());
+    }
+    let cipher = XChaCha20Poly1305::new_from_slice(key).map_err(|_| "加密密钥长度无效".to_string())?;
+    let (nonce, ciphertext) = sealed.split_at(NONCE_SIZE);
+    cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "加密数据校验失败".to_string())
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return Err("十六进制数据无效".to_string());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| "十六进制数据无效".to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        // RFC 4231 测试用例 2
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(to_hex(&mac), "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    }
+
+    #[test]
+    fn test_stretch_key_pbkdf2() {
+        // PBKDF2-HMAC-SHA256 已知结果：password / salt / 1 次迭代
+        assert_eq!(
+            to_hex(&stretch_key(b"password", b"salt", 1)),
+            "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"
+        );
+        assert_ne!(stretch_key(b"password", b"salt-a", 10), stretch_key(b"password", b"salt-b", 10));
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = [7u8; KEY_SIZE];
+        let plaintext = "剪贴板内容 with more than one block".repeat(3);
+        let sealed = seal(&key, plaintext.as_bytes()).unwrap();
+        assert_eq!(sealed.len(), NONCE_SIZE + plaintext.len() + TAG_SIZE);
+        assert_eq!(open(&key, &sealed).unwrap(), plaintext.as_bytes());
+
+        // 密钥错误或数据被篡改
+        assert!(open(&[8u8; KEY_SIZE], &sealed).is_err());
+        let mut tampered = sealed.clone();
+        tampered[NONCE_SIZE] ^= 1;
+        assert!(open(&key, &tampered).is_err());
+        assert_ne!(derive_key(&key, "enc"), derive_key(&key, "mac"));
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        assert_eq!(from_hex(&to_hex(&[0, 15, 255])).unwrap(), vec![0, 15, 255]);
+        assert!(from_hex("abc").is_err());
+    }
+}
@@ -24,7 +24,11 @@ pub fn test_database_optimization() -> Result<(), String> {
             preview TEXT,
             timestamp TEXT NOT NULL,
             source_app TEXT,
-            source_bundle_id TEXT
+            source_bundle_id TEXT,
+            edited INTEGER NOT NULL DEFAULT 0,
+            device_id TEXT,
+            pinned INTEGER NOT NULL DEFAULT 0,
+            pin_order INTEGER
         )",
         [],
     ).map_err(|e| format!("创建测试表失败: {}", e))?;
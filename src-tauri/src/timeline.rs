@@ -0,0 +1,156 @@
+use crate::clipboard_content::ContentKind;
+use crate::db::{get_monitor_heartbeats, open_connection, ConnectionRole};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use rusqlite::params;
+use serde::Serialize;
+use tauri::AppHandle;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// 时间线中的单条记录
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    pub id: i64,
+    pub content_type: ContentKind,
+    pub preview: Option<String>,
+    pub timestamp: String,
+    pub source_app: Option<String>,
+}
+
+// 一个时间段内的捕获情况
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineBucket {
+    pub start: String,
+    pub end: String,
+    pub capture_count: usize,
+    pub active: bool,       // 该时间段内应用在运行（有心跳记录）
+    pub monitor_down: bool, // 心跳显示剪贴板监听曾经停止
+    pub entries: Vec<TimelineEntry>,
+}
+
+// 机器活跃但没有任何捕获的连续时间段
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineGap {
+    pub start: String,
+    pub end: String,
+    pub monitor_down: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaptureTimeline {
+    pub day: String,
+    pub bucket_minutes: u32,
+    pub buckets: Vec<TimelineBucket>,
+    pub gaps: Vec<TimelineGap>,
+}
+
+// 合并相邻的“活跃但无捕获”时间段
+fn detect_gaps(buckets: &[TimelineBucket]) -> Vec<TimelineGap> {
+    let mut gaps: Vec<TimelineGap> = Vec::new();
+    let mut previous_was_gap = false;
+
+    for bucket in buckets {
+        let is_gap = bucket.active && bucket.capture_count == 0;
+        if is_gap {
+            match gaps.last_mut() {
+                Some(gap) if previous_was_gap => {
+                    gap.end = bucket.end.clone();
+                    gap.monitor_down |= bucket.monitor_down;
+                }
+                _ => gaps.push(TimelineGap {
+                    start: bucket.start.clone(),
+                    end: bucket.end.clone(),
+                    monitor_down: bucket.monitor_down,
+                }),
+            }
+        }
+        previous_was_gap = is_gap;
+    }
+    gaps
+}
+
+// Tauri命令：获取某一天的捕获时间线（day 格式 %Y-%m-%d）
+#[tauri::command]
+pub async fn get_capture_timeline(
+    app: AppHandle,
+    day: String,
+    bucket_minutes: Option<u32>,
+) -> Result<CaptureTimeline, String> {
+    let bucket_minutes = bucket_minutes.unwrap_or(60).clamp(5, 24 * 60);
+    let date = NaiveDate::parse_from_str(day.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("无法解析日期 {}: {}", day, e))?;
+    let day_start = date.and_hms_opt(0, 0, 0).ok_or("无效的日期")?;
+    let day_end = day_start + Duration::days(1);
+    let start_str = day_start.format(TIMESTAMP_FORMAT).to_string();
+    let end_str = day_end.format(TIMESTAMP_FORMAT).to_string();
+
+    let conn = open_connection(&app, ConnectionRole::Analytics)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, content_type, preview, timestamp, source_app FROM clipboard_history
+             WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp",
+        )
+        .map_err(|e| format!("准备时间线查询失败: {}", e))?;
+    let entries = stmt
+        .query_map(params![start_str, end_str], |row| {
+            Ok(TimelineEntry {
+                id: row.get(0)?,
+                content_type: row.get(1)?,
+                preview: row.get(2)?,
+                timestamp: row.get(3)?,
+                source_app: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("时间线查询失败: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("处理时间线数据失败: {}", e))?;
+    let heartbeats = get_monitor_heartbeats(&conn, &start_str, &end_str)?;
+
+    // 计算记录所属的时间段下标
+    let bucket_span = Duration::minutes(bucket_minutes as i64);
+    let bucket_count = ((24 * 60) as f64 / bucket_minutes as f64).ceil() as usize;
+    let bucket_index = |timestamp: &str| -> Option<usize> {
+        let time = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+        let index = ((time - day_start).num_minutes() / bucket_minutes as i64) as usize;
+        (index < bucket_count).then_some(index)
+    };
+
+    let mut buckets: Vec<TimelineBucket> = (0..bucket_count)
+        .map(|index| {
+            let start = day_start + bucket_span * index as i32;
+            let end = (start + bucket_span).min(day_end);
+            TimelineBucket {
+                start: start.format(TIMESTAMP_FORMAT).to_string(),
+                end: end.format(TIMESTAMP_FORMAT).to_string(),
+                capture_count: 0,
+                active: false,
+                monitor_down: false,
+                entries: Vec::new(),
+            }
+        })
+        .collect();
+
+    for (timestamp, monitor_running) in heartbeats {
+        if let Some(index) = bucket_index(&timestamp) {
+            buckets[index].active = true;
+            buckets[index].monitor_down |= !monitor_running;
+        }
+    }
+    for entry in entries {
+        if let Some(index) = bucket_index(&entry.timestamp) {
+            // 有捕获说明该时间段应用在运行
+            buckets[index].active = true;
+            buckets[index].capture_count += 1;
+            buckets[index].entries.push(entry);
+        }
+    }
+
+    let gaps = detect_gaps(&buckets);
+    Ok(CaptureTimeline {
+        day: date.format("%Y-%m-%d").to_string(),
+        bucket_minutes,
+        buckets,
+        gaps,
+    })
+}
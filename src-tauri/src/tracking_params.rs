@@ -0,0 +1,72 @@
+use crate::clipboard_content::ClipboardContent;
+use crate::item_actions::detect_url;
+
+// 常见的广告/统计追踪参数（utm_ 前缀另行匹配）
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "twclid", "ttclid", "igshid",
+    "mc_cid", "mc_eid", "_hsenc", "_hsmi", "mkt_tok", "oly_anon_id", "oly_enc_id", "vero_id",
+    "spm", "share_source", "share_medium",
+];
+
+fn is_tracking_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("utm_") || TRACKING_PARAMS.contains(&name.as_str())
+}
+
+// 去掉链接中的追踪参数，保留其他参数和锚点；没有可去掉的参数时返回 None
+pub fn strip_tracking_params(url: &str) -> Option<String> {
+    let (without_fragment, fragment) = match url.split_once('#') {
+        Some((head, fragment)) => (head, Some(fragment)),
+        None => (url, None),
+    };
+    let (base, query) = without_fragment.split_once('?')?;
+
+    let params: Vec<&str> = query.split('&').filter(|param| !param.is_empty()).collect();
+    let kept: Vec<&str> = params
+        .iter()
+        .copied()
+        .filter(|param| !is_tracking_param(param.split('=').next().unwrap_or(param)))
+        .collect();
+    if kept.len() == params.len() {
+        return None;
+    }
+
+    let mut cleaned = base.to_string();
+    if !kept.is_empty() {
+        cleaned.push('?');
+        cleaned.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        cleaned.push('#');
+        cleaned.push_str(fragment);
+    }
+    Some(cleaned)
+}
+
+// 文本内容为单个链接且带有追踪参数时，返回去掉参数后的内容
+pub fn strip_tracking_from_content(content: &ClipboardContent) -> Option<ClipboardContent> {
+    let ClipboardContent::Text { text } = content else {
+        return None;
+    };
+    let url = detect_url(text)?;
+    strip_tracking_params(&url).map(|text| ClipboardContent::Text { text })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_tracking_params() {
+        assert_eq!(
+            strip_tracking_params("https://example.com/post?utm_source=x&UTM_Medium=y&id=42&fbclid=abc#top"),
+            Some("https://example.com/post?id=42#top".to_string())
+        );
+        assert_eq!(
+            strip_tracking_params("https://example.com/?gclid=1"),
+            Some("https://example.com/".to_string())
+        );
+        assert_eq!(strip_tracking_params("https://example.com/?q=rust&page=2"), None);
+        assert_eq!(strip_tracking_params("https://example.com/#utm_source=x"), None);
+    }
+}
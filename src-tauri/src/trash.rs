@@ -0,0 +1,271 @@
+use crate::change_log::emit_change_event;
+use crate::clipboard_content::ContentKind;
+use crate::db::init_database;
+use crate::lock_mode::ensure_unlocked;
+use chrono::{Duration, Local};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::AppHandle;
+
+// 在历史表和回收站之间移动时复制的列
+const TRASH_COLUMNS: &str = "id, content_type, content, content_hash, preview, timestamp, source_app,
+    source_bundle_id, edited, device_id, pinned, pin_order, image_width, image_height, failed_formats, thumbnail,
+    minified, search_truncated, note, category, url_title, url_favicon, color, language, sensitive, expires_at,
+    use_count, last_used_at, sync_state";
+
+// 创建回收站表：删除的记录先移到这里，可恢复，超过保留期后由定时清理永久删除
+pub fn create_trash_table(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS clipboard_trash (
+            id INTEGER PRIMARY KEY,
+            content_type TEXT NOT NULL,
+            content TEXT NOT NULL,
+            content_hash TEXT,
+            preview TEXT,
+            timestamp TEXT NOT NULL,
+            source_app TEXT,
+            source_bundle_id TEXT,
+            edited INTEGER NOT NULL DEFAULT 0,
+            device_id TEXT,
+            pinned INTEGER NOT NULL DEFAULT 0,
+            pin_order INTEGER,
+            image_width INTEGER,
+            image_height INTEGER,
+            failed_formats TEXT,
+            thumbnail TEXT,
+            deleted_at TEXT NOT NULL,
+            minified INTEGER NOT NULL DEFAULT 0,
+            search_truncated INTEGER NOT NULL DEFAULT 0,
+            note TEXT,
+            category TEXT,
+            url_title TEXT,
+            url_favicon TEXT,
+            color TEXT,
+            language TEXT,
+            sensitive INTEGER NOT NULL DEFAULT 0,
+            expires_at TEXT,
+            use_count INTEGER NOT NULL DEFAULT 0,
+            last_used_at TEXT,
+            sync_state INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_clipboard_trash_deleted_at ON clipboard_trash(deleted_at);",
+    )
+    .map_err(|e| format!("创建回收站表失败: {}", e))?;
+
+    // 为旧表添加新列（忽略错误，因为列可能已存在）
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN minified INTEGER NOT NULL DEFAULT 0", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN search_truncated INTEGER NOT NULL DEFAULT 0", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN note TEXT", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN category TEXT", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN url_title TEXT", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN url_favicon TEXT", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN color TEXT", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN language TEXT", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN sensitive INTEGER NOT NULL DEFAULT 0", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN expires_at TEXT", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN use_count INTEGER NOT NULL DEFAULT 0", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN last_used_at TEXT", []);
+    let _ = conn.execute("ALTER TABLE clipboard_trash ADD COLUMN sync_state INTEGER NOT NULL DEFAULT 0", []);
+    Ok(())
+}
+
+fn now_string() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+// 将指定记录移入回收站，返回实际移动的条数（不存在的ID忽略）
+pub fn move_to_trash(conn: &mut Connection, ids: &[i64]) -> Result<usize, String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+
+    let deleted_at = now_string();
+    let mut moved = 0;
+    {
+        let mut copy = tx
+            .prepare(&format!(
+                "INSERT OR REPLACE INTO clipboard_trash ({0}, deleted_at)
+                 SELECT {0}, ?2 FROM clipboard_history WHERE id = ?1",
+                TRASH_COLUMNS
+            ))
+            .map_err(|e| format!("准备移入回收站语句失败: {}", e))?;
+        let mut delete = tx
+            .prepare("DELETE FROM clipboard_history WHERE id = ?1")
+            .map_err(|e| format!("准备删除语句失败: {}", e))?;
+        for id in ids {
+            copy.execute(params![id, deleted_at])
+                .map_err(|e| format!("移入回收站失败: {}", e))?;
+            moved += delete
+                .execute(params![id])
+                .map_err(|e| format!("删除历史记录失败: {}", e))?;
+        }
+    }
+
+    tx.commit().map_err(|e| format!("提交删除失败: {}", e))?;
+    Ok(moved)
+}
+
+// 将全部历史记录移入回收站，返回移动的条数
+pub fn move_all_to_trash(conn: &mut Connection) -> Result<usize, String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+    tx.execute(
+        &format!(
+            "INSERT OR REPLACE INTO clipboard_trash ({0}, deleted_at)
+             SELECT {0}, ?1 FROM clipboard_history",
+            TRASH_COLUMNS
+        ),
+        params![now_string()],
+    )
+    .map_err(|e| format!("移入回收站失败: {}", e))?;
+    let moved = tx
+        .execute("DELETE FROM clipboard_history", [])
+        .map_err(|e| format!("清理所有历史记录失败: {}", e))?;
+    tx.commit().map_err(|e| format!("提交删除失败: {}", e))?;
+    Ok(moved)
+}
+
+// 永久删除在回收站中超过保留天数的记录
+pub fn purge_trash(conn: &Connection, retention_days: i32) -> Result<usize, String> {
+    let cutoff = (Local::now() - Duration::days(retention_days as i64))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    conn.execute("DELETE FROM clipboard_trash WHERE deleted_at < ?1", params![cutoff])
+        .map_err(|e| format!("清理回收站失败: {}", e))
+}
+
+// 回收站中的记录（只返回列表展示需要的字段）
+#[derive(Debug, Serialize)]
+pub struct TrashItem {
+    pub id: i64,
+    pub content_type: ContentKind,
+    pub preview: Option<String>,
+    pub timestamp: String,
+    pub source_app: Option<String>,
+    pub deleted_at: String,
+}
+
+// Tauri命令：获取回收站中的记录，最近删除的在前
+#[tauri::command]
+pub async fn get_trash_items(app: AppHandle) -> Result<Vec<TrashItem>, String> {
+    let conn = init_database(&app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, content_type, preview, timestamp, source_app, deleted_at
+             FROM clipboard_trash ORDER BY deleted_at DESC, id DESC",
+        )
+        .map_err(|e| format!("准备回收站查询失败: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(TrashItem {
+                id: row.get(0)?,
+                content_type: row.get(1)?,
+                preview: row.get(2)?,
+                timestamp: row.get(3)?,
+                source_app: row.get(4)?,
+                deleted_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("查询回收站失败: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("处理回收站数据失败: {}", e))
+}
+
+// 将回收站中的记录恢复到历史记录（保留原ID和时间）
+fn restore_from_trash(conn: &mut Connection, id: i64) -> Result<(), String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("开启事务失败: {}", e))?;
+
+    let content_hash: Option<String> = tx
+        .query_row(
+            "SELECT content_hash FROM clipboard_trash WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("查询回收站失败: {}", e))?
+        .ok_or_else(|| format!("回收站中不存在记录 {}", id))?;
+
+    // 删除后又复制过相同内容时，历史中已有新记录
+    if let Some(ref hash) = content_hash {
+        let exists: bool = tx
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM clipboard_history WHERE content_hash = ?1)",
+                params![hash],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("查询历史记录失败: {}", e))?;
+        if exists {
+            return Err("历史记录中已存在相同内容".to_string());
+        }
+    }
+
+    tx.execute(
+        &format!(
+            "INSERT INTO clipboard_history ({0}) SELECT {0} FROM clipboard_trash WHERE id = ?1",
+            TRASH_COLUMNS
+        ),
+        params![id],
+    )
+    .map_err(|e| format!("恢复记录失败: {}", e))?;
+    tx.execute("DELETE FROM clipboard_trash WHERE id = ?1", params![id])
+        .map_err(|e| format!("从回收站移除记录失败: {}", e))?;
+    tx.commit().map_err(|e| format!("提交恢复失败: {}", e))
+}
+
+// Tauri命令：将回收站中的记录恢复到历史记录
+#[tauri::command]
+pub async fn restore_clipboard_item(app: AppHandle, id: i64) -> Result<(), String> {
+    let mut conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    restore_from_trash(&mut conn, id)?;
+
+    println!("已从回收站恢复记录 {}", id);
+    emit_change_event(&app, "clipboard-updated");
+    Ok(())
+}
+
+// Tauri命令：清空回收站，返回永久删除的条数
+#[tauri::command]
+pub async fn empty_trash(app: AppHandle) -> Result<usize, String> {
+    let conn = init_database(&app)?;
+    ensure_unlocked(&conn)?;
+    let deleted = conn
+        .execute("DELETE FROM clipboard_trash", [])
+        .map_err(|e| format!("清空回收站失败: {}", e))?;
+    println!("已清空回收站，永久删除 {} 条记录", deleted);
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tests::test_connection;
+
+    #[test]
+    fn test_trash_and_restore_keeps_sensitive_flag() {
+        let mut conn = test_connection();
+        conn.execute(
+            "INSERT INTO clipboard_history (id, content_type, content, content_hash, timestamp, sensitive)
+             VALUES (1, 'text', 'sk-secret-token', 'hash-1', '2026-01-01 10:00:00', 1)",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(move_to_trash(&mut conn, &[1]).unwrap(), 1);
+        let trashed: bool = conn
+            .query_row("SELECT sensitive FROM clipboard_trash WHERE id = 1", [], |row| row.get(0))
+            .unwrap();
+        assert!(trashed);
+
+        restore_from_trash(&mut conn, 1).unwrap();
+        let restored: bool = conn
+            .query_row("SELECT sensitive FROM clipboard_history WHERE id = 1", [], |row| row.get(0))
+            .unwrap();
+        assert!(restored);
+        let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM clipboard_trash", [], |row| row.get(0)).unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
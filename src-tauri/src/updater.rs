@@ -0,0 +1,176 @@
+use crate::db::{get_settings, init_database};
+use crate::http::http_get;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+const RELEASES_API: &str = "https://api.github.com/repos/kissesu/oicopy/releases";
+const CHECK_INTERVAL_SECS: u64 = 6 * 3600;
+
+// 更新通道
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateChannel {
+    Stable, // 只接收正式版
+    Beta,   // 同时接收预发布版本
+}
+
+impl UpdateChannel {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "stable" => Some(UpdateChannel::Stable),
+            "beta" => Some(UpdateChannel::Beta),
+            _ => None,
+        }
+    }
+}
+
+// GitHub Release 中需要的字段
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    html_url: String,
+    published_at: Option<String>,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+}
+
+// 更新检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub release_name: Option<String>,
+    pub changelog: Option<String>,
+    pub download_url: String,
+    pub published_at: Option<String>,
+    pub channel: String,
+}
+
+// 解析版本号，返回 (主.次.修订, 是否预发布)
+fn parse_version(version: &str) -> Option<(Vec<u64>, bool)> {
+    let version = version.trim().trim_start_matches('v');
+    let (core, prerelease) = match version.split_once('-') {
+        Some((core, _)) => (core, true),
+        None => (version, false),
+    };
+    let parts = core
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some((parts, prerelease))
+}
+
+// latest 是否比 current 新（同版本号时正式版高于预发布版）
+pub fn is_newer_version(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some((mut latest_parts, latest_pre)), Some((mut current_parts, current_pre))) => {
+            let len = latest_parts.len().max(current_parts.len());
+            latest_parts.resize(len, 0);
+            current_parts.resize(len, 0);
+            match latest_parts.cmp(&current_parts) {
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Equal => current_pre && !latest_pre,
+            }
+        }
+        _ => false,
+    }
+}
+
+// 获取通道内最新的发布版本
+fn fetch_latest_release(channel: UpdateChannel) -> Result<Release, String> {
+    let headers = ["Accept: application/vnd.github+json", "User-Agent: oicopy-updater"];
+    let data = http_get(&format!("{}?per_page=20", RELEASES_API), &headers)
+        .map_err(|e| format!("获取发布信息失败: {}", e))?;
+    let releases: Vec<Release> =
+        serde_json::from_str(&data).map_err(|e| format!("解析发布信息失败: {}", e))?;
+
+    releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        .find(|release| channel == UpdateChannel::Beta || !release.prerelease)
+        .ok_or_else(|| "没有可用的发布版本".to_string())
+}
+
+fn check_updates(app: &AppHandle, channel_name: &str) -> Result<UpdateInfo, String> {
+    let channel = UpdateChannel::parse(channel_name).unwrap_or(UpdateChannel::Stable);
+    let current_version = app.package_info().version.to_string();
+    let release = fetch_latest_release(channel)?;
+    let latest_version = release.tag_name.trim_start_matches('v').to_string();
+
+    Ok(UpdateInfo {
+        update_available: is_newer_version(&latest_version, &current_version),
+        current_version,
+        latest_version,
+        release_name: release.name,
+        changelog: release.body,
+        download_url: release.html_url,
+        published_at: release.published_at,
+        channel: channel_name.to_string(),
+    })
+}
+
+// Tauri命令：检查更新
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, String> {
+    let conn = init_database(&app)?;
+    let settings = get_settings(&conn)?;
+    check_updates(&app, &settings.update_channel)
+}
+
+// 启动后台更新检查任务，发现新版本时通知前端
+pub fn start_update_checker(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+        let mut notified_version: Option<String> = None;
+
+        loop {
+            interval.tick().await;
+
+            let settings = match init_database(&app_handle).and_then(|conn| get_settings(&conn)) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    eprintln!("读取更新设置失败: {}", e);
+                    continue;
+                }
+            };
+            if !settings.auto_check_updates {
+                continue;
+            }
+
+            match check_updates(&app_handle, &settings.update_channel) {
+                Ok(info) if info.update_available => {
+                    // 同一版本只通知一次
+                    if notified_version.as_deref() != Some(info.latest_version.as_str()) {
+                        println!("发现新版本: {} (当前 {})", info.latest_version, info.current_version);
+                        notified_version = Some(info.latest_version.clone());
+                        if let Err(e) = app_handle.emit("update-available", info) {
+                            eprintln!("通知前端更新失败: {}", e);
+                        }
+                    }
+                }
+                Ok(_) => println!("当前已是最新版本"),
+                Err(e) => eprintln!("检查更新失败: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_comparison() {
+        assert!(is_newer_version("0.2.0", "0.1.9"));
+        assert!(is_newer_version("v1.0", "0.9.9"));
+        assert!(is_newer_version("1.0.0", "1.0.0-beta.2"));
+        assert!(!is_newer_version("1.0.0-beta.2", "1.0.0"));
+        assert!(!is_newer_version("0.1.0", "0.1.0"));
+        assert!(!is_newer_version("nightly", "0.1.0"));
+    }
+}
@@ -0,0 +1,340 @@
+use crate::change_log::emit_change_event;
+use crate::clipboard_management::decode_html_entities;
+use crate::db_writer::submit_write;
+use crate::http::{http_get_bytes, http_get_with_timeout};
+use base64::prelude::*;
+use regex::Regex;
+use rusqlite::{params, Connection};
+use std::net::{IpAddr, ToSocketAddrs};
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+// 抓取页面和图标的超时（秒），避免慢站点长时间占用后台线程
+const FETCH_TIMEOUT_SECS: u32 = 5;
+// 标题最大字符数
+const MAX_TITLE_CHARS: usize = 200;
+// 图标最大字节数，超过的不保存
+const MAX_FAVICON_BYTES: usize = 64 * 1024;
+// 页面最大字节数，标题在页面开头，超过的页面不解析
+const MAX_PAGE_BYTES: usize = 1024 * 1024;
+
+// 链接的页面元数据
+#[derive(Debug, Default, PartialEq)]
+pub struct UrlMetadata {
+    pub title: Option<String>,
+    pub favicon: Option<String>, // data URL（base64），前端可直接显示
+}
+
+fn title_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap())
+}
+
+fn meta_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?is)<meta\s[^>]*>").unwrap())
+}
+
+fn link_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?is)<link\s[^>]*>").unwrap())
+}
+
+fn attr_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"(?is)([a-z:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#).unwrap()
+    })
+}
+
+// 读取标签中的属性值（属性名不区分大小写）
+fn tag_attr(tag: &str, name: &str) -> Option<String> {
+    attr_regex().captures_iter(tag).find_map(|caps| {
+        caps[1].eq_ignore_ascii_case(name).then(|| {
+            caps.get(2)
+                .or_else(|| caps.get(3))
+                .or_else(|| caps.get(4))
+                .map(|value| value.as_str().to_string())
+                .unwrap_or_default()
+        })
+    })
+}
+
+// 规整标题：解码实体、合并空白并限制长度
+fn normalize_title(raw: &str) -> Option<String> {
+    let title = decode_html_entities(raw)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if title.is_empty() {
+        return None;
+    }
+    Some(title.chars().take(MAX_TITLE_CHARS).collect())
+}
+
+// 解析页面标题：优先 og:title，其次 <title>
+pub fn parse_title(html: &str) -> Option<String> {
+    let og_title = meta_regex().find_iter(html).find_map(|tag| {
+        let tag = tag.as_str();
+        let property = tag_attr(tag, "property").or_else(|| tag_attr(tag, "name"))?;
+        if property.eq_ignore_ascii_case("og:title") {
+            tag_attr(tag, "content").and_then(|content| normalize_title(&content))
+        } else {
+            None
+        }
+    });
+    og_title.or_else(|| {
+        title_regex()
+            .captures(html)
+            .and_then(|caps| normalize_title(&caps[1]))
+    })
+}
+
+// 解析图标地址：使用 <link rel="icon"> 声明的地址，没有时退回站点根目录的 favicon.ico
+pub fn parse_favicon_url(html: &str, page_url: &str) -> Option<String> {
+    let href = link_regex().find_iter(html).find_map(|tag| {
+        let tag = tag.as_str();
+        let rel = tag_attr(tag, "rel")?.to_ascii_lowercase();
+        let is_icon = rel.split_whitespace().any(|part| part == "icon" || part == "apple-touch-icon");
+        if is_icon {
+            tag_attr(tag, "href").filter(|href| !href.trim().is_empty())
+        } else {
+            None
+        }
+    });
+    resolve_url(page_url, href.as_deref().unwrap_or("/favicon.ico"))
+}
+
+// 将相对地址解析为绝对地址
+fn resolve_url(page_url: &str, href: &str) -> Option<String> {
+    let href = decode_html_entities(href.trim());
+    if href.starts_with("https://") || href.starts_with("http://") {
+        return Some(href);
+    }
+    if href.starts_with("data:") {
+        return None;
+    }
+
+    let (scheme, rest) = page_url.split_once("://")?;
+    if let Some(stripped) = href.strip_prefix("//") {
+        return Some(format!("{}://{}", scheme, stripped));
+    }
+
+    let host_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let origin = format!("{}://{}", scheme, &rest[..host_end]);
+    if href.starts_with('/') {
+        return Some(format!("{}{}", origin, href));
+    }
+
+    // 相对当前页面所在目录
+    let path = rest[host_end..].split(['?', '#']).next().unwrap_or("");
+    let dir = match path.rfind('/') {
+        Some(index) => &path[..=index],
+        None => "/",
+    };
+    Some(format!("{}{}{}", origin, dir, href))
+}
+
+// 根据文件头判断图标的 MIME 类型
+fn favicon_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0x00, 0x00, 0x01, 0x00]) {
+        Some("image/x-icon")
+    } else if bytes.starts_with(b"GIF8") {
+        Some("image/gif")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.len() > 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if String::from_utf8_lossy(&bytes[..bytes.len().min(256)]).contains("<svg") {
+        Some("image/svg+xml")
+    } else {
+        None
+    }
+}
+
+// 链接的主机名（不含端口和账号），IPv6 地址去掉方括号
+fn url_host(url: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let default_port = if scheme.eq_ignore_ascii_case("https") { 443 } else { 80 };
+    let (host, port) = match host_port.strip_prefix('[') {
+        Some(v6) => {
+            let (host, rest) = v6.split_once(']')?;
+            (host, rest.strip_prefix(':'))
+        }
+        None => match host_port.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        },
+    };
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default_port,
+    };
+    (!host.is_empty()).then(|| (host.to_ascii_lowercase(), port))
+}
+
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || (ip.octets()[0] == 100 && (64..128).contains(&ip.octets()[1])) // 运营商级 NAT
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_private_ip(IpAddr::V4(v4)),
+            None => {
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    || (ip.segments()[0] & 0xfe00) == 0xfc00 // 唯一本地地址
+                    || (ip.segments()[0] & 0xffc0) == 0xfe80 // 链路本地地址
+            }
+        },
+    }
+}
+
+// 只抓取公网上的链接：本机和局域网地址（路由器、内网服务）不发请求
+fn is_public_url(url: &str) -> bool {
+    let Some((host, port)) = url_host(url) else {
+        return false;
+    };
+    if host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") || !host.contains(['.', ':']) {
+        return false;
+    }
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return !is_private_ip(ip);
+    }
+    // 域名解析到内网地址时同样跳过
+    match (host.as_str(), port).to_socket_addrs() {
+        Ok(addrs) => {
+            let addrs: Vec<_> = addrs.collect();
+            !addrs.is_empty() && addrs.iter().all(|addr| !is_private_ip(addr.ip()))
+        }
+        Err(_) => false,
+    }
+}
+
+fn fetch_favicon(favicon_url: &str) -> Result<String, String> {
+    if !is_public_url(favicon_url) {
+        return Err("图标地址不是公网地址".to_string());
+    }
+    let bytes = http_get_bytes(favicon_url, &[], FETCH_TIMEOUT_SECS, MAX_FAVICON_BYTES)?;
+    let mime = favicon_mime(&bytes).ok_or_else(|| "无法识别的图标格式".to_string())?;
+    Ok(format!("data:{};base64,{}", mime, BASE64_STANDARD.encode(&bytes)))
+}
+
+// 抓取链接的标题和图标，图标获取失败不影响标题
+pub fn fetch_url_metadata(url: &str) -> Result<UrlMetadata, String> {
+    if !is_public_url(url) {
+        return Err("不获取本机或局域网地址的元数据".to_string());
+    }
+    let html = http_get_with_timeout(url, &["Accept: text/html"], FETCH_TIMEOUT_SECS, MAX_PAGE_BYTES)?;
+    let favicon = parse_favicon_url(&html, url).and_then(|favicon_url| {
+        fetch_favicon(&favicon_url)
+            .map_err(|e| eprintln!("获取 {} 的图标失败: {}", url, e))
+            .ok()
+    });
+    Ok(UrlMetadata {
+        title: parse_title(&html),
+        favicon,
+    })
+}
+
+// 保存链接元数据
+pub fn set_url_metadata(conn: &Connection, id: i64, metadata: &UrlMetadata) -> Result<usize, String> {
+    conn.execute(
+        "UPDATE clipboard_history SET url_title = ?1, url_favicon = ?2 WHERE id = ?3",
+        params![metadata.title, metadata.favicon, id],
+    )
+    .map_err(|e| format!("保存链接元数据失败: {}", e))
+}
+
+// 在后台线程抓取链接元数据，完成后交给写入线程保存并通知前端刷新
+pub fn start_url_metadata_fetch(app_handle: &AppHandle, id: i64, url: String) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let metadata = match fetch_url_metadata(&url) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                eprintln!("获取链接 {} 的元数据失败: {}", url, e);
+                return;
+            }
+        };
+        if metadata == UrlMetadata::default() {
+            return;
+        }
+
+        let result = submit_write(&app_handle, move |app, conn| match set_url_metadata(conn, id, &metadata) {
+            Ok(0) => {} // 记录已被删除
+            Ok(_) => emit_change_event(app, "clipboard-updated"),
+            Err(e) => eprintln!("{}", e),
+        });
+        if let Err(e) = result {
+            eprintln!("提交链接元数据失败: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_title() {
+        let html = r#"<html><head><title> Example &amp; Co
+            </title><meta property="og:title" content="Example Domain"></head></html>"#;
+        assert_eq!(parse_title(html), Some("Example Domain".to_string()));
+        assert_eq!(
+            parse_title("<TITLE>Tom &amp; Jerry</TITLE>"),
+            Some("Tom & Jerry".to_string())
+        );
+        assert_eq!(parse_title("<title>  </title>"), None);
+    }
+
+    #[test]
+    fn test_parse_favicon_url() {
+        let page = "https://example.com/docs/intro.html?lang=zh";
+        assert_eq!(
+            parse_favicon_url(r#"<link rel="shortcut icon" href="/static/icon.png">"#, page),
+            Some("https://example.com/static/icon.png".to_string())
+        );
+        assert_eq!(
+            parse_favicon_url(r#"<link href='img/icon.svg' rel='icon'>"#, page),
+            Some("https://example.com/docs/img/icon.svg".to_string())
+        );
+        assert_eq!(
+            parse_favicon_url(r#"<link rel="icon" href="//cdn.example.com/i.ico">"#, page),
+            Some("https://cdn.example.com/i.ico".to_string())
+        );
+        assert_eq!(
+            parse_favicon_url("<html></html>", "https://example.com"),
+            Some("https://example.com/favicon.ico".to_string())
+        );
+    }
+
+    #[test]
+    fn test_private_urls_are_skipped() {
+        assert_eq!(url_host("https://user:pw@Example.com:8443/a?b"), Some(("example.com".to_string(), 8443)));
+        assert_eq!(url_host("http://[::1]/"), Some(("::1".to_string(), 80)));
+        for url in [
+            "http://localhost:3000/reset?token=abc",
+            "http://127.0.0.1/",
+            "http://192.168.1.1/admin",
+            "http://10.0.0.8:8080/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]:8080/",
+            "http://[fd00::1]/",
+            "http://printer.local/",
+            "http://intranet/",
+        ] {
+            assert!(!is_public_url(url), "{}", url);
+        }
+        assert!(is_public_url("https://93.184.215.14/"));
+    }
+}
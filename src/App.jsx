@@ -6,8 +6,10 @@ import { PermissionContext } from "./context/permissionContext";
 import HomePage from "./components/CheckPermissions";
 import PanelPage from "./components/PanelPage";
 import SettingsPage from "./components/Settings";
+import QuickCapture from "./components/QuickCapture";
 import { checkAccessibilityPermission, checkFullDiskAccessPermission } from "tauri-plugin-macos-permissions-api";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 
 
@@ -50,6 +52,12 @@ function AppContent() {
         return;
       }
 
+      // 快速记录窗口不需要权限检查
+      if (windowLabel === 'quick-capture') {
+        navigate('/quick-capture');
+        return;
+      }
+
       // 如果是权限设置窗口，直接导航到首页（权限设置页面），不进行权限检查
       if (windowLabel === 'check-permissions') {
         navigate('/');
@@ -88,16 +96,35 @@ function AppContent() {
         <Route path="/" element={<HomePage />} />
         <Route path="/panel" element={<PanelPage />} />
         <Route path="/settings" element={<SettingsPage />} />
+        <Route path="/quick-capture" element={<QuickCapture />} />
       </Routes>
     </main>
   )
 }
 
+// 按系统辅助功能设置切换根元素上的样式类
+const applyAccessibilityClasses = (settings) => {
+  const root = document.documentElement;
+  root.classList.toggle("reduce-motion", settings.reduce_motion);
+  root.classList.toggle("reduce-transparency", settings.reduce_transparency);
+  root.classList.toggle("increase-contrast", settings.increase_contrast);
+};
+
 function App() {
 
   const [isAccessible, setIsAccessible] = useState(false);
   const [isFullDiskAccessible, setIsFullDiskAccessible] = useState(false);
 
+  useEffect(() => {
+    invoke("get_accessibility_settings")
+      .then(applyAccessibilityClasses)
+      .catch((error) => console.error("获取辅助功能设置失败:", error));
+    const unlisten = listen("accessibility-changed", (event) => applyAccessibilityClasses(event.payload));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   return (
     <PermissionContext.Provider
       value={{
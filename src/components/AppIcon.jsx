@@ -5,6 +5,46 @@ import { getAppIcon, getAppIconByName } from '../utils/appIcons';
 // 图标缓存
 const iconCache = new Map();
 
+// 等待批量请求的 bundleId 及其回调
+let pendingRequests = new Map();
+let flushScheduled = false;
+
+// 合并同一轮渲染中的图标请求，每个 bundleId 只向后端请求一次
+function flushIconRequests() {
+  const requests = pendingRequests;
+  pendingRequests = new Map();
+  flushScheduled = false;
+
+  invoke('get_app_icons', { bundleIds: [...requests.keys()] })
+    .then((icons) => {
+      requests.forEach((resolvers, bundleId) => {
+        const icon = icons?.[bundleId] || null;
+        iconCache.set(bundleId, icon);
+        resolvers.forEach((resolve) => resolve(icon));
+      });
+    })
+    .catch((err) => {
+      console.error('批量获取应用图标失败:', err);
+      requests.forEach((resolvers, bundleId) => {
+        iconCache.set(bundleId, null);
+        resolvers.forEach((resolve) => resolve(null));
+      });
+    });
+}
+
+function requestIcon(bundleId) {
+  return new Promise((resolve) => {
+    const resolvers = pendingRequests.get(bundleId) || [];
+    resolvers.push(resolve);
+    pendingRequests.set(bundleId, resolvers);
+
+    if (!flushScheduled) {
+      flushScheduled = true;
+      setTimeout(flushIconRequests, 0);
+    }
+  });
+}
+
 function AppIcon({ bundleId, appName, size = 60, className = "" }) {
   const [iconData, setIconData] = useState(null);
   const [loading, setLoading] = useState(false);
@@ -31,20 +71,13 @@ function AppIcon({ bundleId, appName, size = 60, className = "" }) {
     const fetchIcon = async () => {
       setLoading(true);
       try {
-        const result = await invoke('get_app_icon_by_bundle_id', { bundleId });
-        console.log("result: ", result);
-        console.log("result type: ", typeof result);
-        console.log("result length: ", result?.length);
-        
+        const result = await requestIcon(bundleId);
+
         // 检查结果是否为有效的 base64 字符串
         if (result && typeof result === 'string' && result.length > 0) {
-          console.log("Setting iconData with valid result");
-          iconCache.set(bundleId, result);
           setIconData(result);
           setError(false);
         } else {
-          console.log("No valid result received - result is null or empty");
-          iconCache.set(bundleId, null);
           setError(true);
         }
       } catch (err) {
@@ -57,9 +90,6 @@ function AppIcon({ bundleId, appName, size = 60, className = "" }) {
     };
 
     fetchIcon();
-    console.log("iconData", iconData);
-    console.log("error", error);
-    
   }, [bundleId]);
 
   if (loading) {
@@ -14,7 +14,7 @@ function AppInfoTest() {
       console.log('当前前台应用:', info);
     } catch (error) {
       console.error('获取应用信息失败:', error);
-      setAppInfo({ error: error.toString() });
+      setAppInfo({ error: error?.message ?? error.toString() });
     } finally {
       setLoading(false);
     }
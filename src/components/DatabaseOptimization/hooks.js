@@ -76,12 +76,15 @@ export const useDatabaseOptimization = () => {
   // 统一错误处理函数
   const handleError = useCallback((error, operation) => {
     console.error(`${operation} failed:`, error);
+    // 后端命令返回 { kind, message } 形式的错误
+    const message = error?.message ?? String(error);
     setError({
       operation,
-      message: error.toString(),
+      kind: error?.kind,
+      message,
       timestamp: new Date().toISOString()
     });
-    setMessage(`${operation}失败: ${error}`);
+    setMessage(`${operation}失败: ${message}`);
   }, []);
 
   // 通用操作处理函数
@@ -3,7 +3,6 @@ import { invoke } from "@tauri-apps/api/core";
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { listen } from "@tauri-apps/api/event";
 // 或者导入所有函数
-import * as Clipboard from 'tauri-plugin-clipboard-api';
 // import parse from 'html-react-parser';
 import ZoomableHTML from "./ZoomableHTML";
 import dayjs from "dayjs";
@@ -74,6 +73,9 @@ export default function PanelPage() {
   // 用于跟踪是否应该忽略下一次剪切板更新
   const ignoreNextClipboardUpdate = useRef(false);
 
+  // 最后处理过的数据变更序号，用于错过事件后增量对账
+  const lastChangeSeq = useRef(null);
+
   // 初始化缓存系统
   useEffect(() => {
     async function initializeCacheSystem() {
@@ -124,6 +126,7 @@ export default function PanelPage() {
 
       // 监听剪切板更新事件 - 使用缓存系统
       await listen("clipboard-updated", async (event) => {
+        rememberChangeSeq(event);
         // 如果是我们自己触发的复制操作，忽略这次更新
         if (ignoreNextClipboardUpdate.current) {
           console.log("忽略自己触发的剪切板更新");
@@ -136,13 +139,34 @@ export default function PanelPage() {
       });
 
       // 监听数据清理事件
-      await listen("data-cleared", async () => {
+      await listen("data-cleared", async (event) => {
+        rememberChangeSeq(event);
         console.log("数据已清理，清空缓存并重新加载");
         await handleDataCleared();
       });
     }
 
     listenSystemEvents();
+
+    // 记录加载时的变更序号，作为之后对账的起点
+    invoke("get_changes_since", { seq: 0 })
+      .then((changeSet) => {
+        if (lastChangeSeq.current === null) {
+          lastChangeSeq.current = changeSet.current_seq;
+        }
+      })
+      .catch((error) => console.error('获取变更序号失败:', error));
+
+    // 窗口重新获得焦点时（如休眠唤醒后）检查是否错过了变更事件
+    const unlistenFocus = getCurrentWindow().onFocusChanged(({ payload: focused }) => {
+      if (focused) {
+        reconcileChanges();
+      }
+    });
+
+    return () => {
+      unlistenFocus.then((unlisten) => unlisten());
+    };
   }, [])
 
   // 确保面板获得焦点
@@ -385,7 +409,7 @@ export default function PanelPage() {
 
   // 点击剪切板 - 使用useCallback优化性能
   const clickCard = useCallback(async (card) => {
-    const { id, content_type, content } = card;
+    const { id } = card;
     setSelectedId(card ? id : null);
 
     try {
@@ -399,43 +423,8 @@ export default function PanelPage() {
       // 设置标志，忽略下一次剪切板更新事件
       ignoreNextClipboardUpdate.current = true;
       
-      switch (content_type) {
-        case "text":
-          await Clipboard.writeText(content);
-          break;
-
-        case "html":
-          // HTML内容需要同时写入HTML格式和纯文本格式
-          const htmlText = content.replace(/<[^>]+>/g, ''); // 简单去除HTML标签作为纯文本
-          await Clipboard.writeHtmlAndText(content, htmlText);
-          break;
-
-        case "image":
-          const base64 = card.content.trim();
-          await Clipboard.writeImageBase64(base64);
-          break;
-
-        case "files":
-          try {
-            let fileList;
-            if (typeof content === 'string') {
-              try {
-                fileList = JSON.parse(content);
-              } catch {
-                fileList = [content];
-              }
-            } else {
-              fileList = Array.isArray(content) ? content : [String(content)];
-            }
-            await Clipboard.writeFiles(fileList);
-          } catch (fileError) {
-            console.error('处理文件列表失败:', fileError);
-          }
-          break;
-
-        default:
-          console.warn("未知的剪切板类型:", content_type);
-      }
+      // 由后端读取原始记录并按原格式写回剪贴板
+      await invoke('copy_item_to_clipboard', { id });
 
       // 添加同步任务（如果需要）
       if (cacheReady) {
@@ -688,6 +677,44 @@ export default function PanelPage() {
     }
   }
 
+  // 记录事件携带的变更序号
+  function rememberChangeSeq(event) {
+    const seq = event?.payload?.seq;
+    if (typeof seq === 'number' && seq > (lastChangeSeq.current ?? -1)) {
+      lastChangeSeq.current = seq;
+    }
+  }
+
+  // 按变更序号对账：只有新增时增量添加，有修改、删除或变更过多时重新加载
+  async function reconcileChanges() {
+    if (lastChangeSeq.current === null) {
+      return;
+    }
+    try {
+      const changeSet = await invoke("get_changes_since", { seq: lastChangeSeq.current });
+      lastChangeSeq.current = changeSet.current_seq;
+      if (changeSet.changes.length === 0 && !changeSet.full_resync) {
+        return;
+      }
+
+      const onlyAdded = changeSet.changes.every((change) => change.kind === 'added');
+      if (changeSet.full_resync || !onlyAdded) {
+        console.log('检测到错过的变更，重新加载列表');
+        await handleDataCleared();
+        return;
+      }
+
+      const result = await invoke("get_clipboard_history", {
+        limit: changeSet.changes.length,
+        offset: 0
+      });
+      // 从旧到新依次插入，保证最新记录在最前
+      [...result].reverse().forEach((record) => cacheManager.prependRecord(record));
+    } catch (error) {
+      console.error('同步错过的变更失败:', error);
+    }
+  }
+
   // 处理数据清理事件
   async function handleDataCleared() {
     try {
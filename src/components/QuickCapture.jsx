@@ -0,0 +1,92 @@
+import React, { useState, useRef, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+
+// 快速记录窗口：输入的内容直接保存到历史记录，不经过系统剪贴板
+function QuickCapture() {
+  const [content, setContent] = useState("");
+  const [tags, setTags] = useState("");
+  const [error, setError] = useState(null);
+  const [saving, setSaving] = useState(false);
+  const textareaRef = useRef(null);
+
+  // 窗口每次获得焦点时聚焦输入框
+  useEffect(() => {
+    textareaRef.current?.focus();
+    const unlisten = getCurrentWindow().onFocusChanged(({ payload: focused }) => {
+      if (focused) textareaRef.current?.focus();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const closeWindow = () => {
+    setContent("");
+    setTags("");
+    setError(null);
+    invoke("hide_panel_window", { panelName: "quick-capture" });
+  };
+
+  const handleSave = async () => {
+    if (!content.trim() || saving) return;
+    setSaving(true);
+    try {
+      await invoke("create_manual_item", {
+        content,
+        contentType: "text",
+        tags: tags.split(/[,，]/).map((tag) => tag.trim()).filter(Boolean),
+      });
+      closeWindow();
+    } catch (e) {
+      console.error("新建片段失败:", e);
+      setError(String(e));
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const handleKeyDown = (event) => {
+    // Cmd+Enter 保存，Esc 关闭
+    if (event.key === "Enter" && event.metaKey) {
+      event.preventDefault();
+      handleSave();
+    } else if (event.key === "Escape") {
+      event.preventDefault();
+      closeWindow();
+    }
+  };
+
+  return (
+    <div className="flex flex-col h-screen p-3 gap-2 bg-white text-sm" onKeyDown={handleKeyDown}>
+      <textarea
+        ref={textareaRef}
+        className="flex-1 resize-none rounded border border-gray-300 p-2 outline-none focus:border-blue-400"
+        placeholder="输入要保存的内容…"
+        value={content}
+        onChange={(e) => setContent(e.target.value)}
+      />
+      <input
+        className="rounded border border-gray-300 px-2 py-1 outline-none focus:border-blue-400"
+        placeholder="标签（用逗号分隔，可选）"
+        value={tags}
+        onChange={(e) => setTags(e.target.value)}
+      />
+      {error && <div className="text-red-500 text-xs">{error}</div>}
+      <div className="flex justify-end gap-2">
+        <button className="px-3 py-1 rounded text-gray-600 hover:bg-gray-100" onClick={closeWindow}>
+          取消
+        </button>
+        <button
+          className="px-3 py-1 rounded bg-blue-500 text-white disabled:opacity-50"
+          disabled={!content.trim() || saving}
+          onClick={handleSave}
+        >
+          保存 ⌘↩
+        </button>
+      </div>
+    </div>
+  );
+}
+
+export default QuickCapture;
@@ -1,4 +1,5 @@
-import React, { useCallback, useMemo } from 'react';
+import React, { useCallback, useEffect, useMemo, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import dayjs from 'dayjs';
 import relativeTime from 'dayjs/plugin/relativeTime';
 import 'dayjs/locale/zh-cn';
@@ -83,10 +84,34 @@ const VirtualClipboardCard = React.memo(({
     return cardType[card.content_type] || { name: '未知类型', icon: null };
   }, [card.content_type, cardType]);
 
+  // 列表只返回元数据，卡片渲染时再按需加载内容（图片已附带缩略图）
+  const needsContent = card.content_omitted && !card.content;
+  const [loadedContent, setLoadedContent] = useState(null);
+
+  useEffect(() => {
+    if (!needsContent) return;
+    let cancelled = false;
+    setLoadedContent(null);
+    invoke('get_clipboard_item_content', { id: card.id, renderLimited: true })
+      .then((result) => {
+        if (!cancelled) setLoadedContent(result.content);
+      })
+      .catch((error) => console.error('加载卡片内容失败:', card.id, error));
+    return () => {
+      cancelled = true;
+    };
+  }, [card.id, needsContent]);
+
   // Memoized content renderer for better performance
   const renderedContent = useMemo(() => {
+    if (needsContent) {
+      if (loadedContent === null) {
+        return <div className="text-xs text-left text-gray-400 p-1">{card.preview}</div>;
+      }
+      return renderCardContent({ ...card, content: loadedContent });
+    }
     return renderCardContent(card);
-  }, [card]);
+  }, [card, needsContent, loadedContent]);
 
   // Combine virtual positioning styles with custom styles
   const combinedStyle = useMemo(() => ({
@@ -166,10 +191,16 @@ function renderCardContent(card) {
 
   switch (content_type) {
     case "text":
+      // 压缩/超长单行内容显示捕获时折行截断的预览，完整内容通过复制或编辑获取
       return (
-        <pre className="whitespace-pre-wrap break-words text-xs text-left font-mono p-1 rounded-md shadow-inner overflow-x-auto">
-          {content}
-        </pre>
+        <>
+          <pre className="whitespace-pre-wrap break-words text-xs text-left font-mono p-1 rounded-md shadow-inner overflow-x-auto">
+            {card.minified && card.preview ? card.preview : content}
+          </pre>
+          {card.search_truncated && (
+            <div className="text-[10px] text-gray-400 text-left px-1">内容过长，仅前 64K 字符可被搜索</div>
+          )}
+        </>
       );
 
     case "html":
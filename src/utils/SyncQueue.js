@@ -1331,18 +1331,22 @@ class SyncQueue {
    * @returns {string} 错误类型
    */
   _classifyError(error) {
-    const message = error.message.toLowerCase();
+    // 后端命令返回 { kind, message } 形式的错误，按 kind 分类
+    switch (error?.kind) {
+      case 'permission':
+        return 'permission';
+      case 'validation':
+        return 'validation';
+      case 'db':
+        return 'database';
+    }
+
+    const message = String(error?.message ?? error).toLowerCase();
     
     if (message.includes('timeout') || message.includes('超时')) {
       return 'timeout';
     } else if (message.includes('network') || message.includes('连接')) {
       return 'network';
-    } else if (message.includes('database') || message.includes('数据库')) {
-      return 'database';
-    } else if (message.includes('permission') || message.includes('权限')) {
-      return 'permission';
-    } else if (message.includes('validation') || message.includes('验证')) {
-      return 'validation';
     } else if (message.includes('conflict') || message.includes('冲突')) {
       return 'conflict';
     } else {